@@ -0,0 +1,127 @@
+//! A JSON-RPC 2.0 server over TCP, one message per line. It answers
+//! `echo` (returns its params), `ping` (returns "pong") and `sum` (adds an
+//! array of numbers), handles batches and notifications, and reports
+//! malformed messages with the standard error codes.
+//!
+//!     cargo run --example jsonrpc_echo -- 127.0.0.1:4000
+//!     echo '{"jsonrpc": "2.0", "id": 1, "method": "echo", "params": [1]}' | nc 127.0.0.1 4000
+
+use std::collections::HashMap;
+use std::env;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use world::json::{JsonValue, parse, stringify};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+fn main() -> std::io::Result<()> {
+    let addr = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:4000".to_string());
+    let listener = TcpListener::bind(&addr)?;
+    eprintln!("listening on {}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        let stream = stream?;
+        thread::spawn(move || {
+            let peer = stream
+                .peer_addr()
+                .map(|a| a.to_string())
+                .unwrap_or_default();
+            if let Err(e) = serve(stream) {
+                eprintln!("{}: {}", peer, e);
+            }
+        });
+    }
+    Ok(())
+}
+
+fn serve(stream: TcpStream) -> std::io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_message(&line) {
+            writeln!(writer, "{}", stringify(&response))?;
+        }
+    }
+    Ok(())
+}
+
+// The response to one line, or `None` if it held only notifications.
+fn handle_message(line: &str) -> Option<JsonValue> {
+    let message = match parse(line) {
+        Ok(message) => message,
+        Err(e) => return Some(error(JsonValue::Null, PARSE_ERROR, &e.to_string())),
+    };
+    match message {
+        JsonValue::Array(batch) if batch.is_empty() => {
+            Some(error(JsonValue::Null, INVALID_REQUEST, "empty batch"))
+        }
+        JsonValue::Array(batch) => {
+            let responses: JsonValue = batch.iter().filter_map(handle_request).collect();
+            match responses.as_array() {
+                Some(items) if items.is_empty() => None,
+                _ => Some(responses),
+            }
+        }
+        request => handle_request(&request),
+    }
+}
+
+fn handle_request(request: &JsonValue) -> Option<JsonValue> {
+    let id = request.get("id").cloned();
+    let invalid = |message: &str| Some(error(id.clone().into(), INVALID_REQUEST, message));
+    if request.get("jsonrpc").and_then(JsonValue::as_str) != Some("2.0") {
+        return invalid("jsonrpc must be \"2.0\"");
+    }
+    let Some(method) = request.get("method").and_then(JsonValue::as_str) else {
+        return invalid("method must be a string");
+    };
+    let params = request.get("params").cloned().unwrap_or(JsonValue::Null);
+
+    let result = call(method, params);
+    // Notifications get no response, not even for errors.
+    let id = id?;
+    Some(match result {
+        Ok(value) => response(id, "result", value),
+        Err((code, message)) => error(id, code, &message),
+    })
+}
+
+fn call(method: &str, params: JsonValue) -> Result<JsonValue, (i64, String)> {
+    match method {
+        "echo" => Ok(params),
+        "ping" => Ok("pong".into()),
+        "sum" => {
+            let numbers: Option<Vec<f64>> = params
+                .as_array()
+                .map(|items| items.iter().map(JsonValue::as_f64).collect())
+                .unwrap_or(None);
+            let numbers =
+                numbers.ok_or((INVALID_PARAMS, "params must be an array of numbers".into()))?;
+            Ok(numbers.iter().sum::<f64>().into())
+        }
+        _ => Err((METHOD_NOT_FOUND, format!("method not found: {}", method))),
+    }
+}
+
+fn error(id: JsonValue, code: i64, message: &str) -> JsonValue {
+    let mut details = HashMap::new();
+    details.insert("code".to_string(), JsonValue::from(code));
+    details.insert("message".to_string(), JsonValue::from(message));
+    response(id, "error", details.into())
+}
+
+fn response(id: JsonValue, kind: &str, value: JsonValue) -> JsonValue {
+    let mut response = JsonValue::Null;
+    response.entry("jsonrpc").or_insert("2.0".into());
+    response.entry("id").or_insert(id);
+    response.entry(kind).or_insert(value);
+    response
+}
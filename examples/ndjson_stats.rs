@@ -0,0 +1,164 @@
+//! Summarizes a newline-delimited JSON log: records per level, the most
+//! frequent messages, latency percentiles and the slowest requests, plus a
+//! list of lines that are not valid JSON. Reads the file named on the
+//! command line, or stdin.
+//!
+//!     cargo run --example ndjson_stats -- service.log
+//!
+//! Each record is an object such as
+//! `{"ts": "...", "level": "info", "msg": "...", "http": {"path": "/a", "ms": 12}}`.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::process::ExitCode;
+use world::json::{JsonValue, ParseError, ParserOptions, parse_with_options, stringify};
+
+// How many entries the top-N sections show.
+const TOP: usize = 5;
+
+#[derive(Default)]
+struct Stats {
+    records: usize,
+    levels: HashMap<String, usize>,
+    messages: HashMap<String, usize>,
+    // (latency in ms, request path, line number)
+    latencies: Vec<(f64, String, usize)>,
+    malformed: Vec<(usize, ParseError)>,
+}
+
+fn main() -> ExitCode {
+    let input = match read_input(env::args().nth(1)) {
+        Ok(input) => input,
+        Err(message) => {
+            eprintln!("ndjson_stats: {}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+    let stats = analyze(&input);
+    print!("{}", report(&stats));
+    if stats.malformed.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(1)
+    }
+}
+
+fn read_input(path: Option<String>) -> Result<String, String> {
+    match path.as_deref() {
+        None | Some("-") => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|e| format!("cannot read stdin: {}", e))?;
+            Ok(input)
+        }
+        Some(path) => fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e)),
+    }
+}
+
+fn analyze(input: &str) -> Stats {
+    // Logs are written by programs, so hold them to strict JSON, but keep
+    // 64-bit ids and timestamps exact.
+    let options = ParserOptions {
+        preserve_raw_numbers: true,
+        ..ParserOptions::default()
+    };
+    let mut stats = Stats::default();
+    for (i, line) in input.lines().enumerate() {
+        let line_number = i + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = match parse_with_options(line, options.clone()) {
+            Ok(record) => record,
+            Err(e) => {
+                stats.malformed.push((line_number, e));
+                continue;
+            }
+        };
+        stats.records += 1;
+
+        let level = record.path_get("level").and_then(JsonValue::as_str);
+        *stats
+            .levels
+            .entry(level.unwrap_or("(none)").to_lowercase())
+            .or_default() += 1;
+        if let Some(msg) = record.path_get("msg").and_then(JsonValue::as_str) {
+            *stats.messages.entry(msg.to_string()).or_default() += 1;
+        }
+        if let Some(ms) = record.path_get("http.ms").and_then(JsonValue::as_f64) {
+            let path = match record.path_get("http.path") {
+                Some(JsonValue::String(path)) => path.to_string(),
+                Some(other) => stringify(other),
+                None => "?".to_string(),
+            };
+            stats.latencies.push((ms, path, line_number));
+        }
+    }
+    stats
+}
+
+fn report(stats: &Stats) -> String {
+    let mut out = format!(
+        "{} record(s), {} malformed line(s)\n",
+        stats.records,
+        stats.malformed.len()
+    );
+
+    out.push_str("\nby level:\n");
+    for (level, count) in most_frequent(&stats.levels, usize::MAX) {
+        out.push_str(&format!("  {:<8} {}\n", level, count));
+    }
+
+    out.push_str("\nmost frequent messages:\n");
+    for (msg, count) in most_frequent(&stats.messages, TOP) {
+        out.push_str(&format!("  {:>6}  {}\n", count, msg));
+    }
+
+    if !stats.latencies.is_empty() {
+        let mut sorted: Vec<f64> = stats.latencies.iter().map(|l| l.0).collect();
+        sorted.sort_by(f64::total_cmp);
+        out.push_str("\nlatency (ms):\n");
+        for (label, q) in [("p50", 0.5), ("p90", 0.9), ("p99", 0.99), ("max", 1.0)] {
+            out.push_str(&format!("  {:<4} {:.1}\n", label, percentile(&sorted, q)));
+        }
+
+        let mut slowest: Vec<&(f64, String, usize)> = stats.latencies.iter().collect();
+        slowest.sort_by(|a, b| b.0.total_cmp(&a.0));
+        out.push_str("\nslowest requests:\n");
+        for (ms, path, line) in slowest.into_iter().take(TOP) {
+            out.push_str(&format!("  {:>8.1} ms  {} (line {})\n", ms, path, line));
+        }
+    }
+
+    if !stats.malformed.is_empty() {
+        out.push_str("\nmalformed lines:\n");
+        for (line, error) in &stats.malformed {
+            // Each line is parsed on its own, so the error's line is always 1.
+            let column = error.position().column;
+            let message = error.to_string();
+            let message = message.rsplit_once(" at line ").map_or(&*message, |m| m.0);
+            out.push_str(&format!(
+                "  line {}, column {}: {}\n",
+                line, column, message
+            ));
+        }
+    }
+    out
+}
+
+// Entries by descending count, ties in name order.
+fn most_frequent(counts: &HashMap<String, usize>, limit: usize) -> Vec<(&str, usize)> {
+    let mut entries: Vec<(&str, usize)> = counts.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    entries.truncate(limit);
+    entries
+}
+
+// Nearest-rank percentile of sorted, non-empty samples.
+fn percentile(sorted: &[f64], q: f64) -> f64 {
+    let rank = (q * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
@@ -0,0 +1,160 @@
+use crate::{Failure, print};
+use std::hint::black_box;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use world::json::{
+    DocumentIndex, EventReader, JsonValue, MetricsHook, ParseMetrics, ParserOptions, Tape,
+    escape_pointer_token, extract_with_options, parse_with_options, stringify, stringify_pretty,
+    validate_with_options,
+};
+
+// Each operation is repeated until it has run for this long, or `MAX_RUNS`
+// times, whichever comes first.
+const BUDGET: Duration = Duration::from_millis(200);
+const MAX_RUNS: u32 = 10_000;
+
+/// Times the ways of reading and writing `input` and prints a report of the
+/// time per run, throughput and memory use of each.
+pub(crate) fn run(input: &str, options: ParserOptions, query: &str) -> Result<(), Failure> {
+    let metrics = Arc::new(Mutex::new(None));
+    let hook = {
+        let metrics = Arc::clone(&metrics);
+        MetricsHook::new(move |m: &ParseMetrics| *metrics.lock().unwrap() = Some(m.clone()))
+    };
+    let measured = ParserOptions {
+        metrics_hook: Some(hook),
+        ..options.clone()
+    };
+    let value = parse_with_options(input, measured).map_err(|e| Failure::Invalid(e.to_string()))?;
+    let parsed = metrics
+        .lock()
+        .unwrap()
+        .take()
+        .expect("the hook runs on every parse");
+    let index = DocumentIndex::new(&value);
+    let last = last_member(&value);
+
+    let mut lines = vec![
+        format!(
+            "input      {}, {} nodes, depth {}",
+            size(input.len()),
+            index.len(),
+            parsed.peak_depth
+        ),
+        format!(
+            "tree       ~{} in {} allocations",
+            size(value.heap_size()),
+            parsed.allocations
+        ),
+        String::new(),
+        format!(
+            "{:<28} {:>12} {:>14} {:>7}",
+            "operation", "time/run", "throughput", "runs"
+        ),
+    ];
+    let mut row = |name: &str, f: &mut dyn FnMut()| {
+        let (per_run, runs) = time(f);
+        let rate = input.len() as f64 / per_run.as_secs_f64();
+        lines.push(format!(
+            "{:<28} {:>12} {:>14} {:>7}",
+            name,
+            format!("{:.1?}", per_run),
+            format!("{}/s", size(rate as usize)),
+            runs
+        ));
+    };
+
+    row("parse (tree)", &mut || {
+        black_box(parse_with_options(input, options.clone()).ok());
+    });
+    #[cfg(feature = "arena")]
+    let mut arena = world::json::Arena::new();
+    #[cfg(feature = "arena")]
+    row("parse (arena)", &mut || {
+        arena.reset();
+        black_box(world::json::parse_in(input, &arena).is_ok());
+    });
+    #[cfg(feature = "rayon")]
+    row("parse (parallel)", &mut || {
+        let parse = || world::json::parse_array_parallel(input).is_ok();
+        black_box(world::json::with_parser_options(options.clone(), parse));
+    });
+    row("validate (no tree)", &mut || {
+        black_box(validate_with_options(input, options.clone()).ok());
+    });
+    row("events (streaming)", &mut || {
+        let mut reader = EventReader::with_options(input, options.clone());
+        while let Ok(Some(event)) = reader.next_event() {
+            black_box(event);
+        }
+    });
+    row(&format!("extract {} (lazy)", display(&last)), &mut || {
+        black_box(extract_with_options(input, &last, options.clone()).ok());
+    });
+    row(&format!("tape {} (two-stage)", display(&last)), &mut || {
+        let tape = Tape::parse(input);
+        black_box(
+            tape.ok()
+                .and_then(|t| t.pointer(&last).map(|v| v.raw().len())),
+        );
+    });
+    row("index pointers", &mut || {
+        black_box(DocumentIndex::new(&value).len());
+    });
+    row("stringify", &mut || {
+        black_box(stringify(&value));
+    });
+    row("stringify pretty", &mut || {
+        black_box(stringify_pretty(&value));
+    });
+    let mut result = Ok(0);
+    row(&format!("query {}", query), &mut || {
+        result = black_box(value.query(query).map(|m| m.len()));
+    });
+    let matches = result.map_err(|e| Failure::Error(format!("invalid query: {}", e)))?;
+    lines.push(format!("\nquery matched {} node(s)", matches));
+
+    print(&lines.join("\n"))
+}
+
+// Runs `f` repeatedly and returns the mean time per run with the number of
+// runs.
+fn time(f: &mut dyn FnMut()) -> (Duration, u32) {
+    let start = Instant::now();
+    let mut runs = 0;
+    while runs < MAX_RUNS && (runs == 0 || start.elapsed() < BUDGET) {
+        f();
+        runs += 1;
+    }
+    (start.elapsed() / runs, runs)
+}
+
+// The pointer of the last element or greatest key of the root, which a lazy
+// reader has to skip the most input to reach.
+fn last_member(value: &JsonValue) -> String {
+    match value {
+        JsonValue::Array(items) if !items.is_empty() => format!("/{}", items.len() - 1),
+        JsonValue::Object(members) => members
+            .keys()
+            .max()
+            .map(|key| format!("/{}", escape_pointer_token(key)))
+            .unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+fn display(pointer: &str) -> &str {
+    if pointer.is_empty() {
+        "(root)"
+    } else {
+        pointer
+    }
+}
+
+fn size(bytes: usize) -> String {
+    match bytes {
+        0..1024 => format!("{} B", bytes),
+        1024..1_048_576 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+    }
+}
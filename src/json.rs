@@ -1,72 +1,626 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt;
+use std::time::Instant;
 
-#[derive(Debug, PartialEq, Clone)]
+#[cfg(feature = "arena")]
+mod arena;
+mod ast;
+#[cfg(feature = "bson")]
+mod bson_interop;
+mod budget;
+mod builder;
+mod bytes;
+mod cache;
+mod canonical;
+mod checked;
+mod cmp;
+mod color;
+mod convert;
+mod csv;
+mod debug;
+mod decode;
+mod defaults;
+mod diff;
+mod documents;
+mod equivalent;
+mod events;
+mod exact;
+mod extract;
+mod fault;
+mod hash;
+mod html;
+mod hypermedia;
+mod incremental;
+mod index;
+mod intern;
+mod iter;
+mod json5;
+mod logging;
+mod map;
+mod merge;
+mod metrics;
+mod minify;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod nested;
+mod ord;
+mod outline;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod path;
+mod pointer;
+mod pool;
+mod provenance;
+mod query;
+mod raw;
+mod reader;
+mod recovery;
+mod redact;
+mod refs;
+mod render;
+mod schema;
+mod schema_eq;
+mod selfcheck;
+#[cfg(feature = "serde_json")]
+mod serde_interop;
+mod shared;
+mod simd;
+mod small_string;
+mod snapshot;
+mod sort;
+mod stringify;
+mod style;
+mod tape;
+mod three_way;
+#[cfg(feature = "toml")]
+mod toml;
+mod transform;
+mod try_from;
+mod ubjson;
+mod urlencoded;
+mod validate;
+mod view;
+mod visit;
+mod walk;
+mod writer;
+#[cfg(feature = "yaml")]
+mod yaml;
+
+#[cfg(feature = "toml")]
+pub use self::toml::{TomlError, from_toml, to_toml};
+#[cfg(feature = "arena")]
+pub use arena::{Arena, ArenaJsonValue, parse_in};
+pub use ast::to_ast_json;
+#[cfg(feature = "bson")]
+pub use bson_interop::{BsonError, from_bson_bytes, to_bson_bytes};
+pub use budget::{BudgetedOutput, stringify_within_budget};
+pub use builder::{JsonArray, JsonObject};
+pub use bytes::{Utf8Policy, parse_bytes};
+pub use cache::{CacheStats, ParseCache};
+pub use canonical::stringify_canonical;
+pub use checked::{CheckError, CheckedDocument};
+pub use color::{ColorScheme, stringify_colored, stringify_colored_with};
+pub use csv::{CsvError, CsvOptions, QuoteStyle, parse_csv, to_csv};
+pub use decode::{
+    BytesError, DecodeError, DecodeReader, Encoding, InputDecoder, Latin1Decoder, TableDecoder,
+    Utf8Decoder, Utf16Decoder, decode_detect_encoding, decode_to_string, detect_encoding,
+    parse_bytes_detect_encoding, parse_decoded,
+};
+pub use defaults::{
+    reset_defaults, set_default_parser_options, set_default_stringify_options, with_parser_options,
+    with_stringify_options,
+};
+pub use diff::{PatchError, PatchOp, apply_patch, diff};
+pub use documents::{Document, Documents, parse_documents, split_documents};
+pub use equivalent::EqOptions;
+pub use events::{EventReader, JsonEvent};
+pub use exact::{parse_i64_exact, parse_integer_exact, parse_u64_exact};
+pub use extract::{extract, extract_with_options};
+pub use fault::{FaultConfig, FaultInjector};
+pub use html::{HtmlOptions, render_html};
+pub use hypermedia::{HalLink, HalResource, JsonApiDocument, JsonApiResource, ResourceIdentifier};
+pub use incremental::{IncrementalDocument, TextEdit};
+pub use index::DocumentIndex;
+pub use intern::KeyInterner;
+pub use json5::{parse_json5, stringify_json5};
+#[cfg(feature = "tracing")]
+pub use logging::{JsonFieldVisitor, tracing_field};
+pub use logging::{LogValue, log_value};
+pub use map::{Map, MapEntry};
+pub use merge::MergeStrategy;
+pub use metrics::{MetricsHook, ParseMetrics};
+pub use minify::{minify, minify_with_options};
+#[cfg(feature = "mmap")]
+pub use mmap::{FileError, MappedFile, parse_file};
+pub use nested::{embed_nested_json, expand_nested_json};
+pub use outline::{OutlineSymbol, SymbolKind, outline};
+#[cfg(feature = "rayon")]
+pub use parallel::{parse_array_parallel, parse_ndjson_parallel};
+pub use path::PathError;
+pub use pointer::{PointerError, escape_pointer_token};
+pub use pool::{ParserPool, PooledParser, parse_pooled};
+pub use provenance::TrackedValue;
+pub use query::{Explanation, PredicateTrace, QueryError, StepTrace};
+pub use raw::RawJson;
+pub use reader::{ReadError, parse_reader};
+pub use recovery::{Recovered, parse_with_recovery, parse_with_recovery_options};
+pub use redact::redact;
+pub use refs::{RefError, RefResolver};
+pub use schema::{SchemaViolation, schema_violations};
+pub use schema_eq::schema_differences;
+pub use selfcheck::{SelfCheckCase, SelfCheckReport, self_check};
+pub use shared::SharedJsonValue;
+pub use small_string::JsonString;
+pub use snapshot::SnapshotError;
+pub use sort::{sort_arrays_by, sort_keys_recursive};
+pub use stringify::{
+    NewlineStyle, NonFinitePolicy, StringifyOptions, stringify, stringify_pretty, stringify_with,
+};
+pub use style::{FormatStyle, detect_style};
+pub use tape::{Tape, TapeValue};
+pub use three_way::{MergeConflict, MergeResult, merge3};
+pub use try_from::ConversionError;
+pub use ubjson::{UbjsonError, parse_ubjson, to_ubjson};
+pub use urlencoded::{FormError, parse_urlencoded, to_urlencoded};
+pub use validate::{validate, validate_with_options};
+pub use view::{JsonView, ViewError, ViewField, view_field};
+pub use visit::JsonVisitor;
+pub use walk::{Walk, WalkOrder};
+pub use writer::{JsonWriter, WriterError};
+#[cfg(feature = "yaml")]
+pub use yaml::{YamlError, from_yaml, to_yaml};
+
+#[derive(Clone)]
 pub enum JsonValue {
     Null,
     Bool(bool),
     Number(f64),
-    String(String),
+    /// A number kept as its source text, produced when
+    /// `ParserOptions::preserve_raw_numbers` is set. Serializers write the
+    /// text back unchanged, so no precision is lost.
+    RawNumber(JsonString),
+    /// Validated JSON text kept unparsed and written back verbatim.
+    Raw(RawJson),
+    /// Short strings are stored inline; see `JsonString`.
+    String(JsonString),
+    /// A string whose contents are not valid UTF-8, produced by
+    /// `parse_bytes` with `Utf8Policy::Bytes`. Serializers write it with
+    /// each invalid sequence replaced by U+FFFD.
+    Bytes(Box<[u8]>),
     Array(Vec<JsonValue>),
-    Object(HashMap<String, JsonValue>),
+    /// Boxed so that the map, the largest payload, does not set the size of
+    /// every scalar.
+    Object(Box<Map>),
+}
+
+/// A location in the input: a byte offset plus the 1-based line and column
+/// (in characters) it falls on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    /// Computes the line and column of a byte offset into `input`.
+    pub fn at(input: &str, offset: usize) -> Self {
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Position {
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum ParseError {
-    UnexpectedChar(char, usize),
-    UnexpectedEndOfInput,
-    InvalidNumber(String),
-    InvalidEscape(String),
-    InvalidUnicodeEscape(String),
+    UnexpectedChar(char, Position),
+    UnexpectedEndOfInput(Position),
+    InvalidNumber(String, Position),
+    InvalidEscape(String, Position),
+    InvalidUnicodeEscape(String, Position),
+    UnescapedControlChar(char, Position),
+    DuplicateKey(String, Position),
+    /// Arrays and objects nested deeper than `ParserOptions::max_depth`
+    /// allows; the depth is the limit that was exceeded.
+    TooDeep(usize, Position),
+}
+
+impl ParseError {
+    pub fn position(&self) -> Position {
+        match self {
+            ParseError::UnexpectedChar(_, pos)
+            | ParseError::UnexpectedEndOfInput(pos)
+            | ParseError::InvalidNumber(_, pos)
+            | ParseError::InvalidEscape(_, pos)
+            | ParseError::InvalidUnicodeEscape(_, pos)
+            | ParseError::UnescapedControlChar(_, pos)
+            | ParseError::DuplicateKey(_, pos)
+            | ParseError::TooDeep(_, pos) => *pos,
+        }
+    }
+
+    pub(crate) fn position_mut(&mut self) -> &mut Position {
+        match self {
+            ParseError::UnexpectedChar(_, pos)
+            | ParseError::UnexpectedEndOfInput(pos)
+            | ParseError::InvalidNumber(_, pos)
+            | ParseError::InvalidEscape(_, pos)
+            | ParseError::InvalidUnicodeEscape(_, pos)
+            | ParseError::UnescapedControlChar(_, pos)
+            | ParseError::DuplicateKey(_, pos)
+            | ParseError::TooDeep(_, pos) => pos,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedChar(c, pos) => {
+                write!(f, "unexpected character {:?} at {}", c, pos)
+            }
+            ParseError::UnexpectedEndOfInput(pos) => {
+                write!(f, "unexpected end of input at {}", pos)
+            }
+            ParseError::InvalidNumber(s, pos) => write!(f, "invalid number {:?} at {}", s, pos),
+            ParseError::InvalidEscape(s, pos) => write!(f, "invalid escape {:?} at {}", s, pos),
+            ParseError::InvalidUnicodeEscape(s, pos) => {
+                write!(f, "invalid unicode escape \"\\\\u{}\" at {}", s, pos)
+            }
+            ParseError::UnescapedControlChar(c, pos) => {
+                write!(
+                    f,
+                    "unescaped control character {:?} in string at {}",
+                    c, pos
+                )
+            }
+            ParseError::DuplicateKey(key, pos) => write!(f, "duplicate key {:?} at {}", key, pos),
+            ParseError::TooDeep(limit, pos) => {
+                write!(f, "nesting deeper than {} levels at {}", limit, pos)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum StringifyError {
+    /// NaN or an infinity, which JSON cannot represent.
+    NonFiniteNumber(f64),
+    /// The output of `stringify_within_budget` is `size` bytes even with
+    /// every droppable path left out.
+    OverBudget { size: usize, budget: usize },
+}
+
+impl fmt::Display for StringifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StringifyError::NonFiniteNumber(n) => {
+                write!(f, "{} cannot be represented in JSON", n)
+            }
+            StringifyError::OverBudget { size, budget } => {
+                write!(f, "output is {} bytes, over the budget of {}", size, budget)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StringifyError {}
+
+/// What to do when an object contains the same key more than once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeyPolicy {
+    FirstWins,
+    #[default]
+    LastWins,
+    /// Fail with `ParseError::DuplicateKey` at the repeated key.
+    Error,
+}
+
+/// Selects object members by key name, at every nesting level.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum KeyFilter {
+    #[default]
+    All,
+    /// Keep only members whose key is in the set.
+    Include(HashSet<String>),
+    /// Drop members whose key is in the set.
+    Exclude(HashSet<String>),
+}
+
+impl KeyFilter {
+    pub fn include<I: IntoIterator<Item = S>, S: Into<String>>(keys: I) -> Self {
+        KeyFilter::Include(keys.into_iter().map(Into::into).collect())
+    }
+
+    pub fn exclude<I: IntoIterator<Item = S>, S: Into<String>>(keys: I) -> Self {
+        KeyFilter::Exclude(keys.into_iter().map(Into::into).collect())
+    }
+
+    pub fn allows(&self, key: &str) -> bool {
+        match self {
+            KeyFilter::All => true,
+            KeyFilter::Include(keys) => keys.contains(key),
+            KeyFilter::Exclude(keys) => !keys.contains(key),
+        }
+    }
+}
+
+/// The nesting limit used when `ParserOptions::max_depth` is `None`.
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+/// Extensions to standard JSON that the parser may accept. All are off by
+/// default, so the default parser accepts exactly the RFC 8259 grammar.
+#[derive(Debug, Clone, Default)]
+pub struct ParserOptions {
+    /// Accept `0x`-prefixed hexadecimal integers such as `0xFF`.
+    pub allow_hex_numbers: bool,
+    /// Accept `0o`-prefixed octal integers such as `0o17`.
+    pub allow_octal_numbers: bool,
+    /// Accept `0b`-prefixed binary integers such as `0b1010`.
+    pub allow_binary_numbers: bool,
+    /// Accept a trailing comma before `]` or `}`, as in `[1, 2,]`.
+    pub allow_trailing_commas: bool,
+    /// Treat `// line` and `/* block */` comments as whitespace.
+    pub allow_comments: bool,
+    /// Accept `'single-quoted'` strings.
+    pub allow_single_quotes: bool,
+    /// Accept identifiers such as `name` as object keys without quotes.
+    pub allow_unquoted_keys: bool,
+    /// Accept the `NaN` and `Infinity` literals, optionally signed.
+    pub allow_nan_infinity: bool,
+    /// Accept a leading `+`, and a leading or trailing decimal point as in
+    /// `.5` and `5.`.
+    pub allow_relaxed_numbers: bool,
+    /// Accept integer parts with leading zeros such as `0123`.
+    pub allow_leading_zeros: bool,
+    /// Accept raw control characters (U+0000 to U+001F) inside strings.
+    pub allow_control_characters: bool,
+    /// Treat any Unicode whitespace as insignificant, not just space, tab,
+    /// line feed and carriage return.
+    pub allow_unicode_whitespace: bool,
+    /// Decode an unpaired UTF-16 surrogate escape such as `\\ud800` to
+    /// U+FFFD instead of rejecting it.
+    pub allow_lone_surrogates: bool,
+    /// Accept the JSON5 escapes `\'`, `\v`, `\0`, `\xHH`, identity escapes,
+    /// and backslash-newline line continuations in strings.
+    pub allow_json5_escapes: bool,
+    pub duplicate_keys: DuplicateKeyPolicy,
+    /// Which object members to keep. Dropped members are skipped without
+    /// being parsed.
+    pub key_filter: KeyFilter,
+    /// Deepest array/object nesting to build; containers below it are kept
+    /// as `JsonValue::String` holding their original JSON text. The
+    /// top-level container has depth 1.
+    pub materialize_depth: Option<usize>,
+    /// Deepest array/object nesting accepted; anything deeper fails with
+    /// `ParseError::TooDeep` rather than exhausting the stack. `None` means
+    /// `DEFAULT_MAX_DEPTH`. The top-level container has depth 1.
+    pub max_depth: Option<usize>,
+    /// Keep decimal numbers as `JsonValue::RawNumber` holding their original
+    /// text instead of converting them to `f64`. Spellings only accepted by
+    /// the lenient options (`+1`, `.5`, `5.`, `007`) are normalized to
+    /// standard JSON.
+    pub preserve_raw_numbers: bool,
+    /// Replace string values holding a JSON object or array with the parsed
+    /// subtree, as `expand_nested_json` does with no paths.
+    pub expand_nested_json: bool,
+    /// Called with timing and size measurements after every parse.
+    pub metrics_hook: Option<MetricsHook>,
 }
 
-pub struct Parser {
-    input: String,
+impl ParserOptions {
+    /// Options with every extension enabled.
+    pub fn lenient() -> Self {
+        ParserOptions {
+            allow_hex_numbers: true,
+            allow_octal_numbers: true,
+            allow_binary_numbers: true,
+            allow_trailing_commas: true,
+            allow_comments: true,
+            allow_single_quotes: true,
+            allow_unquoted_keys: true,
+            allow_nan_infinity: true,
+            allow_relaxed_numbers: true,
+            allow_leading_zeros: true,
+            allow_control_characters: true,
+            allow_unicode_whitespace: true,
+            allow_lone_surrogates: true,
+            allow_json5_escapes: true,
+            duplicate_keys: DuplicateKeyPolicy::LastWins,
+            key_filter: KeyFilter::All,
+            materialize_depth: None,
+            max_depth: None,
+            preserve_raw_numbers: false,
+            expand_nested_json: false,
+            metrics_hook: None,
+        }
+    }
+
+    /// Options accepting the JSON5 dialect. Octal and binary literals and
+    /// leading zeros are not part of JSON5 and stay disabled.
+    pub fn json5() -> Self {
+        ParserOptions {
+            allow_octal_numbers: false,
+            allow_binary_numbers: false,
+            allow_leading_zeros: false,
+            ..ParserOptions::lenient()
+        }
+    }
+}
+
+pub struct Parser<'a> {
+    input: Cow<'a, str>,
     pos: usize,
+    options: ParserOptions,
+    depth: usize,
+    peak_depth: usize,
+    allocations: usize,
+    // Holds strings with escapes while they are decoded, reused across
+    // strings and, through `with_scratch`, across parsers.
+    scratch: String,
 }
 
-impl Parser {
-    pub fn new(input: &str) -> Self {
+impl<'a> Parser<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Parser::with_options(input, ParserOptions::current())
+    }
+
+    /// Creates a parser that reads `input` in place, without copying it.
+    pub fn with_options(input: &'a str, options: ParserOptions) -> Self {
         Parser {
-            input: input.to_string(),
+            input: Cow::Borrowed(input),
             pos: 0,
+            options,
+            depth: 0,
+            peak_depth: 0,
+            allocations: 0,
+            scratch: String::new(),
         }
     }
 
+    // Hands the parser a decoding buffer kept from an earlier one, so its
+    // capacity is reused; `into_scratch` gives it back.
+    pub(crate) fn with_scratch(mut self, scratch: String) -> Self {
+        self.scratch = scratch;
+        self
+    }
+
+    pub(crate) fn into_scratch(self) -> String {
+        self.scratch
+    }
+
+    // Points the parser at a new document, keeping its decoding buffer.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn reset(&mut self, input: &'a str) {
+        self.input = Cow::Borrowed(input);
+        self.pos = 0;
+        self.depth = 0;
+        self.peak_depth = 0;
+        self.allocations = 0;
+    }
+
     pub fn parse(&mut self) -> Result<JsonValue, ParseError> {
+        let Some(hook) = self.options.metrics_hook.clone() else {
+            return self.parse_document();
+        };
+
+        let started = Instant::now();
+        let result = self.parse_document();
+        hook.call(&self.metrics(started, result.is_ok()));
+        result
+    }
+
+    fn metrics(&self, started: Instant, success: bool) -> ParseMetrics {
+        ParseMetrics {
+            duration: started.elapsed(),
+            bytes: self.pos,
+            allocations: self.allocations,
+            peak_depth: self.peak_depth,
+            success,
+        }
+    }
+
+    fn parse_document(&mut self) -> Result<JsonValue, ParseError> {
         self.skip_whitespace();
-        let value = self.parse_value()?;
+        let mut value = self.parse_value()?;
         self.skip_whitespace();
         if self.pos < self.input.len() {
-            return Err(ParseError::UnexpectedChar(
-                self.current_char().unwrap(),
-                self.pos,
-            ));
+            return Err(self.unexpected());
+        }
+        if self.options.expand_nested_json {
+            expand_nested_json(&mut value, &[]);
         }
         Ok(value)
     }
 
+    fn position(&self, offset: usize) -> Position {
+        Position::at(&self.input, offset)
+    }
+
+    // Error for whatever is at the current position, which the caller could
+    // not accept.
+    fn unexpected(&self) -> ParseError {
+        match self.current_char() {
+            Some(c) => ParseError::UnexpectedChar(c, self.position(self.pos)),
+            None => self.end_of_input(),
+        }
+    }
+
+    fn end_of_input(&self) -> ParseError {
+        ParseError::UnexpectedEndOfInput(self.position(self.input.len()))
+    }
+
     fn current_char(&self) -> Option<char> {
-        self.input.chars().nth(self.pos)
+        self.input[self.pos..].chars().next()
     }
 
     fn peek_char(&self) -> Option<char> {
-        self.input.chars().nth(self.pos + 1)
+        self.input[self.pos..].chars().nth(1)
     }
 
     fn advance(&mut self) {
-        self.pos += 1;
+        if let Some(c) = self.current_char() {
+            self.pos += c.len_utf8();
+        }
     }
 
     fn skip_whitespace(&mut self) {
+        self.pos = simd::skip_whitespace(self.input.as_bytes(), self.pos);
         while let Some(c) = self.current_char() {
-            if c.is_whitespace() {
+            if is_json_whitespace(c) || (self.options.allow_unicode_whitespace && c.is_whitespace())
+            {
                 self.advance();
+            } else if c == '/' && self.options.allow_comments && self.skip_comment() {
+                continue;
             } else {
                 break;
             }
         }
     }
 
+    // Skips a comment starting at the current '/'. An unterminated block
+    // comment is left in place so the caller reports it as unexpected input.
+    fn skip_comment(&mut self) -> bool {
+        match self.peek_char() {
+            Some('/') => {
+                while let Some(c) = self.current_char() {
+                    if c == '\n' {
+                        break;
+                    }
+                    self.advance();
+                }
+                true
+            }
+            Some('*') => {
+                let start = self.pos;
+                self.pos += 2;
+                while let Some(c) = self.current_char() {
+                    if c == '*' && self.peek_char() == Some('/') {
+                        self.pos += 2;
+                        return true;
+                    }
+                    self.advance();
+                }
+                self.pos = start;
+                false
+            }
+            _ => false,
+        }
+    }
+
     fn parse_value(&mut self) -> Result<JsonValue, ParseError> {
         self.skip_whitespace();
 
@@ -75,12 +629,51 @@ impl Parser {
             Some('t') => self.parse_true(),
             Some('f') => self.parse_false(),
             Some('"') => self.parse_string(),
-            Some('[') => self.parse_array(),
-            Some('{') => self.parse_object(),
+            Some('\'') if self.options.allow_single_quotes => self.parse_string(),
+            Some('[') => self.parse_nested(Self::parse_array),
+            Some('{') => self.parse_nested(Self::parse_object),
+            Some('N') if self.options.allow_nan_infinity => self.parse_keyword("NaN", f64::NAN),
+            Some('I') if self.options.allow_nan_infinity => {
+                self.parse_keyword("Infinity", f64::INFINITY)
+            }
             Some(c) if c.is_ascii_digit() || c == '-' => self.parse_number(),
-            Some(c) => Err(ParseError::UnexpectedChar(c, self.pos)),
-            None => Err(ParseError::UnexpectedEndOfInput),
+            Some('+' | '.') if self.options.allow_relaxed_numbers => self.parse_number(),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn parse_nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<JsonValue, ParseError>,
+    ) -> Result<JsonValue, ParseError> {
+        if self
+            .options
+            .materialize_depth
+            .is_some_and(|max| self.depth >= max)
+        {
+            let start = self.pos;
+            self.skip_value_structural()?;
+            self.allocations += 1;
+            return Ok(JsonValue::String(self.input[start..self.pos].into()));
+        }
+
+        self.enter()?;
+        self.allocations += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    // Goes one container deeper, failing at the opening bracket if that
+    // exceeds the depth limit. The caller decrements `depth` when done.
+    pub(crate) fn enter(&mut self) -> Result<(), ParseError> {
+        let limit = self.options.max_depth.unwrap_or(DEFAULT_MAX_DEPTH);
+        if self.depth >= limit {
+            return Err(ParseError::TooDeep(limit, self.position(self.pos)));
         }
+        self.depth += 1;
+        self.peak_depth = self.peak_depth.max(self.depth);
+        Ok(())
     }
 
     fn parse_null(&mut self) -> Result<JsonValue, ParseError> {
@@ -88,10 +681,7 @@ impl Parser {
             self.pos += 4;
             Ok(JsonValue::Null)
         } else {
-            Err(ParseError::UnexpectedChar(
-                self.current_char().unwrap(),
-                self.pos,
-            ))
+            Err(self.unexpected())
         }
     }
 
@@ -100,10 +690,7 @@ impl Parser {
             self.pos += 4;
             Ok(JsonValue::Bool(true))
         } else {
-            Err(ParseError::UnexpectedChar(
-                self.current_char().unwrap(),
-                self.pos,
-            ))
+            Err(self.unexpected())
         }
     }
 
@@ -112,24 +699,71 @@ impl Parser {
             self.pos += 5;
             Ok(JsonValue::Bool(false))
         } else {
-            Err(ParseError::UnexpectedChar(
-                self.current_char().unwrap(),
-                self.pos,
-            ))
+            Err(self.unexpected())
+        }
+    }
+
+    fn parse_keyword(&mut self, word: &str, value: f64) -> Result<JsonValue, ParseError> {
+        if self.input[self.pos..].starts_with(word) {
+            self.pos += word.len();
+            Ok(JsonValue::Number(value))
+        } else {
+            Err(self.unexpected())
         }
     }
 
     fn parse_string(&mut self) -> Result<JsonValue, ParseError> {
+        // Short strings are stored inline without any allocation.
+        let s = JsonString::from(self.string_text()?);
+        self.allocations += usize::from(!s.is_inline());
+        Ok(JsonValue::String(s))
+    }
+
+    fn parse_string_literal(&mut self) -> Result<String, ParseError> {
+        self.allocations += 1;
+        Ok(self.string_text()?.to_string())
+    }
+
+    // Reads a quoted string and returns its text: a slice of the input
+    // when there is nothing to unescape, or else the decoded text in
+    // `scratch`.
+    fn string_text(&mut self) -> Result<&str, ParseError> {
+        let quote = self.current_char().unwrap();
+        let end = simd::find_string_special(self.input.as_bytes(), self.pos + 1, quote as u8);
+        if self.input.as_bytes().get(end) == Some(&(quote as u8)) {
+            let start = self.pos + 1;
+            self.pos = end + 1;
+            return Ok(&self.input[start..end]);
+        }
+        let mut scratch = std::mem::take(&mut self.scratch);
+        scratch.clear();
+        let result = self.read_string_literal(&mut scratch);
+        self.scratch = scratch;
+        result?;
+        Ok(&self.scratch)
+    }
+
+    // Reads a quoted string, passing its decoded characters to `result`.
+    fn read_string_literal<S: CharSink>(&mut self, result: &mut S) -> Result<(), ParseError> {
+        let quote = self.current_char().unwrap();
         self.advance(); // Skip opening quote
-        let mut result = String::new();
 
-        while let Some(c) = self.current_char() {
+        loop {
+            // Copy the run of plain characters up to the next quote,
+            // backslash or control character in one step.
+            let end = simd::find_string_special(self.input.as_bytes(), self.pos, quote as u8);
+            if end > self.pos {
+                result.push_str(&self.input[self.pos..end]);
+                self.pos = end;
+            }
+            let Some(c) = self.current_char() else { break };
             match c {
-                '"' => {
+                _ if c == quote => {
                     self.advance();
-                    return Ok(JsonValue::String(result));
+                    return Ok(());
                 }
                 '\\' => {
+                    let escape_start = self.pos;
                     self.advance();
                     match self.current_char() {
                         Some('"') => result.push('"'),
@@ -142,31 +776,27 @@ impl Parser {
                         Some('t') => result.push('\t'),
                         Some('u') => {
                             self.advance();
-                            let mut hex = String::new();
-                            for _ in 0..4 {
-                                if let Some(h) = self.current_char() {
-                                    hex.push(h);
-                                    self.advance();
-                                } else {
-                                    return Err(ParseError::InvalidUnicodeEscape(hex));
-                                }
-                            }
-                            if let Ok(code_point) = u32::from_str_radix(&hex, 16) {
-                                if let Some(ch) = char::from_u32(code_point) {
-                                    result.push(ch);
-                                } else {
-                                    return Err(ParseError::InvalidUnicodeEscape(hex));
-                                }
-                            } else {
-                                return Err(ParseError::InvalidUnicodeEscape(hex));
-                            }
+                            let ch = self.parse_unicode_escape()?;
+                            result.push(ch);
+                            continue;
+                        }
+                        Some(e) if self.options.allow_json5_escapes => {
+                            self.parse_json5_escape(e, result)?;
                             continue;
                         }
-                        Some(_) => return Err(ParseError::InvalidEscape(format!("\\{}", c))),
-                        None => return Err(ParseError::UnexpectedEndOfInput),
+                        Some(e) => {
+                            return Err(ParseError::InvalidEscape(
+                                format!("\\{}", e),
+                                self.position(escape_start),
+                            ));
+                        }
+                        None => return Err(self.end_of_input()),
                     }
                     self.advance();
                 }
+                _ if c < '\u{20}' && !self.options.allow_control_characters => {
+                    return Err(ParseError::UnescapedControlChar(c, self.position(self.pos)));
+                }
                 _ => {
                     result.push(c);
                     self.advance();
@@ -174,53 +804,281 @@ impl Parser {
             }
         }
 
-        Err(ParseError::UnexpectedEndOfInput)
+        Err(self.end_of_input())
+    }
+
+    // Parses the digits of a `\\u` escape, combining a UTF-16 surrogate pair
+    // written as two consecutive escapes into one character.
+    fn parse_unicode_escape(&mut self) -> Result<char, ParseError> {
+        let escape_start = self.pos - 2; // The `\\u` has been consumed
+        let hex = self.parse_hex4(escape_start)?;
+        let code_point = u32::from_str_radix(&hex, 16).unwrap();
+
+        match code_point {
+            0xD800..=0xDBFF => {
+                let rest = &self.input[self.pos..];
+                if rest.starts_with("\\u") {
+                    let low = u32::from_str_radix(rest.get(2..6).unwrap_or(""), 16);
+                    if let Ok(low @ 0xDC00..=0xDFFF) = low {
+                        self.pos += 6;
+                        let combined = 0x10000 + ((code_point - 0xD800) << 10) + (low - 0xDC00);
+                        return Ok(char::from_u32(combined).unwrap());
+                    }
+                }
+                self.lone_surrogate(hex, escape_start)
+            }
+            0xDC00..=0xDFFF => self.lone_surrogate(hex, escape_start),
+            _ => Ok(char::from_u32(code_point).unwrap()),
+        }
+    }
+
+    fn parse_hex4(&mut self, escape_start: usize) -> Result<String, ParseError> {
+        let mut hex = String::new();
+        for _ in 0..4 {
+            match self.current_char() {
+                Some(h) if h.is_ascii_hexdigit() => {
+                    hex.push(h);
+                    self.advance();
+                }
+                Some(h) => {
+                    hex.push(h);
+                    return Err(ParseError::InvalidUnicodeEscape(
+                        hex,
+                        self.position(escape_start),
+                    ));
+                }
+                None => {
+                    return Err(ParseError::InvalidUnicodeEscape(
+                        hex,
+                        self.position(escape_start),
+                    ));
+                }
+            }
+        }
+        Ok(hex)
+    }
+
+    fn lone_surrogate(&self, hex: String, escape_start: usize) -> Result<char, ParseError> {
+        if self.options.allow_lone_surrogates {
+            Ok(char::REPLACEMENT_CHARACTER)
+        } else {
+            Err(ParseError::InvalidUnicodeEscape(
+                hex,
+                self.position(escape_start),
+            ))
+        }
+    }
+
+    // Handles an escape that only JSON5 allows; `e` is the character after
+    // the backslash and is consumed along with any following digits.
+    fn parse_json5_escape<S: CharSink>(
+        &mut self,
+        e: char,
+        result: &mut S,
+    ) -> Result<(), ParseError> {
+        let escape_start = self.pos - 1; // The backslash has been consumed
+        self.advance();
+        match e {
+            'v' => result.push('\x0b'),
+            '0' if !self.current_char().is_some_and(|c| c.is_ascii_digit()) => result.push('\0'),
+            'x' => {
+                let start = self.pos;
+                for _ in 0..2 {
+                    match self.current_char() {
+                        Some(h) if h.is_ascii_hexdigit() => self.advance(),
+                        _ => {
+                            return Err(ParseError::InvalidEscape(
+                                format!("\\x{}", &self.input[start..self.pos]),
+                                self.position(escape_start),
+                            ));
+                        }
+                    }
+                }
+                let code = u8::from_str_radix(&self.input[start..self.pos], 16).unwrap();
+                result.push(code as char);
+            }
+            '\r' => {
+                if self.current_char() == Some('\n') {
+                    self.advance();
+                }
+            }
+            '\n' | '\u{2028}' | '\u{2029}' => {}
+            c if c.is_ascii_digit() => {
+                return Err(ParseError::InvalidEscape(
+                    format!("\\{}", c),
+                    self.position(escape_start),
+                ));
+            }
+            c => result.push(c),
+        }
+        Ok(())
     }
 
     fn parse_number(&mut self) -> Result<JsonValue, ParseError> {
         let start = self.pos;
 
-        if self.current_char() == Some('-') {
-            self.advance();
+        match self.current_char() {
+            Some('-') => self.advance(),
+            Some('+') if self.options.allow_relaxed_numbers => self.advance(),
+            _ => {}
         }
 
-        while let Some(c) = self.current_char() {
-            if c.is_ascii_digit() {
-                self.advance();
+        if self.options.allow_nan_infinity {
+            let sign = if self.input[start..].starts_with('-') {
+                -1.0
             } else {
-                break;
+                1.0
+            };
+            match self.current_char() {
+                Some('I') => return self.parse_keyword("Infinity", sign * f64::INFINITY),
+                Some('N') => return self.parse_keyword("NaN", f64::NAN),
+                _ => {}
+            }
+        }
+
+        if self.current_char() == Some('0') {
+            let radix = match self.peek_char() {
+                Some('x' | 'X') if self.options.allow_hex_numbers => Some(16),
+                Some('o' | 'O') if self.options.allow_octal_numbers => Some(8),
+                Some('b' | 'B') if self.options.allow_binary_numbers => Some(2),
+                _ => None,
+            };
+            if let Some(radix) = radix {
+                return self.parse_radix_integer(start, radix);
             }
         }
 
+        let int_start = self.pos;
+        let int_digits = self.skip_digits();
+        let mut fraction_digits = None;
+
         if self.current_char() == Some('.') {
             self.advance();
-            while let Some(c) = self.current_char() {
-                if c.is_ascii_digit() {
-                    self.advance();
-                } else {
-                    break;
-                }
-            }
+            fraction_digits = Some(self.skip_digits());
         }
 
+        let mut exponent_digits = None;
         if self.current_char() == Some('e') || self.current_char() == Some('E') {
             self.advance();
             if self.current_char() == Some('+') || self.current_char() == Some('-') {
                 self.advance();
             }
-            while let Some(c) = self.current_char() {
-                if c.is_ascii_digit() {
-                    self.advance();
-                } else {
-                    break;
-                }
-            }
+            exponent_digits = Some(self.skip_digits());
         }
 
         let num_str = &self.input[start..self.pos];
+        let relaxed = self.options.allow_relaxed_numbers;
+        let well_formed = exponent_digits != Some(0)
+            && match (int_digits, fraction_digits) {
+                // `.5` and `5.` are JSON5 forms; `-` and `.` are never numbers
+                (0, Some(f)) => relaxed && f > 0,
+                (0, None) => false,
+                (_, Some(0)) => relaxed,
+                _ => true,
+            };
+        let leading_zero = int_digits > 1 && self.input[int_start..].starts_with('0');
+        if !well_formed || (leading_zero && !self.options.allow_leading_zeros) {
+            return Err(ParseError::InvalidNumber(
+                num_str.to_string(),
+                self.position(start),
+            ));
+        }
+
+        if self.options.preserve_raw_numbers {
+            self.scratch.clear();
+            normalize_number(num_str, &mut self.scratch);
+            return Ok(JsonValue::RawNumber(self.scratch.as_str().into()));
+        }
+
         match num_str.parse::<f64>() {
             Ok(num) => Ok(JsonValue::Number(num)),
-            Err(_) => Err(ParseError::InvalidNumber(num_str.to_string())),
+            Err(_) => Err(ParseError::InvalidNumber(
+                num_str.to_string(),
+                self.position(start),
+            )),
+        }
+    }
+
+    fn skip_digits(&mut self) -> usize {
+        let start = self.pos;
+        while let Some(c) = self.current_char() {
+            if c.is_ascii_digit() {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        self.pos - start
+    }
+
+    fn parse_radix_integer(&mut self, start: usize, radix: u32) -> Result<JsonValue, ParseError> {
+        let negative = self.input[start..].starts_with('-');
+        self.pos += 2; // Skip '0' and the radix prefix
+        let digits_start = self.pos;
+
+        while let Some(c) = self.current_char() {
+            if c.is_digit(radix) {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+
+        let digits = &self.input[digits_start..self.pos];
+        match u64::from_str_radix(digits, radix) {
+            Ok(n) if negative => Ok(JsonValue::Number(-(n as f64))),
+            Ok(n) => Ok(JsonValue::Number(n as f64)),
+            Err(_) => Err(ParseError::InvalidNumber(
+                self.input[start..self.pos].to_string(),
+                self.position(start),
+            )),
+        }
+    }
+
+    // Moves past the next value by scanning structural characters only:
+    // strings are not unescaped and scalars are not validated.
+    fn skip_value_structural(&mut self) -> Result<(), ParseError> {
+        let mut depth = 0usize;
+        loop {
+            self.skip_whitespace();
+            let pos = self.pos;
+            let bytes = self.input.as_bytes();
+            match bytes.get(pos) {
+                None => return Err(self.end_of_input()),
+                Some(b'[' | b'{') => {
+                    depth += 1;
+                    self.pos += 1;
+                    continue;
+                }
+                Some(b']' | b'}') if depth > 0 => {
+                    depth -= 1;
+                    self.pos += 1;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                    continue;
+                }
+                Some(b',' | b':') if depth > 0 => {
+                    self.pos += 1;
+                    continue;
+                }
+                Some(b'"') => {
+                    self.pos = scan_string(bytes, pos, b'"').ok_or_else(|| self.end_of_input())?;
+                }
+                Some(b'\'') if self.options.allow_single_quotes => {
+                    self.pos = scan_string(bytes, pos, b'\'').ok_or_else(|| self.end_of_input())?;
+                }
+                Some(_) => {
+                    let len = simd::find_delimiter(bytes, pos) - pos;
+                    if len == 0 {
+                        return Err(self.unexpected());
+                    }
+                    self.pos += len;
+                }
+            }
+            if depth == 0 {
+                return Ok(());
+            }
         }
     }
 
@@ -244,14 +1102,38 @@ impl Parser {
                 Some(',') => {
                     self.advance();
                     self.skip_whitespace();
+                    if self.options.allow_trailing_commas && self.current_char() == Some(']') {
+                        self.advance();
+                        return Ok(JsonValue::Array(elements));
+                    }
                 }
                 Some(']') => {
                     self.advance();
                     return Ok(JsonValue::Array(elements));
                 }
-                Some(c) => return Err(ParseError::UnexpectedChar(c, self.pos)),
-                None => return Err(ParseError::UnexpectedEndOfInput),
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    fn parse_key(&mut self) -> Result<String, ParseError> {
+        self.skip_whitespace();
+
+        match self.current_char() {
+            Some('"') => self.parse_string_literal(),
+            Some('\'') if self.options.allow_single_quotes => self.parse_string_literal(),
+            Some(c) if self.options.allow_unquoted_keys && is_identifier_start(c) => {
+                let start = self.pos;
+                while let Some(c) = self.current_char() {
+                    if is_identifier_start(c) || c.is_alphanumeric() {
+                        self.advance();
+                    } else {
+                        break;
+                    }
+                }
+                Ok(self.input[start..self.pos].to_string())
             }
+            _ => Err(self.unexpected()),
         }
     }
 
@@ -259,37 +1141,32 @@ impl Parser {
         self.advance(); // Skip '{'
         self.skip_whitespace();
 
-        let mut map = HashMap::new();
+        let mut map = Map::new();
 
         if self.current_char() == Some('}') {
             self.advance();
-            return Ok(JsonValue::Object(map));
+            return Ok(JsonValue::Object(Box::new(map)));
         }
 
         loop {
-            let key = match self.parse_value()? {
-                JsonValue::String(s) => s,
-                _ => {
-                    return Err(ParseError::UnexpectedChar(
-                        self.current_char().unwrap(),
-                        self.pos,
-                    ));
-                }
-            };
+            self.skip_whitespace();
+            let key_pos = self.pos;
+            let key = self.parse_key()?;
 
             self.skip_whitespace();
 
-            if self.current_char() != Some(':') {
-                return Err(ParseError::UnexpectedChar(
-                    self.current_char().unwrap(),
-                    self.pos,
-                ));
+            match self.current_char() {
+                Some(':') => self.advance(),
+                _ => return Err(self.unexpected()),
             }
-            self.advance();
 
             self.skip_whitespace();
-            let value = self.parse_value()?;
-            map.insert(key, value);
+            if !self.options.key_filter.allows(&key) {
+                self.skip_value_structural()?;
+            } else {
+                let value = self.parse_value()?;
+                self.insert_member(&mut map, key, key_pos, value)?;
+            }
 
             self.skip_whitespace();
 
@@ -297,81 +1174,162 @@ impl Parser {
                 Some(',') => {
                     self.advance();
                     self.skip_whitespace();
+                    if self.options.allow_trailing_commas && self.current_char() == Some('}') {
+                        self.advance();
+                        return Ok(JsonValue::Object(Box::new(map)));
+                    }
                 }
                 Some('}') => {
                     self.advance();
-                    return Ok(JsonValue::Object(map));
+                    return Ok(JsonValue::Object(Box::new(map)));
                 }
-                Some(c) => return Err(ParseError::UnexpectedChar(c, self.pos)),
-                None => return Err(ParseError::UnexpectedEndOfInput),
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    fn insert_member(
+        &self,
+        map: &mut Map,
+        key: String,
+        key_pos: usize,
+        value: JsonValue,
+    ) -> Result<(), ParseError> {
+        match self.options.duplicate_keys {
+            DuplicateKeyPolicy::LastWins => {
+                map.insert(key, value);
+            }
+            DuplicateKeyPolicy::FirstWins => {
+                map.entry(key).or_insert(value);
+            }
+            DuplicateKeyPolicy::Error if map.contains_key(&key) => {
+                return Err(ParseError::DuplicateKey(key, self.position(key_pos)));
+            }
+            DuplicateKeyPolicy::Error => {
+                map.insert(key, value);
             }
         }
+        Ok(())
     }
 }
 
+// Receives the decoded characters of a string literal.
+trait CharSink {
+    fn push(&mut self, c: char);
+
+    fn push_str(&mut self, s: &str) {
+        s.chars().for_each(|c| self.push(c));
+    }
+}
+
+impl CharSink for String {
+    fn push(&mut self, c: char) {
+        String::push(self, c);
+    }
+
+    fn push_str(&mut self, s: &str) {
+        String::push_str(self, s);
+    }
+}
+
+// Returns the position just past the string starting with `quote` at `start`.
+fn scan_string(bytes: &[u8], start: usize, quote: u8) -> Option<usize> {
+    let mut i = start + 1;
+    loop {
+        i = simd::find_string_special(bytes, i, quote);
+        match bytes.get(i)? {
+            b'\\' => i += 2,
+            b if *b == quote => return Some(i + 1),
+            _ => i += 1,
+        }
+    }
+}
+
+fn is_json_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r')
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '$'
+}
+
+// Rewrites a decimal number accepted by the lenient grammar in standard
+// JSON form without changing its digits.
+fn normalize_number(text: &str, out: &mut String) {
+    let (sign, unsigned) = match text.as_bytes().first() {
+        Some(b'-') => ("-", &text[1..]),
+        Some(b'+') => ("", &text[1..]),
+        _ => ("", text),
+    };
+    let int_len = unsigned
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(unsigned.len());
+    let (int, rest) = unsigned.split_at(int_len);
+    let int = match int.trim_start_matches('0') {
+        "" => "0",
+        trimmed => trimmed,
+    };
+    let rest = match rest.strip_prefix('.') {
+        Some(after) if !after.starts_with(|c: char| c.is_ascii_digit()) => after,
+        _ => rest,
+    };
+    out.push_str(sign);
+    out.push_str(int);
+    out.push_str(rest);
+}
+
+/// Parses a document with `ParserOptions::current()`, which is standard
+/// JSON unless other defaults are configured.
 pub fn parse(input: &str) -> Result<JsonValue, ParseError> {
     let mut parser = Parser::new(input);
     parser.parse()
 }
 
-pub fn stringify(value: &JsonValue) -> String {
-    match value {
-        JsonValue::Null => "null".to_string(),
-        JsonValue::Bool(true) => "true".to_string(),
-        JsonValue::Bool(false) => "false".to_string(),
-        JsonValue::Number(n) => {
-            if n.fract() == 0.0 {
-                format!("{}", *n as i64)
-            } else {
-                n.to_string()
-            }
-        }
-        JsonValue::String(s) => {
-            let mut result = String::new();
-            result.push('"');
-            for c in s.chars() {
-                match c {
-                    '"' => result.push_str("\\\""),
-                    '\\' => result.push_str("\\\\"),
-                    '\x08' => result.push_str("\\b"),
-                    '\x0c' => result.push_str("\\f"),
-                    '\n' => result.push_str("\\n"),
-                    '\r' => result.push_str("\\r"),
-                    '\t' => result.push_str("\\t"),
-                    _ if c.is_control() => {
-                        let code = c as u32;
-                        result.push_str(&format!("\\u{:04x}", code));
-                    }
-                    _ => result.push(c),
-                }
-            }
-            result.push('"');
-            result
-        }
-        JsonValue::Array(arr) => {
-            let elements: Vec<String> = arr.iter().map(stringify).collect();
-            format!("[{}]", elements.join(","))
-        }
-        JsonValue::Object(obj) => {
-            let pairs: Vec<String> = obj
-                .iter()
-                .map(|(k, v)| {
-                    format!(
-                        "{}:{}",
-                        stringify(&JsonValue::String(k.clone())),
-                        stringify(v)
-                    )
-                })
-                .collect();
-            format!("{{{}}}", pairs.join(","))
-        }
-    }
+pub fn parse_with_options(input: &str, options: ParserOptions) -> Result<JsonValue, ParseError> {
+    let mut parser = Parser::with_options(input, options);
+    parser.parse()
+}
+
+/// Parses JSON with comments (JSONC), as used by VS Code-style config files.
+/// Trailing commas are accepted as well.
+pub fn parse_jsonc(input: &str) -> Result<JsonValue, ParseError> {
+    let options = ParserOptions {
+        allow_comments: true,
+        allow_trailing_commas: true,
+        ..ParserOptions::current()
+    };
+    parse_with_options(input, options)
 }
 
 #[cfg(test)]
 mod tests;
 
 impl JsonValue {
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonValue::Null)
+    }
+
+    pub fn is_bool(&self) -> bool {
+        matches!(self, JsonValue::Bool(_))
+    }
+
+    /// True for numbers, whether parsed or kept raw.
+    pub fn is_number(&self) -> bool {
+        matches!(self, JsonValue::Number(_) | JsonValue::RawNumber(_))
+    }
+
+    pub fn is_string(&self) -> bool {
+        matches!(self, JsonValue::String(_))
+    }
+
+    pub fn is_array(&self) -> bool {
+        matches!(self, JsonValue::Array(_))
+    }
+
+    pub fn is_object(&self) -> bool {
+        matches!(self, JsonValue::Object(_))
+    }
+
     pub fn as_str(&self) -> Option<&str> {
         match self {
             JsonValue::String(s) => Some(s),
@@ -379,13 +1337,30 @@ impl JsonValue {
         }
     }
 
+    /// An owned copy of the string.
+    pub fn as_string(&self) -> Option<String> {
+        self.as_str().map(str::to_string)
+    }
+
     pub fn as_f64(&self) -> Option<f64> {
         match self {
             JsonValue::Number(n) => Some(*n),
+            JsonValue::RawNumber(text) => text.parse().ok(),
             _ => None,
         }
     }
 
+    /// The number as an `i64` if it is a whole number in range. Nothing is
+    /// rounded: `1.5` and `1e19` give `None`.
+    pub fn as_i64(&self) -> Option<i64> {
+        i64::try_from(self).ok()
+    }
+
+    /// Like `as_i64`, for non-negative numbers up to `u64::MAX`.
+    pub fn as_u64(&self) -> Option<u64> {
+        u64::try_from(self).ok()
+    }
+
     pub fn as_bool(&self) -> Option<bool> {
         match self {
             JsonValue::Bool(b) => Some(*b),
@@ -400,7 +1375,10 @@ impl JsonValue {
         }
     }
 
-    pub fn as_object(&self) -> Option<&HashMap<String, JsonValue>> {
+    /// Unless a feature picks another `Map`, the map iterates in an
+    /// unspecified order that can change between runs; use `iter_sorted`
+    /// when traversal must be reproducible.
+    pub fn as_object(&self) -> Option<&Map> {
         match self {
             JsonValue::Object(obj) => Some(obj),
             _ => None,
@@ -420,4 +1398,42 @@ impl JsonValue {
             _ => None,
         }
     }
+
+    /// Moves the value out, leaving `null` in its place.
+    pub fn take(&mut self) -> JsonValue {
+        std::mem::replace(self, JsonValue::Null)
+    }
+
+    /// Puts `new` in place of this value and returns the old one.
+    pub fn replace(&mut self, new: JsonValue) -> JsonValue {
+        std::mem::replace(self, new)
+    }
+
+    /// The entry for `key` in an object, for in-place insertion or update
+    /// with a single lookup. `null` is first replaced by an empty object.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is neither an object nor `null`.
+    pub fn entry(&mut self, key: impl Into<String>) -> MapEntry<'_> {
+        if self.is_null() {
+            *self = JsonValue::Object(Box::default());
+        }
+        match self {
+            JsonValue::Object(obj) => obj.entry(key.into()),
+            _ => panic!("entry called on a JSON value that is not an object"),
+        }
+    }
+
+    /// Iterates an object's members in ascending key order, so traversal is
+    /// reproducible regardless of the map's own (randomized) iteration order.
+    /// Yields nothing for non-objects.
+    pub fn iter_sorted(&self) -> std::vec::IntoIter<(&String, &JsonValue)> {
+        let mut members: Vec<(&String, &JsonValue)> = match self {
+            JsonValue::Object(obj) => obj.iter().collect(),
+            _ => Vec::new(),
+        };
+        members.sort_by(|a, b| a.0.cmp(b.0));
+        members.into_iter()
+    }
 }
@@ -0,0 +1,300 @@
+use super::pointer::{parse_index, unescape_token};
+use super::{JsonValue, Map, ParseError, ParserOptions, scan_string, simd, validate_with_options};
+use bumpalo::Bump;
+use std::collections::HashSet;
+use std::ops::Range;
+
+/// Memory for `parse_in`. Every node, string and array of a parsed value is
+/// bump-allocated here and freed together when the arena is dropped or
+/// reset.
+#[derive(Debug, Default)]
+pub struct Arena {
+    bump: Bump,
+}
+
+impl Arena {
+    pub fn new() -> Self {
+        Arena::default()
+    }
+
+    /// Bytes currently allocated by the arena, including unused space in
+    /// its chunks.
+    pub fn allocated_bytes(&self) -> usize {
+        self.bump.allocated_bytes()
+    }
+
+    /// Frees every value parsed into the arena, keeping its largest chunk
+    /// for reuse.
+    pub fn reset(&mut self) {
+        self.bump.reset();
+    }
+}
+
+/// A JSON value whose contents live in an `Arena`. Object members keep
+/// their document order, duplicates included; keys that repeat across
+/// objects share one copy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArenaJsonValue<'a> {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(&'a str),
+    Array(&'a [ArenaJsonValue<'a>]),
+    Object(&'a [(&'a str, ArenaJsonValue<'a>)]),
+}
+
+/// Parses strict JSON into `arena`. The input is validated first, so a
+/// failed parse allocates nothing.
+pub fn parse_in<'a>(input: &str, arena: &'a Arena) -> Result<&'a ArenaJsonValue<'a>, ParseError> {
+    validate_with_options(input, ParserOptions::default())?;
+    let mut builder = Builder {
+        bytes: input.as_bytes(),
+        input,
+        pos: 0,
+        bump: &arena.bump,
+        keys: HashSet::new(),
+        items: Vec::new(),
+        members: Vec::new(),
+        scratch: String::new(),
+    };
+    let root = builder.value();
+    Ok(arena.bump.alloc(root))
+}
+
+impl<'a> ArenaJsonValue<'a> {
+    pub fn is_null(&self) -> bool {
+        matches!(self, ArenaJsonValue::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ArenaJsonValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ArenaJsonValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self {
+            ArenaJsonValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&'a [ArenaJsonValue<'a>]> {
+        match self {
+            ArenaJsonValue::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&'a [(&'a str, ArenaJsonValue<'a>)]> {
+        match self {
+            ArenaJsonValue::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+
+    /// The member named `key` of an object. If the key repeats, the last
+    /// one wins, as when parsing.
+    pub fn get(&self, key: &str) -> Option<&'a ArenaJsonValue<'a>> {
+        self.as_object()?
+            .iter()
+            .rev()
+            .find(|(name, _)| *name == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Looks up a value by JSON Pointer.
+    pub fn pointer(&'a self, pointer: &str) -> Option<&'a ArenaJsonValue<'a>> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .try_fold(self, |value, token| match value {
+                ArenaJsonValue::Object(_) => value.get(&unescape_token(token)),
+                ArenaJsonValue::Array(items) => items.get(parse_index(token)?),
+                _ => None,
+            })
+    }
+
+    /// Copies the value into an owned tree.
+    pub fn to_value(&self) -> JsonValue {
+        match self {
+            ArenaJsonValue::Null => JsonValue::Null,
+            ArenaJsonValue::Bool(b) => JsonValue::Bool(*b),
+            ArenaJsonValue::Number(n) => JsonValue::Number(*n),
+            ArenaJsonValue::String(s) => JsonValue::String((*s).into()),
+            ArenaJsonValue::Array(items) => {
+                JsonValue::Array(items.iter().map(ArenaJsonValue::to_value).collect())
+            }
+            ArenaJsonValue::Object(members) => JsonValue::Object(Box::new(
+                members
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_value()))
+                    .collect::<Map>(),
+            )),
+        }
+    }
+}
+
+// Builds the tree from validated input. Finished children wait on the
+// `items` and `members` stacks until their container closes and copies
+// them into the arena in one piece, so the heap is only touched when a
+// stack or the key set grows.
+struct Builder<'i, 'a> {
+    input: &'i str,
+    bytes: &'i [u8],
+    pos: usize,
+    bump: &'a Bump,
+    keys: HashSet<&'a str>,
+    items: Vec<ArenaJsonValue<'a>>,
+    members: Vec<(&'a str, ArenaJsonValue<'a>)>,
+    scratch: String,
+}
+
+impl<'a> Builder<'_, 'a> {
+    // The next significant byte, consumed.
+    fn next(&mut self) -> u8 {
+        self.pos = simd::skip_whitespace(self.bytes, self.pos);
+        self.pos += 1;
+        self.bytes[self.pos - 1]
+    }
+
+    fn value(&mut self) -> ArenaJsonValue<'a> {
+        let start = simd::skip_whitespace(self.bytes, self.pos);
+        match self.next() {
+            b'{' => self.object(),
+            b'[' => self.array(),
+            b'"' => ArenaJsonValue::String(self.string(start)),
+            _ => {
+                self.pos = simd::find_delimiter(self.bytes, start);
+                match &self.input[start..self.pos] {
+                    "null" => ArenaJsonValue::Null,
+                    "true" => ArenaJsonValue::Bool(true),
+                    "false" => ArenaJsonValue::Bool(false),
+                    number => {
+                        ArenaJsonValue::Number(number.parse().expect("validated numbers parse"))
+                    }
+                }
+            }
+        }
+    }
+
+    fn array(&mut self) -> ArenaJsonValue<'a> {
+        let base = self.items.len();
+        if self.peek() == b']' {
+            self.pos += 1;
+        } else {
+            loop {
+                let item = self.value();
+                self.items.push(item);
+                if self.next() == b']' {
+                    break;
+                }
+            }
+        }
+        let items = self.bump.alloc_slice_copy(&self.items[base..]);
+        self.items.truncate(base);
+        ArenaJsonValue::Array(items)
+    }
+
+    fn object(&mut self) -> ArenaJsonValue<'a> {
+        let base = self.members.len();
+        if self.peek() == b'}' {
+            self.pos += 1;
+        } else {
+            loop {
+                let start = simd::skip_whitespace(self.bytes, self.pos);
+                self.next();
+                let key = self.key(start);
+                self.next(); // ':'
+                let value = self.value();
+                self.members.push((key, value));
+                if self.next() == b'}' {
+                    break;
+                }
+            }
+        }
+        let members = self.bump.alloc_slice_copy(&self.members[base..]);
+        self.members.truncate(base);
+        ArenaJsonValue::Object(members)
+    }
+
+    fn peek(&mut self) -> u8 {
+        self.pos = simd::skip_whitespace(self.bytes, self.pos);
+        self.bytes[self.pos]
+    }
+
+    // A key, stored once per parse however often it repeats.
+    fn key(&mut self, start: usize) -> &'a str {
+        let plain = self.decode(start);
+        let key = plain.map_or(self.scratch.as_str(), |range| &self.input[range]);
+        if let Some(&pooled) = self.keys.get(key) {
+            return pooled;
+        }
+        let pooled = self.bump.alloc_str(key);
+        self.keys.insert(pooled);
+        pooled
+    }
+
+    fn string(&mut self, start: usize) -> &'a str {
+        let plain = self.decode(start);
+        let text = plain.map_or(self.scratch.as_str(), |range| &self.input[range]);
+        self.bump.alloc_str(text)
+    }
+
+    // Reads the string literal starting at `start`. A literal without
+    // escapes is returned as its range in the input; otherwise it is decoded
+    // into `scratch`.
+    fn decode(&mut self, start: usize) -> Option<Range<usize>> {
+        let end = scan_string(self.bytes, start, b'"').expect("validated strings are closed");
+        self.pos = end;
+        let inner = &self.input[start + 1..end - 1];
+        if !inner.contains('\\') {
+            return Some(start + 1..end - 1);
+        }
+        self.scratch.clear();
+        let mut rest = inner;
+        while let Some(i) = rest.find('\\') {
+            self.scratch.push_str(&rest[..i]);
+            let escape = rest.as_bytes()[i + 1];
+            rest = &rest[i + 2..];
+            let c = match escape {
+                b'b' => '\x08',
+                b'f' => '\x0c',
+                b'n' => '\n',
+                b'r' => '\r',
+                b't' => '\t',
+                b'u' => {
+                    let unit = hex(&rest[..4]);
+                    rest = &rest[4..];
+                    if (0xD800..0xDC00).contains(&unit) && rest.starts_with("\\u") {
+                        let low = hex(&rest[2..6]);
+                        rest = &rest[6..];
+                        char::from_u32(0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00))
+                    } else {
+                        char::from_u32(unit)
+                    }
+                    .expect("validated escapes are scalar values")
+                }
+                other => other as char,
+            };
+            self.scratch.push(c);
+        }
+        self.scratch.push_str(rest);
+        None
+    }
+}
+
+fn hex(digits: &str) -> u32 {
+    u32::from_str_radix(digits, 16).expect("validated escapes are hexadecimal")
+}
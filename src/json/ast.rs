@@ -0,0 +1,86 @@
+use super::incremental::Span;
+use super::{IncrementalDocument, JsonValue, Map};
+
+// Bumped whenever the layout of the exported tree changes.
+const AST_VERSION: f64 = 1.0;
+
+/// Exports the syntax tree of a parsed document as JSON, for linters and
+/// other tools outside Rust. The result is `{"version": 1, "root": node}`,
+/// where each node has a `kind` (`null`, `boolean`, `number`, `string`,
+/// `array` or `object`), `start` and `end` positions (`offset`, `line`,
+/// `column`), and either the scalar's `raw` lexeme, the array's `elements`,
+/// or the object's `members` as `{"key", "value"}` pairs in source order.
+pub fn to_ast_json(document: &IncrementalDocument) -> JsonValue {
+    let text = document.text();
+    let lines: Vec<usize> = std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    object(vec![
+        ("version", JsonValue::Number(AST_VERSION)),
+        ("root", node(text, &lines, document.spans())),
+    ])
+}
+
+fn node(text: &str, lines: &[usize], span: &Span) -> JsonValue {
+    let raw = &text[span.start..span.end];
+    let kind = match raw.as_bytes()[0] {
+        b'{' => "object",
+        b'[' => "array",
+        b'"' | b'\'' => "string",
+        b't' | b'f' => "boolean",
+        b'n' => "null",
+        _ => "number",
+    };
+    let mut fields = vec![
+        ("kind", JsonValue::String(kind.into())),
+        ("start", position(text, lines, span.start)),
+        ("end", position(text, lines, span.end)),
+    ];
+    let children = span.children.iter();
+    match kind {
+        "array" => fields.push((
+            "elements",
+            JsonValue::Array(
+                children
+                    .map(|(_, child)| node(text, lines, child))
+                    .collect(),
+            ),
+        )),
+        "object" => fields.push((
+            "members",
+            JsonValue::Array(
+                children
+                    .map(|(key, child)| {
+                        let key = key.clone().unwrap_or_default();
+                        object(vec![
+                            ("key", JsonValue::String(key.into())),
+                            ("value", node(text, lines, child)),
+                        ])
+                    })
+                    .collect(),
+            ),
+        )),
+        _ => fields.push(("raw", JsonValue::String(raw.into()))),
+    }
+    object(fields)
+}
+
+// Like `Position::at`, finding the line from the precomputed line starts.
+fn position(text: &str, lines: &[usize], offset: usize) -> JsonValue {
+    let line = lines.partition_point(|&start| start <= offset);
+    let column = text[lines[line - 1]..offset].chars().count() + 1;
+    object(vec![
+        ("offset", JsonValue::Number(offset as f64)),
+        ("line", JsonValue::Number(line as f64)),
+        ("column", JsonValue::Number(column as f64)),
+    ])
+}
+
+fn object(fields: Vec<(&str, JsonValue)>) -> JsonValue {
+    JsonValue::Object(Box::new(
+        fields
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect::<Map>(),
+    ))
+}
@@ -0,0 +1,83 @@
+use super::JsonValue;
+use bson::{Bson, Document};
+use std::fmt;
+
+/// A value that cannot be converted to BSON, or bytes that are not a BSON
+/// document.
+#[derive(Debug, PartialEq, Clone)]
+pub struct BsonError {
+    pub message: String,
+}
+
+impl fmt::Display for BsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for BsonError {}
+
+fn error(message: impl fmt::Display) -> BsonError {
+    BsonError {
+        message: message.to_string(),
+    }
+}
+
+/// Types JSON has no equivalent for, such as ObjectIds, dates and binary
+/// data, become MongoDB relaxed extended JSON, e.g. `{"$oid": "..."}`.
+impl From<Bson> for JsonValue {
+    fn from(value: Bson) -> Self {
+        JsonValue::from(value.into_relaxed_extjson())
+    }
+}
+
+impl From<Document> for JsonValue {
+    fn from(document: Document) -> Self {
+        JsonValue::from(Bson::Document(document))
+    }
+}
+
+/// Reads MongoDB extended JSON, canonical or relaxed: objects such as
+/// `{"$oid": "..."}`, `{"$date": ...}` and `{"$binary": ...}` become the
+/// BSON types they describe. Fails if such an object is malformed.
+impl TryFrom<JsonValue> for Bson {
+    type Error = BsonError;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        Bson::try_from(serde_json::Value::from(value)).map_err(error)
+    }
+}
+
+/// Fails unless the value is an object.
+impl TryFrom<JsonValue> for Document {
+    type Error = BsonError;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        match Bson::try_from(value)? {
+            Bson::Document(document) => Ok(document),
+            other => Err(error(format!(
+                "a BSON document must be an object, found {:?}",
+                other.element_type()
+            ))),
+        }
+    }
+}
+
+/// Decodes the BSON documents in `bytes`, such as a `mongodump` `.bson`
+/// file, which holds them back to back.
+pub fn from_bson_bytes(mut bytes: &[u8]) -> Result<Vec<JsonValue>, BsonError> {
+    let mut documents = Vec::new();
+    while !bytes.is_empty() {
+        documents.push(Document::from_reader(&mut bytes).map_err(error)?.into());
+    }
+    Ok(documents)
+}
+
+/// Encodes an object, which may use extended JSON, as one BSON document.
+pub fn to_bson_bytes(value: &JsonValue) -> Result<Vec<u8>, BsonError> {
+    let mut bytes = Vec::new();
+    Document::try_from(value.clone())?
+        .to_writer(&mut bytes)
+        .map_err(error)?;
+    Ok(bytes)
+}
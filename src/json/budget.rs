@@ -0,0 +1,95 @@
+use super::pointer::{escape_pointer_token, parse_index, unescape_token};
+use super::{JsonValue, StringifyError, StringifyOptions, stringify_with};
+
+/// Output of `stringify_within_budget`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetedOutput {
+    pub text: String,
+    /// JSON Pointers of the values left out to fit the budget, in the order
+    /// they were dropped. Each refers to the document as it was when it was
+    /// dropped, after the earlier omissions.
+    pub omitted: Vec<String>,
+}
+
+/// Stringifies `value` in at most `budget` bytes, for responses that must
+/// stay under a size limit. While the output is too large, the paths in
+/// `drop_order` are removed one at a time, lowest priority first, and the
+/// value is written again. Paths are JSON Pointers in which a `*` token
+/// matches every member or element, e.g. `/items/*/debug`. Fails with
+/// `StringifyError::OverBudget` if the output is still too large once all
+/// of them are gone.
+pub fn stringify_within_budget(
+    value: &JsonValue,
+    budget: usize,
+    drop_order: &[&str],
+    options: &StringifyOptions,
+) -> Result<BudgetedOutput, StringifyError> {
+    let mut text = stringify_with(value, options)?;
+    let mut value = value.clone();
+    let mut omitted = Vec::new();
+    let mut paths = drop_order.iter();
+    while text.len() > budget {
+        let Some(path) = paths.next() else {
+            return Err(StringifyError::OverBudget {
+                size: text.len(),
+                budget,
+            });
+        };
+        let mut matches = Vec::new();
+        let tokens: Vec<String> = match path.strip_prefix('/') {
+            Some(rest) => rest.split('/').map(unescape_token).collect(),
+            // The root cannot be left out.
+            None => continue,
+        };
+        find(&value, &tokens, &mut String::new(), &mut matches);
+        if matches.is_empty() {
+            continue;
+        }
+        // Later array elements first, so earlier indices stay valid.
+        for pointer in matches.iter().rev() {
+            value.pointer_remove(pointer);
+        }
+        omitted.extend(matches);
+        text = stringify_with(&value, options)?;
+    }
+    Ok(BudgetedOutput { text, omitted })
+}
+
+// Collects the pointers of the values `tokens` matches below `value`, in
+// document order.
+fn find(value: &JsonValue, tokens: &[String], path: &mut String, out: &mut Vec<String>) {
+    let Some((token, rest)) = tokens.split_first() else {
+        out.push(path.clone());
+        return;
+    };
+    let mut visit = |key: String, child: &JsonValue| {
+        let len = path.len();
+        path.push('/');
+        path.push_str(&escape_pointer_token(&key));
+        find(child, rest, path, out);
+        path.truncate(len);
+    };
+    match value {
+        JsonValue::Object(_) if token == "*" => {
+            for (key, child) in value.iter_sorted() {
+                visit(key.clone(), child);
+            }
+        }
+        JsonValue::Object(obj) => {
+            if let Some(child) = obj.get(token) {
+                visit(token.clone(), child);
+            }
+        }
+        JsonValue::Array(arr) if token == "*" => {
+            for (i, child) in arr.iter().enumerate() {
+                visit(i.to_string(), child);
+            }
+        }
+        JsonValue::Array(arr) => {
+            if let Some(child) = parse_index(token).and_then(|i| arr.get(i)) {
+                visit(token.clone(), child);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,93 @@
+use super::{JsonValue, Map};
+
+/// Builds an object member by member:
+/// `JsonObject::new().field("name", "Alice").field("age", 30).build()`.
+/// A later field with the same key replaces an earlier one.
+#[derive(Debug, Clone, Default)]
+pub struct JsonObject {
+    members: Map,
+}
+
+impl JsonObject {
+    pub fn new() -> Self {
+        JsonObject::default()
+    }
+
+    pub fn field(mut self, key: impl Into<String>, value: impl Into<JsonValue>) -> Self {
+        self.members.insert(key.into(), value.into());
+        self
+    }
+
+    /// Adds the field only when `condition` holds.
+    pub fn field_if(
+        self,
+        condition: bool,
+        key: impl Into<String>,
+        value: impl Into<JsonValue>,
+    ) -> Self {
+        if condition {
+            self.field(key, value)
+        } else {
+            self
+        }
+    }
+
+    /// Adds the field if `value` is `Some`, and leaves it out, rather than
+    /// writing `null`, if it is `None`.
+    pub fn maybe_field<T: Into<JsonValue>>(self, key: impl Into<String>, value: Option<T>) -> Self {
+        match value {
+            Some(value) => self.field(key, value),
+            None => self,
+        }
+    }
+
+    pub fn build(self) -> JsonValue {
+        JsonValue::Object(Box::new(self.members))
+    }
+}
+
+impl From<JsonObject> for JsonValue {
+    fn from(object: JsonObject) -> Self {
+        object.build()
+    }
+}
+
+/// Builds an array element by element, the counterpart of `JsonObject`.
+#[derive(Debug, Clone, Default)]
+pub struct JsonArray {
+    items: Vec<JsonValue>,
+}
+
+impl JsonArray {
+    pub fn new() -> Self {
+        JsonArray::default()
+    }
+
+    pub fn item(mut self, value: impl Into<JsonValue>) -> Self {
+        self.items.push(value.into());
+        self
+    }
+
+    /// Adds the element only when `condition` holds.
+    pub fn item_if(self, condition: bool, value: impl Into<JsonValue>) -> Self {
+        if condition { self.item(value) } else { self }
+    }
+
+    /// Adds the element if `value` is `Some`, and skips it if it is `None`.
+    pub fn maybe_item<T: Into<JsonValue>>(self, value: Option<T>) -> Self {
+        match value {
+            Some(value) => self.item(value),
+            None => self,
+        }
+    }
+
+    pub fn build(self) -> JsonValue {
+        JsonValue::Array(self.items)
+    }
+}
+
+impl From<JsonArray> for JsonValue {
+    fn from(array: JsonArray) -> Self {
+        array.build()
+    }
+}
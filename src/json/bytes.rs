@@ -0,0 +1,167 @@
+use super::{
+    BytesError, DecodeError, JsonValue, Map, ParserOptions, parse, scan_string, simd,
+    validate_with_options,
+};
+
+/// What `parse_bytes` does with input that is not valid UTF-8.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Utf8Policy {
+    /// Fail with a `DecodeError` at the first invalid sequence.
+    #[default]
+    Reject,
+    /// Replace each invalid sequence with U+FFFD, as
+    /// `String::from_utf8_lossy` does, and parse the result.
+    Replace,
+    /// Keep strings that are not valid UTF-8 as `JsonValue::Bytes` holding
+    /// their decoded bytes. Object keys are always text, so invalid
+    /// sequences in keys are replaced.
+    Bytes,
+}
+
+/// Parses JSON straight from bytes, handling invalid UTF-8 by `policy`.
+/// Valid input is parsed as `parse` would; when it is not, `Replace` and
+/// `Bytes` report parse error positions within the text with U+FFFD
+/// substituted, and `Bytes` accepts only strict JSON.
+pub fn parse_bytes(bytes: &[u8], policy: Utf8Policy) -> Result<JsonValue, BytesError> {
+    let error = match std::str::from_utf8(bytes) {
+        Ok(text) => return Ok(parse(text)?),
+        Err(e) => e,
+    };
+    match policy {
+        Utf8Policy::Reject => Err(BytesError::Decode(DecodeError {
+            offset: error.valid_up_to(),
+        })),
+        Utf8Policy::Replace => Ok(parse(&String::from_utf8_lossy(bytes))?),
+        Utf8Policy::Bytes => {
+            // Invalid sequences can only sit inside strings if the text
+            // with them replaced is valid, so the structure can then be
+            // read from the original bytes.
+            validate_with_options(&String::from_utf8_lossy(bytes), ParserOptions::default())?;
+            Ok(Builder { bytes, pos: 0 }.value())
+        }
+    }
+}
+
+// Builds a tree from bytes whose structure has been validated.
+struct Builder<'b> {
+    bytes: &'b [u8],
+    pos: usize,
+}
+
+impl Builder<'_> {
+    // The next significant byte, consumed.
+    fn next(&mut self) -> u8 {
+        self.pos = simd::skip_whitespace(self.bytes, self.pos);
+        self.pos += 1;
+        self.bytes[self.pos - 1]
+    }
+
+    fn peek(&mut self) -> u8 {
+        self.pos = simd::skip_whitespace(self.bytes, self.pos);
+        self.bytes[self.pos]
+    }
+
+    fn value(&mut self) -> JsonValue {
+        match self.peek() {
+            b'{' => self.object(),
+            b'[' => self.array(),
+            b'"' => match String::from_utf8(self.string()) {
+                Ok(s) => JsonValue::String(s.into()),
+                Err(e) => JsonValue::Bytes(e.into_bytes().into()),
+            },
+            _ => {
+                let start = self.pos;
+                self.pos = simd::find_delimiter(self.bytes, start);
+                match &self.bytes[start..self.pos] {
+                    b"null" => JsonValue::Null,
+                    b"true" => JsonValue::Bool(true),
+                    b"false" => JsonValue::Bool(false),
+                    number => JsonValue::Number(
+                        std::str::from_utf8(number)
+                            .ok()
+                            .and_then(|n| n.parse().ok())
+                            .expect("validated numbers parse"),
+                    ),
+                }
+            }
+        }
+    }
+
+    fn array(&mut self) -> JsonValue {
+        self.pos += 1;
+        let mut items = Vec::new();
+        if self.peek() == b']' {
+            self.pos += 1;
+            return JsonValue::Array(items);
+        }
+        loop {
+            items.push(self.value());
+            if self.next() == b']' {
+                return JsonValue::Array(items);
+            }
+        }
+    }
+
+    fn object(&mut self) -> JsonValue {
+        self.pos += 1;
+        let mut members = Map::new();
+        if self.peek() == b'}' {
+            self.pos += 1;
+            return JsonValue::Object(Box::new(members));
+        }
+        loop {
+            self.peek();
+            let key = String::from_utf8_lossy(&self.string()).into_owned();
+            self.next(); // ':'
+            members.insert(key, self.value());
+            if self.next() == b'}' {
+                return JsonValue::Object(Box::new(members));
+            }
+        }
+    }
+
+    // Reads the string literal at the current position and returns its
+    // contents with escapes decoded.
+    fn string(&mut self) -> Vec<u8> {
+        let start = self.pos;
+        let end = scan_string(self.bytes, start, b'"').expect("validated strings are closed");
+        self.pos = end;
+        let mut rest = &self.bytes[start + 1..end - 1];
+        let mut out = Vec::with_capacity(rest.len());
+        while let Some(i) = rest.iter().position(|&b| b == b'\\') {
+            out.extend_from_slice(&rest[..i]);
+            let escape = rest[i + 1];
+            rest = &rest[i + 2..];
+            let c = match escape {
+                b'b' => '\x08',
+                b'f' => '\x0c',
+                b'n' => '\n',
+                b'r' => '\r',
+                b't' => '\t',
+                b'u' => {
+                    let unit = hex(&rest[..4]);
+                    rest = &rest[4..];
+                    if (0xD800..0xDC00).contains(&unit) && rest.starts_with(b"\\u") {
+                        let low = hex(&rest[2..6]);
+                        rest = &rest[6..];
+                        char::from_u32(0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00))
+                    } else {
+                        char::from_u32(unit)
+                    }
+                    .expect("validated escapes are scalar values")
+                }
+                other => other as char,
+            };
+            out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+        }
+        out.extend_from_slice(rest);
+        out
+    }
+}
+
+fn hex(digits: &[u8]) -> u32 {
+    std::str::from_utf8(digits)
+        .ok()
+        .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+        .expect("validated escapes are hexadecimal")
+}
@@ -0,0 +1,212 @@
+use super::{JsonValue, Map, ParseError, ParserOptions, parse_with_options};
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::mem::size_of;
+use std::sync::{Arc, Mutex};
+
+/// Counters reported by `ParseCache::stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub entries: usize,
+    /// Estimated bytes currently held, counting each cached input and its
+    /// parsed tree; the quantity bounded by the cache size.
+    pub bytes: usize,
+}
+
+impl CacheStats {
+    /// Fraction of lookups served from the cache, or 0 before any lookup.
+    pub fn hit_ratio(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+// Entries form a doubly linked list in recency order, threaded through the
+// map by key, so a hit can move its entry to the front and eviction can take
+// the oldest one without a scan.
+struct Entry {
+    input: String,
+    value: Arc<JsonValue>,
+    size: usize,
+    newer: Option<u64>,
+    older: Option<u64>,
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<u64, Entry>,
+    newest: Option<u64>,
+    oldest: Option<u64>,
+    stats: CacheStats,
+}
+
+/// A least-recently-used cache of parsed documents keyed by input text, so
+/// repeated identical payloads are parsed once and shared via `Arc`.
+///
+/// Memory is bounded by the total size of the cached inputs plus an estimate
+/// of their parsed trees (see `JsonValue::heap_size`). Errors are never
+/// cached.
+pub struct ParseCache {
+    options: ParserOptions,
+    max_bytes: usize,
+    inner: Mutex<Inner>,
+}
+
+impl ParseCache {
+    pub fn new(max_bytes: usize) -> Self {
+        ParseCache::with_options(ParserOptions::current(), max_bytes)
+    }
+
+    pub fn with_options(options: ParserOptions, max_bytes: usize) -> Self {
+        ParseCache {
+            options,
+            max_bytes,
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    /// Returns the cached document for `input`, parsing and caching it on a
+    /// miss. Documents larger than the whole cache are parsed but not cached.
+    pub fn parse(&self, input: &str) -> Result<Arc<JsonValue>, ParseError> {
+        let key = hash(input);
+        {
+            let mut inner = self.lock();
+            if let Some(entry) = inner.entries.get(&key)
+                && entry.input == input
+            {
+                let value = Arc::clone(&entry.value);
+                inner.unlink(key);
+                inner.push_newest(key);
+                inner.stats.hits += 1;
+                return Ok(value);
+            }
+            inner.stats.misses += 1;
+        }
+
+        // Parse without holding the lock so other threads are not blocked.
+        let value = Arc::new(parse_with_options(input, self.options.clone())?);
+        let size = input.len() + size_of::<JsonValue>() + value.heap_size();
+        if size <= self.max_bytes {
+            let mut inner = self.lock();
+            inner.remove(key);
+            while inner.stats.bytes + size > self.max_bytes {
+                inner.evict_oldest();
+            }
+            inner.stats.bytes += size;
+            inner.entries.insert(
+                key,
+                Entry {
+                    input: input.to_string(),
+                    value: Arc::clone(&value),
+                    size,
+                    newer: None,
+                    older: None,
+                },
+            );
+            inner.push_newest(key);
+            inner.stats.entries = inner.entries.len();
+        }
+        Ok(value)
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.lock().stats
+    }
+
+    /// Drops every cached document; the hit and miss counters are kept.
+    pub fn clear(&self) {
+        let mut inner = self.lock();
+        inner.entries.clear();
+        inner.newest = None;
+        inner.oldest = None;
+        inner.stats.entries = 0;
+        inner.stats.bytes = 0;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Inner {
+    fn evict_oldest(&mut self) {
+        if let Some(key) = self.oldest {
+            self.remove(key);
+            self.stats.evictions += 1;
+        }
+    }
+
+    fn remove(&mut self, key: u64) {
+        if self.entries.contains_key(&key) {
+            self.unlink(key);
+            let entry = self.entries.remove(&key).unwrap();
+            self.stats.bytes -= entry.size;
+            self.stats.entries = self.entries.len();
+        }
+    }
+
+    // Detaches an entry from the recency list, leaving it in the map.
+    fn unlink(&mut self, key: u64) {
+        let entry = &self.entries[&key];
+        let (newer, older) = (entry.newer, entry.older);
+        match newer {
+            Some(newer) => self.entries.get_mut(&newer).unwrap().older = older,
+            None => self.newest = older,
+        }
+        match older {
+            Some(older) => self.entries.get_mut(&older).unwrap().newer = newer,
+            None => self.oldest = newer,
+        }
+    }
+
+    fn push_newest(&mut self, key: u64) {
+        let previous = self.newest.replace(key);
+        let entry = self.entries.get_mut(&key).unwrap();
+        entry.newer = None;
+        entry.older = previous;
+        match previous {
+            Some(previous) => self.entries.get_mut(&previous).unwrap().newer = Some(key),
+            None => self.oldest = Some(key),
+        }
+    }
+}
+
+impl JsonValue {
+    /// An estimate of the heap memory held by the value, not counting the
+    /// `JsonValue` itself: the elements of each array, the table and keys of
+    /// each object, and the bytes of every string.
+    pub fn heap_size(&self) -> usize {
+        match self {
+            JsonValue::String(s) | JsonValue::RawNumber(s) => s.heap_size(),
+            JsonValue::Bytes(bytes) => bytes.len(),
+            JsonValue::Raw(raw) => raw.as_str().len(),
+            JsonValue::Array(items) => {
+                items.capacity() * size_of::<JsonValue>()
+                    + items.iter().map(JsonValue::heap_size).sum::<usize>()
+            }
+            JsonValue::Object(members) => {
+                size_of::<Map>()
+                    + members.len() * size_of::<(String, JsonValue)>()
+                    + members
+                        .iter()
+                        .map(|(key, value)| key.capacity() + value.heap_size())
+                        .sum::<usize>()
+            }
+            JsonValue::Null | JsonValue::Bool(_) | JsonValue::Number(_) => 0,
+        }
+    }
+}
+
+fn hash(input: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    input.hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,112 @@
+use super::{JsonValue, StringifyError};
+
+/// Serializes a value as RFC 8785 (JCS) canonical JSON: no whitespace,
+/// object members sorted by the UTF-16 code units of their keys, numbers
+/// formatted like ECMAScript's `Number.prototype.toString`, and only the
+/// escapes the RFC prescribes. Equal values always produce identical
+/// bytes, which makes the output suitable for hashing and signing.
+pub fn stringify_canonical(value: &JsonValue) -> Result<String, StringifyError> {
+    let mut out = String::new();
+    write_value(value, &mut out)?;
+    Ok(out)
+}
+
+fn write_value(value: &JsonValue, out: &mut String) -> Result<(), StringifyError> {
+    match value {
+        JsonValue::Null => out.push_str("null"),
+        JsonValue::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        JsonValue::Number(n) => out.push_str(&format_number(*n)?),
+        // JCS defines numbers by their IEEE 754 value, so raw text is
+        // reformatted like any other number.
+        JsonValue::RawNumber(text) => {
+            out.push_str(&format_number(text.parse().unwrap_or(f64::NAN))?)
+        }
+        JsonValue::Raw(raw) => write_value(&raw.parse(), out)?,
+        JsonValue::String(s) => write_string(s, out),
+        JsonValue::Bytes(bytes) => write_string(&String::from_utf8_lossy(bytes), out),
+        JsonValue::Array(arr) => {
+            out.push('[');
+            for (i, item) in arr.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(item, out)?;
+            }
+            out.push(']');
+        }
+        JsonValue::Object(obj) => {
+            let mut members: Vec<(Vec<u16>, &String, &JsonValue)> = obj
+                .iter()
+                .map(|(k, v)| (k.encode_utf16().collect(), k, v))
+                .collect();
+            members.sort_by(|a, b| a.0.cmp(&b.0));
+
+            out.push('{');
+            for (i, (_, key, item)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(key, out);
+                out.push(':');
+                write_value(item, out)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+fn write_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\x08' => out.push_str("\\b"),
+            '\x0c' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            _ if c < '\u{20}' => out.push_str(&format!("\\u{:04x}", c as u32)),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+// ECMAScript Number::toString for finite doubles (ECMA-262 7.1.12.1).
+pub(crate) fn format_number(n: f64) -> Result<String, StringifyError> {
+    if !n.is_finite() {
+        return Err(StringifyError::NonFiniteNumber(n));
+    }
+    if n == 0.0 {
+        return Ok("0".to_string());
+    }
+
+    // `{:e}` yields the shortest round-tripping digits, e.g. "-1.5e-7".
+    let formatted = format!("{:e}", n.abs());
+    let (mantissa, exponent) = formatted.split_once('e').unwrap();
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let k = digits.len() as i32;
+    let n_exp = exponent.parse::<i32>().unwrap() + 1;
+
+    let body = if k <= n_exp && n_exp <= 21 {
+        format!("{}{}", digits, "0".repeat((n_exp - k) as usize))
+    } else if 0 < n_exp && n_exp <= 21 {
+        let (int, frac) = digits.split_at(n_exp as usize);
+        format!("{}.{}", int, frac)
+    } else if -6 < n_exp && n_exp <= 0 {
+        format!("0.{}{}", "0".repeat((-n_exp) as usize), digits)
+    } else {
+        let (first, rest) = digits.split_at(1);
+        let sign = if n_exp - 1 < 0 { '-' } else { '+' };
+        let fraction = if rest.is_empty() {
+            String::new()
+        } else {
+            format!(".{}", rest)
+        };
+        format!("{}{}e{}{}", first, fraction, sign, (n_exp - 1).abs())
+    };
+
+    Ok(if n < 0.0 { format!("-{}", body) } else { body })
+}
@@ -0,0 +1,103 @@
+use super::JsonValue;
+use super::map::remove_member;
+use super::pointer::unescape_token;
+use super::schema::{SchemaViolation, schema_violations};
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum CheckError {
+    /// The pointer's parent does not exist or cannot hold the new value.
+    InvalidPointer(String),
+    /// The change would leave the document in violation of its schema.
+    SchemaViolations(Vec<SchemaViolation>),
+}
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckError::InvalidPointer(pointer) => write!(f, "cannot set \"{}\"", pointer),
+            CheckError::SchemaViolations(violations) => {
+                let messages: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+                write!(f, "schema violation: {}", messages.join("; "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for CheckError {}
+
+/// A document with an attached schema that it is guaranteed to satisfy.
+/// Mutations go through `set_checked`, which refuses any change that would
+/// make the document invalid.
+#[derive(Debug, Clone)]
+pub struct CheckedDocument {
+    value: JsonValue,
+    schema: JsonValue,
+}
+
+impl CheckedDocument {
+    pub fn new(value: JsonValue, schema: JsonValue) -> Result<Self, CheckError> {
+        let violations = schema_violations(&value, &schema);
+        if !violations.is_empty() {
+            return Err(CheckError::SchemaViolations(violations));
+        }
+        Ok(CheckedDocument { value, schema })
+    }
+
+    pub fn value(&self) -> &JsonValue {
+        &self.value
+    }
+
+    pub fn schema(&self) -> &JsonValue {
+        &self.schema
+    }
+
+    pub fn into_value(self) -> JsonValue {
+        self.value
+    }
+
+    /// Sets the value at a JSON Pointer, with the same rules as
+    /// `JsonValue::pointer_set`, but only if the resulting document still
+    /// satisfies the schema. The change is made in place and undone if it
+    /// fails validation, so on error the document is left unchanged.
+    /// Returns the value that was replaced, if any.
+    pub fn set_checked(
+        &mut self,
+        pointer: &str,
+        value: JsonValue,
+    ) -> Result<Option<JsonValue>, CheckError> {
+        let previous = self
+            .value
+            .pointer_set(pointer, value)
+            .map_err(|_| CheckError::InvalidPointer(pointer.to_string()))?;
+
+        let violations = schema_violations(&self.value, &self.schema);
+        if !violations.is_empty() {
+            self.undo_set(pointer, previous);
+            return Err(CheckError::SchemaViolations(violations));
+        }
+        Ok(previous)
+    }
+
+    // Reverses a successful `pointer_set`: puts back the replaced value, or
+    // drops the member or element that was added.
+    fn undo_set(&mut self, pointer: &str, previous: Option<JsonValue>) {
+        if let Some(previous) = previous {
+            let restored = self.value.pointer_set(pointer, previous);
+            debug_assert!(restored.is_ok());
+            return;
+        }
+        let Some((parent, token)) = pointer.rsplit_once('/') else {
+            return;
+        };
+        match self.value.pointer_mut(parent) {
+            Some(JsonValue::Object(obj)) => {
+                remove_member(obj, &unescape_token(token));
+            }
+            Some(JsonValue::Array(arr)) => {
+                arr.pop();
+            }
+            _ => {}
+        }
+    }
+}
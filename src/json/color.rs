@@ -0,0 +1,152 @@
+use super::stringify::{write_newline, write_string, write_value};
+use super::{JsonValue, NonFinitePolicy, StringifyError, StringifyOptions, embed_nested_json};
+
+/// ANSI SGR parameters, such as `"1;34"` for bold blue, for each kind of
+/// token `stringify_colored` writes. An empty string leaves that kind
+/// uncolored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorScheme {
+    pub key: &'static str,
+    pub string: &'static str,
+    pub number: &'static str,
+    /// `true`, `false` and `null`.
+    pub literal: &'static str,
+    /// Brackets, braces, commas and colons.
+    pub punctuation: &'static str,
+}
+
+impl ColorScheme {
+    /// A scheme that writes no escape codes at all.
+    pub const PLAIN: ColorScheme = ColorScheme {
+        key: "",
+        string: "",
+        number: "",
+        literal: "",
+        punctuation: "",
+    };
+}
+
+impl Default for ColorScheme {
+    /// Bold blue keys, green strings, cyan numbers and yellow literals.
+    fn default() -> Self {
+        ColorScheme {
+            key: "1;34",
+            string: "32",
+            number: "36",
+            literal: "33",
+            punctuation: "",
+        }
+    }
+}
+
+/// Like `stringify_pretty`, with each token wrapped in the ANSI escape codes
+/// `scheme` gives for its kind, for display in a terminal.
+pub fn stringify_colored(value: &JsonValue, scheme: ColorScheme) -> String {
+    let mut options = StringifyOptions::current();
+    options.indent.get_or_insert_with(|| "  ".to_string());
+    // Only `NonFinitePolicy::Error` can fail.
+    if options.non_finite == NonFinitePolicy::Error {
+        options.non_finite = NonFinitePolicy::Null;
+    }
+    stringify_colored_with(value, scheme, &options).unwrap()
+}
+
+/// Like `stringify_with`, coloring tokens as `stringify_colored` does.
+pub fn stringify_colored_with(
+    value: &JsonValue,
+    scheme: ColorScheme,
+    options: &StringifyOptions,
+) -> Result<String, StringifyError> {
+    let mut out = String::new();
+    if options.embed_json_at.is_empty() {
+        write_colored(value, scheme, options, 0, &mut out)?;
+    } else {
+        let mut value = value.clone();
+        embed_nested_json(&mut value, &options.embed_json_at);
+        write_colored(&value, scheme, options, 0, &mut out)?;
+    }
+    Ok(out)
+}
+
+fn write_colored(
+    value: &JsonValue,
+    scheme: ColorScheme,
+    options: &StringifyOptions,
+    depth: usize,
+    out: &mut String,
+) -> Result<(), StringifyError> {
+    match value {
+        JsonValue::Array(arr) => {
+            paint(scheme.punctuation, out, |out| out.push('['));
+            for (i, item) in arr.iter().enumerate() {
+                if i > 0 {
+                    paint(scheme.punctuation, out, |out| out.push(','));
+                }
+                write_newline(options, depth + 1, out);
+                write_colored(item, scheme, options, depth + 1, out)?;
+            }
+            if !arr.is_empty() {
+                write_newline(options, depth, out);
+            }
+            paint(scheme.punctuation, out, |out| out.push(']'));
+        }
+        JsonValue::Object(obj) => {
+            let mut members: Vec<(&String, &JsonValue)> = obj
+                .iter()
+                .filter(|(_, item)| !(options.skip_null_fields && item.is_null()))
+                .collect();
+            if options.sort_keys {
+                members.sort_by(|a, b| a.0.cmp(b.0));
+            }
+            paint(scheme.punctuation, out, |out| out.push('{'));
+            let empty = members.is_empty();
+            for (i, (key, item)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    paint(scheme.punctuation, out, |out| out.push(','));
+                }
+                write_newline(options, depth + 1, out);
+                paint(scheme.key, out, |out| write_string(key, options, out));
+                paint(scheme.punctuation, out, |out| out.push(':'));
+                if options.indent.is_some() {
+                    out.push(' ');
+                }
+                write_colored(item, scheme, options, depth + 1, out)?;
+            }
+            if !empty {
+                write_newline(options, depth, out);
+            }
+            paint(scheme.punctuation, out, |out| out.push('}'));
+        }
+        _ => {
+            let color = match value {
+                JsonValue::Null | JsonValue::Bool(_) => scheme.literal,
+                // Non-finite numbers may be written as `null`, which is
+                // still colored as a number.
+                JsonValue::Number(_) | JsonValue::RawNumber(_) => scheme.number,
+                JsonValue::String(_) | JsonValue::Bytes(_) => scheme.string,
+                // Verbatim text may hold any number of tokens.
+                _ => "",
+            };
+            let mut result = Ok(());
+            paint(color, out, |out| {
+                result = write_value(value, options, depth, out);
+            });
+            result?;
+        }
+    }
+    Ok(())
+}
+
+// Runs `write`, surrounded by the escape codes for `color` unless it is
+// empty.
+fn paint(color: &str, out: &mut String, write: impl FnOnce(&mut String)) {
+    if color.is_empty() {
+        write(out);
+    } else {
+        out.push_str("\x1b[");
+        out.push_str(color);
+        out.push('m');
+        write(out);
+        out.push_str("\x1b[0m");
+    }
+}
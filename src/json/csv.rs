@@ -0,0 +1,215 @@
+use super::stringify::stringify_compact;
+use super::{JsonValue, Map};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Which CSV fields `to_csv` puts in quotes. Fields containing the
+/// delimiter, a quote or a line break are always quoted.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum QuoteStyle {
+    #[default]
+    Necessary,
+    Always,
+    /// Every field except numbers, booleans and empty ones.
+    NonNumeric,
+}
+
+/// Knobs for `parse_csv` and `to_csv`. The default is comma-separated
+/// with quotes only where needed.
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    pub delimiter: char,
+    pub quote: QuoteStyle,
+    /// The columns `to_csv` writes, in order. `None` writes every key found
+    /// in any row, in ascending order.
+    pub columns: Option<Vec<String>>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: ',',
+            quote: QuoteStyle::default(),
+            columns: None,
+        }
+    }
+}
+
+/// Malformed CSV, or a value `to_csv` cannot write. `line` is 1-based for
+/// parse errors and 0 otherwise.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CsvError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{} on line {}", self.message, self.line)
+        }
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+fn error(line: usize, message: &str) -> CsvError {
+    CsvError {
+        line,
+        message: message.to_string(),
+    }
+}
+
+/// Parses CSV whose first row names the columns into an array of objects,
+/// one per row, keyed by column name. Fields stay strings and blank lines
+/// are skipped. Quoted fields may contain delimiters, line breaks and
+/// doubled quotes. Every row must have as many fields as the header.
+pub fn parse_csv(input: &str, options: &CsvOptions) -> Result<JsonValue, CsvError> {
+    let mut rows = records(input, options.delimiter)?.into_iter();
+    let Some((_, header)) = rows.next() else {
+        return Ok(JsonValue::Array(Vec::new()));
+    };
+    rows.map(|(line, fields)| {
+        if fields.len() != header.len() {
+            return Err(error(
+                line,
+                &format!("expected {} fields, found {}", header.len(), fields.len()),
+            ));
+        }
+        let row: Map = header
+            .iter()
+            .cloned()
+            .zip(
+                fields
+                    .into_iter()
+                    .map(|field| JsonValue::String(field.into())),
+            )
+            .collect();
+        Ok(JsonValue::Object(Box::new(row)))
+    })
+    .collect()
+}
+
+/// Writes an array of objects as CSV with a header row, lines ending in
+/// `\r\n`. Strings are written as is, `null` and missing members as empty
+/// fields, and arrays and objects as their compact JSON text.
+pub fn to_csv(value: &JsonValue, options: &CsvOptions) -> Result<String, CsvError> {
+    let rows = value
+        .as_array()
+        .ok_or_else(|| error(0, "CSV output needs an array of objects"))?;
+    let rows: Vec<&Map> = rows
+        .iter()
+        .map(|row| {
+            row.as_object()
+                .ok_or_else(|| error(0, "every row must be an object"))
+        })
+        .collect::<Result<_, _>>()?;
+    let columns = match &options.columns {
+        Some(columns) => columns.clone(),
+        None => {
+            let keys: BTreeSet<&String> = rows.iter().flat_map(|row| row.keys()).collect();
+            keys.into_iter().cloned().collect()
+        }
+    };
+
+    let mut out = String::new();
+    let mut write_record = |fields: Vec<(String, bool)>| {
+        let fields: Vec<String> = fields
+            .into_iter()
+            .map(|(text, numeric)| quote(&text, numeric, options))
+            .collect();
+        out.push_str(&fields.join(&options.delimiter.to_string()));
+        out.push_str("\r\n");
+    };
+    write_record(columns.iter().map(|c| (c.clone(), false)).collect());
+    for row in rows {
+        write_record(columns.iter().map(|c| field(row.get(c))).collect());
+    }
+    Ok(out)
+}
+
+// A value's field text, and whether it is a number or boolean.
+fn field(value: Option<&JsonValue>) -> (String, bool) {
+    match value {
+        None | Some(JsonValue::Null) => (String::new(), false),
+        Some(JsonValue::String(s)) => (s.to_string(), false),
+        Some(JsonValue::Bytes(bytes)) => (String::from_utf8_lossy(bytes).into_owned(), false),
+        Some(value) => (
+            stringify_compact(value),
+            value.is_number() || value.is_bool(),
+        ),
+    }
+}
+
+fn quote(text: &str, numeric: bool, options: &CsvOptions) -> String {
+    let needed = text.contains([options.delimiter, '"', '\n', '\r']);
+    let wanted = match options.quote {
+        QuoteStyle::Necessary => false,
+        QuoteStyle::Always => true,
+        QuoteStyle::NonNumeric => !numeric && !text.is_empty(),
+    };
+    if needed || wanted {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+// Splits CSV text into records of fields, each with its starting line.
+fn records(input: &str, delimiter: char) -> Result<Vec<(usize, Vec<String>)>, CsvError> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if field.is_empty() => {
+                let quote_line = line;
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            field.push(c);
+                        }
+                        None => return Err(error(quote_line, "unterminated quoted field")),
+                    }
+                }
+                match chars.peek() {
+                    None | Some('\r' | '\n') => {}
+                    Some(&c) if c == delimiter => {}
+                    Some(_) => return Err(error(line, "unexpected text after a quoted field")),
+                }
+            }
+            c if c == delimiter => fields.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            // Blank lines are skipped.
+            '\n' if field.is_empty() && fields.is_empty() => {
+                line += 1;
+                start = line;
+            }
+            '\n' => {
+                fields.push(std::mem::take(&mut field));
+                records.push((start, std::mem::take(&mut fields)));
+                line += 1;
+                start = line;
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((start, fields));
+    }
+    Ok(records)
+}
@@ -0,0 +1,70 @@
+use super::JsonValue;
+use std::fmt;
+
+// Containers show at most this many members before eliding the rest.
+const MAX_ITEMS: usize = 20;
+// Strings and raw fragments are cut to this many characters.
+const MAX_CHARS: usize = 100;
+
+/// Prints JSON-like structure rather than the enum layout, e.g.
+/// `{"a": [1, true, null]}`, with object keys sorted. Long strings and large
+/// containers are cut short and marked with `..`; `{:#?}` puts each member
+/// on its own line.
+impl fmt::Debug for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonValue::Null => f.write_str("null"),
+            JsonValue::Bool(b) => write!(f, "{}", b),
+            JsonValue::Number(n) => write!(f, "{}", n),
+            JsonValue::RawNumber(text) => f.write_str(text),
+            JsonValue::Raw(raw) => {
+                let (head, cut) = truncate(raw.as_str());
+                f.write_str(head)?;
+                if cut { f.write_str("..") } else { Ok(()) }
+            }
+            JsonValue::String(s) => {
+                let (head, cut) = truncate(s);
+                write!(f, "{:?}", head)?;
+                if cut { f.write_str("..") } else { Ok(()) }
+            }
+            JsonValue::Bytes(bytes) => {
+                let (head, cut) = bytes.split_at(bytes.len().min(MAX_CHARS));
+                write!(f, "b\"{}\"", head.escape_ascii())?;
+                if cut.is_empty() {
+                    Ok(())
+                } else {
+                    f.write_str("..")
+                }
+            }
+            JsonValue::Array(items) => {
+                let mut list = f.debug_list();
+                list.entries(items.iter().take(MAX_ITEMS));
+                if items.len() > MAX_ITEMS {
+                    list.finish_non_exhaustive()
+                } else {
+                    list.finish()
+                }
+            }
+            JsonValue::Object(_) => {
+                let members = self.iter_sorted();
+                let len = members.len();
+                let mut map = f.debug_map();
+                map.entries(members.take(MAX_ITEMS));
+                if len > MAX_ITEMS {
+                    map.finish_non_exhaustive()
+                } else {
+                    map.finish()
+                }
+            }
+        }
+    }
+}
+
+// Cuts `text` to `MAX_CHARS` characters, reporting whether anything was
+// dropped.
+fn truncate(text: &str) -> (&str, bool) {
+    match text.char_indices().nth(MAX_CHARS) {
+        Some((end, _)) => (&text[..end], true),
+        None => (text, false),
+    }
+}
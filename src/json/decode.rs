@@ -0,0 +1,331 @@
+use super::{JsonValue, ParseError, ReadError, parse, parse_reader};
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{self, Read};
+
+/// An invalid byte sequence found while decoding; `offset` counts bytes from
+/// the start of the stream.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DecodeError {
+    pub offset: usize,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid byte sequence at offset {}", self.offset)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Transcodes raw input bytes to UTF-8 one chunk at a time.
+pub trait InputDecoder {
+    /// Decodes a prefix of `input`, appending the text to `output`, and
+    /// returns how many bytes were consumed. A sequence cut off at the end
+    /// of the chunk should be left unconsumed; it is passed again with the
+    /// next chunk. `offset` is the stream position of `input[0]`.
+    fn decode(
+        &mut self,
+        input: &[u8],
+        offset: usize,
+        output: &mut String,
+    ) -> Result<usize, DecodeError>;
+}
+
+/// ISO-8859-1: every byte maps to the code point of the same value.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Latin1Decoder;
+
+impl InputDecoder for Latin1Decoder {
+    fn decode(
+        &mut self,
+        input: &[u8],
+        _: usize,
+        output: &mut String,
+    ) -> Result<usize, DecodeError> {
+        output.extend(input.iter().map(|&b| b as char));
+        Ok(input.len())
+    }
+}
+
+/// Validating UTF-8 pass-through.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Utf8Decoder;
+
+impl InputDecoder for Utf8Decoder {
+    fn decode(
+        &mut self,
+        input: &[u8],
+        offset: usize,
+        output: &mut String,
+    ) -> Result<usize, DecodeError> {
+        match std::str::from_utf8(input) {
+            Ok(text) => {
+                output.push_str(text);
+                Ok(input.len())
+            }
+            Err(e) => {
+                let valid = e.valid_up_to();
+                output.push_str(std::str::from_utf8(&input[..valid]).unwrap());
+                match e.error_len() {
+                    // Truncated sequence at the end of the chunk
+                    None => Ok(valid),
+                    Some(_) => Err(DecodeError {
+                        offset: offset + valid,
+                    }),
+                }
+            }
+        }
+    }
+}
+
+/// A single-byte code page described by a 256-entry table, e.g. an EBCDIC
+/// variant.
+#[derive(Debug, Clone)]
+pub struct TableDecoder {
+    table: [char; 256],
+}
+
+impl TableDecoder {
+    pub fn new(table: [char; 256]) -> Self {
+        TableDecoder { table }
+    }
+}
+
+impl InputDecoder for TableDecoder {
+    fn decode(
+        &mut self,
+        input: &[u8],
+        _: usize,
+        output: &mut String,
+    ) -> Result<usize, DecodeError> {
+        output.extend(input.iter().map(|&b| self.table[b as usize]));
+        Ok(input.len())
+    }
+}
+
+/// UTF-16 in either byte order. Unpaired surrogates are rejected.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Utf16Decoder {
+    pub big_endian: bool,
+}
+
+impl InputDecoder for Utf16Decoder {
+    fn decode(
+        &mut self,
+        input: &[u8],
+        offset: usize,
+        output: &mut String,
+    ) -> Result<usize, DecodeError> {
+        let unit = |i: usize| {
+            let pair = [input[i], input[i + 1]];
+            if self.big_endian {
+                u16::from_be_bytes(pair)
+            } else {
+                u16::from_le_bytes(pair)
+            }
+        };
+        let mut i = 0;
+        while i + 1 < input.len() {
+            let high = unit(i);
+            let (c, len) = match high {
+                0xD800..=0xDBFF if i + 3 >= input.len() => break,
+                0xD800..=0xDBFF => match unit(i + 2) {
+                    low @ 0xDC00..=0xDFFF => {
+                        let code = 0x10000 + ((high as u32 - 0xD800) << 10) + (low as u32 - 0xDC00);
+                        (char::from_u32(code), 4)
+                    }
+                    _ => (None, 2),
+                },
+                _ => (char::from_u32(high as u32), 2),
+            };
+            let c = c.ok_or(DecodeError { offset: offset + i })?;
+            output.push(c);
+            i += len;
+        }
+        Ok(i)
+    }
+}
+
+/// An input encoding recognised by `detect_encoding`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+/// Detects the encoding of JSON text from its byte order mark or, failing
+/// that, from where the zero bytes fall in its first two characters, which
+/// are ASCII in any JSON text (RFC 4627, section 3). Returns the encoding
+/// and the length of the byte order mark, 0 if there is none.
+pub fn detect_encoding(bytes: &[u8]) -> (Encoding, usize) {
+    match bytes {
+        [0xEF, 0xBB, 0xBF, ..] => (Encoding::Utf8, 3),
+        [0xFF, 0xFE, ..] => (Encoding::Utf16Le, 2),
+        [0xFE, 0xFF, ..] => (Encoding::Utf16Be, 2),
+        [0, b, ..] if *b != 0 => (Encoding::Utf16Be, 0),
+        [b, 0, ..] if *b != 0 => (Encoding::Utf16Le, 0),
+        _ => (Encoding::Utf8, 0),
+    }
+}
+
+/// Decodes JSON text in UTF-8 or UTF-16, as told apart by
+/// `detect_encoding`, dropping any byte order mark. UTF-8 input is
+/// borrowed, not copied. Error offsets count bytes from the start of
+/// `bytes`.
+pub fn decode_detect_encoding(bytes: &[u8]) -> Result<Cow<'_, str>, DecodeError> {
+    let (encoding, bom) = detect_encoding(bytes);
+    let body = &bytes[bom..];
+    let big_endian = match encoding {
+        Encoding::Utf8 => {
+            return std::str::from_utf8(body)
+                .map(Cow::Borrowed)
+                .map_err(|e| DecodeError {
+                    offset: bom + e.valid_up_to(),
+                });
+        }
+        Encoding::Utf16Le => false,
+        Encoding::Utf16Be => true,
+    };
+    let mut text = String::with_capacity(body.len() / 2);
+    let consumed = Utf16Decoder { big_endian }.decode(body, bom, &mut text)?;
+    if consumed < body.len() {
+        return Err(DecodeError {
+            offset: bom + consumed,
+        });
+    }
+    Ok(Cow::Owned(text))
+}
+
+/// Input bytes that could not be decoded, or whose text is not valid JSON.
+#[derive(Debug, PartialEq, Clone)]
+pub enum BytesError {
+    Decode(DecodeError),
+    Parse(ParseError),
+}
+
+impl fmt::Display for BytesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BytesError::Decode(e) => write!(f, "{}", e),
+            BytesError::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for BytesError {}
+
+impl From<DecodeError> for BytesError {
+    fn from(e: DecodeError) -> Self {
+        BytesError::Decode(e)
+    }
+}
+
+impl From<ParseError> for BytesError {
+    fn from(e: ParseError) -> Self {
+        BytesError::Parse(e)
+    }
+}
+
+/// Parses JSON bytes in UTF-8, with or without a byte order mark, or in
+/// UTF-16 of either byte order; see `decode_detect_encoding`. Parse error
+/// positions refer to the decoded text, after any byte order mark.
+pub fn parse_bytes_detect_encoding(bytes: &[u8]) -> Result<JsonValue, BytesError> {
+    Ok(parse(&decode_detect_encoding(bytes)?)?)
+}
+
+const CHUNK_SIZE: usize = 8192;
+
+/// Adapts a byte reader in some encoding into a reader of UTF-8 bytes,
+/// decoding as data is pulled rather than converting the whole input first.
+/// `parse_reader` parses from it directly.
+pub struct DecodeReader<R, D> {
+    inner: R,
+    decoder: D,
+    raw: Vec<u8>,
+    raw_offset: usize,
+    decoded: String,
+    decoded_pos: usize,
+    eof: bool,
+}
+
+impl<R: Read, D: InputDecoder> DecodeReader<R, D> {
+    pub fn new(inner: R, decoder: D) -> Self {
+        DecodeReader {
+            inner,
+            decoder,
+            raw: Vec::new(),
+            raw_offset: 0,
+            decoded: String::new(),
+            decoded_pos: 0,
+            eof: false,
+        }
+    }
+
+    // Refills `decoded`; returns false once the input is exhausted.
+    fn fill(&mut self) -> io::Result<bool> {
+        self.decoded.clear();
+        self.decoded_pos = 0;
+
+        while self.decoded.is_empty() {
+            if self.eof {
+                if self.raw.is_empty() {
+                    return Ok(false);
+                }
+                // Leftover bytes the decoder could never complete
+                return Err(invalid_data(DecodeError {
+                    offset: self.raw_offset,
+                }));
+            }
+
+            let start = self.raw.len();
+            self.raw.resize(start + CHUNK_SIZE, 0);
+            let n = self.inner.read(&mut self.raw[start..])?;
+            self.raw.truncate(start + n);
+            self.eof = n == 0;
+
+            let consumed = self
+                .decoder
+                .decode(&self.raw, self.raw_offset, &mut self.decoded)
+                .map_err(invalid_data)?;
+            self.raw.drain(..consumed);
+            self.raw_offset += consumed;
+        }
+        Ok(true)
+    }
+}
+
+impl<R: Read, D: InputDecoder> Read for DecodeReader<R, D> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.decoded_pos == self.decoded.len() && !self.fill()? {
+            return Ok(0);
+        }
+        let available = &self.decoded.as_bytes()[self.decoded_pos..];
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.decoded_pos += n;
+        Ok(n)
+    }
+}
+
+fn invalid_data(e: DecodeError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// Parses a document from a stream in the encoding `decoder` reads,
+/// decoding and parsing it a chunk at a time; see `parse_reader`.
+pub fn parse_decoded<R: Read, D: InputDecoder>(
+    reader: R,
+    decoder: D,
+) -> Result<JsonValue, ReadError> {
+    parse_reader(DecodeReader::new(reader, decoder))
+}
+
+/// Reads and decodes an entire stream into a string, for callers that need
+/// the text itself; `parse_decoded` parses without building it.
+pub fn decode_to_string<R: Read, D: InputDecoder>(reader: R, decoder: D) -> io::Result<String> {
+    let mut text = String::new();
+    DecodeReader::new(reader, decoder).read_to_string(&mut text)?;
+    Ok(text)
+}
@@ -0,0 +1,84 @@
+use super::{ParserOptions, StringifyOptions};
+use std::cell::RefCell;
+use std::sync::RwLock;
+
+static PARSER_DEFAULTS: RwLock<Option<ParserOptions>> = RwLock::new(None);
+static STRINGIFY_DEFAULTS: RwLock<Option<StringifyOptions>> = RwLock::new(None);
+
+thread_local! {
+    static SCOPED_PARSER: RefCell<Option<ParserOptions>> = const { RefCell::new(None) };
+    static SCOPED_STRINGIFY: RefCell<Option<StringifyOptions>> = const { RefCell::new(None) };
+}
+
+/// Sets the options used process-wide by `parse` and the other entry points
+/// that take no `ParserOptions`.
+pub fn set_default_parser_options(options: ParserOptions) {
+    *PARSER_DEFAULTS.write().unwrap() = Some(options);
+}
+
+/// Sets the options used process-wide by `stringify` and `stringify_pretty`.
+pub fn set_default_stringify_options(options: StringifyOptions) {
+    *STRINGIFY_DEFAULTS.write().unwrap() = Some(options);
+}
+
+/// Clears the process-wide defaults, going back to `Default::default()`.
+pub fn reset_defaults() {
+    *PARSER_DEFAULTS.write().unwrap() = None;
+    *STRINGIFY_DEFAULTS.write().unwrap() = None;
+}
+
+/// Runs `f` with `options` as the parser defaults on the current thread,
+/// overriding the process-wide ones. Scopes nest.
+pub fn with_parser_options<R>(options: ParserOptions, f: impl FnOnce() -> R) -> R {
+    scoped(&SCOPED_PARSER, options, f)
+}
+
+/// Runs `f` with `options` as the stringify defaults on the current thread,
+/// overriding the process-wide ones. Scopes nest.
+pub fn with_stringify_options<R>(options: StringifyOptions, f: impl FnOnce() -> R) -> R {
+    scoped(&SCOPED_STRINGIFY, options, f)
+}
+
+impl ParserOptions {
+    /// The options `parse` uses: the innermost `with_parser_options` scope
+    /// on this thread, else the process-wide defaults, else `default()`.
+    pub fn current() -> Self {
+        SCOPED_PARSER
+            .with_borrow(Clone::clone)
+            .or_else(|| PARSER_DEFAULTS.read().unwrap().clone())
+            .unwrap_or_default()
+    }
+}
+
+impl StringifyOptions {
+    /// The options `stringify` uses, resolved like `ParserOptions::current`.
+    pub fn current() -> Self {
+        SCOPED_STRINGIFY
+            .with_borrow(Clone::clone)
+            .or_else(|| STRINGIFY_DEFAULTS.read().unwrap().clone())
+            .unwrap_or_default()
+    }
+}
+
+fn scoped<T: 'static, R>(
+    slot: &'static std::thread::LocalKey<RefCell<Option<T>>>,
+    value: T,
+    f: impl FnOnce() -> R,
+) -> R {
+    // Restores the outer scope even if `f` panics.
+    struct Restore<T: 'static> {
+        slot: &'static std::thread::LocalKey<RefCell<Option<T>>>,
+        previous: Option<T>,
+    }
+    impl<T: 'static> Drop for Restore<T> {
+        fn drop(&mut self) {
+            self.slot.set(self.previous.take());
+        }
+    }
+
+    let _restore = Restore {
+        slot,
+        previous: slot.replace(Some(value)),
+    };
+    f()
+}
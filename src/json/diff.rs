@@ -0,0 +1,263 @@
+use super::map::remove_member;
+use super::pointer::{escape_pointer_token, parse_index, unescape_token};
+use super::{JsonValue, Map};
+use std::fmt;
+
+/// One RFC 6902 JSON Patch operation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchOp {
+    Add { path: String, value: JsonValue },
+    Remove { path: String },
+    Replace { path: String, value: JsonValue },
+}
+
+impl PatchOp {
+    /// The JSON Pointer the operation targets.
+    pub fn path(&self) -> &str {
+        match self {
+            PatchOp::Add { path, .. }
+            | PatchOp::Remove { path }
+            | PatchOp::Replace { path, .. } => path,
+        }
+    }
+
+    /// The operation as a JSON Patch object, e.g.
+    /// `{"op": "remove", "path": "/a"}`.
+    pub fn to_json(&self) -> JsonValue {
+        let (op, value) = match self {
+            PatchOp::Add { value, .. } => ("add", Some(value)),
+            PatchOp::Remove { .. } => ("remove", None),
+            PatchOp::Replace { value, .. } => ("replace", Some(value)),
+        };
+        let mut obj = Map::new();
+        obj.insert("op".to_string(), JsonValue::String(op.into()));
+        obj.insert("path".to_string(), JsonValue::String(self.path().into()));
+        if let Some(value) = value {
+            obj.insert("value".to_string(), value.clone());
+        }
+        JsonValue::Object(Box::new(obj))
+    }
+}
+
+/// A patch operation that does not fit the document, e.g. removing a
+/// member that does not exist.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PatchError {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot apply patch at {:?}: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+/// Applies a JSON Patch. Either every operation applies or, on error, the
+/// value is left unchanged. In `add`, the index `-` appends to an array.
+pub fn apply_patch(value: &mut JsonValue, ops: &[PatchOp]) -> Result<(), PatchError> {
+    let mut patched = value.clone();
+    for op in ops {
+        apply_op(&mut patched, op)?;
+    }
+    *value = patched;
+    Ok(())
+}
+
+pub(crate) fn apply_op(value: &mut JsonValue, op: &PatchOp) -> Result<(), PatchError> {
+    let error = |message: &str| PatchError {
+        path: op.path().to_string(),
+        message: message.to_string(),
+    };
+    if let PatchOp::Replace { path, value: new } = op {
+        *value
+            .pointer_mut(path)
+            .ok_or_else(|| error("no such value"))? = new.clone();
+        return Ok(());
+    }
+    if op.path().is_empty() {
+        match op {
+            PatchOp::Add { value: new, .. } => *value = new.clone(),
+            _ => return Err(error("cannot remove the root")),
+        }
+        return Ok(());
+    }
+
+    let (parent, token) = op
+        .path()
+        .rsplit_once('/')
+        .ok_or_else(|| error("invalid pointer"))?;
+    let token = unescape_token(token);
+    match (value.pointer_mut(parent), op) {
+        (Some(JsonValue::Object(obj)), PatchOp::Add { value: new, .. }) => {
+            obj.insert(token, new.clone());
+        }
+        (Some(JsonValue::Object(obj)), _) => {
+            remove_member(obj, &token).ok_or_else(|| error("no such member"))?;
+        }
+        (Some(JsonValue::Array(arr)), PatchOp::Add { value: new, .. }) => {
+            let index = match token.as_str() {
+                "-" => arr.len(),
+                _ => parse_index(&token)
+                    .filter(|&i| i <= arr.len())
+                    .ok_or_else(|| error("array index out of range"))?,
+            };
+            arr.insert(index, new.clone());
+        }
+        (Some(JsonValue::Array(arr)), _) => {
+            let index = parse_index(&token)
+                .filter(|&i| i < arr.len())
+                .ok_or_else(|| error("array index out of range"))?;
+            arr.remove(index);
+        }
+        _ => return Err(error("parent is not an object or array")),
+    }
+    Ok(())
+}
+
+/// Computes a JSON Patch that turns `from` into `to`. Objects are compared
+/// member by member in key order. Arrays are aligned on their longest
+/// common subsequence, so inserting or removing one element costs one
+/// operation rather than rewriting every element after it; changed elements
+/// are diffed in place.
+pub fn diff(from: &JsonValue, to: &JsonValue) -> Vec<PatchOp> {
+    let mut ops = Vec::new();
+    diff_into(from, to, &mut String::new(), &mut ops);
+    ops
+}
+
+// Beyond this many (element, element) pairs, arrays are compared by
+// position to bound the quadratic alignment.
+const MAX_ALIGNMENT_CELLS: usize = 1 << 20;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Edit {
+    Keep,
+    Delete,
+    Insert,
+}
+
+fn diff_into(from: &JsonValue, to: &JsonValue, path: &mut String, ops: &mut Vec<PatchOp>) {
+    match (from, to) {
+        (JsonValue::Object(a), JsonValue::Object(b)) => {
+            let mut keys: Vec<&String> = a
+                .keys()
+                .chain(b.keys().filter(|k| !a.contains_key(*k)))
+                .collect();
+            keys.sort();
+            for key in keys {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&escape_pointer_token(key));
+                match (a.get(key), b.get(key)) {
+                    (Some(x), Some(y)) => diff_into(x, y, path, ops),
+                    (Some(_), None) => ops.push(PatchOp::Remove { path: path.clone() }),
+                    (None, Some(y)) => ops.push(PatchOp::Add {
+                        path: path.clone(),
+                        value: y.clone(),
+                    }),
+                    (None, None) => unreachable!(),
+                }
+                path.truncate(len);
+            }
+        }
+        (JsonValue::Array(a), JsonValue::Array(b)) => diff_array(a, b, path, ops),
+        _ if from == to => {}
+        _ => ops.push(PatchOp::Replace {
+            path: path.clone(),
+            value: to.clone(),
+        }),
+    }
+}
+
+fn diff_array(a: &[JsonValue], b: &[JsonValue], path: &mut String, ops: &mut Vec<PatchOp>) {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let a = &a[prefix..a.len() - suffix];
+    let b = &b[prefix..b.len() - suffix];
+
+    // Each run of edits between kept elements pairs deletions with
+    // insertions as in-place changes, then removes or adds the rest.
+    let (mut i, mut j, mut index) = (0, 0, prefix);
+    let mut script = align(a, b).into_iter().peekable();
+    while script.peek().is_some() {
+        let (start_i, start_j) = (i, j);
+        while let Some(edit) = script.next_if(|edit| *edit != Edit::Keep) {
+            match edit {
+                Edit::Delete => i += 1,
+                _ => j += 1,
+            }
+        }
+        let (deleted, inserted) = (&a[start_i..i], &b[start_j..j]);
+        let changed = deleted.len().min(inserted.len());
+        for (x, y) in deleted.iter().zip(inserted) {
+            let len = path.len();
+            path.push_str(&format!("/{}", index));
+            diff_into(x, y, path, ops);
+            path.truncate(len);
+            index += 1;
+        }
+        // Remove from the back so the earlier indices stay valid.
+        for k in (index..index + deleted.len() - changed).rev() {
+            ops.push(PatchOp::Remove {
+                path: format!("{}/{}", path, k),
+            });
+        }
+        for y in &inserted[changed..] {
+            ops.push(PatchOp::Add {
+                path: format!("{}/{}", path, index),
+                value: y.clone(),
+            });
+            index += 1;
+        }
+        if script.next_if_eq(&Edit::Keep).is_some() {
+            (i, j, index) = (i + 1, j + 1, index + 1);
+        }
+    }
+}
+
+// An edit script turning `a` into `b` that keeps a longest common
+// subsequence.
+fn align(a: &[JsonValue], b: &[JsonValue]) -> Vec<Edit> {
+    let (n, m) = (a.len(), b.len());
+    if n.saturating_mul(m) > MAX_ALIGNMENT_CELLS {
+        let mut script = vec![Edit::Delete; n];
+        script.resize(n + m, Edit::Insert);
+        return script;
+    }
+
+    // lcs[i][j] is the LCS length of a[i..] and b[j..].
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut script = Vec::with_capacity(n + m);
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && a[i] == b[j] {
+            script.push(Edit::Keep);
+            (i, j) = (i + 1, j + 1);
+        } else if j == m || (i < n && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            script.push(Edit::Delete);
+            i += 1;
+        } else {
+            script.push(Edit::Insert);
+            j += 1;
+        }
+    }
+    script
+}
@@ -0,0 +1,103 @@
+use super::{JsonValue, ParseError, parse};
+
+/// One document from a `---`-separated stream, with its location in the
+/// original input so errors can be mapped back to it.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Document<'a> {
+    pub text: &'a str,
+    /// Byte offset of `text` within the original input.
+    pub offset: usize,
+    /// 1-based line number of the first line of `text`.
+    pub line: usize,
+    /// 1-based column at which `text` starts on that line.
+    pub column: usize,
+}
+
+impl Document<'_> {
+    /// Parses the document, reporting error positions relative to the whole
+    /// stream rather than to `text`.
+    pub fn parse(&self) -> Result<JsonValue, ParseError> {
+        parse(self.text).map_err(|mut e| {
+            let pos = e.position_mut();
+            if pos.line == 1 {
+                pos.column += self.column - 1;
+            }
+            pos.offset += self.offset;
+            pos.line += self.line - 1;
+            e
+        })
+    }
+}
+
+pub struct Documents<'a> {
+    input: &'a str,
+    pos: usize,
+    line: usize,
+}
+
+impl<'a> Iterator for Documents<'a> {
+    type Item = Document<'a>;
+
+    fn next(&mut self) -> Option<Document<'a>> {
+        while self.pos < self.input.len() {
+            let start = self.pos;
+            let start_line = self.line;
+            let mut end = self.input.len();
+
+            // Scan line by line for the next separator.
+            let mut line_start = start;
+            while line_start < self.input.len() {
+                let rest = &self.input[line_start..];
+                let line_len = rest.find('\n').map_or(rest.len(), |i| i + 1);
+                if line_start > start && is_separator(rest) {
+                    end = line_start;
+                    break;
+                }
+                line_start += line_len;
+                self.line += 1;
+            }
+
+            self.pos = end;
+            let mut text = &self.input[start..end];
+            let mut offset = start;
+            let mut column = 1;
+
+            // Drop the separator that opens this document, keeping any
+            // content on the same line (`--- {"a": 1}`).
+            if is_separator(text) {
+                text = &text[3..];
+                offset += 3;
+                column += 3;
+            }
+            if text.trim().is_empty() {
+                continue;
+            }
+            return Some(Document {
+                text,
+                offset,
+                line: start_line,
+                column,
+            });
+        }
+        None
+    }
+}
+
+fn is_separator(line: &str) -> bool {
+    line.starts_with("---") && line[3..].chars().next().is_none_or(char::is_whitespace)
+}
+
+/// Splits a stream of documents separated by YAML-style `---` lines.
+/// Empty documents (e.g. from a leading separator) are skipped.
+pub fn split_documents(input: &str) -> Documents<'_> {
+    Documents {
+        input,
+        pos: 0,
+        line: 1,
+    }
+}
+
+/// Splits and parses every document in a `---`-separated stream.
+pub fn parse_documents(input: &str) -> Vec<Result<JsonValue, ParseError>> {
+    split_documents(input).map(|doc| doc.parse()).collect()
+}
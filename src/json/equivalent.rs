@@ -0,0 +1,109 @@
+use super::JsonValue;
+use super::pointer::escape_pointer_token;
+
+/// Knobs for `JsonValue::equivalent`. The default is strict: it agrees with
+/// `==` except that numbers kept as text compare by value.
+#[derive(Debug, Clone, Default)]
+pub struct EqOptions {
+    /// Treat arrays as multisets: equal if their elements can be paired off
+    /// regardless of position. Elements are paired greedily, first match
+    /// first.
+    pub unordered_arrays: bool,
+    /// Numbers are equal if they differ by at most this much.
+    pub epsilon: f64,
+    /// JSON Pointers of values to leave out of the comparison, on either
+    /// side. A `*` token matches every member or element. Inside unordered
+    /// arrays, elements are addressed by their index on the left.
+    pub ignore_paths: Vec<String>,
+}
+
+impl JsonValue {
+    /// Compares two values the way `options` says, for tests of API
+    /// responses that should not fail on element order, rounding or
+    /// volatile fields such as timestamps.
+    pub fn equivalent(&self, other: &JsonValue, options: EqOptions) -> bool {
+        equivalent(self, other, &options, &mut String::new())
+    }
+}
+
+fn equivalent(a: &JsonValue, b: &JsonValue, options: &EqOptions, path: &mut String) -> bool {
+    if let (Some(x), Some(y)) = (a.as_f64(), b.as_f64()) {
+        return x == y || (x - y).abs() <= options.epsilon;
+    }
+    match (a, b) {
+        (JsonValue::Array(xs), JsonValue::Array(ys)) => {
+            if options.unordered_arrays {
+                unordered(xs, ys, options, path)
+            } else {
+                xs.len() == ys.len()
+                    && xs.iter().zip(ys).enumerate().all(|(i, (x, y))| {
+                        within(path, &i.to_string(), |path| {
+                            ignored(path, options) || equivalent(x, y, options, path)
+                        })
+                    })
+            }
+        }
+        (JsonValue::Object(xs), JsonValue::Object(ys)) => {
+            let mut keys = xs.keys().chain(ys.keys().filter(|k| !xs.contains_key(*k)));
+            keys.all(|key| {
+                within(path, key, |path| {
+                    ignored(path, options)
+                        || match (xs.get(key), ys.get(key)) {
+                            (Some(x), Some(y)) => equivalent(x, y, options, path),
+                            _ => false,
+                        }
+                })
+            })
+        }
+        _ => a == b,
+    }
+}
+
+// Pairs each element of `xs` with an unused equivalent element of `ys`.
+// Ignored elements of `xs` are dropped along with one leftover of `ys`.
+fn unordered(xs: &[JsonValue], ys: &[JsonValue], options: &EqOptions, path: &mut String) -> bool {
+    let mut unused: Vec<&JsonValue> = ys.iter().collect();
+    let mut ignored_count = 0;
+    for (i, x) in xs.iter().enumerate() {
+        let matched = within(path, &i.to_string(), |path| {
+            if ignored(path, options) {
+                ignored_count += 1;
+                return true;
+            }
+            match unused.iter().position(|y| equivalent(x, y, options, path)) {
+                Some(j) => {
+                    unused.swap_remove(j);
+                    true
+                }
+                None => false,
+            }
+        });
+        if !matched {
+            return false;
+        }
+    }
+    unused.len() == ignored_count
+}
+
+fn within<T>(path: &mut String, token: &str, f: impl FnOnce(&mut String) -> T) -> T {
+    let len = path.len();
+    path.push('/');
+    path.push_str(&escape_pointer_token(token));
+    let result = f(path);
+    path.truncate(len);
+    result
+}
+
+fn ignored(path: &str, options: &EqOptions) -> bool {
+    options.ignore_paths.iter().any(|pattern| {
+        let mut want = pattern.split('/');
+        let mut have = path.split('/');
+        loop {
+            match (want.next(), have.next()) {
+                (None, None) => return true,
+                (Some(w), Some(h)) if w == "*" || w == h => {}
+                _ => return false,
+            }
+        }
+    })
+}
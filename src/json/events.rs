@@ -0,0 +1,178 @@
+use super::{JsonValue, ParseError, Parser, ParserOptions};
+
+/// A single step of a document, as produced by `EventReader`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum JsonEvent {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    Key(String),
+    /// A scalar: null, boolean, number or string.
+    Value(JsonValue),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Frame {
+    Array(State),
+    Object(State),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Start,
+    AfterKey,
+    AfterValue,
+    AfterComma,
+}
+
+// What the input holds at the current position once separators are consumed.
+enum Step {
+    Value,
+    Key,
+    End,
+    Done,
+}
+
+/// Pull parser that reports a document as a sequence of events without
+/// building the tree. Duplicate keys are reported as they appear.
+pub struct EventReader<'a> {
+    parser: Parser<'a>,
+    stack: Vec<Frame>,
+    started: bool,
+}
+
+impl<'a> EventReader<'a> {
+    pub fn new(input: &'a str) -> Self {
+        EventReader::with_options(input, ParserOptions::current())
+    }
+
+    pub fn with_options(input: &'a str, options: ParserOptions) -> Self {
+        EventReader {
+            parser: Parser::with_options(input, options),
+            stack: Vec::new(),
+            started: false,
+        }
+    }
+
+    /// Nesting depth of the current position; 0 at the top level.
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Returns the next event, or `None` once the document is complete.
+    pub fn next_event(&mut self) -> Result<Option<JsonEvent>, ParseError> {
+        match self.step()? {
+            Step::Done => Ok(None),
+            Step::End => {
+                let event = match self.stack.pop() {
+                    Some(Frame::Array(_)) => JsonEvent::EndArray,
+                    _ => JsonEvent::EndObject,
+                };
+                self.parser.advance();
+                Ok(Some(event))
+            }
+            Step::Key => {
+                let key = self.parser.parse_key()?;
+                self.set_state(State::AfterKey);
+                Ok(Some(JsonEvent::Key(key)))
+            }
+            Step::Value => self.read_value().map(Some),
+        }
+    }
+
+    /// Skips the next value, including any nested arrays and objects, by
+    /// scanning structural characters only: strings are not unescaped and
+    /// scalars are not validated. Positioned before an object key, the whole
+    /// member is skipped.
+    pub fn skip_value(&mut self) -> Result<(), ParseError> {
+        match self.step()? {
+            Step::Done => Err(self.parser.end_of_input()),
+            Step::End => Err(self.parser.unexpected()),
+            Step::Key => {
+                self.parser.skip_value_structural()?;
+                self.set_state(State::AfterKey);
+                match self.step()? {
+                    Step::Value => self.parser.skip_value_structural(),
+                    _ => unreachable!("step after a key either fails or finds a value"),
+                }
+            }
+            Step::Value => self.parser.skip_value_structural(),
+        }
+    }
+
+    // Consumes separators up to the next key, value or closing bracket.
+    fn step(&mut self) -> Result<Step, ParseError> {
+        loop {
+            self.parser.skip_whitespace();
+            let c = self.parser.current_char();
+            let trailing_commas = self.parser.options.allow_trailing_commas;
+
+            let Some(frame) = self.stack.last().copied() else {
+                if !self.started {
+                    self.started = true;
+                    return Ok(Step::Value);
+                }
+                return match c {
+                    Some(_) => Err(self.parser.unexpected()),
+                    None => Ok(Step::Done),
+                };
+            };
+
+            match (frame, c) {
+                (_, None) => return Err(self.parser.end_of_input()),
+                (Frame::Array(State::Start), Some(']')) => return Ok(Step::End),
+                (Frame::Object(State::Start), Some('}')) => return Ok(Step::End),
+                (Frame::Array(State::AfterComma), Some(']')) if trailing_commas => {
+                    return Ok(Step::End);
+                }
+                (Frame::Object(State::AfterComma), Some('}')) if trailing_commas => {
+                    return Ok(Step::End);
+                }
+                (Frame::Array(State::AfterValue), Some(']')) => return Ok(Step::End),
+                (Frame::Object(State::AfterValue), Some('}')) => return Ok(Step::End),
+                (Frame::Array(State::AfterValue) | Frame::Object(State::AfterValue), Some(',')) => {
+                    self.parser.advance();
+                    self.set_state(State::AfterComma);
+                }
+                (Frame::Array(State::Start | State::AfterComma), Some(_)) => {
+                    self.set_state(State::AfterValue);
+                    return Ok(Step::Value);
+                }
+                (Frame::Object(State::Start | State::AfterComma), Some(_)) => return Ok(Step::Key),
+                (Frame::Object(State::AfterKey), Some(':')) => {
+                    self.parser.advance();
+                    self.parser.skip_whitespace();
+                    self.set_state(State::AfterValue);
+                    return Ok(Step::Value);
+                }
+                (_, Some(_)) => return Err(self.parser.unexpected()),
+            }
+        }
+    }
+
+    fn set_state(&mut self, state: State) {
+        if let Some(frame) = self.stack.last_mut() {
+            *frame = match frame {
+                Frame::Array(_) => Frame::Array(state),
+                Frame::Object(_) => Frame::Object(state),
+            };
+        }
+    }
+
+    fn read_value(&mut self) -> Result<JsonEvent, ParseError> {
+        match self.parser.current_char() {
+            Some('[') => {
+                self.parser.advance();
+                self.stack.push(Frame::Array(State::Start));
+                Ok(JsonEvent::StartArray)
+            }
+            Some('{') => {
+                self.parser.advance();
+                self.stack.push(Frame::Object(State::Start));
+                Ok(JsonEvent::StartObject)
+            }
+            _ => self.parser.parse_value().map(JsonEvent::Value),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use super::{ParseError, Parser, ParserOptions, Position};
+
+/// Parses a JSON number that denotes an integer, such as a large numeric
+/// identifier, without going through `f64`. Surrounding whitespace is
+/// allowed; anything else is an error. Fractions and exponents are accepted
+/// when the value is still a whole number (`1.0`, `25e2`). Non-integers and
+/// values outside the range of `i128` are `InvalidNumber`.
+pub fn parse_integer_exact(input: &str) -> Result<i128, ParseError> {
+    let options = ParserOptions {
+        preserve_raw_numbers: true,
+        ..ParserOptions::default()
+    };
+    let mut parser = Parser::with_options(input, options);
+    parser.skip_whitespace();
+    let start = parser.pos;
+    if !matches!(parser.current_char(), Some('-' | '0'..='9')) {
+        return Err(parser.unexpected());
+    }
+    parser.parse_number()?;
+    let text = &input[start..parser.pos];
+    parser.skip_whitespace();
+    if parser.pos < input.len() {
+        return Err(parser.unexpected());
+    }
+    integer_value(text)
+        .ok_or_else(|| ParseError::InvalidNumber(text.to_string(), parser.position(start)))
+}
+
+/// Like `parse_integer_exact`, for values that must fit in an `i64`.
+pub fn parse_i64_exact(input: &str) -> Result<i64, ParseError> {
+    narrow(input, parse_integer_exact(input)?)
+}
+
+/// Like `parse_integer_exact`, for values that must fit in a `u64`.
+pub fn parse_u64_exact(input: &str) -> Result<u64, ParseError> {
+    narrow(input, parse_integer_exact(input)?)
+}
+
+fn narrow<T: TryFrom<i128>>(input: &str, n: i128) -> Result<T, ParseError> {
+    let start = input.len() - input.trim_start().len();
+    T::try_from(n).map_err(|_| {
+        let text = input.trim().to_string();
+        ParseError::InvalidNumber(text, Position::at(input, start))
+    })
+}
+
+// The exact value of a well-formed JSON number literal, if it is an integer
+// that fits in an `i128`.
+fn integer_value(text: &str) -> Option<i128> {
+    let (negative, unsigned) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().ok()?),
+        None => (unsigned, 0),
+    };
+    let (int, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits: String = [int, fraction].concat();
+    // The value is `digits` times ten to the power of `shift`.
+    let shift = exponent.checked_sub(fraction.len() as i64)?;
+    let digits = digits.trim_start_matches('0');
+    let digits = if shift < 0 {
+        let keep = digits.len().checked_sub(shift.unsigned_abs() as usize);
+        let (kept, dropped) = digits.split_at(keep.unwrap_or(0));
+        if dropped.bytes().any(|b| b != b'0') {
+            return None;
+        }
+        kept
+    } else {
+        digits
+    };
+    if digits.is_empty() {
+        return Some(0);
+    }
+
+    let magnitude = digits.bytes().try_fold(0i128, |n, b| {
+        n.checked_mul(10)?.checked_sub(i128::from(b - b'0'))
+    })?;
+    let scale = 10i128.checked_pow(u32::try_from(shift.max(0)).ok()?)?;
+    // Accumulated as a negative number, which reaches `i128::MIN`.
+    let value = magnitude.checked_mul(scale)?;
+    if negative {
+        Some(value)
+    } else {
+        value.checked_neg()
+    }
+}
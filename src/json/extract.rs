@@ -0,0 +1,103 @@
+use super::pointer::{parse_index, unescape_token};
+use super::{JsonValue, ParseError, Parser, ParserOptions};
+
+/// Parses only the value at a JSON Pointer, skipping over everything before
+/// it without building a tree. Returns `Ok(None)` if the pointer does not
+/// match anything.
+///
+/// Skipped values are scanned structurally, not validated, and the input
+/// after the target value is not read at all, so a malformed document may
+/// still yield a value. If an object repeats a key, the first member wins.
+pub fn extract(input: &str, pointer: &str) -> Result<Option<JsonValue>, ParseError> {
+    extract_with_options(input, pointer, ParserOptions::current())
+}
+
+pub fn extract_with_options(
+    input: &str,
+    pointer: &str,
+    options: ParserOptions,
+) -> Result<Option<JsonValue>, ParseError> {
+    let tokens: Vec<String> = if pointer.is_empty() {
+        Vec::new()
+    } else {
+        match pointer.strip_prefix('/') {
+            Some(rest) => rest.split('/').map(unescape_token).collect(),
+            None => return Ok(None),
+        }
+    };
+
+    let mut parser = Parser::with_options(input, options);
+    for token in &tokens {
+        parser.skip_whitespace();
+        let found = match parser.current_char() {
+            Some('{') => parser.seek_member(token)?,
+            Some('[') => match parse_index(token) {
+                Some(index) => parser.seek_element(index)?,
+                None => false,
+            },
+            _ => false,
+        };
+        if !found {
+            return Ok(None);
+        }
+    }
+    parser.skip_whitespace();
+    parser.parse_value().map(Some)
+}
+
+impl Parser<'_> {
+    // Positioned on '{', moves to the value of the first member named `key`.
+    fn seek_member(&mut self, key: &str) -> Result<bool, ParseError> {
+        self.advance(); // Skip '{'
+        loop {
+            self.skip_whitespace();
+            if self.current_char() == Some('}') {
+                return Ok(false);
+            }
+            let name = self.parse_key()?;
+            self.skip_whitespace();
+            if self.current_char() != Some(':') {
+                return Err(self.unexpected());
+            }
+            self.advance();
+            if name == key {
+                return Ok(true);
+            }
+            self.skip_value_structural()?;
+            if !self.seek_separator('}')? {
+                return Ok(false);
+            }
+        }
+    }
+
+    // Positioned on '[', moves to the element at `index`.
+    fn seek_element(&mut self, index: usize) -> Result<bool, ParseError> {
+        self.advance(); // Skip '['
+        for _ in 0..index {
+            self.skip_whitespace();
+            if self.current_char() == Some(']') {
+                return Ok(false);
+            }
+            self.skip_value_structural()?;
+            if !self.seek_separator(']')? {
+                return Ok(false);
+            }
+        }
+        self.skip_whitespace();
+        Ok(self.current_char() != Some(']'))
+    }
+
+    // Consumes the ',' after a skipped value. Returns false at `close`.
+    fn seek_separator(&mut self, close: char) -> Result<bool, ParseError> {
+        self.skip_whitespace();
+        match self.current_char() {
+            Some(',') => {
+                self.advance();
+                Ok(true)
+            }
+            Some(c) if c == close => Ok(false),
+            Some(_) => Err(self.unexpected()),
+            None => Err(self.end_of_input()),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use super::{JsonValue, ParseError, ParserOptions, Position, parse_with_options, stringify};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// Which faults a `FaultInjector` introduces. The default injects nothing.
+#[derive(Debug, Clone, Default)]
+pub struct FaultConfig {
+    /// 1-based numbers of the `parse` calls that fail with an injected
+    /// `UnexpectedEndOfInput` error instead of parsing.
+    pub fail_parse_at: Vec<usize>,
+    /// Sleep this long before every call.
+    pub latency: Option<Duration>,
+    /// Parse only the first this-many bytes of each input, as if the
+    /// payload had been cut off in transit.
+    pub truncate_input: Option<usize>,
+    /// Cut `stringify` output down to this many bytes.
+    pub truncate_output: Option<usize>,
+}
+
+/// A test double for `parse` and `stringify` that injects the failures in
+/// its `FaultConfig` deterministically, for exercising error handling
+/// around JSON processing.
+pub struct FaultInjector {
+    config: FaultConfig,
+    parser_options: ParserOptions,
+    parse_calls: AtomicUsize,
+    stringify_calls: AtomicUsize,
+}
+
+impl FaultInjector {
+    pub fn new(config: FaultConfig) -> Self {
+        FaultInjector::with_options(config, ParserOptions::current())
+    }
+
+    pub fn with_options(config: FaultConfig, parser_options: ParserOptions) -> Self {
+        FaultInjector {
+            config,
+            parser_options,
+            parse_calls: AtomicUsize::new(0),
+            stringify_calls: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn parse(&self, input: &str) -> Result<JsonValue, ParseError> {
+        let call = self.parse_calls.fetch_add(1, Ordering::SeqCst) + 1;
+        self.delay();
+        let input = match self.config.truncate_input {
+            Some(len) => truncate(input, len),
+            None => input,
+        };
+        if self.config.fail_parse_at.contains(&call) {
+            return Err(ParseError::UnexpectedEndOfInput(Position::at(
+                input,
+                input.len(),
+            )));
+        }
+        parse_with_options(input, self.parser_options.clone())
+    }
+
+    pub fn stringify(&self, value: &JsonValue) -> String {
+        self.stringify_calls.fetch_add(1, Ordering::SeqCst);
+        self.delay();
+        let json = stringify(value);
+        match self.config.truncate_output {
+            Some(len) => truncate(&json, len).to_string(),
+            None => json,
+        }
+    }
+
+    /// Number of `parse` calls made so far.
+    pub fn parse_calls(&self) -> usize {
+        self.parse_calls.load(Ordering::SeqCst)
+    }
+
+    /// Number of `stringify` calls made so far.
+    pub fn stringify_calls(&self) -> usize {
+        self.stringify_calls.load(Ordering::SeqCst)
+    }
+
+    fn delay(&self) {
+        if let Some(latency) = self.config.latency {
+            thread::sleep(latency);
+        }
+    }
+}
+
+// Cuts `text` to at most `len` bytes without splitting a character.
+fn truncate(text: &str, len: usize) -> &str {
+    if len >= text.len() {
+        return text;
+    }
+    let mut end = len;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
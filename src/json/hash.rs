@@ -0,0 +1,67 @@
+use super::JsonValue;
+use std::hash::{Hash, Hasher};
+
+/// Compares numbers by value, except that every NaN equals every other
+/// NaN, so `==` is reflexive even for the `NaN` the lenient parser
+/// produces. `0.0` and `-0.0` are equal. Other variants compare
+/// structurally; a `Number` never equals a `RawNumber`.
+impl PartialEq for JsonValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (JsonValue::Null, JsonValue::Null) => true,
+            (JsonValue::Bool(a), JsonValue::Bool(b)) => a == b,
+            (JsonValue::Number(a), JsonValue::Number(b)) => {
+                normalize_number(*a).to_bits() == normalize_number(*b).to_bits()
+            }
+            (JsonValue::RawNumber(a), JsonValue::RawNumber(b)) => a == b,
+            (JsonValue::Raw(a), JsonValue::Raw(b)) => a == b,
+            (JsonValue::String(a), JsonValue::String(b)) => a == b,
+            (JsonValue::Bytes(a), JsonValue::Bytes(b)) => a == b,
+            (JsonValue::Array(a), JsonValue::Array(b)) => a == b,
+            (JsonValue::Object(a), JsonValue::Object(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// `==` is reflexive for every value, NaN included, so values can be used
+/// as `HashMap` and `HashSet` keys.
+impl Eq for JsonValue {}
+
+// The one number representation behind `==`, `Hash` and `Ord`: `-0.0`
+// folded into `0.0` and every NaN into `f64::NAN`.
+pub(crate) fn normalize_number(n: f64) -> f64 {
+    if n == 0.0 {
+        0.0
+    } else if n.is_nan() {
+        f64::NAN
+    } else {
+        n
+    }
+}
+
+/// Hashes consistently with `==`: numbers by the bits of
+/// `normalize_number`, and object members in ascending key order, so equal objects
+/// hash alike whatever order their maps iterate in.
+impl Hash for JsonValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            JsonValue::Null => {}
+            JsonValue::Bool(b) => b.hash(state),
+            JsonValue::Number(n) => normalize_number(*n).to_bits().hash(state),
+            JsonValue::RawNumber(text) => text.hash(state),
+            JsonValue::Raw(raw) => raw.hash(state),
+            JsonValue::String(s) => s.hash(state),
+            JsonValue::Bytes(bytes) => bytes.hash(state),
+            JsonValue::Array(items) => items.hash(state),
+            JsonValue::Object(members) => {
+                members.len().hash(state);
+                for (key, value) in self.iter_sorted() {
+                    key.hash(state);
+                    value.hash(state);
+                }
+            }
+        }
+    }
+}
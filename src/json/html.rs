@@ -0,0 +1,155 @@
+use super::stringify::{stringify_compact, write_string};
+use super::{JsonValue, StringifyOptions};
+
+/// Knobs for `render_html`.
+#[derive(Debug, Clone)]
+pub struct HtmlOptions {
+    /// Indentation per nesting level.
+    pub indent: String,
+    /// Wrap each non-empty array and object in a `<details open>` element
+    /// whose `<summary>` is its opening line, so it can be folded away.
+    pub collapsible: bool,
+}
+
+impl Default for HtmlOptions {
+    fn default() -> Self {
+        HtmlOptions {
+            indent: "  ".to_string(),
+            collapsible: false,
+        }
+    }
+}
+
+/// Renders a value as indented JSON inside `<pre class="json">`, escaped
+/// for HTML, with each token in a `<span>` whose class names its kind:
+/// `json-key`, `json-string`, `json-number`, `json-literal` for `true`,
+/// `false` and `null`, or `json-punct`. Styling is left to the page.
+pub fn render_html(value: &JsonValue, options: &HtmlOptions) -> String {
+    let mut out = String::from("<pre class=\"json\">");
+    let mut html = Html {
+        options,
+        out: &mut out,
+    };
+    html.value(value, 0, "", "");
+    out.push_str("</pre>");
+    out
+}
+
+struct Html<'o> {
+    options: &'o HtmlOptions,
+    out: &'o mut String,
+}
+
+impl Html<'_> {
+    // Writes `value` on lines of its own. `key` is the member key, already
+    // rendered, and `comma` what follows the value.
+    fn value(&mut self, value: &JsonValue, depth: usize, key: &str, comma: &str) {
+        let (open, close, len) = match value {
+            JsonValue::Array(items) => ('[', ']', items.len()),
+            JsonValue::Object(members) => ('{', '}', members.len()),
+            _ => {
+                self.line_start(depth, key);
+                self.leaf(value);
+                self.out.push_str(comma);
+                self.out.push('\n');
+                return;
+            }
+        };
+        if len == 0 {
+            self.line_start(depth, key);
+            self.token("json-punct", &format!("{}{}", open, close));
+            self.out.push_str(comma);
+            self.out.push('\n');
+            return;
+        }
+        if self.options.collapsible {
+            self.out.push_str("<details open><summary>");
+            self.line_start(depth, key);
+            self.token("json-punct", &open.to_string());
+            self.out.push_str("</summary>");
+        } else {
+            self.line_start(depth, key);
+            self.token("json-punct", &open.to_string());
+            self.out.push('\n');
+        }
+        let punct_comma = span("json-punct", ",");
+        match value {
+            JsonValue::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    let comma = if i + 1 < len {
+                        punct_comma.as_str()
+                    } else {
+                        ""
+                    };
+                    self.value(item, depth + 1, "", comma);
+                }
+            }
+            JsonValue::Object(members) => {
+                for (i, (name, item)) in members.iter().enumerate() {
+                    let mut key = String::new();
+                    write_string(name, &StringifyOptions::default(), &mut key);
+                    let key = format!("{}{} ", span("json-key", &key), span("json-punct", ":"));
+                    let comma = if i + 1 < len {
+                        punct_comma.as_str()
+                    } else {
+                        ""
+                    };
+                    self.value(item, depth + 1, &key, comma);
+                }
+            }
+            _ => unreachable!("only containers get here"),
+        }
+        self.line_start(depth, "");
+        self.token("json-punct", &close.to_string());
+        self.out.push_str(comma);
+        self.out.push('\n');
+        if self.options.collapsible {
+            self.out.push_str("</details>");
+        }
+    }
+
+    fn line_start(&mut self, depth: usize, key: &str) {
+        for _ in 0..depth {
+            self.out.push_str(&self.options.indent);
+        }
+        self.out.push_str(key);
+    }
+
+    fn leaf(&mut self, value: &JsonValue) {
+        let class = match value {
+            JsonValue::Null | JsonValue::Bool(_) => "json-literal",
+            JsonValue::Number(_) | JsonValue::RawNumber(_) => "json-number",
+            JsonValue::String(_) | JsonValue::Bytes(_) => "json-string",
+            // Verbatim text may hold any number of tokens.
+            _ => {
+                escape_into(&stringify_compact(value), self.out);
+                return;
+            }
+        };
+        self.token(class, &stringify_compact(value));
+    }
+
+    fn token(&mut self, class: &str, text: &str) {
+        self.out.push_str(&span(class, text));
+    }
+}
+
+fn span(class: &str, text: &str) -> String {
+    let mut span = format!("<span class=\"{}\">", class);
+    escape_into(text, &mut span);
+    span.push_str("</span>");
+    span
+}
+
+fn escape_into(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+}
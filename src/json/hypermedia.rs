@@ -0,0 +1,191 @@
+use super::JsonValue;
+
+/// A HAL resource: an object whose `_links` and `_embedded` members hold
+/// hypermedia controls and nested resources.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HalResource<'a> {
+    value: &'a JsonValue,
+}
+
+/// A link object from a HAL `_links` member.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HalLink<'a> {
+    pub href: &'a str,
+    /// Whether `href` is a URI template.
+    pub templated: bool,
+    pub title: Option<&'a str>,
+    pub name: Option<&'a str>,
+}
+
+impl<'a> HalResource<'a> {
+    /// Wraps a value, or returns `None` if it is not an object.
+    pub fn new(value: &'a JsonValue) -> Option<Self> {
+        value.as_object().map(|_| HalResource { value })
+    }
+
+    pub fn value(&self) -> &'a JsonValue {
+        self.value
+    }
+
+    /// The `href` of the `self` link.
+    pub fn self_href(&self) -> Option<&'a str> {
+        self.link("self").map(|link| link.href)
+    }
+
+    /// The first link with relation `rel`.
+    pub fn link(&self, rel: &str) -> Option<HalLink<'a>> {
+        self.links(rel).into_iter().next()
+    }
+
+    /// Every link with relation `rel`; HAL allows a single link object or an
+    /// array of them. Entries without an `href` are skipped.
+    pub fn links(&self, rel: &str) -> Vec<HalLink<'a>> {
+        one_or_many(self.value.get("_links").and_then(|links| links.get(rel)))
+            .into_iter()
+            .filter_map(|link| {
+                Some(HalLink {
+                    href: link.get("href")?.as_str()?,
+                    templated: link.get("templated").and_then(JsonValue::as_bool) == Some(true),
+                    title: link.get("title").and_then(JsonValue::as_str),
+                    name: link.get("name").and_then(JsonValue::as_str),
+                })
+            })
+            .collect()
+    }
+
+    /// Every embedded resource with relation `rel`.
+    pub fn embedded(&self, rel: &str) -> Vec<HalResource<'a>> {
+        one_or_many(self.value.get("_embedded").and_then(|e| e.get(rel)))
+            .into_iter()
+            .filter_map(HalResource::new)
+            .collect()
+    }
+
+    /// A state property; `_links` and `_embedded` are not properties.
+    pub fn property(&self, name: &str) -> Option<&'a JsonValue> {
+        match name {
+            "_links" | "_embedded" => None,
+            _ => self.value.get(name),
+        }
+    }
+}
+
+/// A JSON:API top-level document with `data`, `included` and `errors`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JsonApiDocument<'a> {
+    value: &'a JsonValue,
+}
+
+/// A resource object with `type`, `id`, `attributes` and `relationships`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JsonApiResource<'a> {
+    value: &'a JsonValue,
+}
+
+/// A `{type, id}` pair from a relationship's `data`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResourceIdentifier<'a> {
+    pub resource_type: &'a str,
+    pub id: &'a str,
+}
+
+impl<'a> JsonApiDocument<'a> {
+    /// Wraps a value, or returns `None` if it is not an object.
+    pub fn new(value: &'a JsonValue) -> Option<Self> {
+        value.as_object().map(|_| JsonApiDocument { value })
+    }
+
+    pub fn value(&self) -> &'a JsonValue {
+        self.value
+    }
+
+    /// Primary data, whether the document holds a single resource or a
+    /// collection. Empty for `"data": null`.
+    pub fn data(&self) -> Vec<JsonApiResource<'a>> {
+        resources(self.value.get("data"))
+    }
+
+    pub fn included(&self) -> Vec<JsonApiResource<'a>> {
+        resources(self.value.get("included"))
+    }
+
+    pub fn errors(&self) -> Vec<&'a JsonValue> {
+        one_or_many(self.value.get("errors"))
+    }
+
+    /// Finds a resource among `data` and `included` by identifier, for
+    /// following relationships within a compound document.
+    pub fn find(&self, identifier: ResourceIdentifier<'_>) -> Option<JsonApiResource<'a>> {
+        self.data()
+            .into_iter()
+            .chain(self.included())
+            .find(|resource| resource.identifier() == Some(identifier))
+    }
+}
+
+impl<'a> JsonApiResource<'a> {
+    pub fn new(value: &'a JsonValue) -> Option<Self> {
+        value.as_object().map(|_| JsonApiResource { value })
+    }
+
+    pub fn value(&self) -> &'a JsonValue {
+        self.value
+    }
+
+    pub fn id(&self) -> Option<&'a str> {
+        self.value.get("id").and_then(JsonValue::as_str)
+    }
+
+    pub fn resource_type(&self) -> Option<&'a str> {
+        self.value.get("type").and_then(JsonValue::as_str)
+    }
+
+    pub fn identifier(&self) -> Option<ResourceIdentifier<'a>> {
+        Some(ResourceIdentifier {
+            resource_type: self.resource_type()?,
+            id: self.id()?,
+        })
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&'a JsonValue> {
+        self.value.get("attributes").and_then(|a| a.get(name))
+    }
+
+    /// The identifiers a relationship points to; empty for an empty or
+    /// missing relationship.
+    pub fn relationship(&self, name: &str) -> Vec<ResourceIdentifier<'a>> {
+        let data = self
+            .value
+            .get("relationships")
+            .and_then(|r| r.get(name))
+            .and_then(|r| r.get("data"));
+        resources(data)
+            .into_iter()
+            .filter_map(|resource| resource.identifier())
+            .collect()
+    }
+
+    /// A link from the resource's `links` member, given either as a string
+    /// or as a link object with an `href`.
+    pub fn link(&self, name: &str) -> Option<&'a str> {
+        let link = self.value.get("links")?.get(name)?;
+        link.as_str()
+            .or_else(|| link.get("href").and_then(JsonValue::as_str))
+    }
+}
+
+fn resources(value: Option<&JsonValue>) -> Vec<JsonApiResource<'_>> {
+    one_or_many(value)
+        .into_iter()
+        .filter_map(JsonApiResource::new)
+        .collect()
+}
+
+// A member that may hold one item or an array of them; `null` holds none.
+fn one_or_many(value: Option<&JsonValue>) -> Vec<&JsonValue> {
+    match value {
+        None | Some(JsonValue::Null) => Vec::new(),
+        Some(JsonValue::Array(items)) => items.iter().collect(),
+        Some(item) => vec![item],
+    }
+}
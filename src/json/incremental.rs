@@ -0,0 +1,308 @@
+use super::pointer::{escape_pointer_token, parse_index, unescape_token};
+use super::{DuplicateKeyPolicy, JsonValue, Map, ParseError, Parser, ParserOptions};
+use std::ops::Range;
+
+/// A replacement of the bytes in `range` with `text`, as sent by an editor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+impl TextEdit {
+    pub fn new(range: Range<usize>, text: &str) -> Self {
+        TextEdit {
+            range,
+            text: text.to_string(),
+        }
+    }
+}
+
+/// A parsed document that remembers where each value came from, so that a
+/// small edit re-parses only the innermost value enclosing it rather than
+/// the whole text.
+///
+/// `materialize_depth` and `expand_nested_json` are ignored; the tree is
+/// always built in full.
+#[derive(Debug, Clone)]
+pub struct IncrementalDocument {
+    text: String,
+    value: JsonValue,
+    spans: Span,
+    options: ParserOptions,
+}
+
+// The byte range of a value and of the values nested in it, in source
+// order. Object members carry their key; members dropped by `key_filter`
+// are not recorded.
+#[derive(Debug, Clone)]
+pub(super) struct Span {
+    pub(super) start: usize,
+    pub(super) end: usize,
+    pub(super) children: Vec<(Option<String>, Span)>,
+}
+
+impl IncrementalDocument {
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        IncrementalDocument::parse_with_options(text, ParserOptions::current())
+    }
+
+    pub fn parse_with_options(text: &str, options: ParserOptions) -> Result<Self, ParseError> {
+        let (value, spans) = parse_document(text, options.clone())?;
+        Ok(IncrementalDocument {
+            text: text.to_string(),
+            value,
+            spans,
+            options,
+        })
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn value(&self) -> &JsonValue {
+        &self.value
+    }
+
+    pub(super) fn spans(&self) -> &Span {
+        &self.spans
+    }
+
+    /// The byte range of the value at a JSON Pointer, without surrounding
+    /// whitespace.
+    pub fn span(&self, pointer: &str) -> Option<Range<usize>> {
+        let mut span = &self.spans;
+        if !pointer.is_empty() {
+            for token in pointer.strip_prefix('/')?.split('/') {
+                let index = if self.text[span.start..].starts_with('[') {
+                    parse_index(token)?
+                } else {
+                    self.member_index(span, &unescape_token(token))?
+                };
+                span = &span.children.get(index)?.1;
+            }
+        }
+        Some(span.start..span.end)
+    }
+
+    /// Applies an edit to the text and brings the tree up to date. Only the
+    /// innermost value that encloses the edit and still parses as a single
+    /// value is re-parsed; if none does, the whole document is. Returns the
+    /// JSON Pointer of the re-parsed value.
+    ///
+    /// If the edited text is not valid JSON the error is returned and the
+    /// document is left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `edit.range` is out of bounds or does not lie on character
+    /// boundaries.
+    pub fn apply(&mut self, edit: &TextEdit) -> Result<String, ParseError> {
+        let mut text = self.text.clone();
+        text.replace_range(edit.range.clone(), &edit.text);
+        let delta = edit.text.len() as isize - edit.range.len() as isize;
+
+        let path = self.enclosing(&edit.range);
+        for depth in (1..=path.len()).rev() {
+            let old = self.spans.descendant(&path[..depth]);
+            let end = (old.end as isize + delta) as usize;
+            let Some((value, mut span)) = parse_region(&text[old.start..end], &self.options) else {
+                continue;
+            };
+            span.shift(old.start as isize);
+
+            let pointer = self.pointer(&path[..depth]);
+            *self
+                .value
+                .pointer_mut(&pointer)
+                .expect("spans match the tree") = value;
+            self.spans.patch(&path[..depth], span, delta);
+            self.text = text;
+            return Ok(pointer);
+        }
+
+        let (value, spans) = parse_document(&text, self.options.clone())?;
+        self.text = text;
+        self.value = value;
+        self.spans = spans;
+        Ok(String::new())
+    }
+
+    // Child indices leading to the innermost value containing `range` that
+    // can be replaced in the tree on its own.
+    fn enclosing(&self, range: &Range<usize>) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut span = &self.spans;
+        while let Some(index) = span
+            .children
+            .iter()
+            .position(|(_, child)| child.start <= range.start && range.end <= child.end)
+        {
+            // A repeated key's value may not be the one in the tree.
+            if let Some(key) = &span.children[index].0
+                && span
+                    .children
+                    .iter()
+                    .filter(|(k, _)| k.as_ref() == Some(key))
+                    .count()
+                    > 1
+            {
+                break;
+            }
+            path.push(index);
+            span = &span.children[index].1;
+        }
+        path
+    }
+
+    fn pointer(&self, path: &[usize]) -> String {
+        let mut pointer = String::new();
+        let mut span = &self.spans;
+        for &index in path {
+            let (key, child) = &span.children[index];
+            pointer.push('/');
+            match key {
+                Some(key) => pointer.push_str(&escape_pointer_token(key)),
+                None => pointer.push_str(&index.to_string()),
+            }
+            span = child;
+        }
+        pointer
+    }
+
+    // Which of an object's recorded members holds `key` in the tree.
+    fn member_index(&self, span: &Span, key: &str) -> Option<usize> {
+        let mut matching = span
+            .children
+            .iter()
+            .enumerate()
+            .filter(|(_, (k, _))| k.as_deref() == Some(key))
+            .map(|(i, _)| i);
+        match self.options.duplicate_keys {
+            DuplicateKeyPolicy::FirstWins => matching.next(),
+            _ => matching.next_back(),
+        }
+    }
+}
+
+impl Span {
+    fn descendant(&self, path: &[usize]) -> &Span {
+        path.iter()
+            .fold(self, |span, &index| &span.children[index].1)
+    }
+
+    fn shift(&mut self, delta: isize) {
+        self.start = (self.start as isize + delta) as usize;
+        self.end = (self.end as isize + delta) as usize;
+        for (_, child) in &mut self.children {
+            child.shift(delta);
+        }
+    }
+
+    // Puts `replacement` at `path` and moves everything after it by `delta`.
+    fn patch(&mut self, path: &[usize], replacement: Span, delta: isize) {
+        let Some((&index, rest)) = path.split_first() else {
+            *self = replacement;
+            return;
+        };
+        self.end = (self.end as isize + delta) as usize;
+        for (_, later) in &mut self.children[index + 1..] {
+            later.shift(delta);
+        }
+        self.children[index].1.patch(rest, replacement, delta);
+    }
+}
+
+fn parse_document(text: &str, options: ParserOptions) -> Result<(JsonValue, Span), ParseError> {
+    let mut parser = Parser::with_options(text, options);
+    let parsed = parser.parse_spanned()?;
+    parser.skip_whitespace();
+    if parser.pos < parser.input.len() {
+        return Err(parser.unexpected());
+    }
+    Ok(parsed)
+}
+
+// Parses `text` as one value filling it exactly, with spans relative to it.
+fn parse_region(text: &str, options: &ParserOptions) -> Option<(JsonValue, Span)> {
+    let mut parser = Parser::with_options(text, options.clone());
+    let (value, span) = parser.parse_spanned().ok()?;
+    (span.start == 0 && span.end == text.len()).then_some((value, span))
+}
+
+impl Parser<'_> {
+    fn parse_spanned(&mut self) -> Result<(JsonValue, Span), ParseError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let mut children = Vec::new();
+        let value = match self.current_char() {
+            Some('[') => self.parse_spanned_array(&mut children)?,
+            Some('{') => self.parse_spanned_object(&mut children)?,
+            _ => self.parse_value()?,
+        };
+        let span = Span {
+            start,
+            end: self.pos,
+            children,
+        };
+        Ok((value, span))
+    }
+
+    fn parse_spanned_array(
+        &mut self,
+        children: &mut Vec<(Option<String>, Span)>,
+    ) -> Result<JsonValue, ParseError> {
+        self.advance(); // Skip '['
+        self.skip_whitespace();
+        let mut elements = Vec::new();
+        if self.current_char() == Some(']') {
+            self.advance();
+            return Ok(JsonValue::Array(elements));
+        }
+
+        loop {
+            let (value, span) = self.parse_spanned()?;
+            elements.push(value);
+            children.push((None, span));
+            if self.parse_separator(']')? {
+                return Ok(JsonValue::Array(elements));
+            }
+        }
+    }
+
+    fn parse_spanned_object(
+        &mut self,
+        children: &mut Vec<(Option<String>, Span)>,
+    ) -> Result<JsonValue, ParseError> {
+        self.advance(); // Skip '{'
+        self.skip_whitespace();
+        let mut map = Map::new();
+        if self.current_char() == Some('}') {
+            self.advance();
+            return Ok(JsonValue::Object(Box::new(map)));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key_pos = self.pos;
+            let key = self.parse_key()?;
+            self.skip_whitespace();
+            match self.current_char() {
+                Some(':') => self.advance(),
+                _ => return Err(self.unexpected()),
+            }
+            self.skip_whitespace();
+            if self.options.key_filter.allows(&key) {
+                let (value, span) = self.parse_spanned()?;
+                self.insert_member(&mut map, key.clone(), key_pos, value)?;
+                children.push((Some(key), span));
+            } else {
+                self.skip_value_structural()?;
+            }
+            if self.parse_separator('}')? {
+                return Ok(JsonValue::Object(Box::new(map)));
+            }
+        }
+    }
+}
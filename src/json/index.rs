@@ -0,0 +1,56 @@
+use super::JsonValue;
+use super::pointer::escape_pointer_token;
+use std::collections::HashMap;
+
+/// Precomputed JSON Pointer lookups over a parsed document. Building the
+/// index visits every node once; each `get` is then a single hash lookup.
+pub struct DocumentIndex<'a> {
+    nodes: HashMap<String, &'a JsonValue>,
+}
+
+impl<'a> DocumentIndex<'a> {
+    pub fn new(root: &'a JsonValue) -> Self {
+        let mut nodes = HashMap::new();
+        let mut pending = vec![(String::new(), root)];
+        while let Some((pointer, value)) = pending.pop() {
+            match value {
+                JsonValue::Array(arr) => {
+                    for (i, item) in arr.iter().enumerate() {
+                        pending.push((format!("{}/{}", pointer, i), item));
+                    }
+                }
+                JsonValue::Object(obj) => {
+                    for (key, item) in obj.iter() {
+                        pending.push((format!("{}/{}", pointer, escape_pointer_token(key)), item));
+                    }
+                }
+                _ => {}
+            }
+            nodes.insert(pointer, value);
+        }
+        DocumentIndex { nodes }
+    }
+
+    /// Looks up a node by JSON Pointer. Pointers must use the canonical
+    /// form, as produced by `escape_pointer_token`.
+    pub fn get(&self, pointer: &str) -> Option<&'a JsonValue> {
+        self.nodes.get(pointer).copied()
+    }
+
+    pub fn contains(&self, pointer: &str) -> bool {
+        self.nodes.contains_key(pointer)
+    }
+
+    /// Number of indexed nodes, including the root.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn pointers(&self) -> impl Iterator<Item = &str> {
+        self.nodes.keys().map(String::as_str)
+    }
+}
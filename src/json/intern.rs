@@ -0,0 +1,45 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// A pool of object keys, so a key that repeats across many objects, as in
+/// a large array of records, is stored once and compared by pointer.
+#[derive(Debug, Clone, Default)]
+pub struct KeyInterner {
+    keys: HashSet<Arc<str>>,
+}
+
+impl KeyInterner {
+    pub fn new() -> Self {
+        KeyInterner::default()
+    }
+
+    /// The pooled copy of `key`, added on first use. Every call with the same
+    /// text returns a clone of the same `Arc`.
+    pub fn intern(&mut self, key: &str) -> Arc<str> {
+        if let Some(pooled) = self.keys.get(key) {
+            return pooled.clone();
+        }
+        let pooled: Arc<str> = Arc::from(key);
+        self.keys.insert(pooled.clone());
+        pooled
+    }
+
+    /// The pooled copy of `key`, if it has been interned.
+    pub fn get(&self, key: &str) -> Option<Arc<str>> {
+        self.keys.get(key).cloned()
+    }
+
+    /// Number of distinct keys in the pool.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Bytes of key text held, counting each distinct key once.
+    pub fn bytes(&self) -> usize {
+        self.keys.iter().map(|key| key.len()).sum()
+    }
+}
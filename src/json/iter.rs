@@ -0,0 +1,91 @@
+use super::JsonValue;
+
+impl FromIterator<JsonValue> for JsonValue {
+    /// Collects values into an array.
+    fn from_iter<I: IntoIterator<Item = JsonValue>>(iter: I) -> Self {
+        JsonValue::Array(iter.into_iter().collect())
+    }
+}
+
+impl FromIterator<(String, JsonValue)> for JsonValue {
+    /// Collects key-value pairs into an object; later duplicates win.
+    fn from_iter<I: IntoIterator<Item = (String, JsonValue)>>(iter: I) -> Self {
+        JsonValue::Object(Box::new(iter.into_iter().collect()))
+    }
+}
+
+impl Extend<JsonValue> for JsonValue {
+    /// Appends to an array. `null` is first replaced by an empty array.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is neither an array nor `null`.
+    fn extend<I: IntoIterator<Item = JsonValue>>(&mut self, iter: I) {
+        if self.is_null() {
+            *self = JsonValue::Array(Vec::new());
+        }
+        match self {
+            JsonValue::Array(arr) => arr.extend(iter),
+            _ => panic!("extend with values called on a JSON value that is not an array"),
+        }
+    }
+}
+
+impl Extend<(String, JsonValue)> for JsonValue {
+    /// Inserts members into an object, replacing existing ones. `null` is
+    /// first replaced by an empty object.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is neither an object nor `null`.
+    fn extend<I: IntoIterator<Item = (String, JsonValue)>>(&mut self, iter: I) {
+        if self.is_null() {
+            *self = JsonValue::Object(Box::default());
+        }
+        match self {
+            JsonValue::Object(obj) => obj.extend(iter),
+            _ => panic!("extend with members called on a JSON value that is not an object"),
+        }
+    }
+}
+
+impl IntoIterator for JsonValue {
+    type Item = JsonValue;
+    type IntoIter = std::vec::IntoIter<JsonValue>;
+
+    /// Yields the elements of an array, or the values of an object in key
+    /// order; use `iter_sorted` or `as_object` to get keys too. Scalars
+    /// yield nothing.
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            JsonValue::Array(arr) => arr.into_iter(),
+            JsonValue::Object(obj) => {
+                let mut members: Vec<(String, JsonValue)> = obj.into_iter().collect();
+                members.sort_by(|a, b| a.0.cmp(&b.0));
+                members
+                    .into_iter()
+                    .map(|(_, value)| value)
+                    .collect::<Vec<_>>()
+                    .into_iter()
+            }
+            _ => Vec::new().into_iter(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a JsonValue {
+    type Item = &'a JsonValue;
+    type IntoIter = std::vec::IntoIter<&'a JsonValue>;
+
+    /// Borrowing counterpart of `JsonValue::into_iter`.
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            JsonValue::Array(arr) => arr.iter().collect::<Vec<_>>().into_iter(),
+            _ => self
+                .iter_sorted()
+                .map(|(_, value)| value)
+                .collect::<Vec<_>>()
+                .into_iter(),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use super::stringify::stringify_compact;
+use super::{JsonValue, ParseError, ParserOptions, is_identifier_start, parse_with_options};
+
+/// Parses a JSON5 document.
+pub fn parse_json5(input: &str) -> Result<JsonValue, ParseError> {
+    parse_with_options(input, ParserOptions::json5())
+}
+
+/// Serializes a value as compact JSON5: keys that are valid identifiers are
+/// left unquoted and non-finite numbers are written as `NaN`/`Infinity`.
+pub fn stringify_json5(value: &JsonValue) -> String {
+    match value {
+        JsonValue::Number(n) if n.is_nan() => "NaN".to_string(),
+        JsonValue::Number(n) if n.is_infinite() => {
+            if *n > 0.0 {
+                "Infinity".to_string()
+            } else {
+                "-Infinity".to_string()
+            }
+        }
+        JsonValue::Array(arr) => {
+            let elements: Vec<String> = arr.iter().map(stringify_json5).collect();
+            format!("[{}]", elements.join(","))
+        }
+        JsonValue::Object(obj) => {
+            let pairs: Vec<String> = obj
+                .iter()
+                .map(|(k, v)| format!("{}:{}", stringify_key(k), stringify_json5(v)))
+                .collect();
+            format!("{{{}}}", pairs.join(","))
+        }
+        _ => stringify_compact(value),
+    }
+}
+
+fn stringify_key(key: &str) -> String {
+    let mut chars = key.chars();
+    let is_identifier = chars.next().is_some_and(is_identifier_start)
+        && chars.all(|c| is_identifier_start(c) || c.is_alphanumeric());
+    if is_identifier {
+        key.to_string()
+    } else {
+        stringify_compact(&JsonValue::String(key.into()))
+    }
+}
@@ -0,0 +1,153 @@
+use super::JsonValue;
+use super::stringify::write_json;
+use std::fmt;
+
+const DEFAULT_MAX_LEN: usize = 1024;
+
+/// A `JsonValue` prepared for a log record. Serialization happens only when
+/// the record is actually formatted, and stops after `max_len` characters,
+/// which are followed by a `…` marker, so a huge value costs no more to log
+/// than a small one.
+#[derive(Clone, Copy)]
+pub struct LogValue<'a> {
+    value: &'a JsonValue,
+    max_len: usize,
+}
+
+impl<'a> LogValue<'a> {
+    pub fn new(value: &'a JsonValue) -> Self {
+        LogValue {
+            value,
+            max_len: DEFAULT_MAX_LEN,
+        }
+    }
+
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+}
+
+impl fmt::Display for LogValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = Bounded {
+            out: f,
+            remaining: self.max_len,
+            truncated: false,
+        };
+        let result = write_json(self.value, &mut out);
+        if out.truncated {
+            f.write_str("…")
+        } else {
+            result
+        }
+    }
+}
+
+// Passes text through until `remaining` characters have been written, then
+// fails, which stops the serializer writing to it.
+struct Bounded<'a, W> {
+    out: &'a mut W,
+    remaining: usize,
+    truncated: bool,
+}
+
+impl<W: fmt::Write> fmt::Write for Bounded<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.truncated {
+            return Err(fmt::Error);
+        }
+        match s.char_indices().nth(self.remaining) {
+            Some((end, _)) => {
+                self.out.write_str(&s[..end])?;
+                self.truncated = true;
+                Err(fmt::Error)
+            }
+            None => {
+                self.remaining -= s.chars().count();
+                self.out.write_str(s)
+            }
+        }
+    }
+}
+
+impl fmt::Debug for LogValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Wraps a value for logging with the default length limit.
+pub fn log_value(value: &JsonValue) -> LogValue<'_> {
+    LogValue::new(value)
+}
+
+#[cfg(feature = "log")]
+impl log::kv::ToValue for LogValue<'_> {
+    fn to_value(&self) -> log::kv::Value<'_> {
+        log::kv::Value::from_display(self)
+    }
+}
+
+#[cfg(feature = "tracing")]
+pub use self::tracing_support::{JsonFieldVisitor, tracing_field};
+
+#[cfg(feature = "tracing")]
+mod tracing_support {
+    use super::LogValue;
+    use crate::json::{JsonValue, Map};
+    use std::fmt;
+    use tracing::field::{DisplayValue, Field, Visit};
+
+    /// Wraps a value as a lazily formatted `tracing` field:
+    /// `tracing::info!(payload = tracing_field(&value))`.
+    pub fn tracing_field(value: &JsonValue) -> DisplayValue<LogValue<'_>> {
+        tracing::field::display(LogValue::new(value))
+    }
+
+    /// Collects the fields of a `tracing` event or span into a JSON object.
+    #[derive(Debug, Default)]
+    pub struct JsonFieldVisitor {
+        fields: Map,
+    }
+
+    impl JsonFieldVisitor {
+        pub fn new() -> Self {
+            JsonFieldVisitor::default()
+        }
+
+        pub fn into_value(self) -> JsonValue {
+            JsonValue::Object(Box::new(self.fields))
+        }
+
+        fn insert(&mut self, field: &Field, value: JsonValue) {
+            self.fields.insert(field.name().to_string(), value);
+        }
+    }
+
+    impl Visit for JsonFieldVisitor {
+        fn record_f64(&mut self, field: &Field, value: f64) {
+            self.insert(field, JsonValue::Number(value));
+        }
+
+        fn record_i64(&mut self, field: &Field, value: i64) {
+            self.insert(field, JsonValue::Number(value as f64));
+        }
+
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            self.insert(field, JsonValue::Number(value as f64));
+        }
+
+        fn record_bool(&mut self, field: &Field, value: bool) {
+            self.insert(field, JsonValue::Bool(value));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.insert(field, JsonValue::String(value.into()));
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.insert(field, JsonValue::String(format!("{:?}", value).into()));
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use super::JsonValue;
+
+/// The map behind `JsonValue::Object`, chosen at build time. By default it
+/// is a `HashMap`: fastest, but it iterates in an unspecified order. The
+/// `sorted_map` feature makes it a `BTreeMap`, which iterates in ascending
+/// key order, and the `preserve_order` feature an `IndexMap`, which keeps
+/// members in the order they were inserted, so parsed objects keep their
+/// document order. If both are enabled, `preserve_order` wins.
+#[cfg(feature = "preserve_order")]
+pub type Map = indexmap::IndexMap<String, JsonValue>;
+#[cfg(all(feature = "sorted_map", not(feature = "preserve_order")))]
+pub type Map = std::collections::BTreeMap<String, JsonValue>;
+#[cfg(not(any(feature = "sorted_map", feature = "preserve_order")))]
+pub type Map = std::collections::HashMap<String, JsonValue>;
+
+/// An entry of a `Map`, returned by `JsonValue::entry`.
+#[cfg(feature = "preserve_order")]
+pub type MapEntry<'a> = indexmap::map::Entry<'a, String, JsonValue>;
+#[cfg(all(feature = "sorted_map", not(feature = "preserve_order")))]
+pub type MapEntry<'a> = std::collections::btree_map::Entry<'a, String, JsonValue>;
+#[cfg(not(any(feature = "sorted_map", feature = "preserve_order")))]
+pub type MapEntry<'a> = std::collections::hash_map::Entry<'a, String, JsonValue>;
+
+// Removes a member, keeping the order of the rest.
+pub(crate) fn remove_member(map: &mut Map, key: &str) -> Option<JsonValue> {
+    #[cfg(feature = "preserve_order")]
+    return map.shift_remove(key);
+    #[cfg(not(feature = "preserve_order"))]
+    return map.remove(key);
+}
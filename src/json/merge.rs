@@ -0,0 +1,52 @@
+use super::JsonValue;
+
+/// How `JsonValue::merge` combines two arrays. Objects are always merged
+/// member by member, and any other value replaces the one merged into.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum MergeStrategy {
+    /// The incoming array replaces the existing one.
+    #[default]
+    Replace,
+    /// Incoming elements are appended.
+    Concat,
+    /// Object elements whose member `key` equals that of an existing element
+    /// are merged into it; other elements are appended unless an equal one
+    /// is already present.
+    UnionByKey(String),
+}
+
+impl JsonValue {
+    /// Deep-merges `other` into this value, for layering configuration:
+    /// members of `other` override or extend those of `self` at every
+    /// level, and arrays are combined according to `strategy`.
+    pub fn merge(&mut self, other: &JsonValue, strategy: &MergeStrategy) {
+        match (self, other) {
+            (JsonValue::Object(target), JsonValue::Object(layer)) => {
+                for (key, value) in layer.iter() {
+                    match target.get_mut(key) {
+                        Some(existing) => existing.merge(value, strategy),
+                        None => {
+                            target.insert(key.clone(), value.clone());
+                        }
+                    }
+                }
+            }
+            (JsonValue::Array(target), JsonValue::Array(layer)) => match strategy {
+                MergeStrategy::Replace => *target = layer.clone(),
+                MergeStrategy::Concat => target.extend(layer.iter().cloned()),
+                MergeStrategy::UnionByKey(key) => {
+                    for item in layer {
+                        let id = item.get(key);
+                        let existing = target.iter().position(|e| id.is_some() && e.get(key) == id);
+                        match existing {
+                            Some(i) => target[i].merge(item, strategy),
+                            None if !target.contains(item) => target.push(item.clone()),
+                            None => {}
+                        }
+                    }
+                }
+            },
+            (target, other) => *target = other.clone(),
+        }
+    }
+}
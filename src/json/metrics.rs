@@ -0,0 +1,38 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Measurements for a single call to `Parser::parse`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseMetrics {
+    pub duration: Duration,
+    /// Bytes of input consumed before the parse finished or failed.
+    pub bytes: usize,
+    /// Heap-allocated nodes created: strings (including keys), arrays and
+    /// objects.
+    pub allocations: usize,
+    /// Deepest array/object nesting reached; a top-level scalar has depth 0.
+    pub peak_depth: usize,
+    pub success: bool,
+}
+
+/// Callback invoked with the metrics of every document parsed with the
+/// options it is attached to.
+#[derive(Clone)]
+pub struct MetricsHook(Arc<dyn Fn(&ParseMetrics) + Send + Sync>);
+
+impl MetricsHook {
+    pub fn new(hook: impl Fn(&ParseMetrics) + Send + Sync + 'static) -> Self {
+        MetricsHook(Arc::new(hook))
+    }
+
+    pub fn call(&self, metrics: &ParseMetrics) {
+        (self.0)(metrics)
+    }
+}
+
+impl fmt::Debug for MetricsHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MetricsHook(..)")
+    }
+}
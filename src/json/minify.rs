@@ -0,0 +1,32 @@
+use super::{ParseError, Parser, ParserOptions, scan_string, validate_with_options};
+
+/// Removes insignificant whitespace from a JSON document at the text level.
+/// Numbers and strings are copied exactly as written, so unlike
+/// parse-then-stringify no precision or escaping is lost.
+pub fn minify(input: &str) -> Result<String, ParseError> {
+    minify_with_options(input, ParserOptions::current())
+}
+
+/// Like `minify`, accepting the extensions enabled in `options`. With
+/// `allow_comments` set, comments are removed along with whitespace; other
+/// extensions such as trailing commas are kept as written.
+pub fn minify_with_options(input: &str, options: ParserOptions) -> Result<String, ParseError> {
+    validate_with_options(input, options.clone())?;
+
+    let mut parser = Parser::with_options(input, options);
+    let mut out = String::with_capacity(input.len());
+    loop {
+        parser.skip_whitespace();
+        let Some(c) = parser.current_char() else {
+            return Ok(out);
+        };
+        let start = parser.pos;
+        if c == '"' || (c == '\'' && parser.options.allow_single_quotes) {
+            parser.pos = scan_string(parser.input.as_bytes(), start, c as u8)
+                .expect("validated input has closed strings");
+        } else {
+            parser.advance();
+        }
+        out.push_str(&input[start..parser.pos]);
+    }
+}
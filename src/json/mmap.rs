@@ -0,0 +1,89 @@
+use super::{JsonValue, ParseError, ParserOptions, Tape, parse_with_options};
+use memmap2::Mmap;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+/// A file that could not be read, or whose contents are not valid JSON.
+#[derive(Debug)]
+pub enum FileError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileError::Io(e) => write!(f, "{}", e),
+            FileError::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for FileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FileError::Io(e) => Some(e),
+            FileError::Parse(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for FileError {
+    fn from(e: io::Error) -> Self {
+        FileError::Io(e)
+    }
+}
+
+impl From<ParseError> for FileError {
+    fn from(e: ParseError) -> Self {
+        FileError::Parse(e)
+    }
+}
+
+/// A file mapped into memory and checked to be UTF-8, so its text can be
+/// read in place. The operating system pages it in as it is touched,
+/// however large the file is.
+///
+/// As with any memory map, the file must not be changed by another process
+/// while it is mapped.
+pub struct MappedFile {
+    map: Mmap,
+}
+
+impl MappedFile {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, FileError> {
+        let file = File::open(path)?;
+        // SAFETY: the caller is told not to modify the file while it is
+        // mapped; the map is only ever read.
+        let map = unsafe { Mmap::map(&file)? };
+        std::str::from_utf8(&map).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(MappedFile { map })
+    }
+
+    pub fn text(&self) -> &str {
+        // SAFETY: checked to be UTF-8 in `open`, and read-only since.
+        unsafe { std::str::from_utf8_unchecked(&self.map) }
+    }
+
+    /// Builds a `Tape` over the mapped text, for lazy access that never
+    /// copies the file.
+    pub fn tape(&self) -> Result<Tape<'_>, ParseError> {
+        Tape::parse(self.text())
+    }
+
+    /// Parses the whole file into a tree with `ParserOptions::current()`.
+    /// The parser reads the mapped text in place; only the strings and
+    /// numbers kept in the tree are copied out of it.
+    pub fn parse(&self) -> Result<JsonValue, ParseError> {
+        parse_with_options(self.text(), ParserOptions::current())
+    }
+}
+
+/// Memory-maps the file at `path` and parses it into a tree. For reading a
+/// few values out of a huge file, `MappedFile::tape` avoids building the
+/// tree at all.
+pub fn parse_file(path: impl AsRef<Path>) -> Result<JsonValue, FileError> {
+    Ok(MappedFile::open(path)?.parse()?)
+}
@@ -0,0 +1,83 @@
+use super::pointer::escape_pointer_token;
+use super::stringify::stringify_compact;
+use super::{JsonValue, parse};
+
+/// Parses JSON embedded in string values into real subtrees, in place.
+///
+/// With an empty `pointers` list every string holding a JSON object or
+/// array is expanded, including JSON nested inside the expanded text.
+/// Otherwise only the strings at the given JSON Pointers are expanded, and
+/// they may hold any JSON value. Strings that do not parse are left alone.
+///
+/// Returns the pointers that were expanded, outermost first, for passing to
+/// `embed_nested_json` or `StringifyOptions::embed_json_at` later.
+pub fn expand_nested_json(value: &mut JsonValue, pointers: &[&str]) -> Vec<String> {
+    let mut expanded = Vec::new();
+    if pointers.is_empty() {
+        expand_all(value, &mut String::new(), &mut expanded);
+        return expanded;
+    }
+    for pointer in pointers {
+        let Some(node) = value.pointer_mut(pointer) else {
+            continue;
+        };
+        if let JsonValue::String(text) = node
+            && let Ok(parsed) = parse(text)
+        {
+            *node = parsed;
+            expanded.push(pointer.to_string());
+        }
+    }
+    expanded
+}
+
+/// Reverses `expand_nested_json`: replaces the subtree at each pointer with
+/// a string holding its compact JSON text. Inner pointers are embedded
+/// before outer ones, so nested expansions round-trip.
+pub fn embed_nested_json<S: AsRef<str>>(value: &mut JsonValue, pointers: &[S]) {
+    let mut pointers: Vec<&str> = pointers.iter().map(AsRef::as_ref).collect();
+    pointers.sort_by_key(|pointer| std::cmp::Reverse(pointer.matches('/').count()));
+    for pointer in pointers {
+        if let Some(node) = value.pointer_mut(pointer) {
+            *node = JsonValue::String(stringify_compact(node).into());
+        }
+    }
+}
+
+fn expand_all(value: &mut JsonValue, path: &mut String, expanded: &mut Vec<String>) {
+    if let JsonValue::String(text) = value
+        && looks_like_container(text)
+        && let Ok(parsed) = parse(text)
+    {
+        *value = parsed;
+        expanded.push(path.clone());
+    }
+    match value {
+        JsonValue::Array(arr) => {
+            for (i, item) in arr.iter_mut().enumerate() {
+                let len = path.len();
+                path.push_str(&format!("/{}", i));
+                expand_all(item, path, expanded);
+                path.truncate(len);
+            }
+        }
+        JsonValue::Object(obj) => {
+            let mut keys: Vec<String> = obj.keys().cloned().collect();
+            keys.sort();
+            for key in keys {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&escape_pointer_token(&key));
+                expand_all(obj.get_mut(&key).unwrap(), path, expanded);
+                path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn looks_like_container(text: &str) -> bool {
+    let trimmed = text.trim();
+    (trimmed.starts_with('{') && trimmed.ends_with('}'))
+        || (trimmed.starts_with('[') && trimmed.ends_with(']'))
+}
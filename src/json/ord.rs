@@ -0,0 +1,68 @@
+use super::JsonValue;
+use super::hash::normalize_number;
+use std::cmp::Ordering;
+
+/// Orders values of different kinds as null < bool < number < string <
+/// bytes < array < object < raw JSON. Numbers compare by value, with NaN
+/// above every other number, and a number kept as text sorts after an
+/// equal `Number`. Arrays compare element by element, objects member by
+/// member in ascending key order, and raw JSON by its text.
+impl Ord for JsonValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        rank(self)
+            .cmp(&rank(other))
+            .then_with(|| match (self, other) {
+                (JsonValue::Bool(a), JsonValue::Bool(b)) => a.cmp(b),
+                (JsonValue::Number(a), JsonValue::Number(b)) => compare_f64(*a, *b),
+                (JsonValue::Number(_) | JsonValue::RawNumber(_), _) => {
+                    let value = |v: &JsonValue| v.as_f64().unwrap_or(f64::NAN);
+                    compare_f64(value(self), value(other))
+                        .then_with(|| self.is_raw_number().cmp(&other.is_raw_number()))
+                        .then_with(|| match (self, other) {
+                            (JsonValue::RawNumber(a), JsonValue::RawNumber(b)) => a.cmp(b),
+                            _ => Ordering::Equal,
+                        })
+                }
+                (JsonValue::String(a), JsonValue::String(b)) => a.cmp(b),
+                (JsonValue::Bytes(a), JsonValue::Bytes(b)) => a.cmp(b),
+                (JsonValue::Array(a), JsonValue::Array(b)) => a.cmp(b),
+                (JsonValue::Object(_), JsonValue::Object(_)) => {
+                    self.iter_sorted().cmp(other.iter_sorted())
+                }
+                (JsonValue::Raw(a), JsonValue::Raw(b)) => a.as_str().cmp(b.as_str()),
+                _ => Ordering::Equal,
+            })
+    }
+}
+
+impl PartialOrd for JsonValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl JsonValue {
+    fn is_raw_number(&self) -> bool {
+        matches!(self, JsonValue::RawNumber(_))
+    }
+}
+
+fn rank(value: &JsonValue) -> u8 {
+    match value {
+        JsonValue::Null => 0,
+        JsonValue::Bool(_) => 1,
+        JsonValue::Number(_) | JsonValue::RawNumber(_) => 2,
+        JsonValue::String(_) => 3,
+        JsonValue::Bytes(_) => 4,
+        JsonValue::Array(_) => 5,
+        JsonValue::Object(_) => 6,
+        JsonValue::Raw(_) => 7,
+    }
+}
+
+// Agrees with `==`: numbers are normalized as for equality, so `0.0` and
+// `-0.0` are equal and every NaN is equal to every other, sorting above
+// all other numbers.
+fn compare_f64(a: f64, b: f64) -> Ordering {
+    normalize_number(a).total_cmp(&normalize_number(b))
+}
@@ -0,0 +1,112 @@
+use super::JsonValue;
+use super::incremental::{IncrementalDocument, Span};
+use std::ops::Range;
+
+/// What kind of value an `OutlineSymbol` stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Null,
+    Bool,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl SymbolKind {
+    pub fn of(value: &JsonValue) -> Self {
+        match value {
+            JsonValue::Null => SymbolKind::Null,
+            JsonValue::Bool(_) => SymbolKind::Bool,
+            JsonValue::Number(_) | JsonValue::RawNumber(_) => SymbolKind::Number,
+            JsonValue::String(_) | JsonValue::Bytes(_) => SymbolKind::String,
+            JsonValue::Array(_) => SymbolKind::Array,
+            JsonValue::Object(_) => SymbolKind::Object,
+            JsonValue::Raw(raw) => SymbolKind::of(&raw.parse()),
+        }
+    }
+}
+
+/// One entry of a document outline, as shown in an editor's outline view.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineSymbol {
+    /// The member's key, or the element's index; empty for the root.
+    pub name: String,
+    pub kind: SymbolKind,
+    /// Byte range in the source text, when the outline was built from one.
+    pub range: Option<Range<usize>>,
+    /// Number of members or elements; 0 for scalars.
+    pub child_count: usize,
+    pub children: Vec<OutlineSymbol>,
+}
+
+impl OutlineSymbol {
+    /// The chain of symbols enclosing byte `offset`, outermost first, for
+    /// breadcrumbs. Empty if the outline has no ranges or the offset lies
+    /// outside the root.
+    pub fn breadcrumbs(&self, offset: usize) -> Vec<&OutlineSymbol> {
+        let mut trail = Vec::new();
+        let mut next = Some(self).filter(|symbol| symbol.contains(offset));
+        while let Some(symbol) = next {
+            trail.push(symbol);
+            next = symbol.children.iter().find(|child| child.contains(offset));
+        }
+        trail
+    }
+
+    fn contains(&self, offset: usize) -> bool {
+        self.range
+            .as_ref()
+            .is_some_and(|range| range.contains(&offset))
+    }
+}
+
+/// Builds an outline of a value. Object members are listed in key order and
+/// no ranges are known; use `IncrementalDocument::outline` for those.
+pub fn outline(value: &JsonValue) -> OutlineSymbol {
+    symbol(String::new(), value, None)
+}
+
+impl IncrementalDocument {
+    /// Builds an outline with source ranges, listing members in the order
+    /// they appear in the text.
+    pub fn outline(&self) -> OutlineSymbol {
+        symbol(String::new(), self.value(), Some(self.spans()))
+    }
+}
+
+fn symbol(name: String, value: &JsonValue, span: Option<&Span>) -> OutlineSymbol {
+    let children: Vec<OutlineSymbol> = match (value, span) {
+        (JsonValue::Array(items), Some(span)) => items
+            .iter()
+            .zip(&span.children)
+            .enumerate()
+            .map(|(i, (item, (_, child)))| symbol(i.to_string(), item, Some(child)))
+            .collect(),
+        (JsonValue::Array(items), None) => items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| symbol(i.to_string(), item, None))
+            .collect(),
+        (JsonValue::Object(obj), Some(span)) => span
+            .children
+            .iter()
+            .filter_map(|(key, child)| {
+                let key = key.as_ref()?;
+                Some(symbol(key.clone(), obj.get(key)?, Some(child)))
+            })
+            .collect(),
+        (JsonValue::Object(_), None) => value
+            .iter_sorted()
+            .map(|(key, item)| symbol(key.clone(), item, None))
+            .collect(),
+        _ => Vec::new(),
+    };
+    OutlineSymbol {
+        name,
+        kind: SymbolKind::of(value),
+        range: span.map(|span| span.start..span.end),
+        child_count: children.len(),
+        children,
+    }
+}
@@ -0,0 +1,165 @@
+use super::{JsonValue, ParseError, Parser, ParserOptions, Position, scan_string, simd};
+use rayon::prelude::*;
+use std::ops::Range;
+
+// Pieces of work per thread, so a slow chunk does not leave the other
+// threads idle.
+const CHUNKS_PER_THREAD: usize = 4;
+
+/// Parses newline-delimited JSON, one value per line, across the rayon
+/// thread pool. Blank lines are skipped. The input is split into chunks at
+/// line breaks and each chunk is parsed on one thread, so the values come
+/// back in input order. On failure the first error in the input is
+/// returned, with its position counted from the start of `input`. Uses
+/// the calling thread's `ParserOptions::current()`.
+pub fn parse_ndjson_parallel(input: &str) -> Result<Vec<JsonValue>, ParseError> {
+    let options = ParserOptions::current();
+    let chunks = line_chunks(input, rayon::current_num_threads() * CHUNKS_PER_THREAD);
+    let parsed: Vec<Result<Vec<JsonValue>, ParseError>> = chunks
+        .into_par_iter()
+        .map(|chunk| {
+            let mut parser = Parser::with_options("", options.clone());
+            let mut values = Vec::new();
+            let mut start = chunk.start;
+            for line in input[chunk].split_inclusive('\n') {
+                if !line.trim().is_empty() {
+                    parser.reset(line);
+                    values.push(parser.parse().map_err(|e| relocate(e, input, start))?);
+                }
+                start += line.len();
+            }
+            Ok(values)
+        })
+        .collect();
+    let mut values = Vec::new();
+    for chunk in parsed {
+        values.extend(chunk?);
+    }
+    Ok(values)
+}
+
+/// Parses a document whose top level is an array, parsing its elements
+/// across the rayon thread pool. The elements are found by a structural
+/// scan that neither decodes strings nor checks scalars; input it cannot
+/// split, such as a non-array or anything with comments, is parsed on the
+/// calling thread instead, as `parse` would. `materialize_depth` counts
+/// from the elements rather than the array.
+pub fn parse_array_parallel(input: &str) -> Result<JsonValue, ParseError> {
+    let options = ParserOptions::current();
+    let Some(ranges) = element_ranges(input.as_bytes(), options.allow_single_quotes) else {
+        return Parser::with_options(input, options).parse();
+    };
+    let per_chunk = ranges
+        .len()
+        .div_ceil(rayon::current_num_threads() * CHUNKS_PER_THREAD)
+        .max(1);
+    let parsed: Vec<Result<Vec<JsonValue>, ParseError>> = ranges
+        .par_chunks(per_chunk)
+        .map(|chunk| {
+            let mut parser = Parser::with_options("", options.clone());
+            chunk
+                .iter()
+                .map(|range| {
+                    parser.reset(&input[range.clone()]);
+                    parser.parse().map_err(|e| relocate(e, input, range.start))
+                })
+                .collect()
+        })
+        .collect();
+    let mut items = Vec::with_capacity(ranges.len());
+    for chunk in parsed {
+        items.extend(chunk?);
+    }
+    Ok(JsonValue::Array(items))
+}
+
+// Splits `input` into about `count` ranges that end just after a newline.
+fn line_chunks(input: &str, count: usize) -> Vec<Range<usize>> {
+    let target = input.len().div_ceil(count.max(1)).max(1);
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < input.len() {
+        let from = (start + target).min(input.len());
+        let end = input.as_bytes()[from..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(input.len(), |i| from + i + 1);
+        chunks.push(start..end);
+        start = end;
+    }
+    chunks
+}
+
+// The byte ranges of the elements of a top-level array, or None if the
+// input is not an array this scan can split.
+fn element_ranges(bytes: &[u8], single_quotes: bool) -> Option<Vec<Range<usize>>> {
+    let mut pos = simd::skip_whitespace(bytes, 0);
+    if bytes.get(pos) != Some(&b'[') {
+        return None;
+    }
+    pos = simd::skip_whitespace(bytes, pos + 1);
+    let mut ranges = Vec::new();
+    if bytes.get(pos) == Some(&b']') {
+        pos += 1;
+    } else {
+        loop {
+            let end = skip_value(bytes, pos, single_quotes)?;
+            ranges.push(pos..end);
+            pos = simd::skip_whitespace(bytes, end);
+            match bytes.get(pos) {
+                Some(b',') => pos = simd::skip_whitespace(bytes, pos + 1),
+                Some(b']') => {
+                    pos += 1;
+                    break;
+                }
+                _ => return None,
+            }
+        }
+    }
+    (simd::skip_whitespace(bytes, pos) == bytes.len()).then_some(ranges)
+}
+
+// The end of the value starting at `pos`, found from structural
+// characters alone.
+fn skip_value(bytes: &[u8], mut pos: usize, single_quotes: bool) -> Option<usize> {
+    let mut depth = 0usize;
+    loop {
+        pos = simd::skip_whitespace(bytes, pos);
+        match *bytes.get(pos)? {
+            b'[' | b'{' => {
+                depth += 1;
+                pos += 1;
+            }
+            b']' | b'}' if depth > 0 => {
+                depth -= 1;
+                pos += 1;
+            }
+            b',' | b':' if depth > 0 => pos += 1,
+            b'"' => pos = scan_string(bytes, pos, b'"')?,
+            b'\'' if single_quotes => pos = scan_string(bytes, pos, b'\'')?,
+            _ => {
+                let end = simd::find_delimiter(bytes, pos);
+                if end == pos {
+                    return None;
+                }
+                pos = end;
+            }
+        }
+        if depth == 0 {
+            return Some(pos);
+        }
+    }
+}
+
+// Shifts the position of an error in the text starting at `start` so it
+// counts from the start of `input`.
+fn relocate(mut error: ParseError, input: &str, start: usize) -> ParseError {
+    let base = Position::at(input, start);
+    let pos = error.position_mut();
+    if pos.line == 1 {
+        pos.column += base.column - 1;
+    }
+    pos.offset += start;
+    pos.line += base.line - 1;
+    error
+}
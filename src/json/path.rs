@@ -0,0 +1,142 @@
+use super::JsonValue;
+use std::fmt;
+
+/// A malformed dot-path, or one that `path_set` cannot follow. `offset` is
+/// the byte offset in the path.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PathError {
+    pub offset: usize,
+    pub message: String,
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for PathError {}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+impl JsonValue {
+    /// Looks up a value by dot-path, e.g. `config.servers[2].host`. Members
+    /// are separated by `.`, array elements are selected with `[n]`, and
+    /// keys containing `.`, `[` or spaces can be written `["a.b"]`. The
+    /// empty path refers to the whole document. Returns `None` if nothing is
+    /// there or the path is malformed.
+    pub fn path_get(&self, path: &str) -> Option<&JsonValue> {
+        parse_path(path)
+            .ok()?
+            .into_iter()
+            .try_fold(self, |value, (_, segment)| match (value, segment) {
+                (JsonValue::Object(obj), Segment::Key(key)) => obj.get(&key),
+                (JsonValue::Array(arr), Segment::Index(i)) => arr.get(i),
+                _ => None,
+            })
+    }
+
+    /// Sets the value at a dot-path, creating missing objects and arrays
+    /// along the way; `null` counts as missing. Arrays are padded with
+    /// `null` up to a new index. Fails if the path is malformed or runs
+    /// through a value of the wrong kind.
+    pub fn path_set(&mut self, path: &str, value: JsonValue) -> Result<(), PathError> {
+        let mut target = self;
+        for (offset, segment) in parse_path(path)? {
+            if target.is_null() {
+                *target = match segment {
+                    Segment::Key(_) => JsonValue::Object(Box::default()),
+                    Segment::Index(_) => JsonValue::Array(Vec::new()),
+                };
+            }
+            target = match (target, segment) {
+                (JsonValue::Object(obj), Segment::Key(key)) => {
+                    obj.entry(key).or_insert(JsonValue::Null)
+                }
+                (JsonValue::Array(arr), Segment::Index(i)) => {
+                    if i >= arr.len() {
+                        arr.resize(i + 1, JsonValue::Null);
+                    }
+                    &mut arr[i]
+                }
+                (_, Segment::Key(_)) => return Err(error(offset, "not an object")),
+                (_, Segment::Index(_)) => return Err(error(offset, "not an array")),
+            };
+        }
+        *target = value;
+        Ok(())
+    }
+}
+
+fn error(offset: usize, message: &str) -> PathError {
+    PathError {
+        offset,
+        message: message.to_string(),
+    }
+}
+
+// Splits a dot-path into segments, each with the offset where it starts.
+fn parse_path(path: &str) -> Result<Vec<(usize, Segment)>, PathError> {
+    let bytes = path.as_bytes();
+    let mut segments = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let start = pos;
+        match bytes[pos] {
+            b'[' if bytes.get(pos + 1) == Some(&b'"') => {
+                pos += 2;
+                let mut key = String::new();
+                let mut chars = path[pos..].char_indices();
+                loop {
+                    match chars.next() {
+                        Some((i, '"')) => {
+                            pos += i + 1;
+                            break;
+                        }
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, c)) => key.push(c),
+                            None => return Err(error(start, "unterminated key")),
+                        },
+                        Some((_, c)) => key.push(c),
+                        None => return Err(error(start, "unterminated key")),
+                    }
+                }
+                if bytes.get(pos) != Some(&b']') {
+                    return Err(error(pos, "expected ]"));
+                }
+                pos += 1;
+                segments.push((start, Segment::Key(key)));
+            }
+            b'[' => {
+                let end = path[pos..]
+                    .find(']')
+                    .ok_or_else(|| error(start, "unterminated index"))?;
+                let index = path[pos + 1..pos + end]
+                    .parse()
+                    .map_err(|_| error(pos + 1, "expected an array index"))?;
+                pos += end + 1;
+                segments.push((start, Segment::Index(index)));
+            }
+            b'.' if pos == 0 && bytes.len() == 1 => pos += 1,
+            _ => {
+                // A name, after a `.` unless it starts the path.
+                if bytes[pos] == b'.' {
+                    pos += 1;
+                } else if pos != 0 {
+                    return Err(error(pos, "expected . or ["));
+                }
+                let len = path[pos..].find(['.', '[']).unwrap_or(path.len() - pos);
+                if len == 0 {
+                    return Err(error(pos, "expected a key"));
+                }
+                segments.push((start, Segment::Key(path[pos..pos + len].to_string())));
+                pos += len;
+            }
+        }
+    }
+    Ok(segments)
+}
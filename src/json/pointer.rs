@@ -0,0 +1,129 @@
+use super::JsonValue;
+use super::map::remove_member;
+use std::fmt;
+
+/// Why `JsonValue::pointer_set` could not place a value.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PointerError {
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for PointerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.pointer, self.message)
+    }
+}
+
+impl std::error::Error for PointerError {}
+
+impl JsonValue {
+    /// Looks up a value by RFC 6901 JSON Pointer, e.g. `/users/0/name`.
+    /// The empty pointer refers to the whole document.
+    pub fn pointer(&self, pointer: &str) -> Option<&JsonValue> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        let rest = pointer.strip_prefix('/')?;
+        rest.split('/')
+            .map(unescape_token)
+            .try_fold(self, |value, token| match value {
+                JsonValue::Object(obj) => obj.get(&token),
+                JsonValue::Array(arr) => arr.get(parse_index(&token)?),
+                _ => None,
+            })
+    }
+
+    /// Mutable counterpart of `pointer`.
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut JsonValue> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        let rest = pointer.strip_prefix('/')?;
+        rest.split('/')
+            .map(unescape_token)
+            .try_fold(self, |value, token| match value {
+                JsonValue::Object(obj) => obj.get_mut(&token),
+                JsonValue::Array(arr) => arr.get_mut(parse_index(&token)?),
+                _ => None,
+            })
+    }
+
+    /// Stores `value` at a JSON Pointer and returns the value it replaced.
+    /// The parent must exist. A missing object member is added, and an array
+    /// index one past the end, or the `-` token, appends. The empty pointer
+    /// replaces the whole document.
+    pub fn pointer_set(
+        &mut self,
+        pointer: &str,
+        value: JsonValue,
+    ) -> Result<Option<JsonValue>, PointerError> {
+        let error = |message: &str| PointerError {
+            pointer: pointer.to_string(),
+            message: message.to_string(),
+        };
+        if pointer.is_empty() {
+            return Ok(Some(std::mem::replace(self, value)));
+        }
+        let (parent, token) = pointer
+            .rsplit_once('/')
+            .ok_or_else(|| error("invalid pointer"))?;
+        let token = unescape_token(token);
+        match self.pointer_mut(parent) {
+            Some(JsonValue::Object(obj)) => Ok(obj.insert(token, value)),
+            Some(JsonValue::Array(arr)) => {
+                let index = match token.as_str() {
+                    "-" => arr.len(),
+                    _ => parse_index(&token)
+                        .filter(|&i| i <= arr.len())
+                        .ok_or_else(|| error("array index out of range"))?,
+                };
+                if index == arr.len() {
+                    arr.push(value);
+                    Ok(None)
+                } else {
+                    Ok(Some(std::mem::replace(&mut arr[index], value)))
+                }
+            }
+            Some(_) => Err(error("parent is not an object or array")),
+            None => Err(error("parent does not exist")),
+        }
+    }
+
+    /// Removes and returns the value at a JSON Pointer, shifting later array
+    /// elements down. Returns `None` if nothing is there; the whole document
+    /// cannot be removed.
+    pub fn pointer_remove(&mut self, pointer: &str) -> Option<JsonValue> {
+        let (parent, token) = pointer.rsplit_once('/')?;
+        let token = unescape_token(token);
+        match self.pointer_mut(parent)? {
+            JsonValue::Object(obj) => remove_member(obj, &token),
+            JsonValue::Array(arr) => {
+                let index = parse_index(&token).filter(|&i| i < arr.len())?;
+                Some(arr.remove(index))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Escapes a key for use as a JSON Pointer reference token (`~` becomes
+/// `~0` and `/` becomes `~1`).
+pub fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+pub(crate) fn unescape_token(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+// Array indices are decimal without leading zeros, per RFC 6901.
+pub(crate) fn parse_index(token: &str) -> Option<usize> {
+    if token.is_empty() || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    if !token.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    token.parse().ok()
+}
@@ -0,0 +1,108 @@
+use super::{JsonValue, ParseError, Parser, ParserOptions, parse_with_options};
+use std::cell::RefCell;
+use std::sync::Mutex;
+
+const DEFAULT_MAX_IDLE: usize = 16;
+
+/// A shareable pool of parsers configured with the same options. Parsers
+/// read their input in place; what the pool keeps is the buffer each one
+/// decodes escaped strings into, returned when the `PooledParser` guard is
+/// dropped so later parses on any thread reuse its capacity.
+pub struct ParserPool {
+    options: ParserOptions,
+    idle: Mutex<Vec<String>>,
+    max_idle: usize,
+}
+
+impl ParserPool {
+    pub fn new(options: ParserOptions) -> Self {
+        ParserPool::with_max_idle(options, DEFAULT_MAX_IDLE)
+    }
+
+    /// Like `new`, but keeps at most `max_idle` parsers around; any extra
+    /// parsers are dropped when they are returned.
+    pub fn with_max_idle(options: ParserOptions, max_idle: usize) -> Self {
+        ParserPool {
+            options,
+            idle: Mutex::new(Vec::new()),
+            max_idle,
+        }
+    }
+
+    /// Takes an idle parser, or creates one if the pool is empty.
+    pub fn get(&self) -> PooledParser<'_> {
+        let scratch = self
+            .idle
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop()
+            .unwrap_or_default();
+        PooledParser {
+            pool: self,
+            scratch,
+        }
+    }
+
+    pub fn parse(&self, input: &str) -> Result<JsonValue, ParseError> {
+        self.get().parse_str(input)
+    }
+
+    /// Number of parsers currently waiting in the pool.
+    pub fn idle(&self) -> usize {
+        self.idle.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+}
+
+impl Default for ParserPool {
+    fn default() -> Self {
+        ParserPool::new(ParserOptions::current())
+    }
+}
+
+/// A parser borrowed from a `ParserPool`, returned to it on drop.
+pub struct PooledParser<'a> {
+    pool: &'a ParserPool,
+    scratch: String,
+}
+
+impl PooledParser<'_> {
+    /// Parses `input`, reusing this parser's buffers.
+    pub fn parse_str(&mut self, input: &str) -> Result<JsonValue, ParseError> {
+        let scratch = std::mem::take(&mut self.scratch);
+        let mut parser =
+            Parser::with_options(input, self.pool.options.clone()).with_scratch(scratch);
+        let result = parser.parse();
+        self.scratch = parser.into_scratch();
+        result
+    }
+}
+
+impl Drop for PooledParser<'_> {
+    fn drop(&mut self) {
+        let mut idle = self.pool.idle.lock().unwrap_or_else(|e| e.into_inner());
+        if idle.len() < self.pool.max_idle {
+            idle.push(std::mem::take(&mut self.scratch));
+        }
+    }
+}
+
+thread_local! {
+    static THREAD_SCRATCH: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Parses `input` with `ParserOptions::current()` using buffers cached per
+/// thread, so hot paths reuse them without managing a pool.
+pub fn parse_pooled(input: &str) -> Result<JsonValue, ParseError> {
+    THREAD_SCRATCH.with(|scratch| match scratch.try_borrow_mut() {
+        Ok(mut scratch) => {
+            let mut parser = Parser::with_options(input, ParserOptions::current())
+                .with_scratch(std::mem::take(&mut *scratch));
+            let result = parser.parse();
+            *scratch = parser.into_scratch();
+            result
+        }
+        // A parse on this thread is already using the buffer, as when a
+        // metrics hook parses again; this one goes without.
+        Err(_) => parse_with_options(input, ParserOptions::current()),
+    })
+}
@@ -0,0 +1,168 @@
+use super::JsonValue;
+use super::diff::{PatchError, PatchOp, apply_op};
+use super::pointer::{escape_pointer_token, parse_index};
+use std::collections::BTreeMap;
+
+/// A value that remembers which source (a file, a configuration layer, a
+/// patch id) supplied each part of it, as merges and patches are applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackedValue {
+    value: JsonValue,
+    // The source of each value placed by `new`, a merge or a patch, keyed by
+    // pointer. Values below a recorded pointer share its source.
+    origins: BTreeMap<String, String>,
+}
+
+impl TrackedValue {
+    /// Starts tracking `value`, attributing all of it to `source`.
+    pub fn new(value: JsonValue, source: &str) -> Self {
+        let mut origins = BTreeMap::new();
+        origins.insert(String::new(), source.to_string());
+        TrackedValue { value, origins }
+    }
+
+    pub fn value(&self) -> &JsonValue {
+        &self.value
+    }
+
+    pub fn into_value(self) -> JsonValue {
+        self.value
+    }
+
+    /// The source that supplied the value at a JSON Pointer, or `None` if
+    /// there is no value there.
+    pub fn source_of(&self, pointer: &str) -> Option<&str> {
+        self.value.pointer(pointer)?;
+        let mut pointer = pointer;
+        loop {
+            if let Some(source) = self.origins.get(pointer) {
+                return Some(source);
+            }
+            pointer = pointer.rsplit_once('/')?.0;
+        }
+    }
+
+    /// The pointers at which a source placed a value, with that source, in
+    /// pointer order.
+    pub fn origins(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.origins.iter().map(|(p, s)| (p.as_str(), s.as_str()))
+    }
+
+    /// Deep-merges `layer` on top: objects are merged member by member and
+    /// anything else, `null` included, replaces what was there.
+    pub fn merge(&mut self, layer: &JsonValue, source: &str) {
+        let mut value = self.value.take();
+        self.merge_into(&mut value, layer, &mut String::new(), source);
+        self.value = value;
+    }
+
+    /// Applies a JSON Patch, attributing added and replaced values to
+    /// `source`. On error nothing is changed.
+    pub fn apply_patch(&mut self, ops: &[PatchOp], source: &str) -> Result<(), PatchError> {
+        let mut patched = self.clone();
+        for op in ops {
+            patched.apply_op(op, source)?;
+        }
+        *self = patched;
+        Ok(())
+    }
+
+    fn merge_into(
+        &mut self,
+        target: &mut JsonValue,
+        layer: &JsonValue,
+        path: &mut String,
+        source: &str,
+    ) {
+        match (target, layer) {
+            (JsonValue::Object(target), JsonValue::Object(layer)) => {
+                let mut keys: Vec<&String> = layer.keys().collect();
+                keys.sort();
+                for key in keys {
+                    let len = path.len();
+                    path.push('/');
+                    path.push_str(&escape_pointer_token(key));
+                    match target.get_mut(key) {
+                        Some(existing) => self.merge_into(existing, &layer[key], path, source),
+                        None => {
+                            target.insert(key.clone(), layer[key].clone());
+                            self.record(path, source);
+                        }
+                    }
+                    path.truncate(len);
+                }
+            }
+            (target, layer) => {
+                *target = layer.clone();
+                self.record(path, source);
+            }
+        }
+    }
+
+    fn apply_op(&mut self, op: &PatchOp, source: &str) -> Result<(), PatchError> {
+        let path = op.path();
+        let array =
+            path.rsplit_once('/')
+                .and_then(|(parent, token)| match self.value.pointer(parent) {
+                    Some(JsonValue::Array(arr)) => Some((parent, token, arr.len())),
+                    _ => None,
+                });
+        apply_op(&mut self.value, op)?;
+
+        match (op, array) {
+            (PatchOp::Add { .. }, Some((parent, token, len))) => {
+                let index = parse_index(token).unwrap_or(len);
+                self.shift_indices(parent, index, 1);
+                self.origins
+                    .insert(format!("{}/{}", parent, index), source.to_string());
+            }
+            (PatchOp::Remove { .. }, Some((parent, token, _))) => {
+                self.clear(path);
+                self.shift_indices(parent, parse_index(token).unwrap() + 1, -1);
+            }
+            (PatchOp::Remove { .. }, None) => self.clear(path),
+            _ => self.record(path, source),
+        }
+        Ok(())
+    }
+
+    // Attributes the value at `pointer`, and everything below it, to
+    // `source`.
+    fn record(&mut self, pointer: &str, source: &str) {
+        self.clear(pointer);
+        self.origins.insert(pointer.to_string(), source.to_string());
+    }
+
+    fn clear(&mut self, pointer: &str) {
+        let below = format!("{}/", pointer);
+        self.origins
+            .retain(|p, _| p != pointer && !p.starts_with(&below));
+    }
+
+    // Renumbers recorded elements of the array at `array` from index `from`
+    // on, after an element was inserted or removed.
+    fn shift_indices(&mut self, array: &str, from: usize, delta: isize) {
+        let prefix = format!("{}/", array);
+        let moved: Vec<String> = self
+            .origins
+            .keys()
+            .filter(|p| {
+                p.strip_prefix(&prefix)
+                    .and_then(|rest| parse_index(rest.split('/').next().unwrap()))
+                    .is_some_and(|i| i >= from)
+            })
+            .cloned()
+            .collect();
+        let renumbered: Vec<(String, String)> = moved
+            .into_iter()
+            .map(|p| {
+                let source = self.origins.remove(&p).unwrap();
+                let rest = &p[prefix.len()..];
+                let (index, tail) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+                let index = (parse_index(index).unwrap() as isize + delta) as usize;
+                (format!("{}{}{}", prefix, index, tail), source)
+            })
+            .collect();
+        self.origins.extend(renumbered);
+    }
+}
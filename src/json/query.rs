@@ -0,0 +1,634 @@
+use super::pointer::escape_pointer_token;
+use super::stringify::stringify_compact;
+use super::{JsonValue, ParserOptions, parse_with_options};
+use std::fmt;
+
+/// A malformed JSONPath expression. `offset` is the byte offset in the path.
+#[derive(Debug, PartialEq, Clone)]
+pub struct QueryError {
+    pub offset: usize,
+    pub message: String,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// How a JSONPath query was evaluated, as returned by `JsonValue::explain`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Explanation<'a> {
+    pub path: String,
+    pub steps: Vec<StepTrace>,
+    /// Selected nodes, as (JSON Pointer, value) pairs in document order.
+    pub matches: Vec<(String, &'a JsonValue)>,
+}
+
+impl Explanation<'_> {
+    /// Index into `steps` of the first step that selected nothing, which is
+    /// where a query with no results lost its last candidate.
+    pub fn first_empty_step(&self) -> Option<usize> {
+        self.steps.iter().position(|step| step.selected.is_empty())
+    }
+}
+
+/// One selector of the path: the nodes it was applied to and the nodes it
+/// selected, each by JSON Pointer.
+#[derive(Debug, PartialEq, Clone)]
+pub struct StepTrace {
+    pub selector: String,
+    pub visited: Vec<String>,
+    pub selected: Vec<String>,
+    /// For filter selectors, the outcome for every candidate.
+    pub predicates: Vec<PredicateTrace>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PredicateTrace {
+    pub pointer: String,
+    pub matched: bool,
+    pub reason: String,
+}
+
+impl fmt::Display for Explanation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "query: {}", self.path)?;
+        for (i, step) in self.steps.iter().enumerate() {
+            writeln!(
+                f,
+                "step {}: {} (visited {}, selected {})",
+                i + 1,
+                step.selector,
+                step.visited.len(),
+                step.selected.len()
+            )?;
+            for predicate in &step.predicates {
+                let outcome = if predicate.matched {
+                    "match"
+                } else {
+                    "no match"
+                };
+                writeln!(
+                    f,
+                    "  {}: {}: {}",
+                    display_pointer(&predicate.pointer),
+                    outcome,
+                    predicate.reason
+                )?;
+            }
+        }
+        match self.first_empty_step() {
+            Some(i) if self.matches.is_empty() => {
+                write!(f, "no results: step {} selected nothing", i + 1)
+            }
+            _ => write!(f, "{} result(s)", self.matches.len()),
+        }
+    }
+}
+
+impl JsonValue {
+    /// Evaluates a JSONPath expression and returns the selected nodes.
+    ///
+    /// Supported syntax: `$`, `.name`, `['name']`, `[index]` (negative counts
+    /// from the end), `.*` and `[*]`, recursive descent `..name` and `..*`,
+    /// and filters `[?(@.a.b)]` or `[?(@.a op literal)]` with `==`, `!=`,
+    /// `<`, `<=`, `>` and `>=`.
+    pub fn query(&self, path: &str) -> Result<Vec<&JsonValue>, QueryError> {
+        let selectors = PathParser { path, pos: 0 }.parse()?;
+        let mut current = vec![self];
+        for selector in &selectors {
+            let mut next = Vec::new();
+            for node in current {
+                select(selector, node, &mut next);
+            }
+            current = next;
+        }
+        Ok(current)
+    }
+
+    /// Evaluates a JSONPath expression like `query`, recording which nodes
+    /// each step visited and selected and why filters matched or not.
+    pub fn explain(&self, path: &str) -> Result<Explanation<'_>, QueryError> {
+        let selectors = PathParser { path, pos: 0 }.parse()?;
+        let mut current = vec![(String::new(), self)];
+        let mut steps = Vec::new();
+
+        for selector in &selectors {
+            let mut step = StepTrace {
+                selector: selector.to_string(),
+                visited: Vec::new(),
+                selected: Vec::new(),
+                predicates: Vec::new(),
+            };
+            let mut next = Vec::new();
+            for (pointer, node) in current {
+                apply(selector, &pointer, node, &mut step, &mut next);
+            }
+            step.selected = next.iter().map(|(p, _)| p.clone()).collect();
+            steps.push(step);
+            current = next;
+        }
+
+        Ok(Explanation {
+            path: path.to_string(),
+            steps,
+            matches: current,
+        })
+    }
+}
+
+#[derive(Debug)]
+enum Selector {
+    Child(String),
+    Index(i64),
+    Wildcard,
+    /// `..name`, or `..*` when `None`.
+    Descendant(Option<String>),
+    Filter(Filter),
+}
+
+#[derive(Debug)]
+struct Filter {
+    source: String,
+    path: Vec<String>,
+    comparison: Option<(Op, JsonValue)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Selector::Child(name) => write!(f, "['{}']", name),
+            Selector::Index(i) => write!(f, "[{}]", i),
+            Selector::Wildcard => f.write_str("[*]"),
+            Selector::Descendant(Some(name)) => write!(f, "..['{}']", name),
+            Selector::Descendant(None) => f.write_str("..[*]"),
+            Selector::Filter(filter) => write!(f, "[?({})]", filter.source),
+        }
+    }
+}
+
+// Like `apply`, without tracing: collects the selected nodes only.
+fn select<'a>(selector: &Selector, node: &'a JsonValue, out: &mut Vec<&'a JsonValue>) {
+    match (selector, node) {
+        (Selector::Descendant(name), _) => {
+            let mut stack = vec![node];
+            while let Some(node) = stack.pop() {
+                let children = child_nodes(node);
+                for (key, child) in &children {
+                    let selected = match (name, key) {
+                        (None, _) => true,
+                        (Some(name), Some(key)) => name == *key,
+                        (Some(_), None) => false,
+                    };
+                    if selected {
+                        out.push(child);
+                    }
+                }
+                stack.extend(children.into_iter().rev().map(|(_, c)| c));
+            }
+        }
+        (Selector::Child(name), JsonValue::Object(obj)) => out.extend(obj.get(name)),
+        (Selector::Index(i), JsonValue::Array(arr)) => {
+            let index = if *i < 0 { arr.len() as i64 + i } else { *i };
+            out.extend(usize::try_from(index).ok().and_then(|i| arr.get(i)));
+        }
+        (Selector::Wildcard, _) => out.extend(child_nodes(node).into_iter().map(|(_, c)| c)),
+        (Selector::Filter(filter), _) => out.extend(
+            child_nodes(node)
+                .into_iter()
+                .map(|(_, c)| c)
+                .filter(|child| filter.matches(child)),
+        ),
+        _ => {}
+    }
+}
+
+fn apply<'a>(
+    selector: &Selector,
+    pointer: &str,
+    node: &'a JsonValue,
+    step: &mut StepTrace,
+    out: &mut Vec<(String, &'a JsonValue)>,
+) {
+    match selector {
+        Selector::Descendant(name) => {
+            let mut stack = vec![(pointer.to_string(), node)];
+            while let Some((pointer, node)) = stack.pop() {
+                step.visited.push(pointer.clone());
+                let children = children(&pointer, node);
+                for (child_pointer, child, key) in &children {
+                    let selected = match (name, key) {
+                        (None, _) => true,
+                        (Some(name), Some(key)) => name == *key,
+                        (Some(_), None) => false,
+                    };
+                    if selected {
+                        out.push((child_pointer.clone(), child));
+                    }
+                }
+                stack.extend(children.into_iter().rev().map(|(p, c, _)| (p, c)));
+            }
+        }
+        _ => {
+            step.visited.push(pointer.to_string());
+            match (selector, node) {
+                (Selector::Child(name), JsonValue::Object(obj)) => {
+                    if let Some(child) = obj.get(name) {
+                        out.push((child_pointer(pointer, name), child));
+                    }
+                }
+                (Selector::Index(i), JsonValue::Array(arr)) => {
+                    let index = if *i < 0 { arr.len() as i64 + i } else { *i };
+                    if let Some(child) = usize::try_from(index).ok().and_then(|i| arr.get(i)) {
+                        out.push((format!("{}/{}", pointer, index), child));
+                    }
+                }
+                (Selector::Wildcard, _) => {
+                    out.extend(children(pointer, node).into_iter().map(|(p, c, _)| (p, c)));
+                }
+                (Selector::Filter(filter), _) => {
+                    for (child_pointer, child, _) in children(pointer, node) {
+                        let (matched, reason) = filter.evaluate(child);
+                        step.predicates.push(PredicateTrace {
+                            pointer: child_pointer.clone(),
+                            matched,
+                            reason,
+                        });
+                        if matched {
+                            out.push((child_pointer, child));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+// Direct children in document order: array elements, or object members
+// sorted by key so results are reproducible.
+fn children<'a>(
+    pointer: &str,
+    node: &'a JsonValue,
+) -> Vec<(String, &'a JsonValue, Option<&'a String>)> {
+    match node {
+        JsonValue::Array(arr) => arr
+            .iter()
+            .enumerate()
+            .map(|(i, child)| (format!("{}/{}", pointer, i), child, None))
+            .collect(),
+        JsonValue::Object(_) => node
+            .iter_sorted()
+            .map(|(key, child)| (child_pointer(pointer, key), child, Some(key)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// The same children as `children`, without their pointers.
+fn child_nodes(node: &JsonValue) -> Vec<(Option<&String>, &JsonValue)> {
+    match node {
+        JsonValue::Array(arr) => arr.iter().map(|child| (None, child)).collect(),
+        JsonValue::Object(_) => node
+            .iter_sorted()
+            .map(|(key, child)| (Some(key), child))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn child_pointer(pointer: &str, key: &str) -> String {
+    format!("{}/{}", pointer, escape_pointer_token(key))
+}
+
+fn display_pointer(pointer: &str) -> &str {
+    if pointer.is_empty() {
+        "(root)"
+    } else {
+        pointer
+    }
+}
+
+impl Filter {
+    fn matches(&self, node: &JsonValue) -> bool {
+        let Some(value) = self.path.iter().try_fold(node, |v, key| v.get(key)) else {
+            return false;
+        };
+        match &self.comparison {
+            Some((op, literal)) => op.test(value, literal).unwrap_or(false),
+            None => true,
+        }
+    }
+
+    // Like `matches`, with a sentence saying why.
+    fn evaluate(&self, node: &JsonValue) -> (bool, String) {
+        let operand = format!(
+            "@{}",
+            self.path
+                .iter()
+                .map(|k| format!(".{}", k))
+                .collect::<String>()
+        );
+        let Some(value) = self.path.iter().try_fold(node, |v, key| v.get(key)) else {
+            return (false, format!("{} is missing", operand));
+        };
+        let Some((op, literal)) = &self.comparison else {
+            return (true, format!("{} exists", operand));
+        };
+
+        let Some(matched) = op.test(value, literal) else {
+            return (
+                false,
+                format!(
+                    "{} is {}, not comparable with {}",
+                    operand,
+                    stringify_compact(value),
+                    stringify_compact(literal)
+                ),
+            );
+        };
+        let verdict = if matched { "holds" } else { "does not hold" };
+        let value = stringify_compact(value);
+        (
+            matched,
+            format!(
+                "{} is {}; {} {} {} {}",
+                operand,
+                value,
+                value,
+                op.symbol(),
+                stringify_compact(literal),
+                verdict
+            ),
+        )
+    }
+}
+
+impl Op {
+    fn symbol(self) -> &'static str {
+        match self {
+            Op::Eq => "==",
+            Op::Ne => "!=",
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+        }
+    }
+
+    // Whether `value op literal` holds, or `None` if the two cannot be
+    // ordered against each other.
+    fn test(self, value: &JsonValue, literal: &JsonValue) -> Option<bool> {
+        match (self, value, literal) {
+            (Op::Eq, _, _) => Some(value == literal),
+            (Op::Ne, _, _) => Some(value != literal),
+            (_, JsonValue::Number(a), JsonValue::Number(b)) => Some(self.compare(a.partial_cmp(b))),
+            (_, JsonValue::String(a), JsonValue::String(b)) => Some(self.compare(Some(a.cmp(b)))),
+            _ => None,
+        }
+    }
+
+    fn compare(self, ordering: Option<std::cmp::Ordering>) -> bool {
+        let Some(ordering) = ordering else {
+            return false;
+        };
+        match self {
+            Op::Eq => ordering.is_eq(),
+            Op::Ne => ordering.is_ne(),
+            Op::Lt => ordering.is_lt(),
+            Op::Le => ordering.is_le(),
+            Op::Gt => ordering.is_gt(),
+            Op::Ge => ordering.is_ge(),
+        }
+    }
+}
+
+struct PathParser<'p> {
+    path: &'p str,
+    pos: usize,
+}
+
+impl PathParser<'_> {
+    fn parse(mut self) -> Result<Vec<Selector>, QueryError> {
+        if !self.eat('$') {
+            return Err(self.error("expected '$'"));
+        }
+        let mut selectors = Vec::new();
+        while let Some(c) = self.peek() {
+            let selector = match c {
+                '.' if self.path[self.pos..].starts_with("..") => {
+                    self.pos += 2;
+                    if self.eat('*') {
+                        Selector::Descendant(None)
+                    } else {
+                        Selector::Descendant(Some(self.name()?))
+                    }
+                }
+                '.' => {
+                    self.pos += 1;
+                    if self.eat('*') {
+                        Selector::Wildcard
+                    } else {
+                        Selector::Child(self.name()?)
+                    }
+                }
+                '[' => {
+                    self.pos += 1;
+                    let selector = self.bracket()?;
+                    if !self.eat(']') {
+                        return Err(self.error("expected ']'"));
+                    }
+                    selector
+                }
+                _ => return Err(self.error("expected '.' or '['")),
+            };
+            selectors.push(selector);
+        }
+        Ok(selectors)
+    }
+
+    fn bracket(&mut self) -> Result<Selector, QueryError> {
+        match self.peek() {
+            Some('*') => {
+                self.pos += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some(quote @ ('\'' | '"')) => Ok(Selector::Child(self.quoted(quote)?)),
+            Some('?') => {
+                self.pos += 1;
+                Ok(Selector::Filter(self.filter()?))
+            }
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let start = self.pos;
+                self.pos += 1;
+                while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    self.pos += 1;
+                }
+                self.path[start..self.pos]
+                    .parse()
+                    .map(Selector::Index)
+                    .map_err(|_| QueryError {
+                        offset: start,
+                        message: "invalid array index".to_string(),
+                    })
+            }
+            _ => Err(self.error("expected a name, index, '*' or filter")),
+        }
+    }
+
+    fn filter(&mut self) -> Result<Filter, QueryError> {
+        if !self.eat('(') {
+            return Err(self.error("expected '(' after '?'"));
+        }
+        let start = self.pos;
+        let end = self.closing_paren()?;
+        let source = self.path[start..end].trim().to_string();
+
+        self.skip_spaces();
+        if !self.eat('@') {
+            return Err(self.error("expected '@'"));
+        }
+        let mut path = Vec::new();
+        loop {
+            if self.eat('.') {
+                path.push(self.name()?);
+            } else if self.path[self.pos..].starts_with("['")
+                || self.path[self.pos..].starts_with("[\"")
+            {
+                self.pos += 1;
+                let quote = self.peek().unwrap();
+                path.push(self.quoted(quote)?);
+                if !self.eat(']') {
+                    return Err(self.error("expected ']'"));
+                }
+            } else {
+                break;
+            }
+        }
+        self.skip_spaces();
+
+        let comparison = if self.pos < end {
+            let op = self.op()?;
+            let literal_start = self.pos;
+            let text = &self.path[literal_start..end];
+            let options = ParserOptions {
+                allow_single_quotes: true,
+                ..ParserOptions::default()
+            };
+            let literal = parse_with_options(text, options).map_err(|_| QueryError {
+                offset: literal_start,
+                message: "expected a literal".to_string(),
+            })?;
+            Some((op, literal))
+        } else {
+            None
+        };
+
+        self.pos = end + 1;
+        Ok(Filter {
+            source,
+            path,
+            comparison,
+        })
+    }
+
+    fn op(&mut self) -> Result<Op, QueryError> {
+        let ops = [
+            ("==", Op::Eq),
+            ("!=", Op::Ne),
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+        ];
+        for (symbol, op) in ops {
+            if self.path[self.pos..].starts_with(symbol) {
+                self.pos += symbol.len();
+                return Ok(op);
+            }
+        }
+        Err(self.error("expected a comparison operator"))
+    }
+
+    // Finds the ')' closing the filter, skipping quoted literals.
+    fn closing_paren(&self) -> Result<usize, QueryError> {
+        let mut quote = None;
+        for (i, c) in self.path[self.pos..].char_indices() {
+            match (quote, c) {
+                (Some(q), _) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '\'' | '"') => quote = Some(c),
+                (None, ')') => return Ok(self.pos + i),
+                _ => {}
+            }
+        }
+        Err(QueryError {
+            offset: self.path.len(),
+            message: "unclosed filter".to_string(),
+        })
+    }
+
+    fn name(&mut self) -> Result<String, QueryError> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '$')
+        {
+            self.pos += self.peek().unwrap().len_utf8();
+        }
+        if self.pos == start {
+            return Err(self.error("expected a member name"));
+        }
+        Ok(self.path[start..self.pos].to_string())
+    }
+
+    fn quoted(&mut self, quote: char) -> Result<String, QueryError> {
+        let start = self.pos + 1;
+        match self.path[start..].find(quote) {
+            Some(len) => {
+                self.pos = start + len + 1;
+                Ok(self.path[start..start + len].to_string())
+            }
+            None => Err(self.error("unterminated quoted name")),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.path[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek() == Some(' ') {
+            self.pos += 1;
+        }
+    }
+
+    fn error(&self, message: &str) -> QueryError {
+        QueryError {
+            offset: self.pos,
+            message: message.to_string(),
+        }
+    }
+}
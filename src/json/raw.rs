@@ -0,0 +1,42 @@
+use super::{JsonValue, ParseError, ParserOptions, parse_with_options, validate_with_options};
+use std::fmt;
+
+/// A fragment of JSON text that has been checked for validity but not
+/// parsed. Placed in a tree as `JsonValue::Raw`, it is written out verbatim,
+/// so proxies can forward large payloads without rebuilding them.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct RawJson {
+    text: Box<str>,
+}
+
+impl RawJson {
+    /// Validates `text` as a single JSON value. Surrounding whitespace is
+    /// dropped.
+    pub fn new(text: &str) -> Result<Self, ParseError> {
+        validate_with_options(text, ParserOptions::default())?;
+        Ok(RawJson {
+            text: text
+                .trim_matches(|c| matches!(c, ' ' | '\t' | '\n' | '\r'))
+                .into(),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Parses the fragment into a tree.
+    pub fn parse(&self) -> JsonValue {
+        parse_with_options(&self.text, ParserOptions::default()).expect("RawJson holds valid JSON")
+    }
+
+    pub fn into_string(self) -> String {
+        self.text.into_string()
+    }
+}
+
+impl fmt::Display for RawJson {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
@@ -0,0 +1,485 @@
+use super::{
+    DEFAULT_MAX_DEPTH, DuplicateKeyPolicy, JsonValue, Map, ParseError, ParseMetrics, Parser,
+    ParserOptions, Position, expand_nested_json, is_json_whitespace,
+};
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{self, Read};
+use std::time::Instant;
+
+const CHUNK_SIZE: usize = 8192;
+
+/// Input that could not be read, or whose text is not valid JSON.
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Io(e) => write!(f, "{}", e),
+            ReadError::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadError::Io(e) => Some(e),
+            ReadError::Parse(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(e: io::Error) -> Self {
+        ReadError::Io(e)
+    }
+}
+
+impl From<ParseError> for ReadError {
+    fn from(e: ParseError) -> Self {
+        ReadError::Parse(e)
+    }
+}
+
+/// Parses one document of UTF-8 text from `reader` with
+/// `ParserOptions::current()`, pulling it in chunks as parsing proceeds
+/// rather than reading it all first. Wrap the reader in a `DecodeReader` to
+/// parse other encodings, or use `parse_decoded`. Error positions count
+/// from the start of the stream; invalid UTF-8 is an `Io` error of kind
+/// `InvalidData`.
+pub fn parse_reader<R: Read>(reader: R) -> Result<JsonValue, ReadError> {
+    let options = ParserOptions::current();
+    let mut stream = StreamParser {
+        source: Source {
+            reader,
+            raw: Vec::new(),
+            text: String::new(),
+            pos: 0,
+            position: Position {
+                offset: 0,
+                line: 1,
+                column: 1,
+            },
+            eof: false,
+        },
+        scalars: Parser::with_options("", options.clone()),
+        token: String::new(),
+        options,
+        depth: 0,
+        peak_depth: 0,
+        containers: 0,
+    };
+    let Some(hook) = stream.options.metrics_hook.clone() else {
+        return stream.document();
+    };
+
+    let started = Instant::now();
+    let result = stream.document();
+    hook.call(&ParseMetrics {
+        duration: started.elapsed(),
+        bytes: stream.source.position.offset,
+        allocations: stream.containers + stream.scalars.allocations,
+        peak_depth: stream.peak_depth,
+        success: result.is_ok(),
+    });
+    result
+}
+
+// Builds the tree itself, handing the text of each scalar and key to a
+// `Parser` so they follow the same options as with `parse`.
+struct StreamParser<R> {
+    source: Source<R>,
+    scalars: Parser<'static>,
+    token: String,
+    options: ParserOptions,
+    depth: usize,
+    peak_depth: usize,
+    containers: usize,
+}
+
+impl<R: Read> StreamParser<R> {
+    fn document(&mut self) -> Result<JsonValue, ReadError> {
+        self.skip_whitespace()?;
+        let mut value = self.value()?;
+        self.skip_whitespace()?;
+        if self.source.peek()?.is_some() {
+            return Err(self.unexpected());
+        }
+        if self.options.expand_nested_json {
+            expand_nested_json(&mut value, &[]);
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<JsonValue, ReadError> {
+        match self.source.peek()? {
+            Some('[' | '{')
+                if self
+                    .options
+                    .materialize_depth
+                    .is_some_and(|max| self.depth >= max) =>
+            {
+                self.containers += 1;
+                self.verbatim()
+            }
+            Some(c @ ('[' | '{')) => {
+                let limit = self.options.max_depth.unwrap_or(DEFAULT_MAX_DEPTH);
+                if self.depth >= limit {
+                    return Err(ParseError::TooDeep(limit, self.source.position).into());
+                }
+                self.depth += 1;
+                self.peak_depth = self.peak_depth.max(self.depth);
+                self.containers += 1;
+                let result = if c == '[' {
+                    self.array()
+                } else {
+                    self.object()
+                };
+                self.depth -= 1;
+                result
+            }
+            _ => {
+                let start = self.read_token()?;
+                self.parse_token(start, Parser::parse_value)
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<JsonValue, ReadError> {
+        self.source.next()?; // Skip '['
+        self.skip_whitespace()?;
+        let mut elements = Vec::new();
+        if self.source.peek()? == Some(']') {
+            self.source.next()?;
+            return Ok(JsonValue::Array(elements));
+        }
+
+        loop {
+            elements.push(self.value()?);
+            self.skip_whitespace()?;
+            match self.source.peek()? {
+                Some(',') => {
+                    self.source.next()?;
+                    self.skip_whitespace()?;
+                    if self.options.allow_trailing_commas && self.source.peek()? == Some(']') {
+                        self.source.next()?;
+                        return Ok(JsonValue::Array(elements));
+                    }
+                }
+                Some(']') => {
+                    self.source.next()?;
+                    return Ok(JsonValue::Array(elements));
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<JsonValue, ReadError> {
+        self.source.next()?; // Skip '{'
+        self.skip_whitespace()?;
+        let mut map = Map::new();
+        if self.source.peek()? == Some('}') {
+            self.source.next()?;
+            return Ok(JsonValue::Object(Box::new(map)));
+        }
+
+        loop {
+            let key_start = self.read_token()?;
+            let key = self.parse_token(key_start, Parser::parse_key)?;
+            self.skip_whitespace()?;
+            if self.source.peek()? != Some(':') {
+                return Err(self.unexpected());
+            }
+            self.source.next()?;
+            self.skip_whitespace()?;
+            let value = self.value()?;
+            if self.options.key_filter.allows(&key) {
+                match self.options.duplicate_keys {
+                    DuplicateKeyPolicy::LastWins => {
+                        map.insert(key, value);
+                    }
+                    DuplicateKeyPolicy::FirstWins => {
+                        map.entry(key).or_insert(value);
+                    }
+                    DuplicateKeyPolicy::Error if map.contains_key(&key) => {
+                        return Err(ParseError::DuplicateKey(key, key_start).into());
+                    }
+                    DuplicateKeyPolicy::Error => {
+                        map.insert(key, value);
+                    }
+                }
+            }
+
+            self.skip_whitespace()?;
+            match self.source.peek()? {
+                Some(',') => {
+                    self.source.next()?;
+                    self.skip_whitespace()?;
+                    if self.options.allow_trailing_commas && self.source.peek()? == Some('}') {
+                        self.source.next()?;
+                        return Ok(JsonValue::Object(Box::new(map)));
+                    }
+                }
+                Some('}') => {
+                    self.source.next()?;
+                    return Ok(JsonValue::Object(Box::new(map)));
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    // Copies a container below `materialize_depth` into a string without
+    // checking it, as `Parser` does.
+    fn verbatim(&mut self) -> Result<JsonValue, ReadError> {
+        let mut text = String::new();
+        let mut depth = 0usize;
+        let mut quote = None;
+        let mut escaped = false;
+        loop {
+            let Some(c) = self.source.next()? else {
+                return Err(self.unexpected());
+            };
+            text.push(c);
+            match quote {
+                Some(_) if escaped => escaped = false,
+                Some(_) if c == '\\' => escaped = true,
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None => match c {
+                    '"' => quote = Some(c),
+                    '\'' if self.options.allow_single_quotes => quote = Some(c),
+                    '[' | '{' => depth += 1,
+                    ']' | '}' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Ok(JsonValue::String(text.into()));
+                        }
+                    }
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    // Reads the text of one scalar or key into `token` and returns where it
+    // starts: a quoted string through its closing quote, or anything else
+    // up to the next delimiter.
+    fn read_token(&mut self) -> Result<Position, ReadError> {
+        let start = self.source.position;
+        self.token.clear();
+        match self.source.peek()? {
+            Some(quote @ ('"' | '\'')) => {
+                self.source.next()?;
+                self.token.push(quote);
+                while let Some(c) = self.source.next()? {
+                    self.token.push(c);
+                    if c == quote {
+                        break;
+                    }
+                    if c == '\\'
+                        && let Some(escaped) = self.source.next()?
+                    {
+                        self.token.push(escaped);
+                    }
+                }
+            }
+            _ => {
+                while let Some(c) = self.source.peek()? {
+                    if self.ends_token(c) {
+                        break;
+                    }
+                    self.token.push(c);
+                    self.source.next()?;
+                }
+                if self.token.is_empty() {
+                    return Err(self.unexpected());
+                }
+            }
+        }
+        Ok(start)
+    }
+
+    fn ends_token(&self, c: char) -> bool {
+        matches!(c, ',' | ':' | '[' | ']' | '{' | '}' | '"' | '\'')
+            || self.is_whitespace(c)
+            || (c == '/' && self.options.allow_comments)
+    }
+
+    // Parses `token` with `parse`, which must consume all of it.
+    fn parse_token<T>(
+        &mut self,
+        start: Position,
+        parse: fn(&mut Parser<'static>) -> Result<T, ParseError>,
+    ) -> Result<T, ReadError> {
+        let parser = &mut self.scalars;
+        parser.input = Cow::Owned(std::mem::take(&mut self.token));
+        parser.pos = 0;
+        let result = parse(parser).and_then(|value| {
+            if parser.pos < parser.input.len() {
+                Err(parser.unexpected())
+            } else {
+                Ok(value)
+            }
+        });
+        if let Cow::Owned(token) = std::mem::take(&mut parser.input) {
+            self.token = token;
+        }
+        result.map_err(|mut e| {
+            relocate(e.position_mut(), start);
+            e.into()
+        })
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), ReadError> {
+        while let Some(c) = self.source.peek()? {
+            if self.is_whitespace(c) {
+                self.source.next()?;
+            } else if c == '/' && self.options.allow_comments {
+                match self.source.peek_second()? {
+                    Some('/') => while !matches!(self.source.next()?, Some('\n') | None) {},
+                    Some('*') => {
+                        self.source.next()?;
+                        self.source.next()?;
+                        let mut star = false;
+                        loop {
+                            match self.source.next()? {
+                                Some('/') if star => break,
+                                Some(c) => star = c == '*',
+                                None => return Err(self.unexpected()),
+                            }
+                        }
+                    }
+                    _ => break,
+                }
+            } else {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn is_whitespace(&self, c: char) -> bool {
+        is_json_whitespace(c) || (self.options.allow_unicode_whitespace && c.is_whitespace())
+    }
+
+    // Error for whatever is next in the source, which the caller could not
+    // use.
+    fn unexpected(&mut self) -> ReadError {
+        let position = self.source.position;
+        match self.source.peek() {
+            Ok(Some(c)) => ParseError::UnexpectedChar(c, position).into(),
+            Ok(None) => ParseError::UnexpectedEndOfInput(position).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+// Moves a position within a token to where the token starts in the stream.
+fn relocate(position: &mut Position, start: Position) {
+    if position.line == 1 {
+        position.column += start.column - 1;
+    }
+    position.line += start.line - 1;
+    position.offset += start.offset;
+}
+
+// The UTF-8 text of a reader, decoded a chunk at a time.
+struct Source<R> {
+    reader: R,
+    // Bytes read but not yet decoded: a character cut off by a chunk end.
+    raw: Vec<u8>,
+    text: String,
+    pos: usize,
+    // Where `text[pos]` falls in the stream.
+    position: Position,
+    eof: bool,
+}
+
+impl<R: Read> Source<R> {
+    fn peek(&mut self) -> io::Result<Option<char>> {
+        self.peek_nth(0)
+    }
+
+    fn peek_second(&mut self) -> io::Result<Option<char>> {
+        self.peek_nth(1)
+    }
+
+    fn peek_nth(&mut self, n: usize) -> io::Result<Option<char>> {
+        loop {
+            if let Some(c) = self.text[self.pos..].chars().nth(n) {
+                return Ok(Some(c));
+            }
+            if !self.fill()? {
+                return Ok(None);
+            }
+        }
+    }
+
+    fn next(&mut self) -> io::Result<Option<char>> {
+        let next = self.peek()?;
+        if let Some(c) = next {
+            self.pos += c.len_utf8();
+            self.position.offset += c.len_utf8();
+            if c == '\n' {
+                self.position.line += 1;
+                self.position.column = 1;
+            } else {
+                self.position.column += 1;
+            }
+        }
+        Ok(next)
+    }
+
+    // Reads and decodes more input after the unread text; returns false
+    // once the input is exhausted.
+    fn fill(&mut self) -> io::Result<bool> {
+        self.text.drain(..self.pos);
+        self.pos = 0;
+        loop {
+            if self.eof {
+                if self.raw.is_empty() {
+                    return Ok(false);
+                }
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "stream ends inside a UTF-8 sequence",
+                ));
+            }
+
+            let start = self.raw.len();
+            self.raw.resize(start + CHUNK_SIZE, 0);
+            let n = match self.reader.read(&mut self.raw[start..]) {
+                Ok(n) => n,
+                Err(e) => {
+                    self.raw.truncate(start);
+                    if e.kind() == io::ErrorKind::Interrupted {
+                        continue;
+                    }
+                    return Err(e);
+                }
+            };
+            self.raw.truncate(start + n);
+            self.eof = n == 0;
+
+            let valid = match std::str::from_utf8(&self.raw) {
+                Ok(text) => text.len(),
+                // A sequence cut off at the end of the chunk
+                Err(e) if e.error_len().is_none() => e.valid_up_to(),
+                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+            };
+            if valid > 0 {
+                self.text
+                    .push_str(std::str::from_utf8(&self.raw[..valid]).unwrap());
+                self.raw.drain(..valid);
+                return Ok(true);
+            }
+        }
+    }
+}
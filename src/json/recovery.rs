@@ -0,0 +1,229 @@
+use super::{JsonValue, Map, ParseError, Parser, ParserOptions, scan_string};
+
+/// Result of `parse_with_recovery`: the best-effort value plus every problem
+/// found, in input order. Unparseable values become `Null`, and members or
+/// elements that cannot be recovered are dropped.
+#[derive(Debug, PartialEq)]
+pub struct Recovered {
+    pub value: JsonValue,
+    pub diagnostics: Vec<ParseError>,
+}
+
+impl Recovered {
+    pub fn is_clean(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+}
+
+/// Parses `input` without stopping at the first error. After a problem the
+/// parser skips ahead to the next `,`, `]` or `}` at the same nesting level
+/// and carries on, collecting a diagnostic for each problem.
+pub fn parse_with_recovery(input: &str) -> Recovered {
+    parse_with_recovery_options(input, ParserOptions::current())
+}
+
+pub fn parse_with_recovery_options(input: &str, options: ParserOptions) -> Recovered {
+    let mut recovery = Recovery {
+        parser: Parser::with_options(input, options),
+        diagnostics: Vec::new(),
+    };
+    let value = recovery.document();
+    Recovered {
+        value,
+        diagnostics: recovery.diagnostics,
+    }
+}
+
+struct Recovery<'a> {
+    parser: Parser<'a>,
+    diagnostics: Vec<ParseError>,
+}
+
+impl Recovery<'_> {
+    fn document(&mut self) -> JsonValue {
+        self.parser.skip_whitespace();
+        let value = self.value();
+        self.parser.skip_whitespace();
+        if self.parser.current_char().is_some() {
+            self.diagnostics.push(self.parser.unexpected());
+        }
+        value
+    }
+
+    fn value(&mut self) -> JsonValue {
+        self.parser.skip_whitespace();
+        match self.parser.current_char() {
+            Some('[') => self.nested(Self::array),
+            Some('{') => self.nested(Self::object),
+            _ => match self.parser.parse_value() {
+                Ok(value) => value,
+                Err(e) => {
+                    self.diagnostics.push(e);
+                    self.synchronize();
+                    JsonValue::Null
+                }
+            },
+        }
+    }
+
+    // Parses a container unless it is nested too deep, in which case the
+    // whole container is skipped and becomes `Null`.
+    fn nested(&mut self, parse: fn(&mut Self) -> JsonValue) -> JsonValue {
+        if let Err(e) = self.parser.enter() {
+            self.diagnostics.push(e);
+            self.synchronize();
+            return JsonValue::Null;
+        }
+        let value = parse(self);
+        self.parser.depth -= 1;
+        value
+    }
+
+    fn array(&mut self) -> JsonValue {
+        self.parser.advance(); // Skip '['
+        let mut elements = Vec::new();
+
+        loop {
+            self.parser.skip_whitespace();
+            match self.parser.current_char() {
+                Some(']') => {
+                    self.parser.advance();
+                    break;
+                }
+                Some(',') | Some('}') | None => {
+                    self.diagnostics.push(self.parser.unexpected());
+                    if self.parser.current_char() != Some(',') {
+                        break;
+                    }
+                    self.parser.advance();
+                    continue;
+                }
+                Some(_) => elements.push(self.value()),
+            }
+
+            if !self.separator(']') {
+                break;
+            }
+        }
+
+        JsonValue::Array(elements)
+    }
+
+    fn object(&mut self) -> JsonValue {
+        self.parser.advance(); // Skip '{'
+        let mut map = Map::new();
+
+        loop {
+            self.parser.skip_whitespace();
+            match self.parser.current_char() {
+                Some('}') => {
+                    self.parser.advance();
+                    break;
+                }
+                Some(',') | Some(']') | None => {
+                    self.diagnostics.push(self.parser.unexpected());
+                    if self.parser.current_char() != Some(',') {
+                        break;
+                    }
+                    self.parser.advance();
+                    continue;
+                }
+                Some(_) => {}
+            }
+
+            let key_pos = self.parser.pos;
+            let key = match self.parser.parse_key() {
+                Ok(key) => key,
+                Err(e) => {
+                    self.diagnostics.push(e);
+                    self.synchronize();
+                    if !self.separator('}') {
+                        break;
+                    }
+                    continue;
+                }
+            };
+
+            self.parser.skip_whitespace();
+            if self.parser.current_char() == Some(':') {
+                self.parser.advance();
+            } else {
+                // Assume the colon is missing and try the value anyway.
+                self.diagnostics.push(self.parser.unexpected());
+            }
+
+            self.parser.skip_whitespace();
+            if matches!(self.parser.current_char(), Some(',' | '}') | None) {
+                self.diagnostics.push(self.parser.unexpected());
+            } else {
+                let value = self.value();
+                if let Err(e) = self.parser.insert_member(&mut map, key, key_pos, value) {
+                    self.diagnostics.push(e);
+                }
+            }
+
+            if !self.separator('}') {
+                break;
+            }
+        }
+
+        JsonValue::Object(Box::new(map))
+    }
+
+    // Consumes the separator after an element. Returns false once the
+    // container has been closed or cannot continue.
+    fn separator(&mut self, close: char) -> bool {
+        self.parser.skip_whitespace();
+        match self.parser.current_char() {
+            Some(',') => {
+                self.parser.advance();
+                self.parser.skip_whitespace();
+                if self.parser.current_char() == Some(close) {
+                    if !self.parser.options.allow_trailing_commas {
+                        self.diagnostics.push(self.parser.unexpected());
+                    }
+                    self.parser.advance();
+                    return false;
+                }
+                true
+            }
+            Some(c) if c == close => {
+                self.parser.advance();
+                false
+            }
+            // A bracket of the other kind: report it and let the enclosing
+            // container deal with it.
+            Some(']' | '}') | None => {
+                self.diagnostics.push(self.parser.unexpected());
+                false
+            }
+            Some(_) => {
+                // Missing comma; carry on with the next element.
+                self.diagnostics.push(self.parser.unexpected());
+                true
+            }
+        }
+    }
+
+    // Skips to the next `,`, `]` or `}` at the current nesting level.
+    fn synchronize(&mut self) {
+        let bytes = self.parser.input.as_bytes();
+        let mut depth = 0usize;
+        let mut pos = self.parser.pos;
+        while let Some(&b) = bytes.get(pos) {
+            match b {
+                b'"' => {
+                    pos = scan_string(bytes, pos, b'"').unwrap_or(bytes.len());
+                    continue;
+                }
+                b'[' | b'{' => depth += 1,
+                b']' | b'}' if depth == 0 => break,
+                b']' | b'}' => depth -= 1,
+                b',' if depth == 0 => break,
+                _ => {}
+            }
+            pos += 1;
+        }
+        self.parser.pos = pos;
+    }
+}
@@ -0,0 +1,86 @@
+use super::JsonValue;
+use super::pointer::unescape_token;
+
+/// Replaces sensitive values with `replacement` before a document is
+/// logged, returning how many were replaced. A pattern starting with `/` is
+/// a JSON Pointer in which a `*` token matches every member or element,
+/// e.g. `/users/*/email`. Any other pattern is matched against member keys
+/// at every depth, ignoring case, with `*` matching any run of characters,
+/// e.g. `password` or `*token*`. Nothing inside a replaced value is
+/// visited.
+pub fn redact(value: &mut JsonValue, patterns: &[&str], replacement: &JsonValue) -> usize {
+    let patterns: Vec<Pattern> = patterns
+        .iter()
+        .map(|pattern| match pattern.strip_prefix('/') {
+            Some(rest) => Pattern::Pointer(rest.split('/').map(unescape_token).collect()),
+            None => Pattern::Key(pattern.to_lowercase()),
+        })
+        .collect();
+    let mut path = Vec::new();
+    redact_within(value, &patterns, replacement, &mut path)
+}
+
+enum Pattern {
+    Pointer(Vec<String>),
+    Key(String),
+}
+
+impl Pattern {
+    // Whether the value at `path` matches; the root never does.
+    fn matches(&self, path: &[String]) -> bool {
+        match self {
+            Pattern::Pointer(tokens) => {
+                tokens.len() == path.len()
+                    && tokens.iter().zip(path).all(|(t, p)| t == "*" || t == p)
+            }
+            Pattern::Key(glob) => path
+                .last()
+                .is_some_and(|key| glob_matches(glob.as_bytes(), key.to_lowercase().as_bytes())),
+        }
+    }
+}
+
+fn redact_within(
+    value: &mut JsonValue,
+    patterns: &[Pattern],
+    replacement: &JsonValue,
+    path: &mut Vec<String>,
+) -> usize {
+    let mut visit = |key: String, child: &mut JsonValue, is_member: bool| {
+        path.push(key);
+        // Key patterns apply to member keys only, not array indices.
+        let matched = patterns.iter().any(|pattern| match pattern {
+            Pattern::Key(_) if !is_member => false,
+            _ => pattern.matches(path),
+        });
+        let count = if matched {
+            *child = replacement.clone();
+            1
+        } else {
+            redact_within(child, patterns, replacement, path)
+        };
+        path.pop();
+        count
+    };
+    match value {
+        JsonValue::Object(members) => members
+            .iter_mut()
+            .map(|(key, child)| visit(key.clone(), child, true))
+            .sum(),
+        JsonValue::Array(items) => items
+            .iter_mut()
+            .enumerate()
+            .map(|(i, child)| visit(i.to_string(), child, false))
+            .sum(),
+        _ => 0,
+    }
+}
+
+// Matches `text` against `glob`, where `*` matches any run of bytes.
+fn glob_matches(glob: &[u8], text: &[u8]) -> bool {
+    match glob.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => (0..=text.len()).any(|i| glob_matches(rest, &text[i..])),
+        Some((c, rest)) => text.first() == Some(c) && glob_matches(rest, &text[1..]),
+    }
+}
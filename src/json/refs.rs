@@ -0,0 +1,166 @@
+use super::JsonValue;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum RefError {
+    /// No node matches the reference.
+    Unresolved(String),
+    /// Following the reference leads back to itself.
+    Cycle(String),
+}
+
+impl fmt::Display for RefError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RefError::Unresolved(reference) => write!(f, "unresolved reference \"{}\"", reference),
+            RefError::Cycle(reference) => write!(f, "reference cycle through \"{}\"", reference),
+        }
+    }
+}
+
+impl std::error::Error for RefError {}
+
+/// Follows intra-document references such as `{"$ref": "#/definitions/a"}`.
+///
+/// A reference is either a JSON Pointer fragment (`#/path/to/node`, or `#`
+/// for the root) or the value of an identifier member elsewhere in the
+/// document (`{"$id": "node"}`, referenced as `node` or `#node`). An object
+/// carrying a reference stands for its target; its other members are
+/// ignored.
+///
+/// Nodes can be resolved lazily with `resolve`, `get` and `pointer`, or the
+/// whole document expanded with `dereference`.
+pub struct RefResolver<'a> {
+    root: &'a JsonValue,
+    ref_key: String,
+    ids: HashMap<String, &'a JsonValue>,
+}
+
+impl<'a> RefResolver<'a> {
+    /// A resolver using the `$ref` and `$id` member names.
+    pub fn new(root: &'a JsonValue) -> Self {
+        RefResolver::with_keys(root, "$ref", "$id")
+    }
+
+    pub fn with_keys(root: &'a JsonValue, ref_key: &str, id_key: &str) -> Self {
+        let mut ids = HashMap::new();
+        let mut pending = vec![root];
+        while let Some(node) = pending.pop() {
+            match node {
+                JsonValue::Object(obj) => {
+                    if let Some(JsonValue::String(id)) = obj.get(id_key) {
+                        ids.insert(id.trim_start_matches('#').to_string(), node);
+                    }
+                    pending.extend(obj.values());
+                }
+                JsonValue::Array(arr) => pending.extend(arr),
+                _ => {}
+            }
+        }
+        RefResolver {
+            root,
+            ref_key: ref_key.to_string(),
+            ids,
+        }
+    }
+
+    /// Looks up the node a reference string points to, following further
+    /// references from there.
+    pub fn lookup(&self, reference: &str) -> Result<&'a JsonValue, RefError> {
+        let target = self.target(reference)?;
+        self.resolve(target)
+    }
+
+    /// Returns `node` itself, or the final target if it is a reference.
+    pub fn resolve(&self, node: &'a JsonValue) -> Result<&'a JsonValue, RefError> {
+        let mut node = node;
+        let mut seen = Vec::new();
+        while let Some(reference) = self.reference(node) {
+            if seen.contains(&reference) {
+                return Err(RefError::Cycle(reference.to_string()));
+            }
+            seen.push(reference);
+            node = self.target(reference)?;
+        }
+        Ok(node)
+    }
+
+    /// Resolves `node`, then returns its member `key`, also resolved.
+    pub fn get(&self, node: &'a JsonValue, key: &str) -> Result<Option<&'a JsonValue>, RefError> {
+        match self.resolve(node)?.get(key) {
+            Some(child) => self.resolve(child).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Like `JsonValue::pointer`, but resolves references at every step.
+    pub fn pointer(&self, pointer: &str) -> Result<Option<&'a JsonValue>, RefError> {
+        let mut node = self.resolve(self.root)?;
+        if pointer.is_empty() {
+            return Ok(Some(node));
+        }
+        let Some(rest) = pointer.strip_prefix('/') else {
+            return Ok(None);
+        };
+        for token in rest.split('/') {
+            let Some(child) = JsonValue::pointer(node, &format!("/{}", token)) else {
+                return Ok(None);
+            };
+            node = self.resolve(child)?;
+        }
+        Ok(Some(node))
+    }
+
+    /// Returns a copy of the document with every reference replaced by a
+    /// copy of its target. Fails on unresolved references and on cycles,
+    /// which cannot be expanded into a finite tree.
+    pub fn dereference(&self) -> Result<JsonValue, RefError> {
+        self.expand(self.root, &mut Vec::new())
+    }
+
+    fn expand(
+        &self,
+        node: &'a JsonValue,
+        active: &mut Vec<&'a str>,
+    ) -> Result<JsonValue, RefError> {
+        if let Some(reference) = self.reference(node) {
+            if active.contains(&reference) {
+                return Err(RefError::Cycle(reference.to_string()));
+            }
+            active.push(reference);
+            let expanded = self.expand(self.target(reference)?, active);
+            active.pop();
+            return expanded;
+        }
+        match node {
+            JsonValue::Array(arr) => arr
+                .iter()
+                .map(|item| self.expand(item, active))
+                .collect::<Result<_, _>>()
+                .map(JsonValue::Array),
+            JsonValue::Object(obj) => obj
+                .iter()
+                .map(|(key, item)| Ok((key.clone(), self.expand(item, active)?)))
+                .collect::<Result<_, _>>()
+                .map(|members| JsonValue::Object(Box::new(members))),
+            _ => Ok(node.clone()),
+        }
+    }
+
+    fn reference(&self, node: &'a JsonValue) -> Option<&'a str> {
+        node.get(&self.ref_key).and_then(JsonValue::as_str)
+    }
+
+    // The node a reference names, without following it any further.
+    fn target(&self, reference: &str) -> Result<&'a JsonValue, RefError> {
+        let unresolved = || RefError::Unresolved(reference.to_string());
+        match reference.strip_prefix('#') {
+            Some(pointer) if pointer.is_empty() || pointer.starts_with('/') => {
+                self.root.pointer(pointer).ok_or_else(unresolved)
+            }
+            Some(id) => self.ids.get(id).copied().ok_or_else(unresolved),
+            None => self.ids.get(reference).copied().ok_or_else(unresolved),
+        }
+    }
+}
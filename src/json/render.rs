@@ -0,0 +1,127 @@
+use super::ParseError;
+
+impl ParseError {
+    /// Formats the error like a compiler diagnostic: the message, the
+    /// offending line of `input` with a caret under the error position, and a
+    /// hint about what was expected there. `input` must be the text that
+    /// produced the error.
+    pub fn render(&self, input: &str) -> String {
+        let pos = self.position();
+        let line_start = input[..pos.offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[pos.offset..]
+            .find('\n')
+            .map_or(input.len(), |i| pos.offset + i);
+        let line = input[line_start..line_end].trim_end_matches('\r');
+
+        let number = pos.line.to_string();
+        let gutter = " ".repeat(number.len());
+        let mut out = format!("error: {}\n", self);
+        out.push_str(&format!("{} |\n", gutter));
+        out.push_str(&format!("{} | {}\n", number, line));
+        out.push_str(&format!("{} | {}^\n", gutter, " ".repeat(pos.column - 1)));
+        out.push_str(&format!("{} = help: {}\n", gutter, self.hint(input)));
+        out
+    }
+
+    fn hint(&self, input: &str) -> String {
+        let offset = self.position().offset;
+        let previous = input[..offset].trim_end().chars().last();
+        match self {
+            ParseError::UnexpectedChar(c, _) => match (previous, c) {
+                (Some(','), ']' | '}') => "remove the trailing comma".to_string(),
+                (Some('{'), _) | (Some(','), _) if in_object(input, offset) => {
+                    "expected a double-quoted key".to_string()
+                }
+                (None | Some('{' | ',' | '[' | ':'), _) => "expected a value".to_string(),
+                (Some('"'), _) if in_object(input, offset) && !after_colon(input, offset) => {
+                    "expected ':' after the key".to_string()
+                }
+                (Some(_), _) if in_object(input, offset) => "expected ',' or '}'".to_string(),
+                (Some(_), _) if in_array(input, offset) => "expected ',' or ']'".to_string(),
+                (Some(_), _) => "expected end of input after the value".to_string(),
+            },
+            ParseError::UnexpectedEndOfInput(_) => {
+                "expected more input; check for an unclosed string, array or object".to_string()
+            }
+            ParseError::InvalidNumber(_, _) => {
+                "numbers need digits on both sides of '.', an exponent with digits, \
+                 and no leading zeros"
+                    .to_string()
+            }
+            ParseError::InvalidEscape(_, _) => {
+                "valid escapes are \\\", \\\\, \\/, \\b, \\f, \\n, \\r, \\t and \\uXXXX".to_string()
+            }
+            ParseError::InvalidUnicodeEscape(_, _) => {
+                "expected four hex digits; characters outside the BMP need a surrogate pair"
+                    .to_string()
+            }
+            ParseError::UnescapedControlChar(_, _) => {
+                "control characters in strings must be escaped, e.g. \\n or \\u0000".to_string()
+            }
+            ParseError::DuplicateKey(key, _) => format!("\"{}\" was already defined", key),
+            ParseError::TooDeep(_, _) => {
+                "raise ParserOptions::max_depth if such deep nesting is expected".to_string()
+            }
+        }
+    }
+}
+
+// Finds the innermost bracket still open at `offset`, skipping strings.
+fn enclosing(input: &str, offset: usize) -> Option<u8> {
+    let mut stack = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for &b in &input.as_bytes()[..offset] {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'[' | b'{' => stack.push(b),
+            b']' | b'}' => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+    stack.pop()
+}
+
+fn in_object(input: &str, offset: usize) -> bool {
+    enclosing(input, offset) == Some(b'{')
+}
+
+fn in_array(input: &str, offset: usize) -> bool {
+    enclosing(input, offset) == Some(b'[')
+}
+
+// Whether the current object member already has its ':' separator.
+fn after_colon(input: &str, offset: usize) -> bool {
+    let before = &input.as_bytes()[..offset];
+    let mut in_string = false;
+    let mut seen_colon = false;
+    let mut depth = 0usize;
+    for &b in before.iter().rev() {
+        if in_string {
+            if b == b'"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b':' if depth == 0 => seen_colon = true,
+            b']' | b'}' => depth += 1,
+            b'[' | b'{' if depth > 0 => depth -= 1,
+            b',' | b'{' if depth == 0 => return seen_colon,
+            _ => {}
+        }
+    }
+    seen_colon
+}
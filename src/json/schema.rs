@@ -0,0 +1,185 @@
+use super::JsonValue;
+use super::pointer::escape_pointer_token;
+use std::fmt;
+
+/// A value that fails a schema constraint, located by JSON Pointer.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SchemaViolation {
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pointer = if self.pointer.is_empty() {
+            "(root)"
+        } else {
+            &self.pointer
+        };
+        write!(f, "{}: {}", pointer, self.message)
+    }
+}
+
+/// Validates `value` against a JSON Schema, returning every violation.
+///
+/// Supports the structural subset used for configuration documents:
+/// `type`, `enum`, `const`, `properties`, `required`,
+/// `additionalProperties`, `items`, `minimum`, `maximum`, `minLength`,
+/// `maxLength`, `minItems` and `maxItems`. Other keywords are ignored.
+pub fn schema_violations(value: &JsonValue, schema: &JsonValue) -> Vec<SchemaViolation> {
+    let mut violations = Vec::new();
+    check(value, schema, &mut String::new(), &mut violations);
+    violations
+}
+
+fn check(
+    value: &JsonValue,
+    schema: &JsonValue,
+    path: &mut String,
+    violations: &mut Vec<SchemaViolation>,
+) {
+    if let JsonValue::Raw(raw) = value {
+        return check(&raw.parse(), schema, path, violations);
+    }
+
+    let mut fail = |message: String| {
+        violations.push(SchemaViolation {
+            pointer: path.clone(),
+            message,
+        })
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            JsonValue::String(name) => vec![name.as_str()],
+            JsonValue::Array(names) => names.iter().filter_map(JsonValue::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|name| has_type(value, name)) {
+            fail(format!(
+                "expected {}, found {}",
+                allowed.join(" or "),
+                type_name(value)
+            ));
+        }
+    }
+    if let Some(JsonValue::Array(options)) = schema.get("enum")
+        && !options.contains(value)
+    {
+        fail("value is not one of the allowed values".to_string());
+    }
+    if let Some(constant) = schema.get("const")
+        && constant != value
+    {
+        fail("value does not match the required constant".to_string());
+    }
+
+    match value {
+        JsonValue::Number(_) | JsonValue::RawNumber(_) => {
+            let n = value.as_f64().unwrap_or(f64::NAN);
+            if let Some(min) = schema.get("minimum").and_then(JsonValue::as_f64)
+                && n < min
+            {
+                fail(format!("{} is less than the minimum of {}", n, min));
+            }
+            if let Some(max) = schema.get("maximum").and_then(JsonValue::as_f64)
+                && n > max
+            {
+                fail(format!("{} is greater than the maximum of {}", n, max));
+            }
+        }
+        JsonValue::String(s) => {
+            let len = s.chars().count();
+            if let Some(min) = schema.get("minLength").and_then(JsonValue::as_f64)
+                && (len as f64) < min
+            {
+                fail(format!("string is shorter than {} characters", min));
+            }
+            if let Some(max) = schema.get("maxLength").and_then(JsonValue::as_f64)
+                && (len as f64) > max
+            {
+                fail(format!("string is longer than {} characters", max));
+            }
+        }
+        JsonValue::Array(arr) => {
+            if let Some(min) = schema.get("minItems").and_then(JsonValue::as_f64)
+                && (arr.len() as f64) < min
+            {
+                fail(format!("array has fewer than {} items", min));
+            }
+            if let Some(max) = schema.get("maxItems").and_then(JsonValue::as_f64)
+                && (arr.len() as f64) > max
+            {
+                fail(format!("array has more than {} items", max));
+            }
+            if let Some(items) = schema.get("items") {
+                for (i, item) in arr.iter().enumerate() {
+                    let len = path.len();
+                    path.push_str(&format!("/{}", i));
+                    check(item, items, path, violations);
+                    path.truncate(len);
+                }
+            }
+        }
+        JsonValue::Object(obj) => {
+            if let Some(JsonValue::Array(required)) = schema.get("required") {
+                let mut missing: Vec<&str> = required
+                    .iter()
+                    .filter_map(JsonValue::as_str)
+                    .filter(|key| !obj.contains_key(*key))
+                    .collect();
+                missing.sort();
+                for key in missing {
+                    fail(format!("missing required property \"{}\"", key));
+                }
+            }
+
+            let properties = schema.get("properties").and_then(JsonValue::as_object);
+            let additional = schema.get("additionalProperties");
+            let mut keys: Vec<&String> = obj.keys().collect();
+            keys.sort();
+            for key in keys {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&escape_pointer_token(key));
+                match properties.and_then(|p| p.get(key)).or(additional) {
+                    Some(JsonValue::Bool(false)) => violations.push(SchemaViolation {
+                        pointer: path.clone(),
+                        message: "property is not allowed".to_string(),
+                    }),
+                    Some(property) => check(&obj[key], property, path, violations),
+                    None => {}
+                }
+                path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn has_type(value: &JsonValue, name: &str) -> bool {
+    match (name, value) {
+        ("null", JsonValue::Null)
+        | ("boolean", JsonValue::Bool(_))
+        | ("number", JsonValue::Number(_) | JsonValue::RawNumber(_))
+        | ("string", JsonValue::String(_))
+        | ("array", JsonValue::Array(_))
+        | ("object", JsonValue::Object(_)) => true,
+        ("integer", JsonValue::Number(_) | JsonValue::RawNumber(_)) => {
+            value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => false,
+    }
+}
+
+fn type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "boolean",
+        JsonValue::Number(_) | JsonValue::RawNumber(_) => "number",
+        JsonValue::String(_) | JsonValue::Bytes(_) => "string",
+        JsonValue::Array(_) => "array",
+        JsonValue::Object(_) => "object",
+        JsonValue::Raw(raw) => type_name(&raw.parse()),
+    }
+}
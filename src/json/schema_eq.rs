@@ -0,0 +1,97 @@
+use super::JsonValue;
+use super::pointer::escape_pointer_token;
+
+impl JsonValue {
+    /// Compares two values with semantics driven by a JSON Schema:
+    ///
+    /// - object members not listed in `properties` are ignored whenever the
+    ///   schema declares `properties`;
+    /// - numbers under `multipleOf: m` are equal when they round to the same
+    ///   multiple of `m`;
+    /// - a member missing on one side equals the other side's value when that
+    ///   value is the property's `default`.
+    ///
+    /// Arrays are compared element-wise using `items`.
+    pub fn eq_by_schema(&self, other: &JsonValue, schema: &JsonValue) -> bool {
+        schema_differences(self, other, schema).is_empty()
+    }
+}
+
+/// Returns the JSON Pointers at which `expected` and `actual` differ under
+/// the rules of `JsonValue::eq_by_schema`, for reporting in contract tests.
+pub fn schema_differences(
+    expected: &JsonValue,
+    actual: &JsonValue,
+    schema: &JsonValue,
+) -> Vec<String> {
+    let mut differences = Vec::new();
+    compare(
+        expected,
+        actual,
+        schema,
+        &mut String::new(),
+        &mut differences,
+    );
+    differences
+}
+
+fn compare(
+    expected: &JsonValue,
+    actual: &JsonValue,
+    schema: &JsonValue,
+    path: &mut String,
+    differences: &mut Vec<String>,
+) {
+    match (expected, actual) {
+        (JsonValue::Object(a), JsonValue::Object(b)) => {
+            let properties = schema.get("properties").and_then(JsonValue::as_object);
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+
+            for key in keys {
+                let property = match properties {
+                    Some(properties) => match properties.get(key) {
+                        Some(property) => property,
+                        None => continue,
+                    },
+                    None => &JsonValue::Null,
+                };
+                let default = property.get("default");
+                let len = path.len();
+                path.push('/');
+                path.push_str(&escape_pointer_token(key));
+                match (a.get(key), b.get(key)) {
+                    (Some(x), Some(y)) => compare(x, y, property, path, differences),
+                    (Some(x), None) | (None, Some(x)) if Some(x) == default => {}
+                    _ => differences.push(path.clone()),
+                }
+                path.truncate(len);
+            }
+        }
+        (JsonValue::Array(a), JsonValue::Array(b)) => {
+            if a.len() != b.len() {
+                differences.push(path.clone());
+                return;
+            }
+            let items = schema.get("items").unwrap_or(&JsonValue::Null);
+            for (i, (x, y)) in a.iter().zip(b).enumerate() {
+                let len = path.len();
+                path.push_str(&format!("/{}", i));
+                compare(x, y, items, path, differences);
+                path.truncate(len);
+            }
+        }
+        (JsonValue::Number(x), JsonValue::Number(y)) => {
+            let equal = match schema.get("multipleOf").and_then(JsonValue::as_f64) {
+                Some(step) if step > 0.0 => (x / step).round() == (y / step).round(),
+                _ => x == y,
+            };
+            if !equal {
+                differences.push(path.clone());
+            }
+        }
+        _ if expected != actual => differences.push(path.clone()),
+        _ => {}
+    }
+}
@@ -0,0 +1,199 @@
+use super::stringify::stringify_compact;
+use super::{
+    DEFAULT_MAX_DEPTH, JsonValue, ParseError, ParserOptions, parse_with_options,
+    stringify_canonical,
+};
+use std::fmt;
+
+/// The outcome of one conformance vector run by `self_check`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelfCheckCase {
+    pub name: String,
+    pub passed: bool,
+    /// What went wrong, for failed cases.
+    pub failure: Option<String>,
+}
+
+/// The results of `self_check`, in the order the vectors ran.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelfCheckReport {
+    pub cases: Vec<SelfCheckCase>,
+}
+
+impl SelfCheckReport {
+    /// Whether every vector passed.
+    pub fn passed(&self) -> bool {
+        self.cases.iter().all(|case| case.passed)
+    }
+
+    pub fn failures(&self) -> Vec<&SelfCheckCase> {
+        self.cases.iter().filter(|case| !case.passed).collect()
+    }
+}
+
+impl fmt::Display for SelfCheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failures = self.failures();
+        write!(
+            f,
+            "{}/{} self-check vectors passed",
+            self.cases.len() - failures.len(),
+            self.cases.len()
+        )?;
+        for case in failures {
+            write!(
+                f,
+                "\n  {}: {}",
+                case.name,
+                case.failure.as_deref().unwrap_or("failed")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+enum Expect {
+    // Parses, and canonical output matches.
+    Accept(&'static str),
+    // Parses, and canonical output is the input itself.
+    RoundTrip,
+    Reject,
+}
+
+/// Runs a built-in battery of spec edge cases (escapes, numbers, nesting,
+/// Unicode) through the parser and serializer, so embedders can confirm at
+/// runtime that the library behaves as expected on their target.
+pub fn self_check() -> SelfCheckReport {
+    let cases = vectors()
+        .into_iter()
+        .map(|(name, input, expect)| {
+            let failure = run(&input, expect).err();
+            SelfCheckCase {
+                name: name.to_string(),
+                passed: failure.is_none(),
+                failure,
+            }
+        })
+        .collect();
+    SelfCheckReport { cases }
+}
+
+// Vectors test the standard grammar, whatever defaults are configured.
+fn parse(input: &str) -> Result<JsonValue, ParseError> {
+    parse_with_options(input, ParserOptions::default())
+}
+
+fn run(input: &str, expect: Expect) -> Result<(), String> {
+    let expected = match (parse(input), expect) {
+        (Err(_), Expect::Reject) => return Ok(()),
+        (Ok(value), Expect::Reject) => {
+            return Err(format!(
+                "accepted invalid input as {}",
+                stringify_compact(&value)
+            ));
+        }
+        (Err(e), _) => return Err(format!("rejected valid input: {}", e)),
+        (Ok(value), Expect::Accept(expected)) => (value, expected),
+        (Ok(value), Expect::RoundTrip) => (value, input),
+    };
+    let (value, expected) = expected;
+
+    let canonical = stringify_canonical(&value).map_err(|e| e.to_string())?;
+    if canonical != expected {
+        return Err(format!("expected {}, got {}", expected, canonical));
+    }
+    match parse(&stringify_compact(&value)) {
+        Ok(reparsed) if reparsed == value => Ok(()),
+        Ok(_) => Err("value changed in a stringify round trip".to_string()),
+        Err(e) => Err(format!("stringify output did not parse: {}", e)),
+    }
+}
+
+fn vectors() -> Vec<(&'static str, String, Expect)> {
+    use Expect::{Accept, Reject, RoundTrip};
+    let cases: Vec<(&'static str, &str, Expect)> = vec![
+        // Literals and structure
+        ("literals", "[true,false,null]", RoundTrip),
+        (
+            "empty containers",
+            r#"{"a": [], "b": {}}"#,
+            Accept(r#"{"a":[],"b":{}}"#),
+        ),
+        ("surrounding whitespace", " \t\r\n1\n ", Accept("1")),
+        (
+            "key order",
+            r#"{"b": 1, "a": 2}"#,
+            Accept(r#"{"a":2,"b":1}"#),
+        ),
+        ("empty input", "", Reject),
+        ("trailing comma", "[1,]", Reject),
+        ("missing colon", r#"{"a" 1}"#, Reject),
+        ("trailing content", "1 2", Reject),
+        ("unclosed array", "[1", Reject),
+        ("single quotes", "'a'", Reject),
+        ("capitalised literal", "True", Reject),
+        // Numbers
+        ("integer", "-0", Accept("0")),
+        ("fraction", "0.5", Accept("0.5")),
+        ("exponent", "1E2", Accept("100")),
+        ("negative exponent", "25e-1", Accept("2.5")),
+        ("large exponent", "1e21", Accept("1e+21")),
+        ("small number", "5e-7", Accept("5e-7")),
+        (
+            "max safe integer",
+            "9007199254740991",
+            Accept("9007199254740991"),
+        ),
+        ("leading zero", "01", Reject),
+        ("leading plus", "+1", Reject),
+        ("bare fraction", ".5", Reject),
+        ("trailing dot", "1.", Reject),
+        ("empty exponent", "1e", Reject),
+        ("hex", "0x10", Reject),
+        ("NaN", "NaN", Reject),
+        // Strings and escapes
+        (
+            "simple escapes",
+            r#""\"\\\/\b\f\n\r\t""#,
+            Accept(r#""\"\\/\b\f\n\r\t""#),
+        ),
+        ("unicode escape", r#""\u00e9""#, Accept("\"\u{e9}\"")),
+        (
+            "surrogate pair",
+            r#""\ud83d\ude00""#,
+            Accept("\"\u{1F600}\""),
+        ),
+        ("raw multibyte", "\"\u{65e5}\u{672c}\"", RoundTrip),
+        ("escaped control", r#""\u0001""#, RoundTrip),
+        ("lone high surrogate", r#""\ud800""#, Reject),
+        ("lone low surrogate", r#""\udc00""#, Reject),
+        ("unknown escape", r#""\x41""#, Reject),
+        ("short unicode escape", r#""\u12""#, Reject),
+        ("raw control character", "\"a\u{1}b\"", Reject),
+        ("unterminated string", "\"abc", Reject),
+    ];
+
+    let mut vectors: Vec<(&'static str, String, Expect)> = cases
+        .into_iter()
+        .map(|(name, input, expect)| (name, input.to_string(), expect))
+        .collect();
+
+    // Nesting
+    let depth = DEFAULT_MAX_DEPTH;
+    vectors.push((
+        "deep arrays",
+        format!("{}{}", "[".repeat(depth), "]".repeat(depth)),
+        RoundTrip,
+    ));
+    vectors.push((
+        "unbalanced nesting",
+        format!("{}{}", "[".repeat(depth), "]".repeat(depth - 1)),
+        Reject,
+    ));
+    vectors.push((
+        "nesting over the limit",
+        format!("{}{}", "[".repeat(depth + 1), "]".repeat(depth + 1)),
+        Reject,
+    ));
+    vectors
+}
@@ -0,0 +1,69 @@
+use super::JsonValue;
+use serde_json::{Number, Value};
+
+/// Numbers that an `f64` holds exactly become `Number`; larger integers
+/// are kept as `RawNumber` text so nothing is rounded.
+impl From<Value> for JsonValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => JsonValue::Null,
+            Value::Bool(b) => JsonValue::Bool(b),
+            Value::Number(n) => from_number(&n),
+            Value::String(s) => JsonValue::String(s.into()),
+            Value::Array(items) => {
+                JsonValue::Array(items.into_iter().map(JsonValue::from).collect())
+            }
+            Value::Object(members) => JsonValue::Object(Box::new(
+                members
+                    .into_iter()
+                    .map(|(key, value)| (key, JsonValue::from(value)))
+                    .collect(),
+            )),
+        }
+    }
+}
+
+/// Whole numbers become integers, as `stringify` writes them. NaN and
+/// infinities, which `serde_json` cannot represent, become `Null`.
+/// Raw numbers are parsed from their text and raw JSON is expanded.
+impl From<JsonValue> for Value {
+    fn from(value: JsonValue) -> Self {
+        match value {
+            JsonValue::Null => Value::Null,
+            JsonValue::Bool(b) => Value::Bool(b),
+            JsonValue::Number(n) => to_number(n),
+            JsonValue::RawNumber(text) => text.parse::<Number>().map_or(Value::Null, Value::Number),
+            JsonValue::Raw(raw) => Value::from(raw.parse()),
+            JsonValue::String(s) => Value::String(s.into_string()),
+            JsonValue::Bytes(bytes) => Value::String(String::from_utf8_lossy(&bytes).into_owned()),
+            JsonValue::Array(items) => Value::Array(items.into_iter().map(Value::from).collect()),
+            JsonValue::Object(members) => Value::Object(
+                members
+                    .into_iter()
+                    .map(|(key, value)| (key, Value::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+fn from_number(n: &Number) -> JsonValue {
+    let exact = match (n.as_i64(), n.as_u64(), n.as_f64()) {
+        (Some(i), _, Some(f)) => f as i64 == i && f.abs() < 2f64.powi(63),
+        (_, Some(u), Some(f)) => f as u64 == u && f < 2f64.powi(64),
+        (_, _, Some(_)) => true,
+        _ => false,
+    };
+    match n.as_f64() {
+        Some(f) if exact => JsonValue::Number(f),
+        _ => JsonValue::RawNumber(n.to_string().into()),
+    }
+}
+
+fn to_number(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < 2f64.powi(63) {
+        Value::from(n as i64)
+    } else {
+        Number::from_f64(n).map_or(Value::Null, Value::Number)
+    }
+}
@@ -0,0 +1,184 @@
+use super::pointer::{parse_index, unescape_token};
+use super::{JsonValue, KeyInterner, Map};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A JSON value whose strings, arrays and objects are reference-counted,
+/// so cloning it is O(1) however large it is, and clones can be handed to
+/// other threads. Mutation is copy-on-write: only the containers on the
+/// path to a change are copied, and only if they are shared.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SharedJsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    /// A number kept as its source text, as `JsonValue::RawNumber`.
+    RawNumber(Arc<str>),
+    String(Arc<str>),
+    /// A string that is not valid UTF-8, as `JsonValue::Bytes`.
+    Bytes(Arc<[u8]>),
+    Array(Arc<Vec<SharedJsonValue>>),
+    Object(Arc<HashMap<Arc<str>, SharedJsonValue>>),
+}
+
+impl From<JsonValue> for SharedJsonValue {
+    /// Converts a tree, storing each distinct object key once.
+    fn from(value: JsonValue) -> Self {
+        share(value, &mut KeyInterner::new())
+    }
+}
+
+impl From<&SharedJsonValue> for JsonValue {
+    fn from(value: &SharedJsonValue) -> Self {
+        value.to_value()
+    }
+}
+
+fn share(value: JsonValue, keys: &mut KeyInterner) -> SharedJsonValue {
+    match value {
+        JsonValue::Null => SharedJsonValue::Null,
+        JsonValue::Bool(b) => SharedJsonValue::Bool(b),
+        JsonValue::Number(n) => SharedJsonValue::Number(n),
+        JsonValue::RawNumber(text) => SharedJsonValue::RawNumber(text.as_str().into()),
+        JsonValue::Raw(raw) => share(raw.parse(), keys),
+        JsonValue::String(s) => SharedJsonValue::String(s.as_str().into()),
+        JsonValue::Bytes(bytes) => SharedJsonValue::Bytes(bytes.into()),
+        JsonValue::Array(items) => SharedJsonValue::Array(Arc::new(
+            items.into_iter().map(|item| share(item, keys)).collect(),
+        )),
+        JsonValue::Object(members) => SharedJsonValue::Object(Arc::new(
+            members
+                .into_iter()
+                .map(|(key, item)| (keys.intern(&key), share(item, keys)))
+                .collect(),
+        )),
+    }
+}
+
+impl SharedJsonValue {
+    /// Copies the value into an owned tree.
+    pub fn to_value(&self) -> JsonValue {
+        match self {
+            SharedJsonValue::Null => JsonValue::Null,
+            SharedJsonValue::Bool(b) => JsonValue::Bool(*b),
+            SharedJsonValue::Number(n) => JsonValue::Number(*n),
+            SharedJsonValue::RawNumber(text) => JsonValue::RawNumber((**text).into()),
+            SharedJsonValue::String(s) => JsonValue::String((**s).into()),
+            SharedJsonValue::Bytes(bytes) => JsonValue::Bytes(bytes.to_vec().into()),
+            SharedJsonValue::Array(items) => {
+                JsonValue::Array(items.iter().map(SharedJsonValue::to_value).collect())
+            }
+            SharedJsonValue::Object(members) => JsonValue::Object(Box::new(
+                members
+                    .iter()
+                    .map(|(key, item)| (key.to_string(), item.to_value()))
+                    .collect::<Map>(),
+            )),
+        }
+    }
+
+    /// Whether `self` and `other` share the same allocation, so that neither
+    /// has been changed since one was cloned from the other. Always false
+    /// for values without one, such as numbers.
+    pub fn ptr_eq(&self, other: &SharedJsonValue) -> bool {
+        match (self, other) {
+            (SharedJsonValue::String(a), SharedJsonValue::String(b))
+            | (SharedJsonValue::RawNumber(a), SharedJsonValue::RawNumber(b)) => Arc::ptr_eq(a, b),
+            (SharedJsonValue::Bytes(a), SharedJsonValue::Bytes(b)) => Arc::ptr_eq(a, b),
+            (SharedJsonValue::Array(a), SharedJsonValue::Array(b)) => Arc::ptr_eq(a, b),
+            (SharedJsonValue::Object(a), SharedJsonValue::Object(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, SharedJsonValue::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            SharedJsonValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            SharedJsonValue::Number(n) => Some(*n),
+            SharedJsonValue::RawNumber(text) => text.parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            SharedJsonValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&SharedJsonValue> {
+        match self {
+            SharedJsonValue::Object(members) => members.get(key),
+            _ => None,
+        }
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<&SharedJsonValue> {
+        match self {
+            SharedJsonValue::Array(items) => items.get(index),
+            _ => None,
+        }
+    }
+
+    /// Looks up a value by JSON Pointer.
+    pub fn pointer(&self, pointer: &str) -> Option<&SharedJsonValue> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .map(unescape_token)
+            .try_fold(self, |value, token| match value {
+                SharedJsonValue::Object(members) => members.get(token.as_str()),
+                SharedJsonValue::Array(items) => items.get(parse_index(&token)?),
+                _ => None,
+            })
+    }
+
+    /// Mutable counterpart of `pointer`. Every shared container on the way
+    /// is copied first, so clones of `self` are unaffected by the change.
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut SharedJsonValue> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .map(unescape_token)
+            .try_fold(self, |value, token| match value {
+                SharedJsonValue::Object(members) => Arc::make_mut(members).get_mut(token.as_str()),
+                SharedJsonValue::Array(items) => Arc::make_mut(items).get_mut(parse_index(&token)?),
+                _ => None,
+            })
+    }
+
+    /// Sets member `key` of an object, copying the object first if it is
+    /// shared, and returns the value it replaced. Does nothing to values
+    /// that are not objects.
+    pub fn insert(&mut self, key: &str, value: SharedJsonValue) -> Option<SharedJsonValue> {
+        match self {
+            SharedJsonValue::Object(members) => Arc::make_mut(members).insert(key.into(), value),
+            _ => None,
+        }
+    }
+
+    /// Appends to an array, copying it first if it is shared. Does nothing
+    /// to values that are not arrays.
+    pub fn push(&mut self, value: SharedJsonValue) {
+        if let SharedJsonValue::Array(items) = self {
+            Arc::make_mut(items).push(value);
+        }
+    }
+}
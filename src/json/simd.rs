@@ -0,0 +1,183 @@
+// Byte scanning for the parser's hot loops, sixteen bytes at a time. On
+// x86_64 this uses SSE2 and on aarch64 NEON, both of which every CPU of
+// those architectures has, so no runtime detection is needed; elsewhere it
+// falls back to a plain loop. Each function returns the index of the first
+// byte at or after `from` that the scalar loop would stop at, or
+// `bytes.len()`.
+
+const BLOCK: usize = 16;
+
+/// Skips `' '`, `\t`, `\n` and `\r`.
+pub(crate) fn skip_whitespace(bytes: &[u8], from: usize) -> usize {
+    find(
+        bytes,
+        from,
+        |block| !arch::whitespace(block),
+        |b| !matches!(b, b' ' | b'\t' | b'\n' | b'\r'),
+    )
+}
+
+/// Finds the end of a run of plain string content: `quote`, a backslash or
+/// a control character.
+pub(crate) fn find_string_special(bytes: &[u8], from: usize, quote: u8) -> usize {
+    find(
+        bytes,
+        from,
+        |block| arch::string_special(block, quote),
+        |b| b == quote || b == b'\\' || b < 0x20,
+    )
+}
+
+/// Finds the end of an unquoted scalar: a structural character, a quote,
+/// whitespace or the `/` that starts a comment.
+pub(crate) fn find_delimiter(bytes: &[u8], from: usize) -> usize {
+    find(bytes, from, arch::delimiter, |b| {
+        b",:[]{}\"' \t\r\n/".contains(&b)
+    })
+}
+
+// `block_mask` sets bit `i` when byte `i` of a block is one to stop at.
+fn find(
+    bytes: &[u8],
+    from: usize,
+    block_mask: impl Fn(&[u8; BLOCK]) -> u16,
+    stop: impl Fn(u8) -> bool,
+) -> usize {
+    let mut i = from;
+    while let Some(block) = bytes.get(i..i + BLOCK) {
+        let mask = block_mask(block.try_into().expect("a whole block"));
+        if mask != 0 {
+            return i + mask.trailing_zeros() as usize;
+        }
+        i += BLOCK;
+    }
+    bytes[i.min(bytes.len())..]
+        .iter()
+        .position(|&b| stop(b))
+        .map_or(bytes.len(), |n| i + n)
+}
+
+#[cfg(target_arch = "x86_64")]
+mod arch {
+    use super::BLOCK;
+    use std::arch::x86_64::*;
+
+    // SAFETY (every function below): SSE2 is part of the x86_64 baseline,
+    // and loads are unaligned loads of exactly `BLOCK` bytes.
+
+    pub(super) fn whitespace(block: &[u8; BLOCK]) -> u16 {
+        unsafe {
+            let v = _mm_loadu_si128(block.as_ptr().cast());
+            let any = _mm_or_si128(
+                _mm_or_si128(eq(v, b' '), eq(v, b'\t')),
+                _mm_or_si128(eq(v, b'\n'), eq(v, b'\r')),
+            );
+            _mm_movemask_epi8(any) as u16
+        }
+    }
+
+    pub(super) fn string_special(block: &[u8; BLOCK], quote: u8) -> u16 {
+        unsafe {
+            let v = _mm_loadu_si128(block.as_ptr().cast());
+            let control = _mm_cmpeq_epi8(_mm_max_epu8(v, _mm_set1_epi8(0x1f)), _mm_set1_epi8(0x1f));
+            let any = _mm_or_si128(_mm_or_si128(eq(v, quote), eq(v, b'\\')), control);
+            _mm_movemask_epi8(any) as u16
+        }
+    }
+
+    pub(super) fn delimiter(block: &[u8; BLOCK]) -> u16 {
+        unsafe {
+            let v = _mm_loadu_si128(block.as_ptr().cast());
+            let any = b",:[]{}\"' \t\r\n/"
+                .iter()
+                .fold(_mm_setzero_si128(), |any, &b| _mm_or_si128(any, eq(v, b)));
+            _mm_movemask_epi8(any) as u16
+        }
+    }
+
+    unsafe fn eq(v: __m128i, b: u8) -> __m128i {
+        unsafe { _mm_cmpeq_epi8(v, _mm_set1_epi8(b as i8)) }
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod arch {
+    use super::BLOCK;
+    use std::arch::aarch64::*;
+
+    // SAFETY (every function below): NEON is part of the aarch64 baseline,
+    // and loads read exactly `BLOCK` bytes.
+
+    pub(super) fn whitespace(block: &[u8; BLOCK]) -> u16 {
+        unsafe {
+            let v = vld1q_u8(block.as_ptr());
+            let any = vorrq_u8(
+                vorrq_u8(
+                    vceqq_u8(v, vdupq_n_u8(b' ')),
+                    vceqq_u8(v, vdupq_n_u8(b'\t')),
+                ),
+                vorrq_u8(
+                    vceqq_u8(v, vdupq_n_u8(b'\n')),
+                    vceqq_u8(v, vdupq_n_u8(b'\r')),
+                ),
+            );
+            movemask(any)
+        }
+    }
+
+    pub(super) fn string_special(block: &[u8; BLOCK], quote: u8) -> u16 {
+        unsafe {
+            let v = vld1q_u8(block.as_ptr());
+            let any = vorrq_u8(
+                vorrq_u8(
+                    vceqq_u8(v, vdupq_n_u8(quote)),
+                    vceqq_u8(v, vdupq_n_u8(b'\\')),
+                ),
+                vcltq_u8(v, vdupq_n_u8(0x20)),
+            );
+            movemask(any)
+        }
+    }
+
+    pub(super) fn delimiter(block: &[u8; BLOCK]) -> u16 {
+        unsafe {
+            let v = vld1q_u8(block.as_ptr());
+            let any = b",:[]{}\"' \t\r\n/".iter().fold(vdupq_n_u8(0), |any, &b| {
+                vorrq_u8(any, vceqq_u8(v, vdupq_n_u8(b)))
+            });
+            movemask(any)
+        }
+    }
+
+    // One bit per byte of a comparison result, as SSE2's movemask gives.
+    unsafe fn movemask(v: uint8x16_t) -> u16 {
+        const WEIGHTS: [u8; BLOCK] = [1, 2, 4, 8, 16, 32, 64, 128, 1, 2, 4, 8, 16, 32, 64, 128];
+        unsafe {
+            let bits = vandq_u8(v, vld1q_u8(WEIGHTS.as_ptr()));
+            let low = vaddv_u8(vget_low_u8(bits)) as u16;
+            let high = vaddv_u8(vget_high_u8(bits)) as u16;
+            low | high << 8
+        }
+    }
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+mod arch {
+    use super::BLOCK;
+
+    fn mask(block: &[u8; BLOCK], stop: impl Fn(u8) -> bool) -> u16 {
+        (0..BLOCK).fold(0, |mask, i| mask | (stop(block[i]) as u16) << i)
+    }
+
+    pub(super) fn whitespace(block: &[u8; BLOCK]) -> u16 {
+        mask(block, |b| matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+    }
+
+    pub(super) fn string_special(block: &[u8; BLOCK], quote: u8) -> u16 {
+        mask(block, |b| b == quote || b == b'\\' || b < 0x20)
+    }
+
+    pub(super) fn delimiter(block: &[u8; BLOCK]) -> u16 {
+        mask(block, |b| b",:[]{}\"' \t\r\n/".contains(&b))
+    }
+}
@@ -0,0 +1,204 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroU8;
+use std::ops::Deref;
+
+const INLINE_CAPACITY: usize = 15;
+
+/// The text of a `JsonValue::String` or `JsonValue::RawNumber`, in 16
+/// bytes. Strings of up to 15 bytes, which covers most short values and
+/// almost every number, are stored inline without allocating; longer ones
+/// are moved to the heap. Derefs to `str`, and converts from and into
+/// `String`.
+#[derive(Clone)]
+pub struct JsonString {
+    repr: Repr,
+}
+
+#[derive(Clone)]
+enum Repr {
+    // `len` holds the length plus one, so that its zero value is free to
+    // tell the variants apart and the enum stays at 16 bytes.
+    Inline {
+        len: NonZeroU8,
+        buf: [u8; INLINE_CAPACITY],
+    },
+    // Boxed once more so the pointer is thin and fits beside `len`.
+    #[allow(clippy::box_collection)]
+    Heap(Box<String>),
+}
+
+impl JsonString {
+    pub fn new() -> Self {
+        JsonString::default()
+    }
+
+    pub fn as_str(&self) -> &str {
+        match &self.repr {
+            Repr::Inline { len, buf } => {
+                let bytes = &buf[..usize::from(len.get()) - 1];
+                // SAFETY: inline bytes are only ever copied whole from a
+                // `&str`, so they are valid UTF-8.
+                unsafe { std::str::from_utf8_unchecked(bytes) }
+            }
+            Repr::Heap(s) => s,
+        }
+    }
+
+    /// Whether the text is stored inline, without a heap allocation.
+    pub fn is_inline(&self) -> bool {
+        matches!(self.repr, Repr::Inline { .. })
+    }
+
+    /// Bytes allocated on the heap for the text, beyond the 16 bytes of the
+    /// value itself.
+    pub fn heap_size(&self) -> usize {
+        match &self.repr {
+            Repr::Inline { .. } => 0,
+            Repr::Heap(s) => size_of::<String>() + s.capacity(),
+        }
+    }
+
+    pub fn into_string(self) -> String {
+        match self.repr {
+            Repr::Inline { .. } => self.as_str().to_string(),
+            Repr::Heap(s) => *s,
+        }
+    }
+
+    pub fn push_str(&mut self, s: &str) {
+        match &mut self.repr {
+            Repr::Heap(text) => text.push_str(s),
+            Repr::Inline { .. } => {
+                let mut text = self.as_str().to_string();
+                text.push_str(s);
+                *self = JsonString::from(text);
+            }
+        }
+    }
+
+    fn inline(s: &str) -> Option<Self> {
+        if s.len() > INLINE_CAPACITY {
+            return None;
+        }
+        let mut buf = [0; INLINE_CAPACITY];
+        buf[..s.len()].copy_from_slice(s.as_bytes());
+        let len = NonZeroU8::new(s.len() as u8 + 1).expect("length plus one is non-zero");
+        Some(JsonString {
+            repr: Repr::Inline { len, buf },
+        })
+    }
+}
+
+impl Default for JsonString {
+    fn default() -> Self {
+        JsonString::inline("").expect("the empty string fits inline")
+    }
+}
+
+impl From<&str> for JsonString {
+    fn from(s: &str) -> Self {
+        JsonString::inline(s).unwrap_or_else(|| JsonString {
+            repr: Repr::Heap(Box::new(s.to_string())),
+        })
+    }
+}
+
+impl From<String> for JsonString {
+    /// Moves long strings to the heap without copying their text.
+    fn from(s: String) -> Self {
+        JsonString::inline(&s).unwrap_or_else(|| JsonString {
+            repr: Repr::Heap(Box::new(s)),
+        })
+    }
+}
+
+impl From<&String> for JsonString {
+    fn from(s: &String) -> Self {
+        JsonString::from(s.as_str())
+    }
+}
+
+impl From<JsonString> for String {
+    fn from(s: JsonString) -> Self {
+        s.into_string()
+    }
+}
+
+impl Deref for JsonString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for JsonString {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for JsonString {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq for JsonString {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for JsonString {}
+
+impl PartialEq<str> for JsonString {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for JsonString {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for JsonString {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialOrd for JsonString {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for JsonString {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for JsonString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl fmt::Debug for JsonString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for JsonString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,265 @@
+use super::{DEFAULT_MAX_DEPTH, JsonValue, Map, RawJson};
+use std::fmt;
+
+const MAGIC: &[u8; 4] = b"WJSN";
+const VERSION: u8 = 1;
+
+const TAG_NULL: u8 = 0;
+const TAG_FALSE: u8 = 1;
+const TAG_TRUE: u8 = 2;
+const TAG_NUMBER: u8 = 3;
+const TAG_STRING: u8 = 4;
+const TAG_ARRAY: u8 = 5;
+const TAG_OBJECT: u8 = 6;
+const TAG_RAW_NUMBER: u8 = 7;
+const TAG_RAW: u8 = 8;
+const TAG_BYTES: u8 = 9;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum SnapshotError {
+    /// The data does not start with the snapshot header.
+    NotASnapshot,
+    /// The snapshot was written by an incompatible version.
+    UnsupportedVersion(u8),
+    /// The data ends in the middle of a value.
+    Truncated,
+    InvalidTag(u8, usize),
+    InvalidUtf8(usize),
+    /// A raw JSON fragment at the given offset is not valid JSON.
+    InvalidRawJson(usize),
+    /// Bytes remain after the root value, at the given offset.
+    TrailingData(usize),
+    /// An array or object at the given offset is nested more than
+    /// `DEFAULT_MAX_DEPTH` levels deep.
+    TooDeep(usize),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::NotASnapshot => write!(f, "not a JSON snapshot"),
+            SnapshotError::UnsupportedVersion(v) => {
+                write!(f, "unsupported snapshot version {}", v)
+            }
+            SnapshotError::Truncated => write!(f, "snapshot is truncated"),
+            SnapshotError::InvalidTag(tag, offset) => {
+                write!(f, "invalid value tag {} at offset {}", tag, offset)
+            }
+            SnapshotError::InvalidUtf8(offset) => {
+                write!(f, "invalid UTF-8 in string at offset {}", offset)
+            }
+            SnapshotError::InvalidRawJson(offset) => {
+                write!(f, "invalid raw JSON at offset {}", offset)
+            }
+            SnapshotError::TrailingData(offset) => {
+                write!(f, "unexpected data after the value at offset {}", offset)
+            }
+            SnapshotError::TooDeep(offset) => write!(
+                f,
+                "nesting deeper than {} levels at offset {}",
+                DEFAULT_MAX_DEPTH, offset
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl JsonValue {
+    /// Serializes the value in a compact, versioned binary format that
+    /// `from_snapshot` loads much faster than parsing text. The format is
+    /// internal to this crate and not meant for interchange.
+    pub fn to_snapshot(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(64);
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        write_value(self, &mut out);
+        out
+    }
+
+    /// Loads a value written by `to_snapshot`. As when parsing text, arrays
+    /// and objects may be nested at most `DEFAULT_MAX_DEPTH` levels deep.
+    pub fn from_snapshot(data: &[u8]) -> Result<JsonValue, SnapshotError> {
+        let body = data
+            .strip_prefix(MAGIC.as_slice())
+            .ok_or(SnapshotError::NotASnapshot)?;
+        match body.first() {
+            Some(&VERSION) => {}
+            Some(&version) => return Err(SnapshotError::UnsupportedVersion(version)),
+            None => return Err(SnapshotError::Truncated),
+        }
+        let mut reader = Reader {
+            data,
+            pos: MAGIC.len() + 1,
+            depth: 0,
+        };
+        let value = reader.value()?;
+        if reader.pos < data.len() {
+            return Err(SnapshotError::TrailingData(reader.pos));
+        }
+        Ok(value)
+    }
+}
+
+fn write_value(value: &JsonValue, out: &mut Vec<u8>) {
+    match value {
+        JsonValue::Null => out.push(TAG_NULL),
+        JsonValue::Bool(false) => out.push(TAG_FALSE),
+        JsonValue::Bool(true) => out.push(TAG_TRUE),
+        JsonValue::Number(n) => {
+            out.push(TAG_NUMBER);
+            out.extend_from_slice(&n.to_le_bytes());
+        }
+        JsonValue::RawNumber(text) => {
+            out.push(TAG_RAW_NUMBER);
+            write_str(text, out);
+        }
+        JsonValue::Raw(raw) => {
+            out.push(TAG_RAW);
+            write_str(raw.as_str(), out);
+        }
+        JsonValue::String(s) => {
+            out.push(TAG_STRING);
+            write_str(s, out);
+        }
+        JsonValue::Bytes(bytes) => {
+            out.push(TAG_BYTES);
+            write_len(bytes.len(), out);
+            out.extend_from_slice(bytes);
+        }
+        JsonValue::Array(arr) => {
+            out.push(TAG_ARRAY);
+            write_len(arr.len(), out);
+            for item in arr {
+                write_value(item, out);
+            }
+        }
+        JsonValue::Object(obj) => {
+            out.push(TAG_OBJECT);
+            write_len(obj.len(), out);
+            for (key, item) in obj.iter() {
+                write_str(key, out);
+                write_value(item, out);
+            }
+        }
+    }
+}
+
+fn write_str(s: &str, out: &mut Vec<u8>) {
+    write_len(s.len(), out);
+    out.extend_from_slice(s.as_bytes());
+}
+
+// Lengths are LEB128 varints: seven bits per byte, high bit set on all but
+// the last byte.
+fn write_len(mut len: usize, out: &mut Vec<u8>) {
+    while len >= 0x80 {
+        out.push((len as u8) | 0x80);
+        len >>= 7;
+    }
+    out.push(len as u8);
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl Reader<'_> {
+    fn value(&mut self) -> Result<JsonValue, SnapshotError> {
+        let offset = self.pos;
+        match self.byte()? {
+            TAG_NULL => Ok(JsonValue::Null),
+            TAG_FALSE => Ok(JsonValue::Bool(false)),
+            TAG_TRUE => Ok(JsonValue::Bool(true)),
+            TAG_NUMBER => {
+                let bytes = self.take(8)?;
+                Ok(JsonValue::Number(f64::from_le_bytes(
+                    bytes.try_into().unwrap(),
+                )))
+            }
+            TAG_STRING => self.string().map(|s| JsonValue::String(s.into())),
+            TAG_RAW_NUMBER => self.string().map(|s| JsonValue::RawNumber(s.into())),
+            TAG_BYTES => {
+                let len = self.len()?;
+                Ok(JsonValue::Bytes(self.take(len)?.to_vec().into()))
+            }
+            TAG_RAW => {
+                let text = self.string()?;
+                RawJson::new(&text)
+                    .map(JsonValue::Raw)
+                    .map_err(|_| SnapshotError::InvalidRawJson(offset))
+            }
+            TAG_ARRAY | TAG_OBJECT if self.depth == DEFAULT_MAX_DEPTH => {
+                Err(SnapshotError::TooDeep(offset))
+            }
+            TAG_ARRAY => {
+                self.depth += 1;
+                let len = self.len()?;
+                // Every element takes at least one byte, which bounds the
+                // allocation for corrupt lengths.
+                let mut arr = Vec::with_capacity(len.min(self.remaining()));
+                for _ in 0..len {
+                    arr.push(self.value()?);
+                }
+                self.depth -= 1;
+                Ok(JsonValue::Array(arr))
+            }
+            TAG_OBJECT => {
+                self.depth += 1;
+                let len = self.len()?;
+                let mut obj = Map::new();
+                for _ in 0..len {
+                    let key = self.string()?;
+                    obj.insert(key, self.value()?);
+                }
+                self.depth -= 1;
+                Ok(JsonValue::Object(Box::new(obj)))
+            }
+            tag => Err(SnapshotError::InvalidTag(tag, offset)),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, SnapshotError> {
+        let len = self.len()?;
+        let offset = self.pos;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| SnapshotError::InvalidUtf8(offset))
+    }
+
+    fn len(&mut self) -> Result<usize, SnapshotError> {
+        let mut len = 0usize;
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            if shift >= usize::BITS {
+                return Err(SnapshotError::Truncated);
+            }
+            len |= ((byte & 0x7f) as usize) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(len);
+            }
+            shift += 7;
+        }
+    }
+
+    fn byte(&mut self) -> Result<u8, SnapshotError> {
+        let byte = *self.data.get(self.pos).ok_or(SnapshotError::Truncated)?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&[u8], SnapshotError> {
+        if len > self.remaining() {
+            return Err(SnapshotError::Truncated);
+        }
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+}
@@ -0,0 +1,87 @@
+use super::pointer::{parse_index, unescape_token};
+use super::{JsonValue, PointerError};
+use std::cmp::Ordering;
+
+/// Sorts the members of every object in the tree by key, so they are
+/// iterated and written in ascending key order. Only the `preserve_order`
+/// map has an order to change: with the default `HashMap` or the
+/// `sorted_map` `BTreeMap` this does nothing.
+#[cfg(feature = "preserve_order")]
+pub fn sort_keys_recursive(value: &mut JsonValue) {
+    match value {
+        JsonValue::Object(members) => {
+            members.sort_keys();
+            members.values_mut().for_each(sort_keys_recursive);
+        }
+        JsonValue::Array(items) => items.iter_mut().for_each(sort_keys_recursive),
+        _ => {}
+    }
+}
+
+#[cfg(not(feature = "preserve_order"))]
+pub fn sort_keys_recursive(_value: &mut JsonValue) {}
+
+/// Sorts arrays in place, e.g. to normalize a document before comparing or
+/// storing it. `pointer` is a JSON Pointer in which a `*` token matches
+/// every member or element, so `/orders/*/items` sorts the items of every
+/// order. Fails if the pointer names no array, or a value it matches is not
+/// an array.
+pub fn sort_arrays_by(
+    value: &mut JsonValue,
+    pointer: &str,
+    mut compare: impl FnMut(&JsonValue, &JsonValue) -> Ordering,
+) -> Result<(), PointerError> {
+    let tokens: Vec<String> = match pointer {
+        "" => Vec::new(),
+        _ => match pointer.strip_prefix('/') {
+            Some(rest) => rest.split('/').map(unescape_token).collect(),
+            None => return Err(error(pointer, "a pointer must start with /")),
+        },
+    };
+    let mut sorted = 0;
+    sort_matches(value, &tokens, &mut compare, &mut sorted).map_err(|m| error(pointer, m))?;
+    if sorted == 0 && !tokens.iter().any(|t| t == "*") {
+        return Err(error(pointer, "no such value"));
+    }
+    Ok(())
+}
+
+fn error(pointer: &str, message: &str) -> PointerError {
+    PointerError {
+        pointer: pointer.to_string(),
+        message: message.to_string(),
+    }
+}
+
+fn sort_matches(
+    value: &mut JsonValue,
+    tokens: &[String],
+    compare: &mut impl FnMut(&JsonValue, &JsonValue) -> Ordering,
+    sorted: &mut usize,
+) -> Result<(), &'static str> {
+    let Some((token, rest)) = tokens.split_first() else {
+        let JsonValue::Array(items) = value else {
+            return Err("not an array");
+        };
+        items.sort_by(|a, b| compare(a, b));
+        *sorted += 1;
+        return Ok(());
+    };
+    match value {
+        JsonValue::Object(members) if token == "*" => members
+            .values_mut()
+            .try_for_each(|child| sort_matches(child, rest, compare, sorted)),
+        JsonValue::Array(items) if token == "*" => items
+            .iter_mut()
+            .try_for_each(|child| sort_matches(child, rest, compare, sorted)),
+        JsonValue::Object(members) => match members.get_mut(token) {
+            Some(child) => sort_matches(child, rest, compare, sorted),
+            None => Ok(()),
+        },
+        JsonValue::Array(items) => match parse_index(token).and_then(|i| items.get_mut(i)) {
+            Some(child) => sort_matches(child, rest, compare, sorted),
+            None => Ok(()),
+        },
+        _ => Ok(()),
+    }
+}
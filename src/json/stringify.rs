@@ -0,0 +1,304 @@
+use super::{JsonValue, StringifyError, embed_nested_json};
+use std::fmt;
+
+/// Line ending used between lines of indented output.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum NewlineStyle {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl NewlineStyle {
+    fn as_str(self) -> &'static str {
+        match self {
+            NewlineStyle::Lf => "\n",
+            NewlineStyle::CrLf => "\r\n",
+        }
+    }
+}
+
+/// How `stringify_with` writes NaN and infinities, which JSON cannot
+/// represent.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum NonFinitePolicy {
+    /// Write `null`, as JavaScript's `JSON.stringify` does.
+    #[default]
+    Null,
+    /// Fail with `StringifyError::NonFiniteNumber`.
+    Error,
+    /// Write `NaN`, `Infinity` or `-Infinity`, which JSON5 accepts.
+    Literal,
+}
+
+/// Knobs for `stringify_with`. The default produces the same compact
+/// output as `stringify`.
+#[derive(Debug, Clone, Default)]
+pub struct StringifyOptions {
+    /// Put each array element and object member on its own line, indented
+    /// by this string per nesting level. `None` produces compact output.
+    pub indent: Option<String>,
+    /// Line ending for indented output.
+    pub newline: NewlineStyle,
+    /// Emit object members in ascending key order instead of map order, so
+    /// output is identical across runs.
+    pub sort_keys: bool,
+    /// Emit every non-ASCII character as `\uXXXX`, using surrogate pairs
+    /// outside the Basic Multilingual Plane, so the output is pure ASCII.
+    pub escape_non_ascii: bool,
+    /// Escape `<`, `>`, `&`, U+2028 and U+2029 so the output can be embedded
+    /// in an HTML `<script>` element without closing it early or breaking
+    /// older JavaScript parsers.
+    pub escape_html: bool,
+    /// Escape `/` as `\/`.
+    pub escape_slash: bool,
+    /// Omit object members whose value is `null`. Array elements are kept.
+    pub skip_null_fields: bool,
+    pub non_finite: NonFinitePolicy,
+    /// JSON Pointers of subtrees to write as strings holding their JSON
+    /// text, re-embedding values expanded by `expand_nested_json`.
+    pub embed_json_at: Vec<String>,
+}
+
+impl StringifyOptions {
+    /// Options for human-readable output indented by two spaces.
+    pub fn pretty() -> Self {
+        StringifyOptions {
+            indent: Some("  ".to_string()),
+            ..StringifyOptions::default()
+        }
+    }
+}
+
+/// Serializes a value with `StringifyOptions::current()`, which is compact
+/// JSON unless other defaults are configured. NaN and infinities become
+/// `null` even if the defaults say `NonFinitePolicy::Error`.
+pub fn stringify(value: &JsonValue) -> String {
+    stringify_infallible(value, StringifyOptions::current())
+}
+
+/// Like `stringify`, indenting by two spaces unless the defaults set an
+/// indent.
+pub fn stringify_pretty(value: &JsonValue) -> String {
+    let mut options = StringifyOptions::current();
+    options.indent.get_or_insert_with(|| "  ".to_string());
+    stringify_infallible(value, options)
+}
+
+/// Writes the value as `stringify` does, or as `stringify_pretty` with the
+/// alternate flag, `{:#}`.
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str(&stringify_pretty(self))
+        } else {
+            f.write_str(&stringify(self))
+        }
+    }
+}
+
+// Compact output with the built-in options, for internal uses that must not
+// follow configured defaults.
+pub(crate) fn stringify_compact(value: &JsonValue) -> String {
+    stringify_infallible(value, StringifyOptions::default())
+}
+
+fn stringify_infallible(value: &JsonValue, options: StringifyOptions) -> String {
+    stringify_with(value, &infallible(options)).unwrap()
+}
+
+// Only `NonFinitePolicy::Error` can fail.
+fn infallible(mut options: StringifyOptions) -> StringifyOptions {
+    if options.non_finite == NonFinitePolicy::Error {
+        options.non_finite = NonFinitePolicy::Null;
+    }
+    options
+}
+
+// Writes the value as `stringify` does, straight to `out`. Stops at the
+// first error from `out`, which lets a bounded writer cut the work short.
+pub(crate) fn write_json<W: fmt::Write>(value: &JsonValue, out: &mut W) -> fmt::Result {
+    let options = infallible(StringifyOptions::current());
+    let result = if options.embed_json_at.is_empty() {
+        write_value_to(value, &options, 0, out)
+    } else {
+        let mut value = value.clone();
+        embed_nested_json(&mut value, &options.embed_json_at);
+        write_value_to(&value, &options, 0, out)
+    };
+    match result {
+        Err(WriteError::Sink) => Err(fmt::Error),
+        _ => Ok(()),
+    }
+}
+
+pub fn stringify_with(
+    value: &JsonValue,
+    options: &StringifyOptions,
+) -> Result<String, StringifyError> {
+    let mut out = String::new();
+    if options.embed_json_at.is_empty() {
+        write_value(value, options, 0, &mut out)?;
+    } else {
+        let mut value = value.clone();
+        embed_nested_json(&mut value, &options.embed_json_at);
+        write_value(&value, options, 0, &mut out)?;
+    }
+    Ok(out)
+}
+
+pub(crate) fn write_value(
+    value: &JsonValue,
+    options: &StringifyOptions,
+    depth: usize,
+    out: &mut String,
+) -> Result<(), StringifyError> {
+    match write_value_to(value, options, depth, out) {
+        Err(WriteError::Value(e)) => Err(e),
+        // Writing to a `String` cannot fail.
+        _ => Ok(()),
+    }
+}
+
+// Why `write_value_to` stopped: the value cannot be written with the
+// options given, or the output refused more text.
+enum WriteError {
+    Value(StringifyError),
+    Sink,
+}
+
+impl From<fmt::Error> for WriteError {
+    fn from(_: fmt::Error) -> Self {
+        WriteError::Sink
+    }
+}
+
+fn write_value_to<W: fmt::Write>(
+    value: &JsonValue,
+    options: &StringifyOptions,
+    depth: usize,
+    out: &mut W,
+) -> Result<(), WriteError> {
+    match value {
+        JsonValue::Null => out.write_str("null")?,
+        JsonValue::Bool(true) => out.write_str("true")?,
+        JsonValue::Bool(false) => out.write_str("false")?,
+        JsonValue::Number(n) if !n.is_finite() => match options.non_finite {
+            NonFinitePolicy::Null => out.write_str("null")?,
+            NonFinitePolicy::Error => {
+                return Err(WriteError::Value(StringifyError::NonFiniteNumber(*n)));
+            }
+            NonFinitePolicy::Literal if n.is_nan() => out.write_str("NaN")?,
+            NonFinitePolicy::Literal if *n > 0.0 => out.write_str("Infinity")?,
+            NonFinitePolicy::Literal => out.write_str("-Infinity")?,
+        },
+        JsonValue::Number(n) => {
+            // Integers beyond i64 would saturate in the cast.
+            if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+                write!(out, "{}", *n as i64)?;
+            } else {
+                write!(out, "{}", n)?;
+            }
+        }
+        JsonValue::RawNumber(text) => out.write_str(text)?,
+        JsonValue::Raw(raw) => out.write_str(raw.as_str())?,
+        JsonValue::String(s) => write_string_to(s, options, out)?,
+        JsonValue::Bytes(bytes) => write_string_to(&String::from_utf8_lossy(bytes), options, out)?,
+        JsonValue::Array(arr) => {
+            out.write_char('[')?;
+            for (i, item) in arr.iter().enumerate() {
+                if i > 0 {
+                    out.write_char(',')?;
+                }
+                write_newline_to(options, depth + 1, out)?;
+                write_value_to(item, options, depth + 1, out)?;
+            }
+            if !arr.is_empty() {
+                write_newline_to(options, depth, out)?;
+            }
+            out.write_char(']')?;
+        }
+        JsonValue::Object(obj) => {
+            let mut members: Vec<(&String, &JsonValue)> = obj
+                .iter()
+                .filter(|(_, item)| !(options.skip_null_fields && item.is_null()))
+                .collect();
+            if options.sort_keys {
+                members.sort_by(|a, b| a.0.cmp(b.0));
+            }
+            out.write_char('{')?;
+            let empty = members.is_empty();
+            for (i, (key, item)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.write_char(',')?;
+                }
+                write_newline_to(options, depth + 1, out)?;
+                write_string_to(key, options, out)?;
+                out.write_str(if options.indent.is_some() { ": " } else { ":" })?;
+                write_value_to(item, options, depth + 1, out)?;
+            }
+            if !empty {
+                write_newline_to(options, depth, out)?;
+            }
+            out.write_char('}')?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn write_newline(options: &StringifyOptions, depth: usize, out: &mut String) {
+    // Writing to a `String` cannot fail.
+    let _ = write_newline_to(options, depth, out);
+}
+
+fn write_newline_to<W: fmt::Write>(
+    options: &StringifyOptions,
+    depth: usize,
+    out: &mut W,
+) -> fmt::Result {
+    if let Some(indent) = &options.indent {
+        out.write_str(options.newline.as_str())?;
+        for _ in 0..depth {
+            out.write_str(indent)?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn write_string(s: &str, options: &StringifyOptions, out: &mut String) {
+    // Writing to a `String` cannot fail.
+    let _ = write_string_to(s, options, out);
+}
+
+fn write_string_to<W: fmt::Write>(s: &str, options: &StringifyOptions, out: &mut W) -> fmt::Result {
+    out.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            '/' if options.escape_slash => out.write_str("\\/")?,
+            '\x08' => out.write_str("\\b")?,
+            '\x0c' => out.write_str("\\f")?,
+            '\n' => out.write_str("\\n")?,
+            '\r' => out.write_str("\\r")?,
+            '\t' => out.write_str("\\t")?,
+            _ if c.is_control() => write_unicode_escape(out, c)?,
+            '<' | '>' | '&' | '\u{2028}' | '\u{2029}' if options.escape_html => {
+                write_unicode_escape(out, c)?
+            }
+            _ if options.escape_non_ascii && !c.is_ascii() => write_unicode_escape(out, c)?,
+            _ => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
+
+// Writes `c` as a `\\uXXXX` escape, using a UTF-16 surrogate pair for
+// characters outside the Basic Multilingual Plane.
+fn write_unicode_escape<W: fmt::Write>(out: &mut W, c: char) -> fmt::Result {
+    let mut units = [0u16; 2];
+    for unit in c.encode_utf16(&mut units) {
+        write!(out, "\\u{:04x}", unit)?;
+    }
+    Ok(())
+}
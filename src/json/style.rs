@@ -0,0 +1,98 @@
+use super::{NewlineStyle, StringifyOptions, scan_string};
+use std::collections::HashMap;
+
+/// The formatting conventions of an existing document, as inferred by
+/// `detect_style`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatStyle {
+    /// The string added per nesting level, or `None` for a document written
+    /// on a single line.
+    pub indent: Option<String>,
+    pub newline: NewlineStyle,
+    pub space_before_colon: bool,
+    pub space_after_colon: bool,
+    /// Whether the document ends with a line break.
+    pub final_newline: bool,
+}
+
+impl FormatStyle {
+    /// Stringify options reproducing the indentation and line endings.
+    /// Colon spacing and the final newline are left to the caller, since
+    /// `stringify_with` does not vary them.
+    pub fn stringify_options(&self) -> StringifyOptions {
+        StringifyOptions {
+            indent: self.indent.clone(),
+            newline: self.newline,
+            ..StringifyOptions::default()
+        }
+    }
+}
+
+/// Infers how `input` is formatted: the indent unit (the most common step
+/// between the indentation of consecutive lines, tabs or spaces), the line
+/// ending used by most lines, and whether colons are padded with spaces.
+/// Text inside strings is ignored. The input need not be valid JSON.
+pub fn detect_style(input: &str) -> FormatStyle {
+    let bytes = input.as_bytes();
+    let (mut crlf, mut lf) = (0, 0);
+    let (mut before, mut after, mut colons) = (0, 0, 0);
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                i = scan_string(bytes, i, b'"').unwrap_or(bytes.len());
+                continue;
+            }
+            b'\n' if i > 0 && bytes[i - 1] == b'\r' => crlf += 1,
+            b'\n' => lf += 1,
+            b':' => {
+                colons += 1;
+                before += usize::from(i > 0 && matches!(bytes[i - 1], b' ' | b'\t'));
+                after += usize::from(matches!(bytes.get(i + 1), Some(b' ' | b'\t')));
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    FormatStyle {
+        indent: detect_indent(input),
+        newline: if crlf > lf {
+            NewlineStyle::CrLf
+        } else {
+            NewlineStyle::Lf
+        },
+        space_before_colon: colons > 0 && before * 2 > colons,
+        space_after_colon: colons > 0 && after * 2 > colons,
+        final_newline: input.ends_with('\n'),
+    }
+}
+
+fn detect_indent(input: &str) -> Option<String> {
+    let mut steps: HashMap<String, usize> = HashMap::new();
+    let mut previous = "";
+    let mut lines = 0;
+    for line in input.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        lines += 1;
+        let indent = &line[..line.len() - line.trim_start().len()];
+        // A deeper line adds one level to the previous line's indentation.
+        if let Some(step) = indent.strip_prefix(previous)
+            && !step.is_empty()
+        {
+            *steps.entry(step.to_string()).or_default() += 1;
+        }
+        previous = indent;
+    }
+    if lines <= 1 {
+        return None;
+    }
+    let step = steps
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.len().cmp(&a.0.len())))
+        .map(|(step, _)| step);
+    // Multi-line but never indented, as in a flat list of top-level lines.
+    Some(step.unwrap_or_default())
+}
@@ -0,0 +1,271 @@
+use super::{JsonValue, ParseError, ParserOptions, parse_with_options, scan_string, simd};
+use super::{pointer::unescape_token, validate_with_options};
+use std::borrow::Cow;
+
+/// A document parsed in two stages, for reads that touch only a few
+/// fields of a large input. The input is validated, then its structure is
+/// recorded on a flat tape of byte ranges; nothing is decoded or allocated
+/// per value until it is read through a `TapeValue`.
+#[derive(Debug, Clone)]
+pub struct Tape<'a> {
+    input: &'a str,
+    nodes: Vec<Node>,
+}
+
+/// One value on a `Tape`: a cheap handle that decodes only what it is asked
+/// for.
+#[derive(Debug, Clone, Copy)]
+pub struct TapeValue<'t, 'a> {
+    tape: &'t Tape<'a>,
+    index: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Null,
+    Bool,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+// A value's byte range, and the index of the node after everything inside
+// it. Members of an object appear as key and value nodes in turn.
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    kind: Kind,
+    start: usize,
+    end: usize,
+    next: usize,
+}
+
+impl<'a> Tape<'a> {
+    /// Validates `input` as strict JSON, nested no deeper than the
+    /// `max_depth` of `ParserOptions::current()`, and builds its tape.
+    pub fn parse(input: &'a str) -> Result<Self, ParseError> {
+        let options = ParserOptions {
+            max_depth: ParserOptions::current().max_depth,
+            ..ParserOptions::default()
+        };
+        validate_with_options(input, options)?;
+        let bytes = input.as_bytes();
+        let mut nodes: Vec<Node> = Vec::new();
+        let mut open = Vec::new();
+        let mut pos = 0;
+        loop {
+            pos = simd::skip_whitespace(bytes, pos);
+            let Some(&byte) = bytes.get(pos) else { break };
+            let (kind, end) = match byte {
+                b'{' | b'[' => {
+                    open.push(nodes.len());
+                    let kind = if byte == b'{' {
+                        Kind::Object
+                    } else {
+                        Kind::Array
+                    };
+                    nodes.push(Node {
+                        kind,
+                        start: pos,
+                        end: pos,
+                        next: 0,
+                    });
+                    pos += 1;
+                    continue;
+                }
+                b'}' | b']' => {
+                    let index = open.pop().expect("validated input is balanced");
+                    nodes[index].end = pos + 1;
+                    nodes[index].next = nodes.len();
+                    pos += 1;
+                    continue;
+                }
+                b',' | b':' => {
+                    pos += 1;
+                    continue;
+                }
+                b'"' => (
+                    Kind::String,
+                    scan_string(bytes, pos, b'"').expect("validated strings are closed"),
+                ),
+                b't' | b'f' => (Kind::Bool, simd::find_delimiter(bytes, pos)),
+                b'n' => (Kind::Null, simd::find_delimiter(bytes, pos)),
+                _ => (Kind::Number, simd::find_delimiter(bytes, pos)),
+            };
+            nodes.push(Node {
+                kind,
+                start: pos,
+                end,
+                next: nodes.len() + 1,
+            });
+            pos = end;
+        }
+        Ok(Tape { input, nodes })
+    }
+
+    pub fn root(&self) -> TapeValue<'_, 'a> {
+        TapeValue {
+            tape: self,
+            index: 0,
+        }
+    }
+
+    /// Shorthand for `root().get(key)`.
+    pub fn get(&self, key: &str) -> Option<TapeValue<'_, 'a>> {
+        self.root().get(key)
+    }
+
+    /// Shorthand for `root().pointer(pointer)`.
+    pub fn pointer(&self, pointer: &str) -> Option<TapeValue<'_, 'a>> {
+        self.root().pointer(pointer)
+    }
+}
+
+impl<'t, 'a> TapeValue<'t, 'a> {
+    fn node(&self) -> Node {
+        self.tape.nodes[self.index]
+    }
+
+    fn at(&self, index: usize) -> TapeValue<'t, 'a> {
+        TapeValue {
+            tape: self.tape,
+            index,
+        }
+    }
+
+    // The indices of the nodes directly inside a container.
+    fn children(&self) -> impl Iterator<Item = usize> + 't {
+        let nodes = &self.tape.nodes;
+        let first = match self.node().kind {
+            Kind::Array | Kind::Object => Some(self.index + 1),
+            _ => None,
+        };
+        let end = self.node().next;
+        std::iter::successors(first.filter(|&i| i < end), move |&i| {
+            Some(nodes[i].next).filter(|&next| next < end)
+        })
+    }
+
+    /// The value's JSON text, exactly as it appears in the input.
+    pub fn raw(&self) -> &'a str {
+        let node = self.node();
+        &self.tape.input[node.start..node.end]
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.node().kind == Kind::Null
+    }
+
+    pub fn is_array(&self) -> bool {
+        self.node().kind == Kind::Array
+    }
+
+    pub fn is_object(&self) -> bool {
+        self.node().kind == Kind::Object
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.node().kind {
+            Kind::Bool => Some(self.raw() == "true"),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.node().kind {
+            Kind::Number => self.raw().parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// The decoded string, borrowed from the input unless it has escapes.
+    pub fn as_str(&self) -> Option<Cow<'a, str>> {
+        match self.node().kind {
+            Kind::String => Some(decode(self.raw())),
+            _ => None,
+        }
+    }
+
+    /// The number of elements or members; 0 for scalars.
+    pub fn len(&self) -> usize {
+        let children = self.children().count();
+        match self.node().kind {
+            Kind::Object => children / 2,
+            _ => children,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The element at `index` of an array.
+    pub fn get_index(&self, index: usize) -> Option<TapeValue<'t, 'a>> {
+        if !self.is_array() {
+            return None;
+        }
+        self.children().nth(index).map(|i| self.at(i))
+    }
+
+    /// The member named `key` of an object. If the key repeats, the last
+    /// one wins, as when parsing.
+    pub fn get(&self, key: &str) -> Option<TapeValue<'t, 'a>> {
+        self.members()
+            .filter(|(name, _)| name == key)
+            .last()
+            .map(|(_, value)| value)
+    }
+
+    /// Looks up a value by JSON Pointer.
+    pub fn pointer(&self, pointer: &str) -> Option<TapeValue<'t, 'a>> {
+        if pointer.is_empty() {
+            return Some(*self);
+        }
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .map(unescape_token)
+            .try_fold(*self, |value, token| match value.node().kind {
+                Kind::Object => value.get(&token),
+                Kind::Array => value.get_index(token.parse().ok()?),
+                _ => None,
+            })
+    }
+
+    /// The elements of an array; nothing for other values.
+    pub fn elements(&self) -> impl Iterator<Item = TapeValue<'t, 'a>> + 't {
+        let this = *self;
+        self.children()
+            .filter(move |_| this.is_array())
+            .map(move |i| this.at(i))
+    }
+
+    /// The members of an object in document order, with decoded keys;
+    /// nothing for other values.
+    pub fn members(&self) -> impl Iterator<Item = (Cow<'a, str>, TapeValue<'t, 'a>)> + 't {
+        let this = *self;
+        let mut children = self.children().filter(move |_| this.is_object());
+        std::iter::from_fn(move || {
+            let key = this.at(children.next()?);
+            let value = this.at(children.next()?);
+            Some((decode(key.raw()), value))
+        })
+    }
+
+    /// Parses the value into an owned tree.
+    pub fn to_value(&self) -> JsonValue {
+        parse_with_options(self.raw(), ParserOptions::default()).expect("the tape was validated")
+    }
+}
+
+// Decodes a validated string literal, borrowing it when it has no escapes.
+fn decode(literal: &str) -> Cow<'_, str> {
+    let inner = &literal[1..literal.len() - 1];
+    if !inner.contains('\\') {
+        return Cow::Borrowed(inner);
+    }
+    match parse_with_options(literal, ParserOptions::default()) {
+        Ok(JsonValue::String(s)) => Cow::Owned(s.into_string()),
+        _ => unreachable!("the tape was validated"),
+    }
+}
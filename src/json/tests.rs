@@ -1,7 +1,41 @@
 #[cfg(test)]
+#[allow(clippy::module_inception, clippy::approx_constant)]
 mod tests {
-    use crate::json::{JsonValue, ParseError, parse, stringify};
-    use std::collections::HashMap;
+    use crate::json::{
+        BytesError, CacheStats, CheckError, CheckedDocument, ColorScheme, CsvOptions,
+        DEFAULT_MAX_DEPTH, DecodeError, DecodeReader, Document, DocumentIndex, DuplicateKeyPolicy,
+        Encoding, EqOptions, EventReader, FaultConfig, FaultInjector, HalResource, HtmlOptions,
+        IncrementalDocument, InputDecoder, JsonApiDocument, JsonArray, JsonEvent, JsonObject,
+        JsonString, JsonValue, JsonView, JsonVisitor, JsonWriter, KeyFilter, KeyInterner,
+        Latin1Decoder, Map, MergeStrategy, MetricsHook, NewlineStyle, NonFinitePolicy, ParseCache,
+        ParseError, ParseMetrics, ParserOptions, ParserPool, PatchOp, Position, QueryError,
+        QuoteStyle, RawJson, ReadError, RefError, RefResolver, ResourceIdentifier, SchemaViolation,
+        SharedJsonValue, SnapshotError, StringifyError, StringifyOptions, SymbolKind, TableDecoder,
+        Tape, TextEdit, TrackedValue, Utf8Decoder, Utf8Policy, Utf16Decoder, WalkOrder,
+        WriterError, apply_patch, decode_detect_encoding, decode_to_string, detect_encoding,
+        detect_style, diff, embed_nested_json, escape_pointer_token, expand_nested_json, extract,
+        log_value, merge3, minify, minify_with_options, outline, parse, parse_bytes,
+        parse_bytes_detect_encoding, parse_csv, parse_decoded, parse_documents, parse_i64_exact,
+        parse_integer_exact, parse_json5, parse_jsonc, parse_pooled, parse_reader, parse_u64_exact,
+        parse_ubjson, parse_urlencoded, parse_with_options, parse_with_recovery,
+        parse_with_recovery_options, redact, render_html, reset_defaults, schema_differences,
+        schema_violations, self_check, set_default_stringify_options, sort_arrays_by,
+        sort_keys_recursive, split_documents, stringify, stringify_canonical, stringify_colored,
+        stringify_json5, stringify_pretty, stringify_with, stringify_within_budget, to_ast_json,
+        to_csv, to_ubjson, to_urlencoded, validate, validate_with_options, with_parser_options,
+        with_stringify_options,
+    };
+    use std::borrow::Cow;
+    use std::collections::{BTreeSet, HashMap, HashSet};
+    use std::sync::{Arc, Mutex};
+
+    fn pos(offset: usize, line: usize, column: usize) -> Position {
+        Position {
+            offset,
+            line,
+            column,
+        }
+    }
 
     #[test]
     fn test_parse_null() {
@@ -42,13 +76,13 @@ mod tests {
     #[test]
     fn test_parse_string_simple() {
         let result = parse(r#""hello""#);
-        assert_eq!(result, Ok(JsonValue::String("hello".to_string())));
+        assert_eq!(result, Ok(JsonValue::String("hello".into())));
     }
 
     #[test]
     fn test_parse_string_with_escape() {
         let result = parse(r#""hello \"world\"""#);
-        assert_eq!(result, Ok(JsonValue::String("hello \"world\"".to_string())));
+        assert_eq!(result, Ok(JsonValue::String("hello \"world\"".into())));
     }
 
     #[test]
@@ -82,24 +116,24 @@ mod tests {
     #[test]
     fn test_parse_object_empty() {
         let result = parse("{}");
-        assert_eq!(result, Ok(JsonValue::Object(HashMap::new())));
+        assert_eq!(result, Ok(JsonValue::Object(Box::default())));
     }
 
     #[test]
     fn test_parse_object_simple() {
         let result = parse(r#"{"key": "value"}"#);
-        let mut expected = HashMap::new();
-        expected.insert("key".to_string(), JsonValue::String("value".to_string()));
-        assert_eq!(result, Ok(JsonValue::Object(expected)));
+        let mut expected = Map::new();
+        expected.insert("key".to_string(), JsonValue::String("value".into()));
+        assert_eq!(result, Ok(JsonValue::Object(Box::new(expected))));
     }
 
     #[test]
     fn test_parse_object_multiple() {
         let result = parse(r#"{"a": 1, "b": 2}"#);
-        let mut expected = HashMap::new();
+        let mut expected = Map::new();
         expected.insert("a".to_string(), JsonValue::Number(1.0));
         expected.insert("b".to_string(), JsonValue::Number(2.0));
-        assert_eq!(result, Ok(JsonValue::Object(expected)));
+        assert_eq!(result, Ok(JsonValue::Object(Box::new(expected))));
     }
 
     #[test]
@@ -140,16 +174,13 @@ mod tests {
 
     #[test]
     fn test_stringify_string() {
-        assert_eq!(
-            stringify(&JsonValue::String("hello".to_string())),
-            r#""hello""#
-        );
+        assert_eq!(stringify(&JsonValue::String("hello".into())), r#""hello""#);
     }
 
     #[test]
     fn test_stringify_string_with_quotes() {
         assert_eq!(
-            stringify(&JsonValue::String("hello \"world\"".to_string())),
+            stringify(&JsonValue::String("hello \"world\"".into())),
             r#""hello \"world\"""#
         );
     }
@@ -166,9 +197,9 @@ mod tests {
 
     #[test]
     fn test_stringify_object() {
-        let mut obj = HashMap::new();
-        obj.insert("key".to_string(), JsonValue::String("value".to_string()));
-        let value = JsonValue::Object(obj);
+        let mut obj = Map::new();
+        obj.insert("key".to_string(), JsonValue::String("value".into()));
+        let value = JsonValue::Object(Box::new(obj));
         assert_eq!(stringify(&value), r#"{"key":"value"}"#);
     }
 
@@ -190,7 +221,3424 @@ mod tests {
     #[test]
     fn test_parse_error_unexpected_end() {
         let result = parse("{");
-        assert!(matches!(result, Err(ParseError::UnexpectedEndOfInput)));
+        assert!(matches!(result, Err(ParseError::UnexpectedEndOfInput(_))));
+    }
+
+    #[test]
+    fn test_parse_radix_integers_lenient() {
+        let options = ParserOptions::lenient();
+        assert_eq!(
+            parse_with_options("0xFF", options.clone()),
+            Ok(JsonValue::Number(255.0))
+        );
+        assert_eq!(
+            parse_with_options("0o17", options.clone()),
+            Ok(JsonValue::Number(15.0))
+        );
+        assert_eq!(
+            parse_with_options("[0b1010, -0x10]", options),
+            Ok(JsonValue::Array(vec![
+                JsonValue::Number(10.0),
+                JsonValue::Number(-16.0),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_parse_radix_integers_rejected_by_default() {
+        assert!(parse("0xFF").is_err());
+        let options = ParserOptions {
+            allow_hex_numbers: true,
+            ..ParserOptions::default()
+        };
+        assert!(parse_with_options("0b101", options.clone()).is_err());
+        assert!(matches!(
+            parse_with_options("0x", options),
+            Err(ParseError::InvalidNumber(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_parse_trailing_commas_lenient() {
+        let options = ParserOptions {
+            allow_trailing_commas: true,
+            ..ParserOptions::default()
+        };
+        assert_eq!(
+            parse_with_options("[1, 2, 3,]", options.clone()),
+            Ok(JsonValue::Array(vec![
+                JsonValue::Number(1.0),
+                JsonValue::Number(2.0),
+                JsonValue::Number(3.0),
+            ]))
+        );
+        let mut expected = Map::new();
+        expected.insert("a".to_string(), JsonValue::Number(1.0));
+        assert_eq!(
+            parse_with_options(r#"{"a": 1, }"#, options.clone()),
+            Ok(JsonValue::Object(Box::new(expected)))
+        );
+        assert!(parse_with_options("[1,,]", options.clone()).is_err());
+        assert!(parse_with_options("[,]", options).is_err());
+    }
+
+    #[test]
+    fn test_parse_trailing_commas_rejected_by_default() {
+        assert!(matches!(
+            parse("[1, 2,]"),
+            Err(ParseError::UnexpectedChar(']', _))
+        ));
+        assert!(parse(r#"{"a": 1,}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_jsonc_comments() {
+        let input = r#"{
+            // editor settings
+            "tabSize": 4, /* spaces */
+            "rulers": [80, 120,],
+        }"#;
+        let value = parse_jsonc(input).unwrap();
+        assert_eq!(value.get("tabSize"), Some(&JsonValue::Number(4.0)));
+        assert_eq!(
+            value.get("rulers"),
+            Some(&JsonValue::Array(vec![
+                JsonValue::Number(80.0),
+                JsonValue::Number(120.0),
+            ]))
+        );
+        assert_eq!(parse_jsonc("1 // trailing"), Ok(JsonValue::Number(1.0)));
+    }
+
+    #[test]
+    fn test_parse_comments_rejected() {
+        assert!(parse("// comment\n1").is_err());
+        assert_eq!(
+            parse_jsonc("1 /* unterminated"),
+            Err(ParseError::UnexpectedChar('/', pos(2, 1, 3)))
+        );
+    }
+
+    #[test]
+    fn test_split_documents() {
+        let input = "---\n{\"a\": 1}\n---\n[1, 2]\n--- true\n";
+        let docs: Vec<Document> = split_documents(input).collect();
+        assert_eq!(docs.len(), 3);
+        assert_eq!(docs[0].line, 1);
+        assert_eq!(docs[1].line, 3);
+        assert_eq!(docs[2].line, 5);
+        assert_eq!(
+            &input[docs[2].offset..docs[2].offset + docs[2].text.len()],
+            docs[2].text
+        );
+        assert_eq!(docs[2].parse(), Ok(JsonValue::Bool(true)));
+
+        let results = parse_documents("1\n---\n{oops}\n");
+        assert_eq!(results[0], Ok(JsonValue::Number(1.0)));
+        assert!(results[1].is_err());
+    }
+
+    #[test]
+    fn test_split_documents_ignores_dashes_inside_lines() {
+        let input = "\"---\"\n  ---\n";
+        let docs: Vec<Document> = split_documents(input).collect();
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0].text, input);
+    }
+
+    #[test]
+    fn test_parse_json5() {
+        let input = r#"{
+            // JSON5 config
+            name: 'rust-json',
+            $version: +2.,
+            ratio: .5,
+            mask: 0xFF,
+            limit: -Infinity,
+            quote: 'it\'s',
+            long: "line one \
+line two",
+            escapes: '\x41\v\0',
+            "quoted key": [1, 2,],
+        }"#;
+        let value = parse_json5(input).unwrap();
+        assert_eq!(
+            value.get("name"),
+            Some(&JsonValue::String("rust-json".into()))
+        );
+        assert_eq!(value.get("$version"), Some(&JsonValue::Number(2.0)));
+        assert_eq!(value.get("ratio"), Some(&JsonValue::Number(0.5)));
+        assert_eq!(value.get("mask"), Some(&JsonValue::Number(255.0)));
+        assert_eq!(
+            value.get("limit"),
+            Some(&JsonValue::Number(f64::NEG_INFINITY))
+        );
+        assert_eq!(value.get("quote").and_then(|v| v.as_str()), Some("it's"));
+        assert_eq!(
+            value.get("long").and_then(|v| v.as_str()),
+            Some("line one line two")
+        );
+        assert_eq!(
+            value.get("escapes").and_then(|v| v.as_str()),
+            Some("A\x0b\0")
+        );
+        assert!(value.get("quoted key").is_some());
+        assert!(parse_json5("NaN").unwrap().as_f64().unwrap().is_nan());
+        assert!(parse_json5("0o17").is_err());
+    }
+
+    #[test]
+    fn test_parse_unicode_before_keyword() {
+        let result = parse(r#"["é", null, 1]"#);
+        assert_eq!(
+            result,
+            Ok(JsonValue::Array(vec![
+                JsonValue::String("é".into()),
+                JsonValue::Null,
+                JsonValue::Number(1.0),
+            ]))
+        );
+        assert_eq!(
+            parse_json5("{ñame: 1}").unwrap().get("ñame"),
+            Some(&JsonValue::Number(1.0))
+        );
+    }
+
+    #[test]
+    fn test_parse_object_truncated() {
+        assert_eq!(
+            parse(r#"{"a""#),
+            Err(ParseError::UnexpectedEndOfInput(pos(4, 1, 5)))
+        );
+        assert_eq!(
+            parse("{1"),
+            Err(ParseError::UnexpectedChar('1', pos(1, 1, 2)))
+        );
+    }
+
+    #[test]
+    fn test_stringify_json5() {
+        let mut obj = Map::new();
+        obj.insert("plain_key".to_string(), JsonValue::Number(f64::INFINITY));
+        assert_eq!(
+            stringify_json5(&JsonValue::Object(Box::new(obj))),
+            "{plain_key:Infinity}"
+        );
+
+        let mut obj = Map::new();
+        obj.insert("needs quotes".to_string(), JsonValue::Number(f64::NAN));
+        assert_eq!(
+            stringify_json5(&JsonValue::Object(Box::new(obj))),
+            r#"{"needs quotes":NaN}"#
+        );
+
+        let value = parse_json5("{a: [1, 'two', null]}").unwrap();
+        assert_eq!(parse_json5(&stringify_json5(&value)), Ok(value));
+    }
+
+    #[test]
+    fn test_log_value_truncates() {
+        let value = parse(r#"{"message": "hello world"}"#).unwrap();
+        assert_eq!(
+            log_value(&value).to_string(),
+            r#"{"message":"hello world"}"#
+        );
+        assert_eq!(
+            log_value(&value).max_len(12).to_string(),
+            r#"{"message":"…"#
+        );
+        assert_eq!(format!("{:?}", log_value(&JsonValue::Null)), "null");
+    }
+
+    #[test]
+    fn test_metrics_hook() {
+        let recorded: Arc<Mutex<Vec<ParseMetrics>>> = Arc::default();
+        let sink = recorded.clone();
+        let options = ParserOptions {
+            metrics_hook: Some(MetricsHook::new(move |m| {
+                sink.lock().unwrap().push(m.clone())
+            })),
+            ..ParserOptions::default()
+        };
+
+        let input = r#"{"a": [1, {"b": "c"}]}"#;
+        assert!(parse_with_options(input, options.clone()).is_ok());
+        assert!(parse_with_options("[[1,", options).is_err());
+
+        let recorded = recorded.lock().unwrap();
+        assert_eq!(recorded.len(), 2);
+        assert!(recorded[0].success);
+        assert_eq!(recorded[0].bytes, input.len());
+        assert_eq!(recorded[0].peak_depth, 3);
+        // Three containers plus the keys "a" and "b"; the string "c" is
+        // short enough to be stored inline.
+        assert_eq!(recorded[0].allocations, 5);
+        assert!(!recorded[1].success);
+        assert_eq!(recorded[1].peak_depth, 2);
+    }
+
+    #[test]
+    fn test_strict_numbers() {
+        for valid in ["0", "-0", "10", "0.5", "-1.25e+3", "1E-2", "0e0"] {
+            assert!(parse(valid).is_ok(), "{} should parse", valid);
+        }
+        for invalid in ["0123", "-012", "1.", "-", "1e", "1e+", "-.5", "01.5"] {
+            assert_eq!(
+                parse(invalid),
+                Err(ParseError::InvalidNumber(invalid.to_string(), pos(0, 1, 1))),
+                "{} should be rejected",
+                invalid
+            );
+        }
+        assert_eq!(
+            parse(".5"),
+            Err(ParseError::UnexpectedChar('.', pos(0, 1, 1)))
+        );
+        assert_eq!(
+            parse("+1"),
+            Err(ParseError::UnexpectedChar('+', pos(0, 1, 1)))
+        );
+    }
+
+    #[test]
+    fn test_relaxed_numbers() {
+        let options = ParserOptions {
+            allow_relaxed_numbers: true,
+            allow_leading_zeros: true,
+            ..ParserOptions::default()
+        };
+        assert_eq!(
+            parse_with_options("0123", options.clone()),
+            Ok(JsonValue::Number(123.0))
+        );
+        assert_eq!(
+            parse_with_options("1.", options.clone()),
+            Ok(JsonValue::Number(1.0))
+        );
+        assert!(parse_with_options("-", options.clone()).is_err());
+        assert!(parse_with_options(".", options).is_err());
+    }
+
+    #[test]
+    fn test_strict_strings_and_whitespace() {
+        assert_eq!(
+            parse("\"a\tb\""),
+            Err(ParseError::UnescapedControlChar('\t', pos(2, 1, 3)))
+        );
+        assert!(parse("\"a\u{7f}b\"").is_ok());
+        assert!(parse("\u{a0}1").is_err());
+
+        let options = ParserOptions {
+            allow_control_characters: true,
+            allow_unicode_whitespace: true,
+            ..ParserOptions::default()
+        };
+        assert_eq!(
+            parse_with_options("\u{a0}\"a\tb\"", options),
+            Ok(JsonValue::String("a\tb".into()))
+        );
+    }
+
+    #[test]
+    fn test_parse_surrogate_pairs() {
+        assert_eq!(
+            parse(r#""\ud83d\ude00""#),
+            Ok(JsonValue::String("\u{1f600}".into()))
+        );
+        assert_eq!(
+            parse(r#""a\uD834\uDD1Eb""#),
+            Ok(JsonValue::String("a\u{1d11e}b".into()))
+        );
+        assert_eq!(
+            parse(r#""\ud83d""#),
+            Err(ParseError::InvalidUnicodeEscape(
+                "d83d".to_string(),
+                pos(1, 1, 2)
+            ))
+        );
+        assert_eq!(
+            parse(r#""\ude00\ud83d""#),
+            Err(ParseError::InvalidUnicodeEscape(
+                "de00".to_string(),
+                pos(1, 1, 2)
+            ))
+        );
+        assert!(parse(r#""\ud83dA""#).is_err());
+        assert_eq!(
+            parse(r#""\u00zz""#),
+            Err(ParseError::InvalidUnicodeEscape(
+                "00z".to_string(),
+                pos(1, 1, 2)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_lone_surrogates_replaced() {
+        let options = ParserOptions {
+            allow_lone_surrogates: true,
+            ..ParserOptions::default()
+        };
+        assert_eq!(
+            parse_with_options(r#""\ud83dA""#, options),
+            Ok(JsonValue::String("\u{fffd}A".into()))
+        );
+    }
+
+    // Hands out the input a few bytes at a time to exercise chunk boundaries.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl std::io::Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.0.len().min(buf.len()).min(3);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_decode_latin1() {
+        let bytes = b"{\"caf\xe9\": \"na\xefve\"}";
+        let text = decode_to_string(&bytes[..], Latin1Decoder).unwrap();
+        assert_eq!(
+            parse(&text).unwrap().get("caf\u{e9}"),
+            Some(&JsonValue::String("na\u{ef}ve".into()))
+        );
+    }
+
+    #[test]
+    fn test_decode_utf8_across_chunks() {
+        let input = "[\"\u{1f600}\u{e9}\"]";
+        let text = decode_to_string(Trickle(input.as_bytes()), Utf8Decoder).unwrap();
+        assert_eq!(text, input);
+
+        let err = decode_to_string(&b"[\"\xff\"]"[..], Utf8Decoder).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        let truncated = decode_to_string(&b"\"\xe2\x82"[..], Utf8Decoder).unwrap_err();
+        assert_eq!(truncated.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_decode_table_and_custom_decoder() {
+        let mut table = [' '; 256];
+        table[0x01] = '1';
+        table[0x02] = '[';
+        table[0x03] = ']';
+        let text = decode_to_string(&[2u8, 1, 3][..], TableDecoder::new(table)).unwrap();
+        assert_eq!(
+            parse(&text),
+            Ok(JsonValue::Array(vec![JsonValue::Number(1.0)]))
+        );
+
+        struct Rejecting;
+        impl InputDecoder for Rejecting {
+            fn decode(
+                &mut self,
+                _: &[u8],
+                offset: usize,
+                _: &mut String,
+            ) -> Result<usize, DecodeError> {
+                Err(DecodeError { offset })
+            }
+        }
+        let mut reader = DecodeReader::new(&b"1"[..], Rejecting);
+        let mut text = String::new();
+        assert!(std::io::Read::read_to_string(&mut reader, &mut text).is_err());
+    }
+
+    #[test]
+    fn test_parse_reader_agrees_with_parse() {
+        let streamed = |input: &str| match parse_reader(Trickle(input.as_bytes())) {
+            Ok(value) => Ok(value),
+            Err(ReadError::Parse(e)) => Err(e),
+            Err(ReadError::Io(e)) => panic!("{}", e),
+        };
+        let inputs = [
+            r#"{"a": [1, -2.5e3, true, null], "b": {"c": "é\n😀"}}"#,
+            "  \"caf\u{e9} \u{1f600}\"  ",
+            "[1,\n 2,\n tru]",
+            "{\"a\": 1 \"b\": 2}",
+            "[1, 2",
+            "[1] 2",
+            "{\"a\": 1, \"a\": 2}",
+            "01",
+            "[1,]",
+        ];
+        for input in inputs {
+            assert_eq!(streamed(input), parse(input), "{}", input);
+        }
+        let lenient = "// c\n{a: [0x1F, 'b', .5,], /* d */ 'e': NaN,}";
+        with_parser_options(ParserOptions::lenient(), || {
+            assert_eq!(
+                format!("{:?}", streamed(lenient)),
+                format!("{:?}", parse(lenient))
+            );
+        });
+        let options = ParserOptions {
+            max_depth: Some(2),
+            materialize_depth: Some(1),
+            ..ParserOptions::default()
+        };
+        with_parser_options(options, || {
+            assert_eq!(streamed("[[1, [2]], {}]"), parse("[[1, [2]], {}]"));
+        });
+
+        assert!(matches!(
+            parse_reader(&b"[\"\xff\"]"[..]),
+            Err(ReadError::Io(e)) if e.kind() == std::io::ErrorKind::InvalidData
+        ));
+    }
+
+    #[test]
+    fn test_parse_decoded_utf16() {
+        let text = "{\"name\": \"caf\u{e9} \u{1f600}\", \"n\": [1, 2]}";
+        let bytes: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let decoder = Utf16Decoder { big_endian: false };
+        assert_eq!(
+            parse_decoded(Trickle(&bytes), decoder).unwrap(),
+            parse(text).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_duplicate_key_policy() {
+        let input = r#"{"a": 1, "b": 2, "a": 3}"#;
+        assert_eq!(
+            parse(input).unwrap().get("a"),
+            Some(&JsonValue::Number(3.0))
+        );
+
+        let options = |duplicate_keys| ParserOptions {
+            duplicate_keys,
+            ..ParserOptions::default()
+        };
+        let first = parse_with_options(input, options(DuplicateKeyPolicy::FirstWins)).unwrap();
+        assert_eq!(first.get("a"), Some(&JsonValue::Number(1.0)));
+        assert_eq!(
+            parse_with_options(input, options(DuplicateKeyPolicy::Error)),
+            Err(ParseError::DuplicateKey("a".to_string(), pos(17, 1, 18)))
+        );
+        assert!(
+            parse_with_options(r#"{"a": {"a": 1}}"#, options(DuplicateKeyPolicy::Error)).is_ok()
+        );
+    }
+
+    fn collect_events(reader: &mut EventReader) -> Result<Vec<JsonEvent>, ParseError> {
+        let mut events = Vec::new();
+        while let Some(event) = reader.next_event()? {
+            events.push(event);
+        }
+        Ok(events)
+    }
+
+    #[test]
+    fn test_event_reader() {
+        let mut reader = EventReader::new(r#"{"a": [1, true], "b": {}}"#);
+        assert_eq!(
+            collect_events(&mut reader),
+            Ok(vec![
+                JsonEvent::StartObject,
+                JsonEvent::Key("a".to_string()),
+                JsonEvent::StartArray,
+                JsonEvent::Value(JsonValue::Number(1.0)),
+                JsonEvent::Value(JsonValue::Bool(true)),
+                JsonEvent::EndArray,
+                JsonEvent::Key("b".to_string()),
+                JsonEvent::StartObject,
+                JsonEvent::EndObject,
+                JsonEvent::EndObject,
+            ])
+        );
+        assert!(collect_events(&mut EventReader::new("[1 2]")).is_err());
+        assert!(collect_events(&mut EventReader::new("[1,]")).is_err());
+        assert!(collect_events(&mut EventReader::new("[1] 2")).is_err());
+        assert_eq!(
+            collect_events(&mut EventReader::new(r#"{"a" 1}"#)),
+            Err(ParseError::UnexpectedChar('1', pos(5, 1, 6)))
+        );
+    }
+
+    #[test]
+    fn test_event_reader_skip_value() {
+        let input = r#"[{"id": 1, "blob": {"x": ["]", "\"}", [[]]]}, "n": -1.5e3}, 2]"#;
+        let mut reader = EventReader::new(input);
+        assert_eq!(reader.next_event(), Ok(Some(JsonEvent::StartArray)));
+        assert_eq!(reader.next_event(), Ok(Some(JsonEvent::StartObject)));
+        assert_eq!(
+            reader.next_event(),
+            Ok(Some(JsonEvent::Key("id".to_string())))
+        );
+        assert_eq!(
+            reader.next_event(),
+            Ok(Some(JsonEvent::Value(JsonValue::Number(1.0))))
+        );
+        // Skips the whole "blob" member, then the value of "n".
+        assert_eq!(reader.skip_value(), Ok(()));
+        assert_eq!(
+            reader.next_event(),
+            Ok(Some(JsonEvent::Key("n".to_string())))
+        );
+        assert_eq!(reader.skip_value(), Ok(()));
+        assert!(reader.skip_value().is_err());
+        assert_eq!(reader.next_event(), Ok(Some(JsonEvent::EndObject)));
+        assert_eq!(reader.skip_value(), Ok(()));
+        assert_eq!(reader.next_event(), Ok(Some(JsonEvent::EndArray)));
+        assert_eq!(reader.next_event(), Ok(None));
+
+        let mut reader = EventReader::new("[[1, 2");
+        assert_eq!(reader.next_event(), Ok(Some(JsonEvent::StartArray)));
+        assert_eq!(
+            reader.skip_value(),
+            Err(ParseError::UnexpectedEndOfInput(pos(6, 1, 7)))
+        );
     }
-}
 
+    #[test]
+    fn test_key_filter() {
+        let input = r#"{"id": 1, "blob": {"x": [1, "]"]}, "meta": {"id": 2, "tags": []}}"#;
+        let options = |key_filter| ParserOptions {
+            key_filter,
+            ..ParserOptions::default()
+        };
+
+        let value = parse_with_options(input, options(KeyFilter::include(["id", "meta"]))).unwrap();
+        assert_eq!(value.get("blob"), None);
+        assert_eq!(value.get("id"), Some(&JsonValue::Number(1.0)));
+        let meta = value.get("meta").unwrap();
+        assert_eq!(meta.get("id"), Some(&JsonValue::Number(2.0)));
+        assert_eq!(meta.get("tags"), None);
+
+        let value = parse_with_options(input, options(KeyFilter::exclude(["blob"]))).unwrap();
+        assert_eq!(value.as_object().unwrap().len(), 2);
+        assert!(value.get("meta").unwrap().get("tags").is_some());
+
+        assert!(
+            parse_with_options(r#"{"blob": [1, 2}"#, options(KeyFilter::exclude(["blob"])))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_materialize_depth() {
+        let options = ParserOptions {
+            materialize_depth: Some(1),
+            ..ParserOptions::default()
+        };
+        let value = parse_with_options(r#"{"a": {"b": [1, 2]}, "c": 3}"#, options).unwrap();
+        assert_eq!(
+            value.get("a"),
+            Some(&JsonValue::String((r#"{"b": [1, 2]}"#.to_string()).into()))
+        );
+        assert_eq!(value.get("c"), Some(&JsonValue::Number(3.0)));
+
+        let options = ParserOptions {
+            materialize_depth: Some(0),
+            ..ParserOptions::default()
+        };
+        assert_eq!(
+            parse_with_options("[1, [2]]", options.clone()),
+            Ok(JsonValue::String("[1, [2]]".into()))
+        );
+        assert_eq!(parse_with_options("7", options), Ok(JsonValue::Number(7.0)));
+    }
+
+    #[test]
+    fn test_parse_error_line_and_column() {
+        let input = "{\n  \"a\": 1,\n  \"b\": tru\n}";
+        let err = parse(input).unwrap_err();
+        assert_eq!(err, ParseError::UnexpectedChar('t', pos(19, 3, 8)));
+        assert_eq!(err.position().line, 3);
+        assert_eq!(
+            err.to_string(),
+            "unexpected character 't' at line 3, column 8"
+        );
+
+        let err = parse("[\"é\", 01]").unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::InvalidNumber("01".to_string(), pos(7, 1, 7))
+        );
+
+        let boxed: Box<dyn std::error::Error> = Box::new(parse("[").unwrap_err());
+        assert_eq!(
+            boxed.to_string(),
+            "unexpected end of input at line 1, column 2"
+        );
+    }
+
+    #[test]
+    fn test_document_errors_use_stream_positions() {
+        let input = "[1]\n--- {\"a\": x}\n";
+        let results = parse_documents(input);
+        assert_eq!(
+            results[1],
+            Err(ParseError::UnexpectedChar('x', pos(14, 2, 11)))
+        );
+    }
+
+    #[test]
+    fn test_render_error() {
+        let input = "{\n  \"a\": 1,\n  \"b\": tru\n}";
+        let rendered = parse(input).unwrap_err().render(input);
+        assert_eq!(
+            rendered,
+            "error: unexpected character 't' at line 3, column 8\n  |\n3 |   \"b\": tru\n  |        ^\n  = help: expected a value\n"
+        );
+    }
+
+    #[test]
+    fn test_render_error_hints() {
+        let hint = |input: &str| {
+            let rendered = parse(input).unwrap_err().render(input);
+            rendered.lines().last().unwrap().to_string()
+        };
+        assert_eq!(hint("[1, 2,]"), "  = help: remove the trailing comma");
+        assert_eq!(
+            hint(r#"{"a": 1, 2}"#),
+            "  = help: expected a double-quoted key"
+        );
+        assert_eq!(hint(r#"{"a" 1}"#), "  = help: expected ':' after the key");
+        assert_eq!(hint(r#"{"a": "x" "y"}"#), "  = help: expected ',' or '}'");
+        assert_eq!(hint("[1 2]"), "  = help: expected ',' or ']'");
+        assert_eq!(
+            hint("[1] 2"),
+            "  = help: expected end of input after the value"
+        );
+        assert!(hint("[1, ").contains("unclosed"));
+    }
+
+    #[test]
+    fn test_pointer() {
+        let value = parse(r#"{"users": [{"name": "Alice"}], "a/b": {"~k": 1}, "": 2}"#).unwrap();
+        assert_eq!(value.pointer(""), Some(&value));
+        assert_eq!(
+            value.pointer("/users/0/name"),
+            Some(&JsonValue::String("Alice".into()))
+        );
+        assert_eq!(value.pointer("/a~1b/~0k"), Some(&JsonValue::Number(1.0)));
+        assert_eq!(value.pointer("/"), Some(&JsonValue::Number(2.0)));
+        assert_eq!(value.pointer("/users/01"), None);
+        assert_eq!(value.pointer("/users/1"), None);
+        assert_eq!(value.pointer("users"), None);
+        assert_eq!(escape_pointer_token("a/b~c"), "a~1b~0c");
+    }
+
+    #[test]
+    fn test_document_index() {
+        let value = parse(r#"{"users": [{"name": "Alice"}, {"name": "Bob"}], "a/b": 1}"#).unwrap();
+        let index = DocumentIndex::new(&value);
+        assert_eq!(index.len(), 7);
+        assert_eq!(index.get(""), Some(&value));
+        assert_eq!(
+            index.get("/users/1/name"),
+            Some(&JsonValue::String("Bob".into()))
+        );
+        assert_eq!(index.get("/a~1b"), Some(&JsonValue::Number(1.0)));
+        assert!(!index.contains("/users/2"));
+        for pointer in index.pointers() {
+            assert_eq!(value.pointer(pointer), index.get(pointer));
+        }
+    }
+
+    #[test]
+    fn test_eq_by_schema() {
+        let schema = parse(
+            r#"{
+                "properties": {
+                    "id": {},
+                    "price": {"multipleOf": 0.01},
+                    "status": {"default": "active"},
+                    "tags": {"items": {"properties": {"name": {}}}}
+                }
+            }"#,
+        )
+        .unwrap();
+        let expected = parse(r#"{"id": 1, "price": 9.99, "tags": [{"name": "a"}]}"#).unwrap();
+        let actual = parse(
+            r#"{"id": 1, "price": 9.9900001, "status": "active", "extra": true,
+                "tags": [{"name": "a", "color": "red"}]}"#,
+        )
+        .unwrap();
+        assert!(expected.eq_by_schema(&actual, &schema));
+        assert_ne!(expected, actual);
+
+        let changed =
+            parse(r#"{"id": 2, "price": 9.98, "status": "disabled", "tags": [{"name": "b"}]}"#)
+                .unwrap();
+        assert_eq!(
+            schema_differences(&expected, &changed, &schema),
+            vec!["/id", "/price", "/status", "/tags/0/name"]
+        );
+    }
+
+    #[test]
+    fn test_parse_with_recovery() {
+        let input = r#"{"a": tru, "b": [1, 2 3, ], "c" 4, "d": {"x": }, "e": "ok"}"#;
+        let recovered = parse_with_recovery(input);
+        let messages: Vec<String> = recovered
+            .diagnostics
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "unexpected character 't' at line 1, column 7",
+                "unexpected character '3' at line 1, column 23",
+                "unexpected character ']' at line 1, column 26",
+                "unexpected character '4' at line 1, column 33",
+                "unexpected character '}' at line 1, column 47",
+            ]
+        );
+
+        let value = recovered.value;
+        assert_eq!(value.get("a"), Some(&JsonValue::Null));
+        assert_eq!(
+            value.get("b"),
+            Some(&JsonValue::Array(vec![
+                JsonValue::Number(1.0),
+                JsonValue::Number(2.0),
+                JsonValue::Number(3.0),
+            ]))
+        );
+        assert_eq!(value.get("c"), Some(&JsonValue::Number(4.0)));
+        assert_eq!(value.get("d"), Some(&JsonValue::Object(Box::default())));
+        assert_eq!(value.get("e"), Some(&JsonValue::String("ok".into())));
+    }
+
+    #[test]
+    fn test_parse_with_recovery_truncated_and_clean() {
+        let recovered = parse_with_recovery(r#"[1, {"a": [2"#);
+        assert_eq!(recovered.diagnostics.len(), 3);
+        assert!(matches!(
+            recovered.diagnostics[0],
+            ParseError::UnexpectedEndOfInput(_)
+        ));
+
+        let recovered = parse_with_recovery("[1, 2]");
+        assert!(recovered.is_clean());
+        assert_eq!(Ok(recovered.value), parse("[1, 2]"));
+
+        let recovered = parse_with_recovery("[1]]");
+        assert_eq!(recovered.diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_stringify_canonical() {
+        let value = parse(
+            r#"{"b": [1e21, 1.5e-7, -0.0, 0.000001, 100, 123.456], "a": "é\u001f\"", "€": 1, "😀": 2, "דּ": 3}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            stringify_canonical(&value),
+            Ok("{\"a\":\"\u{e9}\\u001f\\\"\",\"b\":[1e+21,1.5e-7,0,0.000001,100,123.456],\"\u{20ac}\":1,\"\u{1f600}\":2,\"\u{fb33}\":3}".to_string())
+        );
+        assert!(matches!(
+            stringify_canonical(&JsonValue::Number(f64::INFINITY)),
+            Err(StringifyError::NonFiniteNumber(_))
+        ));
+    }
+
+    #[test]
+    fn test_iter_sorted() {
+        let value = parse(r#"{"b": 2, "c": 3, "a": 1, "B": 0}"#).unwrap();
+        let keys: Vec<&str> = value.iter_sorted().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["B", "a", "b", "c"]);
+        assert_eq!(
+            value.iter_sorted().nth(1),
+            Some((&"a".to_string(), &JsonValue::Number(1.0)))
+        );
+        assert_eq!(JsonValue::Array(vec![]).iter_sorted().count(), 0);
+    }
+
+    #[test]
+    fn test_stringify_sort_keys() {
+        let value =
+            parse(r#"{"z": {"y": 1, "x": [{"b": 2, "a": 1}]}, "a": null, "m": true}"#).unwrap();
+        let options = StringifyOptions {
+            sort_keys: true,
+            ..StringifyOptions::default()
+        };
+        let expected = r#"{"a":null,"m":true,"z":{"x":[{"a":1,"b":2}],"y":1}}"#;
+        assert_eq!(stringify_with(&value, &options).unwrap(), expected);
+        assert_eq!(parse(&stringify(&value)).unwrap(), value);
+    }
+
+    #[test]
+    fn test_parser_pool() {
+        let pool = ParserPool::with_max_idle(ParserOptions::lenient(), 1);
+        assert_eq!(
+            pool.parse("[1, 2,]"),
+            Ok(JsonValue::Array(vec![
+                JsonValue::Number(1.0),
+                JsonValue::Number(2.0),
+            ]))
+        );
+        assert_eq!(pool.idle(), 1);
+
+        {
+            let mut a = pool.get();
+            let mut b = pool.get();
+            assert_eq!(pool.idle(), 0);
+            assert_eq!(a.parse_str("'a'"), Ok(JsonValue::String("a".into())));
+            assert!(b.parse_str("{").is_err());
+            assert_eq!(b.parse_str("null"), Ok(JsonValue::Null));
+        }
+        assert_eq!(pool.idle(), 1);
+
+        let handles: Vec<_> = (0..4)
+            .map(|i| std::thread::spawn(move || parse_pooled(&format!("[{}]", i))))
+            .collect();
+        for (i, handle) in handles.into_iter().enumerate() {
+            let value = handle.join().unwrap().unwrap();
+            assert_eq!(value, JsonValue::Array(vec![JsonValue::Number(i as f64)]));
+        }
+        assert!(parse_pooled("[1,]").is_err());
+    }
+
+    #[test]
+    fn test_stringify_escape_non_ascii() {
+        let value = JsonValue::String("café ☕ 😀\n".into());
+        let options = StringifyOptions {
+            escape_non_ascii: true,
+            ..StringifyOptions::default()
+        };
+        let json = stringify_with(&value, &options).unwrap();
+        assert_eq!(json, r#""caf\u00e9 \u2615 \ud83d\ude00\n""#);
+        assert!(json.is_ascii());
+        assert_eq!(parse(&json).unwrap(), value);
+        assert_eq!(stringify(&value), "\"café ☕ 😀\\n\"");
+    }
+
+    #[test]
+    fn test_schema_violations() {
+        let schema = parse(
+            r#"{
+                "type": "object",
+                "required": ["name", "port"],
+                "additionalProperties": false,
+                "properties": {
+                    "name": {"type": "string", "minLength": 1},
+                    "port": {"type": "integer", "minimum": 1, "maximum": 65535},
+                    "tags": {"type": "array", "items": {"enum": ["a", "b"]}}
+                }
+            }"#,
+        )
+        .unwrap();
+        let value = parse(r#"{"port": 70000.5, "tags": ["a", "c"], "extra": 1}"#).unwrap();
+        let pointers: Vec<String> = schema_violations(&value, &schema)
+            .into_iter()
+            .map(|v| v.to_string())
+            .collect();
+        assert_eq!(
+            pointers,
+            vec![
+                "(root): missing required property \"name\"",
+                "/extra: property is not allowed",
+                "/port: expected integer, found number",
+                "/port: 70000.5 is greater than the maximum of 65535",
+                "/tags/1: value is not one of the allowed values",
+            ]
+        );
+        let valid = parse(r#"{"name": "api", "port": 80, "tags": ["b"]}"#).unwrap();
+        assert!(schema_violations(&valid, &schema).is_empty());
+    }
+
+    #[test]
+    fn test_checked_document_set_checked() {
+        let schema = parse(
+            r#"{"properties": {"port": {"type": "integer"}, "hosts": {"items": {"type": "string"}}}}"#,
+        )
+        .unwrap();
+        let value = parse(r#"{"port": 80, "hosts": ["a"]}"#).unwrap();
+        let mut doc = CheckedDocument::new(value.clone(), schema.clone()).unwrap();
+
+        assert_eq!(
+            doc.set_checked("/port", JsonValue::Number(8080.0)),
+            Ok(Some(JsonValue::Number(80.0)))
+        );
+        assert_eq!(
+            doc.set_checked("/port", JsonValue::String("80".into())),
+            Err(CheckError::SchemaViolations(vec![SchemaViolation {
+                pointer: "/port".to_string(),
+                message: "expected integer, found string".to_string(),
+            }]))
+        );
+        assert!(doc.set_checked("/hosts/0", JsonValue::Null).is_err());
+        assert!(doc.set_checked("/hosts/-", JsonValue::Null).is_err());
+        assert_eq!(
+            doc.set_checked("/hosts/2", JsonValue::Null),
+            Err(CheckError::InvalidPointer("/hosts/2".to_string()))
+        );
+        assert_eq!(doc.set_checked("/hosts/-", "b".into()), Ok(None));
+        assert_eq!(doc.set_checked("/hosts/2", "c".into()), Ok(None));
+        assert_eq!(
+            doc.value().pointer("/hosts").map(JsonValue::to_string),
+            Some(r#"["a","b","c"]"#.to_string())
+        );
+        assert_eq!(doc.set_checked("/name", JsonValue::Bool(true)), Ok(None));
+        assert_eq!(doc.value().get("port"), Some(&JsonValue::Number(8080.0)));
+        assert_eq!(
+            doc.value().pointer("/hosts/0").and_then(JsonValue::as_str),
+            Some("a")
+        );
+
+        let invalid = parse(r#"{"port": "x"}"#).unwrap();
+        assert!(CheckedDocument::new(invalid, schema).is_err());
+
+        // A rejected addition is rolled back rather than left in place.
+        let schema = parse(r#"{"additionalProperties": {"type": "number"}}"#).unwrap();
+        let mut doc = CheckedDocument::new(parse(r#"{"a": 1}"#).unwrap(), schema).unwrap();
+        assert!(doc.set_checked("/b", JsonValue::Null).is_err());
+        assert_eq!(doc.value(), &parse(r#"{"a": 1}"#).unwrap());
+    }
+
+    #[test]
+    fn test_query() {
+        let value = parse(
+            r#"{"store": {"book": [
+                {"title": "A", "price": 8.95, "tags": ["x"]},
+                {"title": "B", "price": 12.99},
+                {"title": "C", "price": 22, "isbn": "123"}
+            ], "bicycle": {"price": 19.95}}}"#,
+        )
+        .unwrap();
+        let titles = |path: &str| -> Vec<String> {
+            value
+                .query(path)
+                .unwrap()
+                .into_iter()
+                .map(|v| v.as_str().unwrap_or("?").to_string())
+                .collect()
+        };
+        assert_eq!(titles("$.store.book[*].title"), vec!["A", "B", "C"]);
+        assert_eq!(titles("$.store.book[-1].title"), vec!["C"]);
+        assert_eq!(titles("$['store']['book'][0]['title']"), vec!["A"]);
+        assert_eq!(
+            titles("$.store.book[?(@.price < 15)].title"),
+            vec!["A", "B"]
+        );
+        assert_eq!(titles("$.store.book[?(@.isbn)].title"), vec!["C"]);
+        assert_eq!(titles("$..book[?(@.title == 'B')].title"), vec!["B"]);
+        assert_eq!(value.query("$..price").unwrap().len(), 4);
+        assert_eq!(
+            value.query("$.store[?(@.price >)]"),
+            Err(QueryError {
+                offset: 19,
+                message: "expected a literal".to_string()
+            })
+        );
+        assert!(value.query("store").is_err());
+    }
+
+    #[test]
+    fn test_query_explain() {
+        let value = parse(r#"{"items": [{"price": 8}, {"price": "12"}, {}]}"#).unwrap();
+        let explanation = value.explain("$.items[?(@.price > 10)].name").unwrap();
+        assert!(explanation.matches.is_empty());
+        assert_eq!(explanation.first_empty_step(), Some(1));
+        assert_eq!(explanation.steps[0].selected, vec!["/items"]);
+        assert_eq!(
+            explanation.to_string(),
+            "query: $.items[?(@.price > 10)].name\n\
+             step 1: ['items'] (visited 1, selected 1)\n\
+             step 2: [?(@.price > 10)] (visited 1, selected 0)\n\
+             \x20 /items/0: no match: @.price is 8; 8 > 10 does not hold\n\
+             \x20 /items/1: no match: @.price is \"12\", not comparable with 10\n\
+             \x20 /items/2: no match: @.price is missing\n\
+             step 3: ['name'] (visited 0, selected 0)\n\
+             no results: step 2 selected nothing"
+        );
+    }
+
+    #[test]
+    fn test_stringify_escape_html() {
+        let value = parse(r#"{"html": "</script><b>&amp;</b>\u2028\u2029"}"#).unwrap();
+        let options = StringifyOptions {
+            escape_html: true,
+            ..StringifyOptions::default()
+        };
+        let json = stringify_with(&value, &options).unwrap();
+        assert_eq!(
+            json,
+            r#"{"html":"\u003c/script\u003e\u003cb\u003e\u0026amp;\u003c/b\u003e\u2028\u2029"}"#
+        );
+        assert!(!json.contains("</"));
+        assert_eq!(parse(&json).unwrap(), value);
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let text = format!(
+            r#"{{"name": "café", "n": [0, -1.5, 1e300, null, true, false], "long": "{}", "o": {{}}}}"#,
+            "x".repeat(300)
+        );
+        let value = parse(&text).unwrap();
+        let snapshot = value.to_snapshot();
+        assert_eq!(JsonValue::from_snapshot(&snapshot), Ok(value));
+
+        assert_eq!(
+            JsonValue::Null.to_snapshot(),
+            vec![b'W', b'J', b'S', b'N', 1, 0]
+        );
+        assert_eq!(
+            JsonValue::from_snapshot(b"{}"),
+            Err(SnapshotError::NotASnapshot)
+        );
+        assert_eq!(
+            JsonValue::from_snapshot(b"WJSN\x02\x00"),
+            Err(SnapshotError::UnsupportedVersion(2))
+        );
+        assert_eq!(
+            JsonValue::from_snapshot(&snapshot[..snapshot.len() - 1]),
+            Err(SnapshotError::Truncated)
+        );
+        assert_eq!(
+            JsonValue::from_snapshot(b"WJSN\x01\x0a"),
+            Err(SnapshotError::InvalidTag(10, 5))
+        );
+        assert_eq!(
+            JsonValue::from_snapshot(b"WJSN\x01\x00\x00"),
+            Err(SnapshotError::TrailingData(6))
+        );
+    }
+
+    #[test]
+    fn test_stringify_pretty() {
+        let value = parse(r#"{"b": [1, {"c": null}], "a": {}, "d": [], "e": null}"#).unwrap();
+        let options = StringifyOptions {
+            sort_keys: true,
+            ..StringifyOptions::pretty()
+        };
+        assert_eq!(
+            stringify_with(&value, &options).unwrap(),
+            "{\n  \"a\": {},\n  \"b\": [\n    1,\n    {\n      \"c\": null\n    }\n  ],\n  \"d\": [],\n  \"e\": null\n}"
+        );
+        assert_eq!(parse(&stringify_pretty(&value)).unwrap(), value);
+
+        let options = StringifyOptions {
+            indent: Some("\t".to_string()),
+            newline: NewlineStyle::CrLf,
+            sort_keys: true,
+            skip_null_fields: true,
+            ..StringifyOptions::default()
+        };
+        assert_eq!(
+            stringify_with(&value, &options).unwrap(),
+            "{\r\n\t\"a\": {},\r\n\t\"b\": [\r\n\t\t1,\r\n\t\t{}\r\n\t],\r\n\t\"d\": []\r\n}"
+        );
+    }
+
+    #[test]
+    fn test_stringify_escape_slash() {
+        let value = JsonValue::String("a/b".into());
+        let options = StringifyOptions {
+            escape_slash: true,
+            ..StringifyOptions::default()
+        };
+        assert_eq!(stringify_with(&value, &options).unwrap(), r#""a\/b""#);
+        assert_eq!(stringify(&value), r#""a/b""#);
+    }
+
+    #[test]
+    fn test_parse_cache() {
+        let size =
+            |text: &str| text.len() + size_of::<JsonValue>() + parse(text).unwrap().heap_size();
+        let (first, second, third) = (r#"{"a": 1}"#, "[1, 2, 3]", "[true]");
+        let cache = ParseCache::new(size(first) + size(second).max(size(third)));
+        let a = cache.parse(first).unwrap();
+        let again = cache.parse(first).unwrap();
+        assert!(Arc::ptr_eq(&a, &again));
+        assert!(cache.parse("{").is_err());
+
+        cache.parse(second).unwrap();
+        cache.parse(first).unwrap(); // refreshes the first entry
+        cache.parse(third).unwrap(); // evicts the second
+        let refreshed = cache.parse(first).unwrap();
+        assert!(Arc::ptr_eq(&a, &refreshed));
+        let too_big = format!("{:?}", "x".repeat(size(first) + size(second)));
+        assert!(cache.parse(&too_big).is_ok());
+
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 3,
+                misses: 5,
+                evictions: 1,
+                entries: 2,
+                bytes: size(first) + size(third),
+            }
+        );
+        assert_eq!(cache.stats().hit_ratio(), 3.0 / 8.0);
+        cache.clear();
+        assert_eq!(cache.stats().entries, 0);
+
+        // The bound covers the parsed tree, not just the input text.
+        let nested = format!("[{}[]]", "[],".repeat(100));
+        let cache = ParseCache::new(nested.len() * 2);
+        cache.parse(&nested).unwrap();
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    #[test]
+    fn test_ref_resolver() {
+        let doc = parse(
+            r##"{
+                "definitions": {
+                    "name": {"type": "string"},
+                    "alias": {"$ref": "#/definitions/name"},
+                    "point": {"$id": "point", "x": {"$ref": "#/definitions/alias"}}
+                },
+                "properties": {"a": {"$ref": "#point"}, "b": {"$ref": "point", "ignored": 1}}
+            }"##,
+        )
+        .unwrap();
+        let resolver = RefResolver::new(&doc);
+        let string_schema = parse(r##"{"type": "string"}"##).unwrap();
+        assert_eq!(
+            resolver.pointer("/properties/a/x"),
+            Ok(Some(&string_schema))
+        );
+        assert_eq!(
+            resolver.get(doc.pointer("/definitions").unwrap(), "alias"),
+            Ok(Some(&string_schema))
+        );
+        assert_eq!(
+            resolver.lookup("#/missing"),
+            Err(RefError::Unresolved("#/missing".to_string()))
+        );
+
+        let expanded = resolver.dereference().unwrap();
+        let point = parse(r##"{"$id": "point", "x": {"type": "string"}}"##).unwrap();
+        assert_eq!(expanded.pointer("/properties/a"), Some(&point));
+        assert_eq!(expanded.pointer("/properties/b"), Some(&point));
+
+        let cyclic = parse(r##"{"a": {"$ref": "#/b"}, "b": {"$ref": "#/a"}}"##).unwrap();
+        let resolver = RefResolver::new(&cyclic);
+        assert!(matches!(resolver.pointer("/a"), Err(RefError::Cycle(_))));
+        assert!(matches!(resolver.dereference(), Err(RefError::Cycle(_))));
+
+        let custom = parse(r##"{"_links": {"self": {"href": "#/x"}}, "x": 1}"##).unwrap();
+        let resolver = RefResolver::with_keys(&custom, "href", "id");
+        assert_eq!(
+            resolver.pointer("/_links/self"),
+            Ok(Some(&JsonValue::Number(1.0)))
+        );
+    }
+
+    #[test]
+    fn test_preserve_raw_numbers() {
+        let input = r#"{"amount":12345678901234567890.000000000000001,"n":[1e400,-0.10]}"#;
+        let options = ParserOptions {
+            preserve_raw_numbers: true,
+            ..ParserOptions::default()
+        };
+        let value = parse_with_options(input, options).unwrap();
+        assert_eq!(
+            value.get("amount"),
+            Some(&JsonValue::RawNumber(
+                "12345678901234567890.000000000000001".to_string().into()
+            ))
+        );
+        assert_eq!(
+            value.pointer("/n/1").and_then(JsonValue::as_f64),
+            Some(-0.1)
+        );
+        let options = StringifyOptions {
+            sort_keys: true,
+            ..StringifyOptions::default()
+        };
+        assert_eq!(stringify_with(&value, &options).unwrap(), input);
+        assert_eq!(JsonValue::from_snapshot(&value.to_snapshot()), Ok(value));
+
+        let options = ParserOptions {
+            preserve_raw_numbers: true,
+            ..ParserOptions::lenient()
+        };
+        let value = parse_with_options("[+1, .5, 5., 007, -00.25e2, 0x10]", options).unwrap();
+        assert_eq!(stringify(&value), "[1,0.5,5,7,-0.25e2,16]");
+    }
+
+    #[test]
+    fn test_stringify_non_finite_policy() {
+        let value = JsonValue::Array(vec![
+            JsonValue::Number(f64::NAN),
+            JsonValue::Number(f64::INFINITY),
+            JsonValue::Number(f64::NEG_INFINITY),
+            JsonValue::Number(1.5),
+        ]);
+        assert_eq!(stringify(&value), "[null,null,null,1.5]");
+
+        let options = StringifyOptions {
+            non_finite: NonFinitePolicy::Literal,
+            ..StringifyOptions::default()
+        };
+        let json = stringify_with(&value, &options).unwrap();
+        assert_eq!(json, "[NaN,Infinity,-Infinity,1.5]");
+        let reparsed = parse_with_options(&json, ParserOptions::lenient()).unwrap();
+        assert_eq!(
+            reparsed.get_index(2),
+            Some(&JsonValue::Number(f64::NEG_INFINITY))
+        );
+        assert!(parse(&json).is_err());
+
+        let options = StringifyOptions {
+            non_finite: NonFinitePolicy::Error,
+            ..StringifyOptions::default()
+        };
+        assert!(matches!(
+            stringify_with(&value, &options),
+            Err(StringifyError::NonFiniteNumber(n)) if n.is_nan()
+        ));
+    }
+
+    #[test]
+    fn test_hal_resource() {
+        let value = parse(
+            r#"{
+                "total": 2,
+                "_links": {
+                    "self": {"href": "/orders"},
+                    "find": {"href": "/orders{?id}", "templated": true},
+                    "item": [{"href": "/orders/1"}, {"href": "/orders/2", "title": "Second"}]
+                },
+                "_embedded": {"orders": [{"_links": {"self": {"href": "/orders/1"}}, "total": 30}]}
+            }"#,
+        )
+        .unwrap();
+        let hal = HalResource::new(&value).unwrap();
+        assert_eq!(hal.self_href(), Some("/orders"));
+        assert!(hal.link("find").unwrap().templated);
+        let items: Vec<_> = hal
+            .links("item")
+            .iter()
+            .map(|l| (l.href, l.title))
+            .collect();
+        assert_eq!(
+            items,
+            vec![("/orders/1", None), ("/orders/2", Some("Second"))]
+        );
+        let orders = hal.embedded("orders");
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].self_href(), Some("/orders/1"));
+        assert_eq!(hal.property("total"), Some(&JsonValue::Number(2.0)));
+        assert_eq!(hal.property("_links"), None);
+        assert!(HalResource::new(&JsonValue::Null).is_none());
+    }
+
+    #[test]
+    fn test_json_api_document() {
+        let value = parse(
+            r#"{
+                "data": {
+                    "type": "articles", "id": "1",
+                    "attributes": {"title": "JSON:API"},
+                    "relationships": {"author": {"data": {"type": "people", "id": "9"}}},
+                    "links": {"self": "/articles/1"}
+                },
+                "included": [{"type": "people", "id": "9", "attributes": {"name": "Dan"}}]
+            }"#,
+        )
+        .unwrap();
+        let doc = JsonApiDocument::new(&value).unwrap();
+        let article = doc.data()[0];
+        assert_eq!(article.resource_type(), Some("articles"));
+        assert_eq!(
+            article.attribute("title").and_then(JsonValue::as_str),
+            Some("JSON:API")
+        );
+        assert_eq!(article.link("self"), Some("/articles/1"));
+
+        let author = article.relationship("author");
+        assert_eq!(
+            author,
+            vec![ResourceIdentifier {
+                resource_type: "people",
+                id: "9"
+            }]
+        );
+        let person = doc.find(author[0]).unwrap();
+        assert_eq!(
+            person.attribute("name").and_then(JsonValue::as_str),
+            Some("Dan")
+        );
+        assert!(article.relationship("comments").is_empty());
+        assert!(doc.errors().is_empty());
+    }
+
+    #[test]
+    fn test_expand_nested_json() {
+        let input =
+            r#"{"event": "{\"id\": 7, \"meta\": \"[1, 2]\"}", "note": "{not json}", "n": "42"}"#;
+        let mut value = parse(input).unwrap();
+        let expanded = expand_nested_json(&mut value, &[]);
+        assert_eq!(expanded, vec!["/event", "/event/meta"]);
+        assert_eq!(
+            value.pointer("/event/meta/1"),
+            Some(&JsonValue::Number(2.0))
+        );
+        assert_eq!(
+            value.get("note").and_then(JsonValue::as_str),
+            Some("{not json}")
+        );
+        assert_eq!(value.get("n").and_then(JsonValue::as_str), Some("42"));
+
+        let options = StringifyOptions {
+            embed_json_at: expanded.clone(),
+            sort_keys: true,
+            ..StringifyOptions::default()
+        };
+        let json = stringify_with(&value, &options).unwrap();
+        assert!(json.contains(r#""meta\":\"[1,2]\""#));
+        let mut reparsed = parse(&json).unwrap();
+        expand_nested_json(&mut reparsed, &[]);
+        assert_eq!(reparsed, value);
+        embed_nested_json(&mut value, &expanded);
+        assert_eq!(value.pointer("/event/id"), None);
+
+        let mut value = parse(input).unwrap();
+        assert_eq!(
+            expand_nested_json(&mut value, &["/n", "/note", "/missing"]),
+            vec!["/n"]
+        );
+        assert_eq!(value.get("n"), Some(&JsonValue::Number(42.0)));
+
+        let options = ParserOptions {
+            expand_nested_json: true,
+            ..ParserOptions::default()
+        };
+        let value = parse_with_options(input, options).unwrap();
+        assert_eq!(value.pointer("/event/id"), Some(&JsonValue::Number(7.0)));
+    }
+
+    #[test]
+    fn test_raw_json() {
+        let payload = RawJson::new(" {\"b\": [1,  2.50],\n \"a\": null} ").unwrap();
+        assert_eq!(payload.as_str(), "{\"b\": [1,  2.50],\n \"a\": null}");
+        assert!(RawJson::new("{\"a\":").is_err());
+
+        let mut envelope = Map::new();
+        envelope.insert("payload".to_string(), JsonValue::Raw(payload.clone()));
+        let value = JsonValue::Object(Box::new(envelope));
+        assert_eq!(
+            stringify(&value),
+            "{\"payload\":{\"b\": [1,  2.50],\n \"a\": null}}"
+        );
+        assert_eq!(
+            stringify_canonical(&value).unwrap(),
+            r#"{"payload":{"a":null,"b":[1,2.5]}}"#
+        );
+        assert_eq!(
+            payload.parse().pointer("/b/1"),
+            Some(&JsonValue::Number(2.5))
+        );
+        assert_eq!(JsonValue::from_snapshot(&value.to_snapshot()), Ok(value));
+    }
+
+    #[test]
+    fn test_fault_injector() {
+        let faults = FaultInjector::new(FaultConfig {
+            fail_parse_at: vec![2],
+            truncate_output: Some(7),
+            ..FaultConfig::default()
+        });
+        assert!(faults.parse("[1]").is_ok());
+        assert_eq!(
+            faults.parse("[1]"),
+            Err(ParseError::UnexpectedEndOfInput(pos(3, 1, 4)))
+        );
+        assert!(faults.parse("[1]").is_ok());
+        assert_eq!(faults.parse_calls(), 3);
+
+        let value = parse(r#"["abc", "é"]"#).unwrap();
+        assert_eq!(faults.stringify(&value), r#"["abc","#);
+        assert_eq!(faults.stringify_calls(), 1);
+
+        let truncated = FaultInjector::new(FaultConfig {
+            truncate_input: Some(5),
+            ..FaultConfig::default()
+        });
+        assert_eq!(
+            truncated.parse(r#"{"a": 1}"#),
+            Err(ParseError::UnexpectedEndOfInput(pos(5, 1, 6)))
+        );
+    }
+
+    #[test]
+    fn test_extract() {
+        let input = r#"{
+            "meta": {"skip": [1, {"x": "}]"}], "s": "a\"b"},
+            "data": {"items": [{"id": 1}, {"id": 2}, {}, {"id": "fourth", "tags": ["t"]}]}
+        }"#;
+        assert_eq!(
+            extract(input, "/data/items/3/id"),
+            Ok(Some(JsonValue::String("fourth".into())))
+        );
+        assert_eq!(
+            extract(input, "/meta/s"),
+            Ok(Some(JsonValue::String("a\"b".into())))
+        );
+        assert_eq!(extract(input, "").unwrap(), Some(parse(input).unwrap()));
+        assert_eq!(extract(input, "/data/items/4"), Ok(None));
+        assert_eq!(extract(input, "/data/items/2/id"), Ok(None));
+        assert_eq!(extract(input, "/data/items/01"), Ok(None));
+        assert_eq!(extract(input, "/meta/s/x"), Ok(None));
+        assert_eq!(extract(input, "data"), Ok(None));
+
+        // Text after the target is never read.
+        assert_eq!(
+            extract(r#"{"a": 1, "b": tru"#, "/a"),
+            Ok(Some(JsonValue::Number(1.0)))
+        );
+        assert!(extract(r#"{"a" 1}"#, "/b").is_err());
+    }
+
+    #[test]
+    fn test_validate_matches_parse() {
+        let inputs = [
+            r#"{"a": [1, 2.5e3, true, null, "x\u00e9"], "b": {}}"#,
+            r#"{"a": 1,}"#,
+            r#"[1, 2"#,
+            r#""\ud800""#,
+            r#"{"a" 1}"#,
+            r#"[01]"#,
+            r#"{} x"#,
+            "",
+        ];
+        for input in inputs {
+            assert_eq!(validate(input), parse(input).map(|_| ()), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_validate_with_options() {
+        let options = ParserOptions {
+            allow_trailing_commas: true,
+            allow_unquoted_keys: true,
+            allow_single_quotes: true,
+            ..Default::default()
+        };
+        assert!(validate_with_options("{a: 'x', b: [1,],}", options).is_ok());
+
+        let options = ParserOptions {
+            duplicate_keys: DuplicateKeyPolicy::Error,
+            ..Default::default()
+        };
+        let input = r#"{"a": 1, "a": 2}"#;
+        assert_eq!(
+            validate_with_options(input, options.clone()),
+            parse_with_options(input, options).map(|_| ())
+        );
+        assert!(validate(input).is_ok());
+    }
+
+    #[test]
+    fn test_debug_is_json_like() {
+        let value = parse(r#"{"b": [1, 2.5, true, null], "a": {"x": "y"}}"#).unwrap();
+        assert_eq!(
+            format!("{:?}", value),
+            r#"{"a": {"x": "y"}, "b": [1, 2.5, true, null]}"#
+        );
+        assert_eq!(format!("{:#?}", parse("[1]").unwrap()), "[\n    1,\n]");
+    }
+
+    #[test]
+    fn test_debug_truncates_large_nodes() {
+        let long = JsonValue::Array((0..100).map(|i| JsonValue::Number(i as f64)).collect());
+        let printed = format!("{:?}", long);
+        assert!(printed.starts_with("[0, 1, 2"));
+        assert!(printed.ends_with("19, ..]"));
+
+        let printed = format!("{:?}", JsonValue::String("x".repeat(1000).into()));
+        assert_eq!(printed, format!("{:?}..", "x".repeat(100)));
+    }
+
+    #[test]
+    fn test_self_check_passes() {
+        let report = self_check();
+        assert!(report.passed(), "{}", report);
+        assert!(report.cases.len() > 30);
+        let big = JsonValue::Number(1e21);
+        assert_eq!(parse(&stringify(&big)).unwrap(), big);
+        assert!(
+            report
+                .to_string()
+                .starts_with(&format!("{0}/{0} ", report.cases.len()))
+        );
+    }
+
+    #[test]
+    fn test_minify_preserves_lexemes() {
+        let input =
+            "{\n  \"price\": 1.10,\n  \"id\": 12345678901234567890,\n  \"s\": \" a\\u0041 \"\n}\n";
+        assert_eq!(
+            minify(input).unwrap(),
+            r#"{"price":1.10,"id":12345678901234567890,"s":" a\u0041 "}"#
+        );
+        assert!(minify("[1, 2").is_err());
+    }
+
+    #[test]
+    fn test_minify_strips_comments_in_lenient_mode() {
+        let input = "// header\n[1, /* two */ 2, 'a // b',]";
+        assert_eq!(
+            minify_with_options(input, ParserOptions::lenient()).unwrap(),
+            "[1,2,'a // b',]"
+        );
+        assert!(minify(input).is_err());
+    }
+
+    #[test]
+    fn test_incremental_reparse_patches_innermost_value() {
+        let text = r#"{"users": [{"name": "ann", "age": 30}, {"name": "bob"}], "n": 1}"#;
+        let mut doc = IncrementalDocument::parse(text).unwrap();
+        let age = doc.span("/users/0/age").unwrap();
+        assert_eq!(&doc.text()[age.clone()], "30");
+
+        let pointer = doc
+            .apply(&TextEdit::new(age.start..age.end, "314"))
+            .unwrap();
+        assert_eq!(pointer, "/users/0/age");
+        assert_eq!(doc.value(), &parse(doc.text()).unwrap());
+
+        // Spans after the edit have moved.
+        let bob = doc.span("/users/1/name").unwrap();
+        assert_eq!(&doc.text()[bob], r#""bob""#);
+
+        // An edit that adds a member falls back to the enclosing object.
+        let end = doc.span("/users/1/name").unwrap().end;
+        let pointer = doc
+            .apply(&TextEdit::new(end..end, r#", "age": 7"#))
+            .unwrap();
+        assert_eq!(pointer, "/users/1");
+        assert_eq!(doc.value(), &parse(doc.text()).unwrap());
+        assert_eq!(doc.span("/n").map(|r| &doc.text()[r]), Some("1"));
+    }
+
+    #[test]
+    fn test_incremental_reparse_rejects_invalid_edits() {
+        let mut doc = IncrementalDocument::parse("[1, 2]").unwrap();
+        assert!(doc.apply(&TextEdit::new(5..6, "")).is_err());
+        assert_eq!(doc.text(), "[1, 2]");
+
+        assert_eq!(doc.apply(&TextEdit::new(0..6, "{}")).unwrap(), "");
+        assert_eq!(doc.value(), &JsonValue::Object(Box::default()));
+    }
+
+    #[test]
+    fn test_outline_with_ranges() {
+        let text = r#"{"z": [1, {"k": null}], "a": "x"}"#;
+        let doc = IncrementalDocument::parse(text).unwrap();
+        let root = doc.outline();
+        assert_eq!(root.kind, SymbolKind::Object);
+        assert_eq!(root.range, Some(0..text.len()));
+        let names: Vec<&str> = root.children.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["z", "a"]);
+
+        let z = &root.children[0];
+        assert_eq!((z.kind, z.child_count), (SymbolKind::Array, 2));
+        assert_eq!(&text[z.range.clone().unwrap()], r#"[1, {"k": null}]"#);
+
+        let offset = text.find("null").unwrap();
+        let trail: Vec<&str> = root
+            .breadcrumbs(offset)
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(trail, ["", "z", "1", "k"]);
+    }
+
+    #[test]
+    fn test_outline_of_value() {
+        let value = parse(r#"{"b": true, "a": [1, 2, 3]}"#).unwrap();
+        let root = outline(&value);
+        assert_eq!(root.range, None);
+        assert_eq!(root.children[0].name, "a");
+        assert_eq!(root.children[0].child_count, 3);
+        assert_eq!(root.children[1].kind, SymbolKind::Bool);
+        assert!(root.breadcrumbs(0).is_empty());
+    }
+
+    #[test]
+    fn test_diff_emits_json_patch() {
+        let from = parse(r#"{"a": 1, "b": [1, 2, 3], "c": {"x": true}}"#).unwrap();
+        let to = parse(r#"{"a": 2, "b": [1], "c": {"x": true}, "d/e": null}"#).unwrap();
+        assert_eq!(
+            diff(&from, &to),
+            vec![
+                PatchOp::Replace {
+                    path: "/a".to_string(),
+                    value: JsonValue::Number(2.0)
+                },
+                PatchOp::Remove {
+                    path: "/b/2".to_string()
+                },
+                PatchOp::Remove {
+                    path: "/b/1".to_string()
+                },
+                PatchOp::Add {
+                    path: "/d~1e".to_string(),
+                    value: JsonValue::Null
+                },
+            ]
+        );
+        assert!(diff(&from, &from.clone()).is_empty());
+        let op = PatchOp::Remove {
+            path: "/a".to_string(),
+        };
+        assert_eq!(
+            op.to_json(),
+            parse(r#"{"op": "remove", "path": "/a"}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_scoped_default_options() {
+        let lenient = ParserOptions {
+            allow_comments: true,
+            ..Default::default()
+        };
+        assert!(parse("[1] // c").is_err());
+        let inner = with_parser_options(lenient, || {
+            assert!(parse("[1] // c").is_ok());
+            assert!(validate("/* c */ 1").is_ok());
+            // Nested scopes take priority and restore on exit.
+            with_parser_options(ParserOptions::default(), || parse("[1] // c").is_ok())
+        });
+        assert!(!inner);
+        assert!(parse("[1] // c").is_err());
+
+        let escaping = StringifyOptions {
+            escape_html: true,
+            non_finite: NonFinitePolicy::Error,
+            ..Default::default()
+        };
+        with_stringify_options(escaping, || {
+            assert_eq!(stringify(&JsonValue::String("<".into())), r#""\u003c""#);
+            assert_eq!(stringify(&JsonValue::Number(f64::NAN)), "null");
+            assert_eq!(stringify_pretty(&parse("[1]").unwrap()), "[\n  1\n]");
+        });
+        assert_eq!(stringify(&JsonValue::String("<".into())), r#""<""#);
+    }
+
+    #[test]
+    fn test_process_wide_default_options() {
+        // Key sorting cannot change the outcome of tests running alongside.
+        set_default_stringify_options(StringifyOptions {
+            sort_keys: true,
+            ..Default::default()
+        });
+        let value = parse(r#"{"b": 1, "a": 2, "c": 3}"#).unwrap();
+        assert_eq!(stringify(&value), r#"{"a":2,"b":1,"c":3}"#);
+        assert!(StringifyOptions::current().sort_keys);
+        reset_defaults();
+        assert!(!StringifyOptions::current().sort_keys);
+    }
+
+    #[test]
+    fn test_provenance_through_merge_and_patch() {
+        let base = parse(r#"{"server": {"host": "localhost", "port": 80}, "tags": ["a", "b"]}"#);
+        let mut config = TrackedValue::new(base.unwrap(), "defaults.json");
+        config.merge(
+            &parse(r#"{"server": {"port": 8080}}"#).unwrap(),
+            "prod.json",
+        );
+        assert_eq!(config.source_of("/server/port"), Some("prod.json"));
+        assert_eq!(config.source_of("/server/host"), Some("defaults.json"));
+        assert_eq!(config.source_of("/missing"), None);
+
+        let ops = [
+            PatchOp::Add {
+                path: "/tags/0".to_string(),
+                value: JsonValue::String("z".into()),
+            },
+            PatchOp::Replace {
+                path: "/tags/2".to_string(),
+                value: JsonValue::String("c".into()),
+            },
+            PatchOp::Remove {
+                path: "/tags/1".to_string(),
+            },
+        ];
+        config.apply_patch(&ops, "patch-7").unwrap();
+        assert_eq!(
+            config.value().pointer("/tags"),
+            parse(r#"["z", "c"]"#).ok().as_ref()
+        );
+        assert_eq!(config.source_of("/tags/0"), Some("patch-7"));
+        assert_eq!(config.source_of("/tags/1"), Some("patch-7"));
+        assert_eq!(config.source_of("/tags"), Some("defaults.json"));
+
+        let bad = [PatchOp::Remove {
+            path: "/nope".to_string(),
+        }];
+        let before = config.clone();
+        assert!(config.apply_patch(&bad, "patch-8").is_err());
+        assert_eq!(config, before);
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        let mut value = parse(r#"{"a": [1, 2]}"#).unwrap();
+        let ops = [
+            PatchOp::Add {
+                path: "/a/-".to_string(),
+                value: JsonValue::Number(3.0),
+            },
+            PatchOp::Remove {
+                path: "/a/0".to_string(),
+            },
+            PatchOp::Add {
+                path: "/b".to_string(),
+                value: JsonValue::Bool(true),
+            },
+        ];
+        apply_patch(&mut value, &ops).unwrap();
+        assert_eq!(value, parse(r#"{"a": [2, 3], "b": true}"#).unwrap());
+
+        let target = parse(r#"{"x": {"y": 1}, "a": [true]}"#).unwrap();
+        let mut patched = value.clone();
+        apply_patch(&mut patched, &diff(&value, &target)).unwrap();
+        assert_eq!(patched, target);
+
+        let err = apply_patch(
+            &mut value,
+            &[PatchOp::Remove {
+                path: "/a/5".to_string(),
+            }],
+        );
+        assert_eq!(err.unwrap_err().path, "/a/5");
+    }
+
+    #[test]
+    fn test_diff_aligns_arrays() {
+        let from = parse("[1, 2, 3, 4, 5]").unwrap();
+        let to = parse("[0, 1, 2, 3, 5]").unwrap();
+        assert_eq!(
+            diff(&from, &to),
+            vec![
+                PatchOp::Add {
+                    path: "/0".to_string(),
+                    value: JsonValue::Number(0.0)
+                },
+                PatchOp::Remove {
+                    path: "/4".to_string()
+                },
+            ]
+        );
+
+        let cases = [
+            ("[]", "[1, 2]"),
+            ("[1, 2, 3]", "[]"),
+            ("[1, 2, 3]", "[3, 2, 1]"),
+            (
+                r#"[{"id": 1, "v": "a"}, 2]"#,
+                r#"[{"id": 1, "v": "b"}, 2, 7]"#,
+            ),
+            ("[1, [2, 3], 4, 4]", "[[2, 3, 9], 4, 5, 4, 6]"),
+            (r#"{"a": [1, 2, 1, 2]}"#, r#"{"a": [2, 1, 2, 1, 2]}"#),
+        ];
+        for (from, to) in cases {
+            let (from, to) = (parse(from).unwrap(), parse(to).unwrap());
+            let mut patched = from.clone();
+            apply_patch(&mut patched, &diff(&from, &to)).unwrap();
+            assert_eq!(patched, to);
+        }
+
+        // A changed element is patched inside rather than replaced.
+        let ops = diff(&parse(cases[3].0).unwrap(), &parse(cases[3].1).unwrap());
+        assert_eq!(ops[0].path(), "/0/v");
+    }
+
+    #[test]
+    fn test_merge_strategies() {
+        let base = parse(r#"{"db": {"host": "a", "port": 1}, "tags": ["x"], "v": 1}"#).unwrap();
+        let layer = parse(r#"{"db": {"port": 2}, "tags": ["y"], "v": null}"#).unwrap();
+
+        let mut merged = base.clone();
+        merged.merge(&layer, &MergeStrategy::Replace);
+        let expected = r#"{"db": {"host": "a", "port": 2}, "tags": ["y"], "v": null}"#;
+        assert_eq!(merged, parse(expected).unwrap());
+
+        let mut merged = base.clone();
+        merged.merge(&layer, &MergeStrategy::Concat);
+        assert_eq!(merged.get("tags"), parse(r#"["x", "y"]"#).ok().as_ref());
+    }
+
+    #[test]
+    fn test_merge_union_by_key() {
+        let mut servers = parse(r#"[{"name": "a", "port": 1}, {"name": "b"}, 7]"#).unwrap();
+        let layer = parse(r#"[{"name": "b", "port": 2}, {"name": "c"}, 7, 8]"#).unwrap();
+        servers.merge(&layer, &MergeStrategy::UnionByKey("name".to_string()));
+        let expected =
+            r#"[{"name": "a", "port": 1}, {"name": "b", "port": 2}, 7, {"name": "c"}, 8]"#;
+        assert_eq!(servers, parse(expected).unwrap());
+    }
+
+    #[test]
+    fn test_path_get() {
+        let value =
+            parse(r#"{"config": {"servers": [{"host": "a"}, {"host": "b"}], "a.b": 1}}"#).unwrap();
+        assert_eq!(
+            value.path_get("config.servers[1].host"),
+            Some(&JsonValue::String("b".into()))
+        );
+        assert_eq!(
+            value.path_get(r#".config["a.b"]"#),
+            Some(&JsonValue::Number(1.0))
+        );
+        assert_eq!(value.path_get(""), Some(&value));
+        assert_eq!(value.path_get("config.servers[5]"), None);
+        assert_eq!(value.path_get("config..servers"), None);
+    }
+
+    #[test]
+    fn test_path_set_creates_intermediates() {
+        let mut value = parse(r#"{"config": {"name": "x"}}"#).unwrap();
+        value
+            .path_set("config.servers[1].host", JsonValue::String("b".into()))
+            .unwrap();
+        let expected = r#"{"config": {"name": "x", "servers": [null, {"host": "b"}]}}"#;
+        assert_eq!(value, parse(expected).unwrap());
+
+        let err = value
+            .path_set("config.name.first", JsonValue::Null)
+            .unwrap_err();
+        assert_eq!(err.offset, 11);
+        assert_eq!(err.message, "not an object");
+        assert_eq!(
+            value.path_set("a[x]", JsonValue::Null).unwrap_err().offset,
+            2
+        );
+    }
+
+    #[test]
+    fn test_to_ast_json() {
+        let doc = IncrementalDocument::parse("{\"b\": [1.50, null],\n \"a\": \"x\"}").unwrap();
+        let ast = to_ast_json(&doc);
+        assert_eq!(ast.get("version"), Some(&JsonValue::Number(1.0)));
+
+        let root = ast.get("root").unwrap();
+        assert_eq!(root.get("kind").and_then(JsonValue::as_str), Some("object"));
+        let members = root.get("members").and_then(JsonValue::as_array).unwrap();
+        let keys: Vec<_> = members
+            .iter()
+            .filter_map(|m| m.get("key")?.as_str())
+            .collect();
+        assert_eq!(keys, ["b", "a"]);
+
+        let number = members[0].pointer("/value/elements/0").unwrap();
+        assert_eq!(number.get("raw").and_then(JsonValue::as_str), Some("1.50"));
+        assert_eq!(
+            number.pointer("/start/offset"),
+            Some(&JsonValue::Number(7.0))
+        );
+        let string = members[1].get("value").unwrap();
+        let start = parse(r#"{"offset": 26, "line": 2, "column": 7}"#).unwrap();
+        assert_eq!(string.get("start"), Some(&start));
+        assert_eq!(
+            string.get("kind").and_then(JsonValue::as_str),
+            Some("string")
+        );
+    }
+
+    #[test]
+    fn test_pointer_set_and_remove() {
+        let mut doc = parse(r#"{"users": [{"name": "a"}], "a/b": 1}"#).unwrap();
+        let b = JsonValue::String("b".into());
+        assert_eq!(
+            doc.pointer_set("/users/-", parse(r#"{"name": "c"}"#).unwrap()),
+            Ok(None)
+        );
+        let old = doc.pointer_set("/users/1/name", b.clone()).unwrap();
+        assert_eq!(old, Some(JsonValue::String("c".into())));
+        assert_eq!(doc.pointer_set("/users/2", JsonValue::Null), Ok(None));
+        *doc.pointer_mut("/a~1b").unwrap() = JsonValue::Bool(true);
+
+        let expected = r#"{"users": [{"name": "a"}, {"name": "b"}, null], "a/b": true}"#;
+        assert_eq!(doc, parse(expected).unwrap());
+
+        let err = doc.pointer_set("/users/9", JsonValue::Null).unwrap_err();
+        assert_eq!(err.message, "array index out of range");
+        assert!(doc.pointer_set("/missing/x", JsonValue::Null).is_err());
+
+        assert_eq!(
+            doc.pointer_remove("/users/0"),
+            parse(r#"{"name": "a"}"#).ok()
+        );
+        assert_eq!(doc.pointer("/users/0/name"), Some(&b));
+        assert_eq!(doc.pointer_remove("/users/-"), None);
+        assert_eq!(doc.pointer_remove(""), None);
+    }
+
+    #[test]
+    fn test_parse_integer_exact() {
+        assert_eq!(
+            parse_integer_exact(" 123456789012345678901234567890 "),
+            Ok(123456789012345678901234567890)
+        );
+        assert_eq!(
+            parse_integer_exact("-9007199254740993"),
+            Ok(-9007199254740993)
+        );
+        assert_eq!(parse_integer_exact("12.50e1"), Ok(125));
+        assert_eq!(parse_integer_exact("-0.0"), Ok(0));
+        assert_eq!(parse_integer_exact(&i128::MIN.to_string()), Ok(i128::MIN));
+        assert!(matches!(
+            parse_integer_exact("1.5"),
+            Err(ParseError::InvalidNumber(..))
+        ));
+        assert!(parse_integer_exact(&format!("{}0", i128::MAX)).is_err());
+        assert!(parse_integer_exact("\"1\"").is_err());
+        assert!(parse_integer_exact("1 2").is_err());
+        assert!(parse_integer_exact("01").is_err());
+    }
+
+    #[test]
+    fn test_parse_fixed_width_integers_exact() {
+        assert_eq!(parse_u64_exact("18446744073709551615"), Ok(u64::MAX));
+        assert_eq!(parse_i64_exact("-9223372036854775808"), Ok(i64::MIN));
+        let err = parse_u64_exact(" -1").unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::InvalidNumber("-1".to_string(), Position::at(" -1", 1))
+        );
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut value = parse(r#"{"count": 1}"#).unwrap();
+        value
+            .entry("tags")
+            .or_insert_with(|| JsonValue::Array(vec![]));
+        value
+            .entry("count")
+            .and_modify(|n| *n = JsonValue::Number(2.0));
+        assert_eq!(value, parse(r#"{"count": 2, "tags": []}"#).unwrap());
+
+        let mut empty = JsonValue::Null;
+        empty.entry("a").or_insert(JsonValue::Bool(true));
+        assert_eq!(empty, parse(r#"{"a": true}"#).unwrap());
+    }
+
+    #[test]
+    fn test_detect_style() {
+        let input = "{\r\n\t\"a\" : [\r\n\t\t1\r\n\t],\r\n\t\"b:c\" : 2\r\n}";
+        let style = detect_style(input);
+        assert_eq!(style.indent.as_deref(), Some("\t"));
+        assert_eq!(style.newline, NewlineStyle::CrLf);
+        assert!(style.space_before_colon && style.space_after_colon);
+        assert!(!style.final_newline);
+
+        let value = parse(input).unwrap();
+        let options = style.stringify_options();
+        let round_trip = stringify_with(
+            &value,
+            &StringifyOptions {
+                sort_keys: true,
+                ..options
+            },
+        );
+        assert_eq!(round_trip.unwrap().replace(": ", " : "), input);
+
+        let compact = detect_style("{\"a\":1}\n");
+        assert_eq!(compact.indent, None);
+        assert!(!compact.space_after_colon && compact.final_newline);
+        let four = detect_style("{\n    \"a\": {\n        \"b\": 1\n    }\n}");
+        assert_eq!(four.indent.as_deref(), Some("    "));
+    }
+
+    #[test]
+    fn test_merge3() {
+        let base = parse(r#"{"port": 80, "host": "a", "debug": false, "tags": [1]}"#).unwrap();
+        let ours = parse(r#"{"port": 8080, "host": "a", "tags": [1, 2]}"#).unwrap();
+        let theirs = parse(r#"{"port": 80, "host": "b", "tags": [1, 3]}"#).unwrap();
+        let result = merge3(&base, &ours, &theirs);
+        let expected = r#"{"port": 8080, "host": "b", "tags": [1, 2]}"#;
+        assert_eq!(result.merged, parse(expected).unwrap());
+        assert!(!result.is_clean());
+        let paths: Vec<_> = result.conflicts.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, ["/tags"]);
+        assert_eq!(
+            result.conflicts_json(),
+            parse(r#"[{"path": "/tags", "base": [1], "ours": [1, 2], "theirs": [1, 3]}]"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_merge3_render_markers() {
+        let base = parse(r#"{"db": {"port": 1, "user": "x"}, "name": "n"}"#).unwrap();
+        let ours = parse(r#"{"db": {"port": 2}, "name": "n"}"#).unwrap();
+        let theirs = parse(r#"{"db": {"port": 3, "user": "y"}, "name": "n"}"#).unwrap();
+        let rendered = merge3(&base, &ours, &theirs).render_markers();
+        let expected = r#"{
+  "db": {
+<<<<<<< ours
+    "port": 2,
+=======
+    "port": 3,
+>>>>>>> theirs
+<<<<<<< ours
+=======
+    "user": "y"
+>>>>>>> theirs
+  },
+  "name": "n"
+}
+"#;
+        assert_eq!(rendered, expected);
+
+        let clean = merge3(&base, &ours, &base);
+        assert!(clean.is_clean());
+        assert_eq!(parse(&clean.render_markers()).unwrap(), ours);
+    }
+
+    #[test]
+    fn test_take_and_replace() {
+        let mut doc = parse(r#"{"items": [1, 2], "name": "a"}"#).unwrap();
+        let items = doc.pointer_mut("/items").unwrap().take();
+        assert_eq!(items, parse("[1, 2]").unwrap());
+        let old = doc
+            .pointer_mut("/name")
+            .unwrap()
+            .replace(JsonValue::String("b".into()));
+        assert_eq!(old, JsonValue::String("a".into()));
+        assert_eq!(doc, parse(r#"{"items": null, "name": "b"}"#).unwrap());
+    }
+
+    #[test]
+    fn test_iterator_traits() {
+        let doubled: JsonValue = parse("[1, 2, 3]")
+            .unwrap()
+            .into_iter()
+            .map(|v| JsonValue::Number(v.as_f64().unwrap() * 2.0))
+            .collect();
+        assert_eq!(doubled, parse("[2, 4, 6]").unwrap());
+
+        let mut obj: JsonValue = [("a".to_string(), JsonValue::Bool(true))]
+            .into_iter()
+            .collect();
+        obj.extend([("b".to_string(), JsonValue::Null)]);
+        assert_eq!(obj, parse(r#"{"a": true, "b": null}"#).unwrap());
+        let values: Vec<&JsonValue> = (&obj).into_iter().collect();
+        assert_eq!(values, [&JsonValue::Bool(true), &JsonValue::Null]);
+
+        let mut list = JsonValue::Null;
+        list.extend(vec![JsonValue::Number(1.0)]);
+        assert_eq!(list, parse("[1]").unwrap());
+        assert_eq!(JsonValue::Bool(true).into_iter().count(), 0);
+    }
+
+    #[test]
+    fn test_stringify_within_budget() {
+        let value = parse(
+            r#"{"items": [{"id": 1, "debug": "xxxxxxxx"}, {"id": 2, "debug": "yyyyyyyy"}], "trace": "zzzzzzzzzz"}"#,
+        )
+        .unwrap();
+        let options = StringifyOptions {
+            sort_keys: true,
+            ..StringifyOptions::default()
+        };
+        let drop = ["/trace", "/items/*/debug", "/items"];
+
+        let output = stringify_within_budget(&value, 60, &drop, &options).unwrap();
+        assert_eq!(output.text, r#"{"items":[{"id":1},{"id":2}]}"#);
+        assert_eq!(
+            output.omitted,
+            ["/trace", "/items/0/debug", "/items/1/debug"]
+        );
+
+        let whole = stringify_within_budget(&value, 1000, &drop, &options).unwrap();
+        assert!(whole.omitted.is_empty());
+        assert_eq!(
+            stringify_within_budget(&value, 1, &drop, &options),
+            Err(StringifyError::OverBudget { size: 2, budget: 1 })
+        );
+    }
+
+    #[test]
+    fn test_from_conversions() {
+        let n: JsonValue = 42.into();
+        assert_eq!(n, JsonValue::Number(42.0));
+        assert_eq!(JsonValue::from(1.5), JsonValue::Number(1.5));
+        assert_eq!(JsonValue::from(true), JsonValue::Bool(true));
+        assert_eq!(JsonValue::from("a"), JsonValue::String("a".into()));
+        assert_eq!(JsonValue::from(None::<i64>), JsonValue::Null);
+        assert_eq!(
+            JsonValue::from(vec![Some("x"), None]),
+            parse(r#"["x", null]"#).unwrap()
+        );
+        let mut members = HashMap::new();
+        members.insert("ids".to_string(), vec![1u64, 2]);
+        assert_eq!(
+            JsonValue::from(members),
+            parse(r#"{"ids": [1, 2]}"#).unwrap()
+        );
+    }
+
+    crate::json_view! {
+        struct User<'a> {
+            name: &'a str,
+            id: u64 = "userId",
+            email: Option<&'a str>,
+            tags: Vec<&'a str>,
+        }
+    }
+
+    crate::json_view! {
+        struct Team<'a> {
+            members: Vec<User<'a>>,
+        }
+    }
+
+    #[test]
+    fn test_json_view() {
+        let doc = parse(
+            r#"{"name": "ann", "userId": 12345678901234567, "tags": ["a", 1], "email": null}"#,
+        )
+        .unwrap();
+        let user = User::view(&doc).unwrap();
+        assert_eq!(user.name(), Ok("ann"));
+        assert_eq!(user.email(), Ok(None));
+        assert_eq!(user.json(), &doc);
+        assert_eq!(
+            user.tags().unwrap_err().to_string(),
+            "field tags: expected an array of matching elements, found an array"
+        );
+
+        let raw = parse_with_options(
+            r#"{"userId": 12345678901234567}"#,
+            ParserOptions {
+                preserve_raw_numbers: true,
+                ..ParserOptions::default()
+            },
+        )
+        .unwrap();
+        let user = User::view(&raw).unwrap();
+        assert_eq!(user.id(), Ok(12345678901234567));
+        assert_eq!(user.name().unwrap_err().message, "missing");
+        assert!(User::view(&JsonValue::Null).is_none());
+
+        let team = parse(r#"{"members": [{"name": "bo"}]}"#).unwrap();
+        let members = Team::view(&team).unwrap().members().unwrap();
+        assert_eq!(members[0].name(), Ok("bo"));
+    }
+
+    #[test]
+    fn test_try_from_scalars() {
+        assert_eq!(
+            String::try_from(parse(r#""a""#).unwrap()),
+            Ok("a".to_string())
+        );
+        assert_eq!(u8::try_from(&JsonValue::Number(255.0)), Ok(255));
+        assert_eq!(
+            u8::try_from(&JsonValue::Number(256.0)).unwrap_err().message,
+            "256 is out of range for u8"
+        );
+        assert_eq!(
+            i32::try_from(JsonValue::Number(1.5)).unwrap_err().message,
+            "1.5 is not an integer"
+        );
+        let big = JsonValue::RawNumber("18446744073709551615".into());
+        assert_eq!(u64::try_from(&big), Ok(u64::MAX));
+        assert_eq!(
+            bool::try_from(JsonValue::Null).unwrap_err().to_string(),
+            "expected a boolean, found null"
+        );
+    }
+
+    #[test]
+    fn test_try_from_collections() {
+        let value = parse(r#"{"a": [1, 2], "b": [3]}"#).unwrap();
+        let map = HashMap::<String, Vec<u32>>::try_from(&value).unwrap();
+        assert_eq!(map["a"], [1, 2]);
+
+        let bad = parse(r#"{"a/b": [1, "x"]}"#).unwrap();
+        let err = HashMap::<String, Vec<u32>>::try_from(bad).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "/a~1b/1: expected an integer, found a string"
+        );
+    }
+
+    #[test]
+    fn test_partial_eq_primitives() {
+        let user = parse(r#"{"name": "Alice", "age": 30, "admin": false, "score": 9.5}"#).unwrap();
+        assert!(*user.get("name").unwrap() == "Alice");
+        assert_eq!(*user.get("name").unwrap(), *"Alice");
+        assert_eq!(*user.get("age").unwrap(), 30);
+        assert_eq!(*user.get("admin").unwrap(), false);
+        assert_eq!(*user.get("score").unwrap(), 9.5);
+        assert_ne!(*user.get("score").unwrap(), 9);
+        assert_ne!(*user.get("age").unwrap(), "30");
+
+        let big = JsonValue::RawNumber("9007199254740993".into());
+        assert_eq!(big, 9007199254740993i64);
+        assert_ne!(JsonValue::Number(9007199254740992.0), 9007199254740993i64);
+    }
+
+    #[test]
+    fn test_type_predicates_and_integer_accessors() {
+        let value = parse(r#"[null, true, 1, "s", [], {}]"#).unwrap();
+        let items = value.as_array().unwrap();
+        assert!(items[0].is_null() && items[1].is_bool() && items[2].is_number());
+        assert!(items[3].is_string() && items[4].is_array() && items[5].is_object());
+        assert!(!items[0].is_object() && !items[3].is_number());
+        assert!(JsonValue::RawNumber("1".into()).is_number());
+        assert_eq!(items[3].as_string(), Some("s".to_string()));
+
+        assert_eq!(JsonValue::Number(-3.0).as_i64(), Some(-3));
+        assert_eq!(JsonValue::Number(-3.0).as_u64(), None);
+        assert_eq!(JsonValue::Number(1.5).as_i64(), None);
+        assert_eq!(JsonValue::Number(1e19).as_i64(), None);
+        assert_eq!(
+            JsonValue::Number(1e19).as_u64(),
+            Some(10_000_000_000_000_000_000)
+        );
+        let raw = JsonValue::RawNumber("18446744073709551615".into());
+        assert_eq!(raw.as_u64(), Some(u64::MAX));
+    }
+
+    #[test]
+    fn test_visitor_enter_leave_order() {
+        struct Trace(Vec<String>);
+        impl JsonVisitor for Trace {
+            fn enter_object(&mut self, _: &Map) {
+                self.0.push("{".to_string());
+            }
+            fn leave_object(&mut self, _: &Map) {
+                self.0.push("}".to_string());
+            }
+            fn enter_member(&mut self, key: &str) {
+                self.0.push(format!("{}:", key));
+            }
+            fn enter_array(&mut self, items: &[JsonValue]) {
+                self.0.push(format!("[{}", items.len()));
+            }
+            fn leave_array(&mut self, _: &[JsonValue]) {
+                self.0.push("]".to_string());
+            }
+            fn scalar(&mut self, value: &JsonValue) {
+                self.0.push(stringify(value));
+            }
+        }
+        let value = parse(r#"{"b": [1, null], "a": "x"}"#).unwrap();
+        let mut trace = Trace(Vec::new());
+        value.accept(&mut trace);
+        assert_eq!(
+            trace.0,
+            ["{", "a:", "\"x\"", "b:", "[2", "1", "null", "]", "}"]
+        );
+    }
+
+    #[test]
+    fn test_deep_transform_combinators() {
+        let mut value =
+            parse(r#"{"Name": " ann ", "Tags": [" a", null, 1.26], "Meta": {"Id": null}}"#)
+                .unwrap();
+        value.map_values(|v| {
+            if let JsonValue::String(s) = v {
+                *s = s.trim().to_string().into();
+            } else if let JsonValue::Number(n) = v {
+                *n = (*n * 10.0).round() / 10.0;
+            }
+        });
+        value.map_keys(|key| key.to_lowercase());
+        value.retain(|v| !v.is_null());
+        assert_eq!(
+            value,
+            parse(r#"{"name": "ann", "tags": ["a", 1.3], "meta": {}}"#).unwrap()
+        );
+
+        // Children are rewritten before their parent sees them.
+        let mut value = parse("[[1, 2], [3]]").unwrap();
+        value.transform(|v| {
+            if let Some(items) = v.as_array() {
+                *v = JsonValue::Number(items.iter().filter_map(JsonValue::as_f64).sum());
+            }
+        });
+        assert_eq!(value, 6.0);
+    }
+
+    #[test]
+    fn test_walk_depth_and_breadth_first() {
+        let value = parse(r#"{"b": [true, {"c/d": 1}], "a": 2}"#).unwrap();
+        let pointers =
+            |order| -> Vec<String> { value.walk_with(order).map(|(pointer, _)| pointer).collect() };
+        assert_eq!(
+            pointers(WalkOrder::DepthFirst),
+            ["", "/a", "/b", "/b/0", "/b/1", "/b/1/c~1d"]
+        );
+        let value = parse(r#"[[1], 2]"#).unwrap();
+        let depth: Vec<String> = value.walk().map(|(p, _)| p).collect();
+        let breadth: Vec<String> = value
+            .walk_with(WalkOrder::BreadthFirst)
+            .map(|(p, _)| p)
+            .collect();
+        assert_eq!(depth, ["", "/0", "/0/0", "/1"]);
+        assert_eq!(breadth, ["", "/0", "/1", "/0/0"]);
+        let numbers: Vec<String> = value
+            .walk()
+            .filter(|(_, v)| v.is_number())
+            .map(|(p, _)| p)
+            .collect();
+        assert_eq!(numbers, ["/0/0", "/1"]);
+    }
+
+    #[test]
+    fn test_redact_keys_and_pointers() {
+        let mut value = parse(
+            r#"{"user": {"Password": "x", "apiToken": "t", "name": "ann"},
+                "cards": [{"number": "4111", "type": "visa"}], "token": [1, 2]}"#,
+        )
+        .unwrap();
+        let count = redact(
+            &mut value,
+            &["password", "*token*", "/cards/*/number"],
+            &JsonValue::from("***"),
+        );
+        assert_eq!(count, 4);
+        assert_eq!(
+            value,
+            parse(
+                r#"{"user": {"Password": "***", "apiToken": "***", "name": "ann"},
+                    "cards": [{"number": "***", "type": "visa"}], "token": "***"}"#
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_sort_arrays_by_pointer_pattern() {
+        let mut value =
+            parse(r#"{"orders": [{"items": [3, 1, 2]}, {"items": ["b", "a"]}], "n": 1}"#).unwrap();
+        let by_text = |a: &JsonValue, b: &JsonValue| stringify(a).cmp(&stringify(b));
+        sort_arrays_by(&mut value, "/orders/*/items", by_text).unwrap();
+        assert_eq!(
+            value,
+            parse(r#"{"orders": [{"items": [1, 2, 3]}, {"items": ["a", "b"]}], "n": 1}"#).unwrap()
+        );
+        let error = sort_arrays_by(&mut value, "/n", by_text).unwrap_err();
+        assert_eq!(error.to_string(), "/n: not an array");
+        assert!(sort_arrays_by(&mut value, "/missing", by_text).is_err());
+    }
+
+    #[test]
+    fn test_hash_deduplicates_equal_values() {
+        let payloads = [
+            r#"{"a": 1, "b": [true, null]}"#,
+            r#"{"b": [true, null], "a": 1.0}"#,
+            r#"{"a": 1, "b": [true]}"#,
+            "0",
+            "-0",
+        ];
+        let unique: HashSet<JsonValue> = payloads.iter().map(|p| parse(p).unwrap()).collect();
+        assert_eq!(unique.len(), 3);
+    }
+
+    #[test]
+    fn test_total_ordering_across_kinds() {
+        let mut values: Vec<JsonValue> = [
+            r#"{"a": 1}"#,
+            r#"[1, 2]"#,
+            r#""b""#,
+            "2",
+            "true",
+            "null",
+            r#"[1]"#,
+            r#""a""#,
+            "-0.5",
+            "false",
+            r#"{"a": 0, "b": 0}"#,
+        ]
+        .iter()
+        .map(|text| parse(text).unwrap())
+        .collect();
+        values.sort();
+        let sorted: Vec<String> = values
+            .iter()
+            .map(|v| stringify_canonical(v).unwrap())
+            .collect();
+        assert_eq!(
+            sorted,
+            [
+                "null",
+                "false",
+                "true",
+                "-0.5",
+                "2",
+                r#""a""#,
+                r#""b""#,
+                "[1]",
+                "[1,2]",
+                r#"{"a":0,"b":0}"#,
+                r#"{"a":1}"#,
+            ]
+        );
+        assert_eq!(
+            JsonValue::Number(-0.0).cmp(&JsonValue::Number(0.0)),
+            std::cmp::Ordering::Equal
+        );
+        let raw = JsonValue::RawNumber("2".into());
+        assert!(JsonValue::Number(2.0) < raw && raw < JsonValue::Number(3.0));
+    }
+
+    #[test]
+    fn test_equivalent_with_options() {
+        let a = parse(r#"{"ids": [1, 2, 3], "total": 0.1, "at": "10:00", "rows": [{"at": 1}]}"#)
+            .unwrap();
+        let b =
+            parse(r#"{"ids": [3, 1, 2], "total": 0.1000001, "at": "10:05", "rows": [{"at": 2}]}"#)
+                .unwrap();
+        assert!(!a.equivalent(&b, EqOptions::default()));
+        let options = EqOptions {
+            unordered_arrays: true,
+            epsilon: 1e-6,
+            ignore_paths: vec!["/at".to_string(), "/rows/*/at".to_string()],
+        };
+        assert!(a.equivalent(&b, options.clone()));
+        let c = parse(r#"{"ids": [1, 2, 2], "total": 0.1, "at": 0, "rows": [{}]}"#).unwrap();
+        assert!(!a.equivalent(&c, options));
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn test_serde_json_round_trip() {
+        let text = r#"{"a": [1, 2.5, null, true], "big": 9007199254740993, "s": "x"}"#;
+        let theirs: serde_json::Value = serde_json::from_str(text).unwrap();
+        let ours = JsonValue::from(theirs.clone());
+        assert_eq!(
+            ours.get("big"),
+            Some(&JsonValue::RawNumber("9007199254740993".into()))
+        );
+        assert_eq!(ours.get("a"), parse(text).unwrap().get("a"));
+        assert_eq!(serde_json::Value::from(ours), theirs);
+        assert_eq!(
+            serde_json::Value::from(JsonValue::Number(f64::NAN)),
+            serde_json::Value::Null
+        );
+    }
+
+    #[cfg(feature = "bson")]
+    #[test]
+    fn test_bson_extended_json_round_trip() {
+        use crate::json::{from_bson_bytes, to_bson_bytes};
+        let text = r#"{"_id": {"$oid": "507f1f77bcf86cd799439011"}, "at": {"$date": "2024-01-02T03:04:05Z"},
+            "data": {"$binary": {"base64": "AQI=", "subType": "00"}}, "n": 3, "tags": ["a"]}"#;
+        let value = parse(text).unwrap();
+        let document = bson::Document::try_from(value.clone()).unwrap();
+        assert!(matches!(document.get("_id"), Some(bson::Bson::ObjectId(_))));
+        assert!(matches!(document.get("at"), Some(bson::Bson::DateTime(_))));
+        let bytes = [
+            to_bson_bytes(&value).unwrap(),
+            to_bson_bytes(&value).unwrap(),
+        ]
+        .concat();
+        let decoded = from_bson_bytes(&bytes).unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].get("_id"), value.get("_id"));
+        assert_eq!(decoded[0].get("n"), Some(&JsonValue::Number(3.0)));
+        assert!(to_bson_bytes(&JsonValue::from(1)).is_err());
+    }
+
+    #[test]
+    fn test_ubjson_round_trip_and_typed_containers() {
+        let value = parse(
+            r#"{"id": 70000, "bytes": [1, 2, 255], "mixed": [-1, 1.5, "x", null, true], "n": -300}"#,
+        )
+        .unwrap();
+        let encoded = to_ubjson(&value);
+        assert_eq!(parse_ubjson(&encoded).unwrap(), value);
+        assert_eq!(to_ubjson(&parse("[1,2]").unwrap()), b"[$U#U\x02\x01\x02");
+
+        // Plain containers, a char, and an int64 beyond 2^53.
+        let bytes = b"{U\x01aCzU\x01b[L\x00\x20\x00\x00\x00\x00\x00\x01Z]}";
+        let mut expected = parse(r#"{"a": "z", "b": [0, null]}"#).unwrap();
+        expected
+            .pointer_set("/b/0", JsonValue::RawNumber("9007199254740993".into()))
+            .unwrap();
+        assert_eq!(parse_ubjson(bytes).unwrap(), expected);
+        let error = parse_ubjson(b"[#U\x02Z").unwrap_err();
+        assert_eq!(error.to_string(), "count exceeds the input at offset 4");
+        let error = parse_ubjson(b"SU\x05ab").unwrap_err();
+        assert_eq!(error.to_string(), "unexpected end of input at offset 3");
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_bridge() {
+        use crate::json::{from_yaml, to_yaml};
+        let yaml = "name: app\nports: [80, 443]\nbase: &base {debug: true}\ndev: *base\n1: one\nratio: 0.5\n";
+        let value = from_yaml(yaml).unwrap();
+        assert_eq!(
+            value,
+            parse(
+                r#"{"name": "app", "ports": [80, 443], "base": {"debug": true},
+                     "dev": {"debug": true}, "1": "one", "ratio": 0.5}"#
+            )
+            .unwrap()
+        );
+        assert_eq!(from_yaml(&to_yaml(&value).unwrap()).unwrap(), value);
+        assert_eq!(
+            to_yaml(&parse(r#"{"b": [1], "a": null}"#).unwrap()).unwrap(),
+            "a: null\nb:\n- 1\n"
+        );
+        assert!(from_yaml("? [1]\n: x\n").is_err());
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_bridge() {
+        use crate::json::{from_toml, to_toml};
+        let toml = "name = \"world\"\nbig = 9007199254740993\n\n[build]\nat = 2024-01-02T03:04:05Z\njobs = [1, 2]\n";
+        let value = from_toml(toml).unwrap();
+        assert_eq!(
+            value.pointer("/build/at"),
+            Some(&JsonValue::from("2024-01-02T03:04:05Z"))
+        );
+        assert_eq!(
+            value.get("big"),
+            Some(&JsonValue::RawNumber("9007199254740993".into()))
+        );
+        assert_eq!(
+            value.pointer("/build/jobs"),
+            Some(&parse("[1, 2]").unwrap())
+        );
+
+        let value = parse(r#"{"b": {"x": 1.5, "gone": null}, "a": 1}"#).unwrap();
+        assert_eq!(to_toml(&value).unwrap(), "a = 1\n\n[b]\nx = 1.5\n");
+        assert!(to_toml(&parse("[1]").unwrap()).is_err());
+        assert!(to_toml(&parse(r#"{"a": [null]}"#).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_csv_import_and_export() {
+        let input = "name,note\r\nann,\"says \"\"hi\"\", twice\"\n\nbob,\"two\nlines\"\n";
+        let rows = parse_csv(input, &CsvOptions::default()).unwrap();
+        assert_eq!(
+            rows,
+            parse(r#"[{"name": "ann", "note": "says \"hi\", twice"}, {"name": "bob", "note": "two\nlines"}]"#)
+                .unwrap()
+        );
+        let error = parse_csv("a,b\n1\n", &CsvOptions::default()).unwrap_err();
+        assert_eq!(error.to_string(), "expected 2 fields, found 1 on line 2");
+
+        let rows =
+            parse(r#"[{"id": 1, "name": "a;b", "tags": [1]}, {"id": 2, "ok": true}]"#).unwrap();
+        let options = CsvOptions {
+            delimiter: ';',
+            quote: QuoteStyle::NonNumeric,
+            columns: Some(vec!["id".to_string(), "name".to_string(), "ok".to_string()]),
+        };
+        assert_eq!(
+            to_csv(&rows, &options).unwrap(),
+            "\"id\";\"name\";\"ok\"\r\n1;\"a;b\";\r\n2;;true\r\n"
+        );
+        assert_eq!(
+            to_csv(&rows, &CsvOptions::default()).unwrap(),
+            "id,name,ok,tags\r\n1,a;b,,[1]\r\n2,,true,\r\n"
+        );
+        assert_eq!(
+            parse_csv(
+                &to_csv(&rows, &CsvOptions::default()).unwrap(),
+                &CsvOptions::default()
+            )
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_urlencoded_brackets_round_trip() {
+        let value = parse_urlencoded(
+            "a[0][b]=1&a[1][b]=2&tag[]=x&tag[]=y+z&q=%C3%A9%26&flag&page=2&page=3",
+        )
+        .unwrap();
+        assert_eq!(
+            value,
+            parse(r#"{"a": [{"b": "1"}, {"b": "2"}], "tag": ["x", "y z"], "q": "é&", "flag": "", "page": "3"}"#)
+                .unwrap()
+        );
+        let encoded = to_urlencoded(&value).unwrap();
+        assert_eq!(
+            encoded,
+            "a%5B0%5D%5Bb%5D=1&a%5B1%5D%5Bb%5D=2&flag=&page=3&q=%C3%A9%26&tag%5B0%5D=x&tag%5B1%5D=y+z"
+        );
+        assert_eq!(parse_urlencoded(&encoded).unwrap(), value);
+        assert!(parse_urlencoded("a=1&a[b]=2").is_err());
+        assert!(parse_urlencoded("a=%zz").is_err());
+    }
+
+    #[cfg(feature = "preserve_order")]
+    #[test]
+    fn test_preserve_order_keeps_document_order() {
+        let mut value = parse(r#"{"z": 1, "a": {"y": 2, "b": 3}, "m": 4}"#).unwrap();
+        assert_eq!(stringify(&value), r#"{"z":1,"a":{"y":2,"b":3},"m":4}"#);
+        value.pointer_remove("/z");
+        value.entry("c").or_insert(JsonValue::Null);
+        assert_eq!(stringify(&value), r#"{"a":{"y":2,"b":3},"m":4,"c":null}"#);
+    }
+
+    #[test]
+    fn test_simd_scanners_match_scalar_loops() {
+        use crate::json::simd::{find_delimiter, find_string_special, skip_whitespace};
+        let mut inputs: Vec<Vec<u8>> = Vec::new();
+        for len in [0, 1, 15, 16, 17, 31, 33, 64] {
+            for stop in 0..len {
+                for byte in [b'"', b'\\', b'\n', b'x', 0x01, 0xc3, b':', b'/'] {
+                    let mut input = vec![b' '; len];
+                    input[stop] = byte;
+                    inputs.push(input.clone());
+                    input.iter_mut().take(stop).for_each(|b| *b = b'a');
+                    inputs.push(input);
+                }
+            }
+        }
+        for input in &inputs {
+            for from in [0, 1, 16] {
+                let scalar = |stop: &dyn Fn(u8) -> bool| {
+                    (from..input.len())
+                        .find(|&i| stop(input[i]))
+                        .unwrap_or(input.len())
+                };
+                let from_clamped = from.min(input.len());
+                assert_eq!(
+                    skip_whitespace(input, from_clamped),
+                    scalar(&|b| !b" \t\n\r".contains(&b)).max(from_clamped)
+                );
+                assert_eq!(
+                    find_string_special(input, from_clamped, b'"'),
+                    scalar(&|b| b == b'"' || b == b'\\' || b < 0x20).max(from_clamped)
+                );
+                assert_eq!(
+                    find_delimiter(input, from_clamped),
+                    scalar(&|b| b",:[]{}\"' \t\r\n/".contains(&b)).max(from_clamped)
+                );
+            }
+        }
+        assert_eq!(
+            parse(r#"  ["café \" long enough to span blocks", 1]  "#).unwrap(),
+            JsonValue::Array(vec!["café \" long enough to span blocks".into(), 1.into()])
+        );
+    }
+
+    #[test]
+    fn test_tape_lazy_navigation() {
+        let input = r#" {"users": [{"name": "ann", "tags": []}, {"name": "b\u00f6b", "age": 41.5}],
+                        "ok": true, "none": null, "a/b": 1, "ok": false} "#;
+        let tape = Tape::parse(input).unwrap();
+        assert_eq!(tape.root().len(), 5);
+        let users = tape.get("users").unwrap();
+        assert_eq!(users.len(), 2);
+        assert!(matches!(
+            users.get_index(0).unwrap().get("name").unwrap().as_str(),
+            Some(Cow::Borrowed("ann"))
+        ));
+        assert_eq!(
+            tape.pointer("/users/1/name").unwrap().as_str().unwrap(),
+            "böb"
+        );
+        assert_eq!(tape.pointer("/users/1/age").unwrap().as_f64(), Some(41.5));
+        assert_eq!(tape.pointer("/users/0/tags").unwrap().raw(), "[]");
+        assert_eq!(tape.get("ok").unwrap().as_bool(), Some(false));
+        assert!(tape.get("none").unwrap().is_null());
+        assert_eq!(tape.pointer("/a~1b").unwrap().as_f64(), Some(1.0));
+        assert!(tape.get("missing").is_none());
+        let keys: Vec<_> = tape.root().members().map(|(k, _)| k.into_owned()).collect();
+        assert_eq!(keys, ["users", "ok", "none", "a/b", "ok"]);
+        assert_eq!(users.to_value(), parse(users.raw()).unwrap());
+        assert!(Tape::parse("[1,]").is_err());
+    }
+
+    #[test]
+    fn test_key_interner_shares_repeated_keys() {
+        let mut keys = KeyInterner::new();
+        let records = parse(r#"[{"id": 1, "name": "a"}, {"id": 2, "name": "b"}]"#).unwrap();
+        let interned: Vec<_> = records
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|record| {
+                record
+                    .iter_sorted()
+                    .map(|(key, _)| key.clone())
+                    .collect::<Vec<_>>()
+            })
+            .map(|key| keys.intern(&key))
+            .collect();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys.bytes(), "id".len() + "name".len());
+        assert!(std::sync::Arc::ptr_eq(&interned[0], &interned[2]));
+        assert!(std::sync::Arc::ptr_eq(
+            &keys.get("name").unwrap(),
+            &interned[3]
+        ));
+        assert!(keys.get("missing").is_none());
+    }
+
+    #[cfg(feature = "arena")]
+    #[test]
+    fn test_parse_in_arena() {
+        use crate::json::{Arena, ArenaJsonValue, parse_in};
+        let arena = Arena::new();
+        let input =
+            r#"[{"id": 1, "name": "a\u00e9\ud83d\ude00"}, {"id": 2, "name": "b", "id": 3}]"#;
+        let value = parse_in(input, &arena).unwrap();
+        assert_eq!(
+            value.pointer("/0/name").unwrap().as_str(),
+            Some("a\u{e9}\u{1f600}")
+        );
+        assert_eq!(value.pointer("/1/id").unwrap().as_f64(), Some(3.0));
+        let records = value.as_array().unwrap();
+        let ArenaJsonValue::Object(first) = records[0] else {
+            panic!("not an object")
+        };
+        let ArenaJsonValue::Object(second) = records[1] else {
+            panic!("not an object")
+        };
+        assert!(std::ptr::eq(first[0].0, second[0].0));
+        assert_eq!(value.to_value(), parse(input).unwrap());
+        assert!(parse_in("[1,]", &arena).is_err());
+    }
+
+    #[test]
+    fn test_json_value_fits_in_three_words() {
+        assert!(std::mem::size_of::<JsonValue>() <= 24);
+        assert_eq!(std::mem::size_of::<JsonString>(), 16);
+        let mut value = parse(r#"{"a": {"b": [1]}}"#).unwrap();
+        value.entry("c").or_insert(JsonValue::Null);
+        assert_eq!(value.pointer("/a/b/0"), Some(&JsonValue::Number(1.0)));
+        assert_eq!(value.as_object().map(|members| members.len()), Some(2));
+    }
+
+    #[test]
+    fn test_shared_value_clones_cheaply_and_copies_on_write() {
+        let original = SharedJsonValue::from(
+            parse(r#"{"db": {"host": "a", "ports": [1, 2]}, "log": {"level": "info"}}"#).unwrap(),
+        );
+        let mut copy = original.clone();
+        assert!(copy.ptr_eq(&original));
+        *copy.pointer_mut("/db/ports/1").unwrap() = SharedJsonValue::Number(3.0);
+        copy.pointer_mut("/db")
+            .unwrap()
+            .insert("user", SharedJsonValue::String("root".into()));
+        assert_eq!(
+            original.pointer("/db/ports/1").and_then(|v| v.as_f64()),
+            Some(2.0)
+        );
+        assert_eq!(
+            copy.pointer("/db/ports/1").and_then(|v| v.as_f64()),
+            Some(3.0)
+        );
+        assert!(original.get("db").unwrap().get("user").is_none());
+        assert!(
+            copy.get("log")
+                .unwrap()
+                .ptr_eq(original.get("log").unwrap())
+        );
+        assert!(!copy.get("db").unwrap().ptr_eq(original.get("db").unwrap()));
+        let handle = std::thread::spawn({
+            let copy = copy.clone();
+            move || copy.to_value()
+        });
+        assert_eq!(
+            handle.join().unwrap(),
+            parse(r#"{"db": {"host": "a", "ports": [1, 3], "user": "root"}, "log": {"level": "info"}}"#)
+                .unwrap()
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_ndjson_and_array_parsing() {
+        use crate::json::{parse_array_parallel, parse_ndjson_parallel};
+        let lines: Vec<String> = (0..500)
+            .map(|i| format!(r#"{{"n": {}, "s": "é{}"}}"#, i, i))
+            .collect();
+        let ndjson = lines.join("\r\n") + "\n\n";
+        let values = parse_ndjson_parallel(&ndjson).unwrap();
+        assert_eq!(values.len(), 500);
+        assert_eq!(values[499], parse(&lines[499]).unwrap());
+
+        let bad = format!("{}\n{{\"n\": }}\n", lines[..300].join("\n"));
+        let error = parse_ndjson_parallel(&bad).unwrap_err();
+        assert_eq!((error.position().line, error.position().column), (301, 7));
+
+        let array = format!("[{}]", lines.join(",\n"));
+        assert_eq!(parse_array_parallel(&array), parse(&array));
+        let broken = format!("[{}, [1, tru]]", lines.join(","));
+        assert_eq!(parse_array_parallel(&broken), parse(&broken));
+        assert_eq!(parse_array_parallel("[1, 2,]"), parse("[1, 2,]"));
+        assert_eq!(parse_array_parallel(" {} "), parse(" {} "));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_parse_file_memory_maps_input() {
+        use crate::json::{FileError, MappedFile, parse_file};
+        let dir = std::env::temp_dir().join(format!("rust-json-mmap-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let good = dir.join("good.json");
+        std::fs::write(&good, r#"{"items": [1, 2, {"name": "x"}]}"#).unwrap();
+        assert_eq!(
+            parse_file(&good).unwrap(),
+            parse(r#"{"items": [1, 2, {"name": "x"}]}"#).unwrap()
+        );
+        let mapped = MappedFile::open(&good).unwrap();
+        let tape = mapped.tape().unwrap();
+        assert_eq!(
+            tape.pointer("/items/2/name").unwrap().as_str().unwrap(),
+            "x"
+        );
+
+        let bad = dir.join("bad.json");
+        std::fs::write(&bad, "[1,").unwrap();
+        assert!(matches!(parse_file(&bad), Err(FileError::Parse(_))));
+        std::fs::write(&bad, b"[\"\xff\"]").unwrap();
+        assert!(
+            matches!(parse_file(&bad), Err(FileError::Io(e)) if e.kind() == std::io::ErrorKind::InvalidData)
+        );
+        assert!(matches!(
+            parse_file(dir.join("missing.json")),
+            Err(FileError::Io(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_bytes_detects_bom_and_utf16() {
+        let text = r#"{"name": "Zoë 😀", "n": [1]}"#;
+        let expected = parse(text).unwrap();
+        let utf16 = |big_endian: bool| -> Vec<u8> {
+            text.encode_utf16()
+                .flat_map(|u| {
+                    if big_endian {
+                        u.to_be_bytes()
+                    } else {
+                        u.to_le_bytes()
+                    }
+                })
+                .collect()
+        };
+        let with_bom = [b"\xEF\xBB\xBF".as_slice(), text.as_bytes()].concat();
+        assert_eq!(parse_bytes_detect_encoding(&with_bom), Ok(expected.clone()));
+        assert!(matches!(
+            decode_detect_encoding(text.as_bytes()),
+            Ok(Cow::Borrowed(_))
+        ));
+        for big_endian in [false, true] {
+            assert_eq!(
+                parse_bytes_detect_encoding(&utf16(big_endian)),
+                Ok(expected.clone())
+            );
+            let bom: &[u8] = if big_endian { b"\xFE\xFF" } else { b"\xFF\xFE" };
+            let marked = [bom, &utf16(big_endian)].concat();
+            assert_eq!(detect_encoding(&marked).1, 2);
+            assert_eq!(parse_bytes_detect_encoding(&marked), Ok(expected.clone()));
+        }
+        assert_eq!(detect_encoding(b"1\0"), (Encoding::Utf16Le, 0));
+        let mut truncated = utf16(false);
+        truncated.pop();
+        assert!(matches!(
+            parse_bytes_detect_encoding(&truncated),
+            Err(BytesError::Decode(DecodeError { offset })) if offset == truncated.len() - 1
+        ));
+        assert!(matches!(
+            parse_bytes_detect_encoding(b"\xEF\xBB\xBF[1,"),
+            Err(BytesError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_bytes_utf8_policies() {
+        let input = b"{\"name\": \"caf\xe9\", \"ok\": \"\\u00e9\", \"k\xff\": [1, true]}";
+        assert_eq!(
+            parse_bytes(input, Utf8Policy::Reject),
+            Err(BytesError::Decode(DecodeError { offset: 13 }))
+        );
+        let replaced = parse_bytes(input, Utf8Policy::Replace).unwrap();
+        assert_eq!(
+            replaced.get("name").and_then(|v| v.as_str()),
+            Some("caf\u{fffd}")
+        );
+        let kept = parse_bytes(input, Utf8Policy::Bytes).unwrap();
+        assert_eq!(
+            kept.get("name"),
+            Some(&JsonValue::Bytes((b"caf\xe9".to_vec()).into()))
+        );
+        assert_eq!(kept.get("ok").and_then(|v| v.as_str()), Some("\u{e9}"));
+        assert!(kept.get("k\u{fffd}").is_some());
+        assert_eq!(
+            stringify(&kept.get("name").unwrap().clone()),
+            "\"caf\u{fffd}\""
+        );
+        assert!(matches!(
+            parse_bytes(b"[\"\xff\", \xff]", Utf8Policy::Bytes),
+            Err(BytesError::Parse(_))
+        ));
+        assert_eq!(
+            parse_bytes(b"[1]", Utf8Policy::Reject),
+            Ok(parse("[1]").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_display_compact_and_pretty() {
+        let value = parse(r#"{"a": [1, "x"]}"#).unwrap();
+        assert_eq!(format!("{}", value), stringify(&value));
+        assert_eq!(
+            format!("{:#}", value),
+            "{\n  \"a\": [\n    1,\n    \"x\"\n  ]\n}"
+        );
+        assert_eq!(JsonValue::String("q\"".into()).to_string(), r#""q\"""#);
+    }
+
+    #[test]
+    fn test_object_and_array_builders() {
+        let nickname: Option<&str> = None;
+        let body = JsonObject::new()
+            .field("name", "Alice")
+            .field("age", 30)
+            .field_if(false, "admin", true)
+            .maybe_field("nickname", nickname)
+            .maybe_field("email", Some("a@example.com"))
+            .field(
+                "tags",
+                JsonArray::new()
+                    .item("x")
+                    .item_if(true, 2)
+                    .maybe_item(None::<bool>),
+            )
+            .build();
+        assert_eq!(
+            body,
+            parse(r#"{"name": "Alice", "age": 30, "email": "a@example.com", "tags": ["x", 2]}"#)
+                .unwrap()
+        );
+        assert_eq!(JsonArray::new().build(), JsonValue::Array(Vec::new()));
+    }
+
+    #[test]
+    fn test_json_writer_streams_valid_json() {
+        let mut writer = JsonWriter::new(Vec::new());
+        writer.begin_object().unwrap();
+        writer.key("name").unwrap();
+        writer.value("Al\"ice").unwrap();
+        writer.key("items").unwrap();
+        writer.begin_array().unwrap();
+        for i in 0..3 {
+            writer.value(i).unwrap();
+        }
+        writer.begin_object().unwrap();
+        writer.end().unwrap();
+        writer.end().unwrap();
+        assert!(!writer.is_complete());
+        writer.end().unwrap();
+        let out = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(out, r#"{"name":"Al\"ice","items":[0,1,2,{}]}"#);
+        assert!(parse(&out).is_ok());
+    }
+
+    #[test]
+    fn test_json_writer_rejects_invalid_call_sequences() {
+        let unexpected =
+            |r: Result<(), WriterError>| matches!(r, Err(WriterError::UnexpectedCall(_)));
+        let mut writer = JsonWriter::new(Vec::new());
+        assert!(unexpected(writer.key("a")));
+        assert!(unexpected(writer.end()));
+        writer.begin_object().unwrap();
+        assert!(unexpected(writer.value(1)));
+        writer.key("a").unwrap();
+        assert!(unexpected(writer.key("b")));
+        assert!(unexpected(writer.end()));
+        writer.value(1).unwrap();
+        writer.end().unwrap();
+        assert!(unexpected(writer.value(2)));
+        assert_eq!(writer.finish().unwrap(), b"{\"a\":1}");
+        assert!(matches!(
+            JsonWriter::new(Vec::new()).finish(),
+            Err(WriterError::UnexpectedCall(_))
+        ));
+    }
+
+    #[test]
+    fn test_stringify_colored() {
+        let value = parse(r#"{"a": [1, "x", true, null]}"#).unwrap();
+        let scheme = ColorScheme {
+            key: "1",
+            string: "2",
+            number: "3",
+            literal: "4",
+            punctuation: "",
+        };
+        assert_eq!(
+            stringify_colored(&value, scheme),
+            "{\n  \x1b[1m\"a\"\x1b[0m: [\n    \x1b[3m1\x1b[0m,\n    \x1b[2m\"x\"\x1b[0m,\n    \
+             \x1b[4mtrue\x1b[0m,\n    \x1b[4mnull\x1b[0m\n  ]\n}"
+        );
+        assert_eq!(
+            stringify_colored(&value, ColorScheme::PLAIN),
+            stringify_pretty(&value)
+        );
+    }
+
+    #[test]
+    fn test_render_html() {
+        let value = parse(r#"{"a<b": ["x&y", 1, null, {}]}"#).unwrap();
+        let html = render_html(&value, &HtmlOptions::default());
+        assert_eq!(
+            html,
+            "<pre class=\"json\"><span class=\"json-punct\">{</span>\n\
+             \x20\x20<span class=\"json-key\">&quot;a&lt;b&quot;</span><span class=\"json-punct\">:</span> \
+             <span class=\"json-punct\">[</span>\n\
+             \x20\x20\x20\x20<span class=\"json-string\">&quot;x&amp;y&quot;</span><span class=\"json-punct\">,</span>\n\
+             \x20\x20\x20\x20<span class=\"json-number\">1</span><span class=\"json-punct\">,</span>\n\
+             \x20\x20\x20\x20<span class=\"json-literal\">null</span><span class=\"json-punct\">,</span>\n\
+             \x20\x20\x20\x20<span class=\"json-punct\">{}</span>\n\
+             \x20\x20<span class=\"json-punct\">]</span>\n\
+             <span class=\"json-punct\">}</span>\n</pre>"
+        );
+
+        let options = HtmlOptions {
+            collapsible: true,
+            ..HtmlOptions::default()
+        };
+        let html = render_html(&value, &options);
+        assert_eq!(html.matches("<details open><summary>").count(), 2);
+        assert_eq!(html.matches("</details>").count(), 2);
+    }
+
+    #[test]
+    fn test_deep_nesting_is_an_error_not_a_stack_overflow() {
+        let deep = "[".repeat(200_000);
+        assert!(matches!(
+            validate(&deep),
+            Err(ParseError::TooDeep(DEFAULT_MAX_DEPTH, _))
+        ));
+        assert!(matches!(parse(&deep), Err(ParseError::TooDeep(_, _))));
+
+        let options = ParserOptions {
+            max_depth: Some(2),
+            ..ParserOptions::default()
+        };
+        assert!(validate_with_options("[[1]]", options.clone()).is_ok());
+        let err = validate_with_options("[[[1]]]", options.clone()).unwrap_err();
+        assert_eq!(err, ParseError::TooDeep(2, Position::at("[[[1]]]", 2)));
+        assert_eq!(
+            parse_with_options(r#"{"a": {"b": []}}"#, options).unwrap_err(),
+            ParseError::TooDeep(2, Position::at(r#"{"a": {"b": []}}"#, 12))
+        );
+    }
+
+    #[test]
+    fn test_deep_snapshot_is_an_error_not_a_stack_overflow() {
+        // Nested one-element arrays: tag 5, length 1, repeated.
+        let mut data = b"WJSN\x01".to_vec();
+        for _ in 0..200_000 {
+            data.extend_from_slice(&[5, 1]);
+        }
+        data.push(0);
+        let offset = 5 + 2 * DEFAULT_MAX_DEPTH;
+        assert_eq!(
+            JsonValue::from_snapshot(&data),
+            Err(SnapshotError::TooDeep(offset))
+        );
+
+        let mut value = JsonValue::Null;
+        for _ in 0..DEFAULT_MAX_DEPTH {
+            value = JsonValue::Array(vec![value]);
+        }
+        assert_eq!(JsonValue::from_snapshot(&value.to_snapshot()), Ok(value));
+    }
+
+    #[test]
+    fn test_recovery_stops_at_the_depth_limit() {
+        let recovered = parse_with_recovery(&"[".repeat(200_000));
+        assert!(matches!(
+            recovered.diagnostics[0],
+            ParseError::TooDeep(DEFAULT_MAX_DEPTH, _)
+        ));
+
+        let options = ParserOptions {
+            max_depth: Some(2),
+            ..ParserOptions::default()
+        };
+        let recovered = parse_with_recovery_options("[1, [[2]], [3]]", options);
+        assert_eq!(recovered.value, parse("[1, [null], [3]]").unwrap());
+        assert_eq!(recovered.diagnostics.len(), 1);
+        assert!(matches!(
+            recovered.diagnostics[0],
+            ParseError::TooDeep(2, _)
+        ));
+    }
+
+    #[test]
+    fn test_tape_rejects_input_over_the_depth_limit() {
+        assert!(matches!(
+            Tape::parse(&"[".repeat(200_000)),
+            Err(ParseError::TooDeep(DEFAULT_MAX_DEPTH, _))
+        ));
+        let shallow = ParserOptions {
+            max_depth: Some(1),
+            allow_comments: true,
+            ..ParserOptions::default()
+        };
+        with_parser_options(shallow, || {
+            assert!(Tape::parse("[1]").is_ok());
+            assert!(matches!(
+                Tape::parse("[[1]]"),
+                Err(ParseError::TooDeep(1, _))
+            ));
+            // Only the limit is taken from the scope; the grammar stays strict.
+            assert!(Tape::parse("[1] // c").is_err());
+        });
+    }
+
+    #[test]
+    fn test_parse_pooled_follows_the_current_scope() {
+        let lenient = ParserOptions {
+            allow_comments: true,
+            ..ParserOptions::default()
+        };
+        // Run on a fresh thread so this is the cached parser's first use.
+        std::thread::spawn(move || {
+            with_parser_options(lenient.clone(), || {
+                assert!(parse_pooled("[1] // c").is_ok());
+            });
+            assert!(parse_pooled("[1] // c").is_err());
+            assert!(parse("[1] // c").is_err());
+            with_parser_options(lenient, || {
+                assert!(parse_pooled("[1] // c").is_ok());
+            });
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_sort_keys_recursive() {
+        let mut value = parse(r#"{"z": [{"b": 1, "a": 2}], "a": {"y": 2, "b": 3}}"#).unwrap();
+        let before = value.clone();
+        sort_keys_recursive(&mut value);
+        assert_eq!(value, before);
+        if cfg!(any(feature = "preserve_order", feature = "sorted_map")) {
+            assert_eq!(
+                stringify(&value),
+                r#"{"a":{"b":3,"y":2},"z":[{"a":2,"b":1}]}"#
+            );
+        }
+    }
+
+    #[test]
+    fn test_nan_is_equal_to_itself_and_dedups() {
+        let nan = parse_with_options("[NaN, -0.0]", ParserOptions::lenient()).unwrap();
+        assert_eq!(nan, nan.clone());
+        assert_eq!(JsonValue::Number(f64::NAN), JsonValue::Number(-f64::NAN));
+        assert_eq!(JsonValue::Number(-0.0), JsonValue::Number(0.0));
+        assert_ne!(JsonValue::Number(f64::NAN), JsonValue::Number(1.0));
+
+        let set: HashSet<JsonValue> = [nan.clone(), nan, parse("[0, 0]").unwrap()]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_ord_agrees_with_eq_for_nan_and_zero() {
+        let nan = JsonValue::Number(f64::NAN);
+        let values = [
+            nan.clone(),
+            JsonValue::Number(-f64::NAN),
+            JsonValue::Number(0.0),
+            JsonValue::Number(-0.0),
+            JsonValue::Number(f64::INFINITY),
+            JsonValue::Number(-1.0),
+        ];
+        for a in &values {
+            for b in &values {
+                assert_eq!(
+                    a.cmp(b) == std::cmp::Ordering::Equal,
+                    a == b,
+                    "{:?} {:?}",
+                    a,
+                    b
+                );
+            }
+        }
+        assert_eq!(
+            nan.cmp(&JsonValue::Number(f64::INFINITY)),
+            std::cmp::Ordering::Greater
+        );
+        let set: BTreeSet<JsonValue> = values.into_iter().collect();
+        assert_eq!(set.len(), 4);
+        assert!(set.contains(&JsonValue::Number(-f64::NAN)));
+    }
+
+    #[test]
+    fn test_short_strings_are_stored_inline() {
+        let value =
+            parse(r#"["short", "a string longer than fifteen bytes", "\u00e9t\u00e9"]"#).unwrap();
+        let strings: Vec<&JsonString> = value
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| match item {
+                JsonValue::String(s) => s,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert!(strings[0].is_inline());
+        assert!(!strings[1].is_inline());
+        assert!(strings[2].is_inline());
+        assert_eq!(*strings[2], "\u{e9}t\u{e9}");
+        assert_eq!(strings[1].len(), 34);
+
+        let mut s = JsonString::from("123456789012345");
+        assert!(s.is_inline());
+        s.push_str("6");
+        assert!(!s.is_inline());
+        assert_eq!(s.into_string(), "1234567890123456");
+        assert_eq!(JsonString::new().as_str(), "");
+    }
+
+    #[test]
+    fn test_log_value_stops_serializing_at_max_len() {
+        // A counting writer shows how much text the serializer produced.
+        struct Counting(usize);
+        impl std::fmt::Write for Counting {
+            fn write_str(&mut self, s: &str) -> std::fmt::Result {
+                self.0 += s.len();
+                Ok(())
+            }
+        }
+
+        let big = JsonValue::Array(vec![JsonValue::String("é\n".repeat(1000).into()); 1000]);
+        let mut out = Counting(0);
+        std::fmt::write(&mut out, format_args!("{}", log_value(&big).max_len(8))).unwrap();
+        assert!(out.0 < 64, "wrote {} bytes", out.0);
+        assert_eq!(log_value(&big).max_len(8).to_string(), "[\"é\\né\\n…");
+
+        let exact = JsonValue::String("abcdef".into());
+        assert_eq!(log_value(&exact).max_len(8).to_string(), "\"abcdef\"");
+    }
+
+    #[test]
+    fn test_query_selects_what_explain_reports() {
+        let value =
+            parse(r#"{"b": [{"n": 1, "t": "x"}, {"n": "2"}, [3, {"n": 4}]], "a": {"n": 5}}"#)
+                .unwrap();
+        for path in [
+            "$..n",
+            "$..*",
+            "$.b[*]",
+            "$.b[-1][1].n",
+            "$.b[?(@.n > 1)]",
+            "$.b[?(@.t)].n",
+            "$.b[?(@.n == '2')]",
+            "$.missing[0]",
+        ] {
+            let explained: Vec<&JsonValue> = value
+                .explain(path)
+                .unwrap()
+                .matches
+                .into_iter()
+                .map(|(_, v)| v)
+                .collect();
+            assert_eq!(value.query(path).unwrap(), explained, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_parse_pooled_is_reentrant() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let nested = Arc::new(AtomicBool::new(false));
+        let inner = Arc::clone(&nested);
+        let options = ParserOptions {
+            metrics_hook: Some(MetricsHook::new(move |_| {
+                // The hook runs while the outer parse holds the thread's
+                // buffer; only the first call parses again.
+                if !inner.swap(true, Ordering::SeqCst) {
+                    assert_eq!(
+                        parse_pooled(r#""x\ty""#),
+                        Ok(JsonValue::String("x\ty".into()))
+                    );
+                }
+            })),
+            ..ParserOptions::default()
+        };
+        with_parser_options(options, || {
+            assert_eq!(
+                parse_pooled(r#"["a\nb", "c\"d"]"#),
+                Ok(JsonValue::Array(vec![
+                    JsonValue::String("a\nb".into()),
+                    JsonValue::String("c\"d".into()),
+                ]))
+            );
+        });
+        assert!(nested.load(Ordering::SeqCst));
+        assert_eq!(
+            parse_pooled(r#""\u00e9""#),
+            Ok(JsonValue::String("é".into()))
+        );
+    }
+
+    #[test]
+    fn test_exponent_needs_digits() {
+        let options = ParserOptions {
+            preserve_raw_numbers: true,
+            ..ParserOptions::default()
+        };
+        for input in ["1e", "1E+", "-2e-", "[1e]"] {
+            assert!(
+                matches!(
+                    parse_with_options(input, options.clone()),
+                    Err(ParseError::InvalidNumber(..))
+                ),
+                "{}",
+                input
+            );
+            assert!(parse(input).is_err(), "{}", input);
+        }
+        assert_eq!(
+            parse_with_options("-2e-3", options),
+            Ok(JsonValue::RawNumber("-2e-3".into()))
+        );
+    }
+    #[test]
+    fn test_internal_serializers_ignore_stringify_defaults() {
+        let value = parse(r#"[{"k": {"x": [1, "é<"]}, "n": 2}]"#).unwrap();
+        let record = parse(r#"{"k": {"x": [1, "é<"]}, "n": 2}"#).unwrap();
+        let expected = (
+            to_csv(&value, &CsvOptions::default()).unwrap(),
+            stringify_json5(&value),
+            to_urlencoded(&record).unwrap(),
+            value.explain("$[?(@.k.x == 1)]").unwrap().to_string(),
+        );
+        let options = StringifyOptions {
+            indent: Some("  ".to_string()),
+            escape_html: true,
+            escape_non_ascii: true,
+            ..StringifyOptions::default()
+        };
+        with_stringify_options(options, || {
+            let actual = (
+                to_csv(&value, &CsvOptions::default()).unwrap(),
+                stringify_json5(&value),
+                to_urlencoded(&record).unwrap(),
+                value.explain("$[?(@.k.x == 1)]").unwrap().to_string(),
+            );
+            assert_eq!(actual, expected);
+        });
+        assert!(
+            expected.3.contains(r#"@.k.x is [1,"é<"]"#),
+            "{}",
+            expected.3
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use super::pointer::{escape_pointer_token, unescape_token};
+use super::{JsonValue, Map, StringifyOptions, stringify_with};
+use std::collections::{BTreeSet, HashMap};
+
+/// A member two sides changed in different ways. Each side is `None` where
+/// the member is absent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+    /// JSON Pointer of the conflicting value.
+    pub path: String,
+    pub base: Option<JsonValue>,
+    pub ours: Option<JsonValue>,
+    pub theirs: Option<JsonValue>,
+}
+
+/// The outcome of `merge3`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeResult {
+    /// The merged document, keeping our side of every conflict.
+    pub merged: JsonValue,
+    /// Unresolved conflicts, in path order.
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// Three-way merges two edits of `base`. Object members are merged one by
+/// one: a change made on only one side is taken, and identical changes on
+/// both agree. Arrays and scalars are merged as a whole, so two different
+/// edits of one array conflict.
+pub fn merge3(base: &JsonValue, ours: &JsonValue, theirs: &JsonValue) -> MergeResult {
+    let mut conflicts = Vec::new();
+    let merged = merge_value(
+        Some(base),
+        Some(ours),
+        Some(theirs),
+        &mut String::new(),
+        &mut conflicts,
+    );
+    MergeResult {
+        merged: merged.expect("both sides have a root"),
+        conflicts,
+    }
+}
+
+impl MergeResult {
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    /// Pretty-prints the merged document with each conflict written out
+    /// between `<<<<<<< ours`, `=======` and `>>>>>>> theirs` lines, as git
+    /// does for text. Keys are sorted. The result is not JSON while any
+    /// conflict remains.
+    pub fn render_markers(&self) -> String {
+        let conflicts: HashMap<&str, &MergeConflict> = self
+            .conflicts
+            .iter()
+            .map(|c| (c.path.as_str(), c))
+            .collect();
+        let mut out = String::new();
+        match conflicts.get("") {
+            Some(conflict) => write_conflict(None, conflict, 0, false, &mut out),
+            None => {
+                write_value(&self.merged, &conflicts, &mut String::new(), 0, &mut out);
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    /// The conflicts as a JSON array of `{"path", "base", "ours", "theirs"}`
+    /// objects, omitting the sides where the value is absent, for tools
+    /// that present conflicts their own way.
+    pub fn conflicts_json(&self) -> JsonValue {
+        let conflict = |c: &MergeConflict| {
+            let mut obj = Map::new();
+            obj.insert("path".to_string(), JsonValue::String(c.path.clone().into()));
+            for (side, value) in [("base", &c.base), ("ours", &c.ours), ("theirs", &c.theirs)] {
+                if let Some(value) = value {
+                    obj.insert(side.to_string(), value.clone());
+                }
+            }
+            JsonValue::Object(Box::new(obj))
+        };
+        JsonValue::Array(self.conflicts.iter().map(conflict).collect())
+    }
+}
+
+fn merge_value(
+    base: Option<&JsonValue>,
+    ours: Option<&JsonValue>,
+    theirs: Option<&JsonValue>,
+    path: &mut String,
+    conflicts: &mut Vec<MergeConflict>,
+) -> Option<JsonValue> {
+    if ours == theirs || theirs == base {
+        return ours.cloned();
+    }
+    if ours == base {
+        return theirs.cloned();
+    }
+    if let (Some(JsonValue::Object(o)), Some(JsonValue::Object(t))) = (ours, theirs) {
+        let b = match base {
+            Some(JsonValue::Object(b)) => Some(b),
+            _ => None,
+        };
+        let keys: BTreeSet<&String> = o.keys().chain(t.keys()).collect();
+        let mut merged = Map::new();
+        for key in keys {
+            let len = path.len();
+            path.push('/');
+            path.push_str(&escape_pointer_token(key));
+            let base = b.and_then(|b| b.get(key));
+            if let Some(value) = merge_value(base, o.get(key), t.get(key), path, conflicts) {
+                merged.insert(key.clone(), value);
+            }
+            path.truncate(len);
+        }
+        return Some(JsonValue::Object(Box::new(merged)));
+    }
+    conflicts.push(MergeConflict {
+        path: path.clone(),
+        base: base.cloned(),
+        ours: ours.cloned(),
+        theirs: theirs.cloned(),
+    });
+    ours.cloned()
+}
+
+// Writes `value` at nesting level `depth`, descending into objects that
+// hold conflicts.
+fn write_value(
+    value: &JsonValue,
+    conflicts: &HashMap<&str, &MergeConflict>,
+    path: &mut String,
+    depth: usize,
+    out: &mut String,
+) {
+    let prefix = format!("{}/", path);
+    let JsonValue::Object(obj) = value else {
+        out.push_str(&pretty(value, depth));
+        return;
+    };
+    if !conflicts.keys().any(|p| p.starts_with(&prefix)) {
+        out.push_str(&pretty(value, depth));
+        return;
+    }
+
+    // Members deleted on our side are only present in the conflicts.
+    let mut keys: BTreeSet<String> = obj.keys().cloned().collect();
+    for conflict in conflicts.values() {
+        if let Some(token) = conflict.path.strip_prefix(&prefix)
+            && !token.contains('/')
+        {
+            keys.insert(unescape_token(token));
+        }
+    }
+    out.push('{');
+    let count = keys.len();
+    for (i, key) in keys.into_iter().enumerate() {
+        let len = path.len();
+        path.push('/');
+        path.push_str(&escape_pointer_token(&key));
+        let comma = i + 1 < count;
+        if let Some(conflict) = conflicts.get(path.as_str()) {
+            out.push('\n');
+            write_conflict(Some(&key), conflict, depth + 1, comma, out);
+            // The closing brace or next member starts its own line.
+            out.pop();
+        } else {
+            out.push('\n');
+            out.push_str(&"  ".repeat(depth + 1));
+            out.push_str(&pretty(&JsonValue::String(key.clone().into()), 0));
+            out.push_str(": ");
+            write_value(&obj[&key], conflicts, path, depth + 1, out);
+            if comma {
+                out.push(',');
+            }
+        }
+        path.truncate(len);
+    }
+    out.push('\n');
+    out.push_str(&"  ".repeat(depth));
+    out.push('}');
+}
+
+// Writes the marker block for one conflict, each side as a member `key`, or
+// as a whole document at the root. Every line ends with a newline.
+fn write_conflict(
+    key: Option<&str>,
+    conflict: &MergeConflict,
+    depth: usize,
+    comma: bool,
+    out: &mut String,
+) {
+    let side = |value: &Option<JsonValue>, out: &mut String| {
+        let Some(value) = value else {
+            return;
+        };
+        out.push_str(&"  ".repeat(depth));
+        if let Some(key) = key {
+            out.push_str(&pretty(&JsonValue::String(key.into()), 0));
+            out.push_str(": ");
+        }
+        out.push_str(&pretty(value, depth));
+        if comma {
+            out.push(',');
+        }
+        out.push('\n');
+    };
+    out.push_str("<<<<<<< ours\n");
+    side(&conflict.ours, out);
+    out.push_str("=======\n");
+    side(&conflict.theirs, out);
+    out.push_str(">>>>>>> theirs\n");
+}
+
+// Pretty-prints a value whose first line is already indented to `depth`.
+fn pretty(value: &JsonValue, depth: usize) -> String {
+    let options = StringifyOptions {
+        indent: Some("  ".to_string()),
+        sort_keys: true,
+        ..StringifyOptions::default()
+    };
+    let text = stringify_with(value, &options).unwrap_or_default();
+    text.replace('\n', &format!("\n{}", "  ".repeat(depth)))
+}
@@ -0,0 +1,97 @@
+use super::{JsonValue, parse_integer_exact};
+use std::fmt;
+use toml::{Table, Value};
+
+/// TOML that is malformed, or a value TOML cannot hold.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TomlError {
+    pub message: String,
+}
+
+impl fmt::Display for TomlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for TomlError {}
+
+fn error(message: impl fmt::Display) -> TomlError {
+    TomlError {
+        message: message.to_string(),
+    }
+}
+
+/// Parses a TOML document into an object. Tables become objects and
+/// arrays arrays. Datetimes become their RFC 3339 text, so they lose their
+/// type but not their value. Integers beyond 2^53 are kept as `RawNumber`.
+pub fn from_toml(input: &str) -> Result<JsonValue, TomlError> {
+    let table: Table = input.parse().map_err(error)?;
+    Ok(from_value(Value::Table(table)))
+}
+
+/// Writes an object as a TOML document. TOML has no null, so `null`
+/// members are left out, and `null` array elements are an error. Whole
+/// numbers that fit an `i64` become integers and other numbers floats, so
+/// raw numbers beyond the `i64` range are rounded. Fails unless the value
+/// is an object.
+pub fn to_toml(value: &JsonValue) -> Result<String, TomlError> {
+    match to_value(value)? {
+        Some(Value::Table(table)) => toml::to_string(&table).map_err(error),
+        _ => Err(error("a TOML document must be an object")),
+    }
+}
+
+fn from_value(value: Value) -> JsonValue {
+    match value {
+        Value::String(s) => JsonValue::String(s.into()),
+        Value::Integer(n) if n.unsigned_abs() > 1 << 53 => {
+            JsonValue::RawNumber(n.to_string().into())
+        }
+        Value::Integer(n) => JsonValue::Number(n as f64),
+        Value::Float(n) => JsonValue::Number(n),
+        Value::Boolean(b) => JsonValue::Bool(b),
+        Value::Datetime(datetime) => JsonValue::String(datetime.to_string().into()),
+        Value::Array(items) => JsonValue::Array(items.into_iter().map(from_value).collect()),
+        Value::Table(members) => JsonValue::Object(Box::new(
+            members
+                .into_iter()
+                .map(|(key, value)| (key, from_value(value)))
+                .collect(),
+        )),
+    }
+}
+
+// `None` for `null`, which the caller drops or rejects.
+fn to_value(value: &JsonValue) -> Result<Option<Value>, TomlError> {
+    Ok(Some(match value {
+        JsonValue::Null => return Ok(None),
+        JsonValue::Bool(b) => Value::Boolean(*b),
+        JsonValue::Number(n) if n.fract() == 0.0 && n.abs() < 2f64.powi(63) => {
+            Value::Integer(*n as i64)
+        }
+        JsonValue::Number(n) => Value::Float(*n),
+        JsonValue::RawNumber(text) => match parse_integer_exact(text).map(i64::try_from) {
+            Ok(Ok(n)) => Value::Integer(n),
+            _ => Value::Float(text.parse().unwrap_or(f64::NAN)),
+        },
+        JsonValue::Raw(raw) => return to_value(&raw.parse()),
+        JsonValue::String(s) => Value::String(s.to_string()),
+        JsonValue::Bytes(bytes) => Value::String(String::from_utf8_lossy(bytes).into_owned()),
+        JsonValue::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| to_value(item)?.ok_or_else(|| error("TOML arrays cannot hold null")))
+                .collect::<Result<_, _>>()?,
+        ),
+        JsonValue::Object(_) => {
+            let mut table = Table::new();
+            for (key, item) in value.iter_sorted() {
+                if let Some(item) = to_value(item)? {
+                    table.insert(key.clone(), item);
+                }
+            }
+            Value::Table(table)
+        }
+    }))
+}
@@ -0,0 +1,69 @@
+use super::JsonValue;
+use std::collections::HashMap;
+
+impl JsonValue {
+    /// Calls `f` on every node, children before their parent, so `f` can
+    /// rewrite any value in place and sees containers already rewritten.
+    pub fn transform(&mut self, mut f: impl FnMut(&mut JsonValue)) {
+        transform(self, &mut f);
+    }
+
+    /// Calls `f` on every value that is neither an array nor an object,
+    /// e.g. to trim every string or round every number.
+    pub fn map_values(&mut self, mut f: impl FnMut(&mut JsonValue)) {
+        transform(self, &mut |value: &mut JsonValue| {
+            if !value.is_array() && !value.is_object() {
+                f(value);
+            }
+        });
+    }
+
+    /// Renames every object key, at every depth. If two keys of one object
+    /// map to the same name, the member whose original key sorts last wins.
+    pub fn map_keys(&mut self, mut f: impl FnMut(&str) -> String) {
+        transform(self, &mut |value: &mut JsonValue| {
+            if let JsonValue::Object(members) = value {
+                // The original key each new one came from, to settle clashes.
+                let mut sources: HashMap<String, String> = HashMap::new();
+                for (key, member) in std::mem::take(&mut **members) {
+                    let new = f(&key);
+                    if sources.get(&new).is_some_and(|source| *source > key) {
+                        continue;
+                    }
+                    sources.insert(new.clone(), key);
+                    members.insert(new, member);
+                }
+            }
+        });
+    }
+
+    /// Removes every array element and object member, at every depth, for
+    /// which `keep` returns false. Containers are tested before their
+    /// contents, so nothing inside a removed value is visited.
+    pub fn retain(&mut self, mut keep: impl FnMut(&JsonValue) -> bool) {
+        retain(self, &mut keep);
+    }
+}
+
+fn transform(value: &mut JsonValue, f: &mut impl FnMut(&mut JsonValue)) {
+    match value {
+        JsonValue::Array(items) => items.iter_mut().for_each(|item| transform(item, f)),
+        JsonValue::Object(members) => members.values_mut().for_each(|item| transform(item, f)),
+        _ => {}
+    }
+    f(value);
+}
+
+fn retain(value: &mut JsonValue, keep: &mut impl FnMut(&JsonValue) -> bool) {
+    match value {
+        JsonValue::Array(items) => {
+            items.retain(|item| keep(item));
+            items.iter_mut().for_each(|item| retain(item, keep));
+        }
+        JsonValue::Object(members) => {
+            members.retain(|_, item| keep(item));
+            members.values_mut().for_each(|item| retain(item, keep));
+        }
+        _ => {}
+    }
+}
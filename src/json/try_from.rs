@@ -0,0 +1,230 @@
+use super::pointer::escape_pointer_token;
+use super::view::kind;
+use super::{JsonValue, parse_integer_exact};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Why a `JsonValue` could not be converted to a Rust type. `pointer`
+/// locates the offending value inside an array or object.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ConversionError {
+    pub pointer: String,
+    pub message: String,
+}
+
+impl ConversionError {
+    fn new(message: String) -> Self {
+        ConversionError {
+            pointer: String::new(),
+            message,
+        }
+    }
+
+    fn expected(expected: &str, found: &JsonValue) -> Self {
+        ConversionError::new(format!("expected {}, found {}", expected, kind(found)))
+    }
+
+    // Places an error from a member or element under `token`.
+    fn within(mut self, token: &str) -> Self {
+        self.pointer = format!("/{}{}", escape_pointer_token(token), self.pointer);
+        self
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.pointer.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.pointer, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+impl TryFrom<JsonValue> for String {
+    type Error = ConversionError;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        match value {
+            JsonValue::String(s) => Ok(s.into_string()),
+            other => Err(ConversionError::expected("a string", &other)),
+        }
+    }
+}
+
+impl TryFrom<&JsonValue> for String {
+    type Error = ConversionError;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        match value {
+            JsonValue::String(s) => Ok(s.to_string()),
+            other => Err(ConversionError::expected("a string", other)),
+        }
+    }
+}
+
+impl TryFrom<&JsonValue> for bool {
+    type Error = ConversionError;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        value
+            .as_bool()
+            .ok_or_else(|| ConversionError::expected("a boolean", value))
+    }
+}
+
+impl TryFrom<&JsonValue> for f64 {
+    type Error = ConversionError;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        value
+            .as_f64()
+            .ok_or_else(|| ConversionError::expected("a number", value))
+    }
+}
+
+impl TryFrom<&JsonValue> for f32 {
+    type Error = ConversionError;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let n = f64::try_from(value)?;
+        if n.is_finite() && n.abs() > f32::MAX as f64 {
+            return Err(ConversionError::new(format!(
+                "{} is out of range for f32",
+                n
+            )));
+        }
+        Ok(n as f32)
+    }
+}
+
+// The exact value of a whole number. Raw numbers are read without rounding.
+fn integer(value: &JsonValue) -> Result<i128, ConversionError> {
+    match value {
+        JsonValue::Number(n) if n.fract() != 0.0 || !n.is_finite() => {
+            Err(ConversionError::new(format!("{} is not an integer", n)))
+        }
+        // Beyond this every f64 integer is out of range of every target.
+        JsonValue::Number(n) if n.abs() >= 2f64.powi(127) => {
+            Err(ConversionError::new(format!("{} is out of range", n)))
+        }
+        JsonValue::Number(n) => Ok(*n as i128),
+        JsonValue::RawNumber(text) => parse_integer_exact(text)
+            .map_err(|_| ConversionError::new(format!("{} is not an integer in range", text))),
+        other => Err(ConversionError::expected("an integer", other)),
+    }
+}
+
+macro_rules! try_from_integer {
+    ($($t:ty),*) => {$(
+        impl TryFrom<&JsonValue> for $t {
+            type Error = ConversionError;
+
+            fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+                let n = integer(value)?;
+                <$t>::try_from(n).map_err(|_| {
+                    ConversionError::new(format!("{} is out of range for {}", n, stringify!($t)))
+                })
+            }
+        }
+    )*};
+}
+
+try_from_integer!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+// Scalars hold nothing worth moving, so the owned conversions borrow.
+macro_rules! try_from_owned {
+    ($($t:ty),*) => {$(
+        impl TryFrom<JsonValue> for $t {
+            type Error = ConversionError;
+
+            fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+                <$t>::try_from(&value)
+            }
+        }
+    )*};
+}
+
+try_from_owned!(
+    bool, f32, f64, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+impl<T> TryFrom<JsonValue> for Vec<T>
+where
+    T: TryFrom<JsonValue, Error = ConversionError>,
+{
+    type Error = ConversionError;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        match value {
+            JsonValue::Array(items) => items
+                .into_iter()
+                .enumerate()
+                .map(|(i, item)| T::try_from(item).map_err(|e| e.within(&i.to_string())))
+                .collect(),
+            other => Err(ConversionError::expected("an array", &other)),
+        }
+    }
+}
+
+impl<'a, T> TryFrom<&'a JsonValue> for Vec<T>
+where
+    T: TryFrom<&'a JsonValue, Error = ConversionError>,
+{
+    type Error = ConversionError;
+
+    fn try_from(value: &'a JsonValue) -> Result<Self, Self::Error> {
+        match value {
+            JsonValue::Array(items) => items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| T::try_from(item).map_err(|e| e.within(&i.to_string())))
+                .collect(),
+            other => Err(ConversionError::expected("an array", other)),
+        }
+    }
+}
+
+impl<T> TryFrom<JsonValue> for HashMap<String, T>
+where
+    T: TryFrom<JsonValue, Error = ConversionError>,
+{
+    type Error = ConversionError;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        match value {
+            JsonValue::Object(members) => members
+                .into_iter()
+                .map(|(key, item)| match T::try_from(item) {
+                    Ok(item) => Ok((key, item)),
+                    Err(e) => Err(e.within(&key)),
+                })
+                .collect(),
+            other => Err(ConversionError::expected("an object", &other)),
+        }
+    }
+}
+
+impl<'a, T> TryFrom<&'a JsonValue> for HashMap<String, T>
+where
+    T: TryFrom<&'a JsonValue, Error = ConversionError>,
+{
+    type Error = ConversionError;
+
+    fn try_from(value: &'a JsonValue) -> Result<Self, Self::Error> {
+        match value {
+            JsonValue::Object(members) => members
+                .iter()
+                .map(|(key, item)| match T::try_from(item) {
+                    Ok(item) => Ok((key.clone(), item)),
+                    Err(e) => Err(e.within(key)),
+                })
+                .collect(),
+            other => Err(ConversionError::expected("an object", other)),
+        }
+    }
+}
@@ -0,0 +1,325 @@
+use super::{JsonValue, Map, parse_integer_exact};
+use std::fmt;
+
+/// Bytes that are not valid UBJSON. `offset` is where the problem was
+/// found.
+#[derive(Debug, PartialEq, Clone)]
+pub struct UbjsonError {
+    pub offset: usize,
+    pub message: String,
+}
+
+impl fmt::Display for UbjsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for UbjsonError {}
+
+/// Encodes a value as Universal Binary JSON (Draft 12). Whole numbers use
+/// the smallest integer type that holds them and other numbers `float64`;
+/// raw numbers that fit no integer type are written as high-precision
+/// text. Containers are written with a leading element count, and arrays
+/// of small non-negative integers as typed `uint8` arrays. Object keys are
+/// written in ascending order.
+pub fn to_ubjson(value: &JsonValue) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_value(value, &mut out);
+    out
+}
+
+/// Decodes one Universal Binary JSON value, accepting every container form:
+/// plain, counted (`#`) and typed (`$`). Integers beyond 2^53 and
+/// high-precision numbers are kept as `RawNumber` so no digits are lost.
+/// No-op markers between values are skipped.
+pub fn parse_ubjson(bytes: &[u8]) -> Result<JsonValue, UbjsonError> {
+    let mut reader = Reader { bytes, pos: 0 };
+    let marker = reader.marker()?;
+    let value = reader.value(marker)?;
+    while reader.bytes.get(reader.pos) == Some(&b'N') {
+        reader.pos += 1;
+    }
+    if reader.pos != bytes.len() {
+        return Err(reader.error("trailing bytes after the value"));
+    }
+    Ok(value)
+}
+
+fn write_value(value: &JsonValue, out: &mut Vec<u8>) {
+    match value {
+        JsonValue::Null => out.push(b'Z'),
+        JsonValue::Bool(true) => out.push(b'T'),
+        JsonValue::Bool(false) => out.push(b'F'),
+        JsonValue::Number(n) if n.fract() == 0.0 && n.abs() < 2f64.powi(63) => {
+            write_integer(*n as i64, out)
+        }
+        JsonValue::Number(n) => {
+            out.push(b'D');
+            out.extend_from_slice(&n.to_be_bytes());
+        }
+        JsonValue::RawNumber(text) => match parse_integer_exact(text).map(i64::try_from) {
+            Ok(Ok(n)) => write_integer(n, out),
+            _ => {
+                out.push(b'H');
+                write_string(text, out);
+            }
+        },
+        JsonValue::Raw(raw) => write_value(&raw.parse(), out),
+        JsonValue::String(s) => {
+            out.push(b'S');
+            write_string(s, out);
+        }
+        JsonValue::Bytes(bytes) => {
+            out.push(b'S');
+            write_string(&String::from_utf8_lossy(bytes), out);
+        }
+        JsonValue::Array(items) => {
+            let bytes: Option<Vec<u8>> = items
+                .iter()
+                .map(|item| match item {
+                    JsonValue::Number(n) if n.fract() == 0.0 && (0.0..=255.0).contains(n) => {
+                        Some(*n as u8)
+                    }
+                    _ => None,
+                })
+                .collect();
+            out.push(b'[');
+            match bytes {
+                Some(bytes) if !bytes.is_empty() => {
+                    out.extend_from_slice(b"$U#");
+                    write_integer(bytes.len() as i64, out);
+                    out.extend_from_slice(&bytes);
+                }
+                _ => {
+                    out.push(b'#');
+                    write_integer(items.len() as i64, out);
+                    items.iter().for_each(|item| write_value(item, out));
+                }
+            }
+        }
+        JsonValue::Object(_) => {
+            out.extend_from_slice(b"{#");
+            let members: Vec<_> = value.iter_sorted().collect();
+            write_integer(members.len() as i64, out);
+            for (key, item) in members {
+                write_string(key, out);
+                write_value(item, out);
+            }
+        }
+    }
+}
+
+fn write_integer(n: i64, out: &mut Vec<u8>) {
+    if let Ok(n) = u8::try_from(n) {
+        out.push(b'U');
+        out.push(n);
+    } else if let Ok(n) = i8::try_from(n) {
+        out.push(b'i');
+        out.extend_from_slice(&n.to_be_bytes());
+    } else if let Ok(n) = i16::try_from(n) {
+        out.push(b'I');
+        out.extend_from_slice(&n.to_be_bytes());
+    } else if let Ok(n) = i32::try_from(n) {
+        out.push(b'l');
+        out.extend_from_slice(&n.to_be_bytes());
+    } else {
+        out.push(b'L');
+        out.extend_from_slice(&n.to_be_bytes());
+    }
+}
+
+// A length-prefixed UTF-8 string, without the `S` marker.
+fn write_string(s: &str, out: &mut Vec<u8>) {
+    write_integer(s.len() as i64, out);
+    out.extend_from_slice(s.as_bytes());
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn error(&self, message: &str) -> UbjsonError {
+        UbjsonError {
+            offset: self.pos,
+            message: message.to_string(),
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&[u8], UbjsonError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| self.error("unexpected end of input"))?;
+        let taken = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(taken)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], UbjsonError> {
+        Ok(self.take(N)?.try_into().expect("took N bytes"))
+    }
+
+    // The next type marker, skipping no-ops.
+    fn marker(&mut self) -> Result<u8, UbjsonError> {
+        loop {
+            match self.take(1)?[0] {
+                b'N' => continue,
+                marker => return Ok(marker),
+            }
+        }
+    }
+
+    fn value(&mut self, marker: u8) -> Result<JsonValue, UbjsonError> {
+        Ok(match marker {
+            b'Z' => JsonValue::Null,
+            b'T' => JsonValue::Bool(true),
+            b'F' => JsonValue::Bool(false),
+            b'i' | b'U' | b'I' | b'l' | b'L' => {
+                let n = self.integer(marker)?;
+                if n.unsigned_abs() <= 1 << 53 {
+                    JsonValue::Number(n as f64)
+                } else {
+                    JsonValue::RawNumber(n.to_string().into())
+                }
+            }
+            b'd' => JsonValue::Number(f32::from_be_bytes(self.take_array()?) as f64),
+            b'D' => JsonValue::Number(f64::from_be_bytes(self.take_array()?)),
+            b'H' => {
+                let start = self.pos;
+                let text = self.string()?;
+                match super::parse(&text) {
+                    Ok(n) if n.is_number() => JsonValue::RawNumber(text.into()),
+                    _ => {
+                        self.pos = start;
+                        return Err(self.error("invalid high-precision number"));
+                    }
+                }
+            }
+            b'C' => {
+                let byte = self.take(1)?[0];
+                if !byte.is_ascii() {
+                    self.pos -= 1;
+                    return Err(self.error("char is not ASCII"));
+                }
+                JsonValue::String(((byte as char).to_string()).into())
+            }
+            b'S' => JsonValue::String(self.string()?.into()),
+            b'[' => {
+                let mut items = Vec::new();
+                match self.container_header()? {
+                    Some((element, count)) => {
+                        for _ in 0..count {
+                            let marker = self.element_marker(element)?;
+                            items.push(self.value(marker)?);
+                        }
+                    }
+                    None => loop {
+                        match self.marker()? {
+                            b']' => break,
+                            marker => items.push(self.value(marker)?),
+                        }
+                    },
+                }
+                JsonValue::Array(items)
+            }
+            b'{' => {
+                let mut members = Map::new();
+                match self.container_header()? {
+                    Some((element, count)) => {
+                        for _ in 0..count {
+                            let key = self.string()?;
+                            let marker = self.element_marker(element)?;
+                            members.insert(key, self.value(marker)?);
+                        }
+                    }
+                    None => loop {
+                        if self.bytes.get(self.pos) == Some(&b'}') {
+                            self.pos += 1;
+                            break;
+                        }
+                        let key = self.string()?;
+                        let marker = self.marker()?;
+                        members.insert(key, self.value(marker)?);
+                    },
+                }
+                JsonValue::Object(Box::new(members))
+            }
+            _ => {
+                self.pos -= 1;
+                return Err(self.error("unknown type marker"));
+            }
+        })
+    }
+
+    fn integer(&mut self, marker: u8) -> Result<i64, UbjsonError> {
+        Ok(match marker {
+            b'i' => i8::from_be_bytes(self.take_array()?) as i64,
+            b'U' => u8::from_be_bytes(self.take_array()?) as i64,
+            b'I' => i16::from_be_bytes(self.take_array()?) as i64,
+            b'l' => i32::from_be_bytes(self.take_array()?) as i64,
+            b'L' => i64::from_be_bytes(self.take_array()?),
+            _ => {
+                self.pos -= 1;
+                return Err(self.error("expected an integer type"));
+            }
+        })
+    }
+
+    // A length: an integer of any type, not negative.
+    fn length(&mut self) -> Result<usize, UbjsonError> {
+        let marker = self.marker()?;
+        let start = self.pos;
+        let n = self.integer(marker)?;
+        usize::try_from(n).map_err(|_| UbjsonError {
+            offset: start,
+            message: "negative length".to_string(),
+        })
+    }
+
+    // A length-prefixed UTF-8 string, without the `S` marker.
+    fn string(&mut self) -> Result<String, UbjsonError> {
+        let len = self.length()?;
+        let start = self.pos;
+        let bytes = self.take(len)?.to_vec();
+        String::from_utf8(bytes).map_err(|_| UbjsonError {
+            offset: start,
+            message: "string is not UTF-8".to_string(),
+        })
+    }
+
+    // The optional `$type` and `#count` after a container's opening
+    // marker. `None` means the container runs to its closing marker.
+    fn container_header(&mut self) -> Result<Option<(Option<u8>, usize)>, UbjsonError> {
+        let element = if self.bytes.get(self.pos) == Some(&b'$') {
+            self.pos += 1;
+            Some(self.take(1)?[0])
+        } else {
+            None
+        };
+        if self.bytes.get(self.pos) == Some(&b'#') {
+            self.pos += 1;
+            let count = self.length()?;
+            // Every element takes at least one byte unless its type is fixed
+            // to one without a payload.
+            if element.is_none() && count > self.bytes.len() - self.pos {
+                return Err(self.error("count exceeds the input"));
+            }
+            Ok(Some((element, count)))
+        } else if element.is_some() {
+            Err(self.error("expected # after a container type"))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn element_marker(&mut self, element: Option<u8>) -> Result<u8, UbjsonError> {
+        match element {
+            Some(marker) => Ok(marker),
+            None => self.marker(),
+        }
+    }
+}
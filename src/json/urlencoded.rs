@@ -0,0 +1,182 @@
+use super::map::remove_member;
+use super::stringify::stringify_compact;
+use super::{JsonValue, Map};
+use std::fmt;
+
+/// A query string that cannot be decoded, or a value that has no form
+/// encoding.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FormError {
+    pub message: String,
+}
+
+impl fmt::Display for FormError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for FormError {}
+
+fn error(message: String) -> FormError {
+    FormError { message }
+}
+
+/// Decodes an `application/x-www-form-urlencoded` string, such as a query
+/// string without its `?`, into an object. Bracketed keys nest:
+/// `a[b]=1` sets member `b` of `a`, `a[]=1` appends to an array, and an
+/// object whose keys are exactly `0`, `1`, ... becomes an array, so
+/// `a[0][b]=1` gives `{"a": [{"b": "1"}]}`. Values are always strings, and
+/// when a key repeats the last value wins.
+pub fn parse_urlencoded(input: &str) -> Result<JsonValue, FormError> {
+    let mut root = Map::new();
+    for pair in input.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let key = decode(key)?;
+        let value = decode(value)?;
+        let (name, mut rest) = match key.find('[') {
+            Some(i) if i > 0 => (&key[..i], &key[i..]),
+            _ => (key.as_str(), ""),
+        };
+        let mut segments = vec![name.to_string()];
+        while let Some(inner) = rest.strip_prefix('[') {
+            let Some(end) = inner.find(']') else { break };
+            segments.push(inner[..end].to_string());
+            rest = &inner[end + 1..];
+        }
+        insert(&mut root, &segments, value, &key, false)?;
+    }
+    Ok(JsonValue::Object(Box::new(
+        root.into_iter()
+            .map(|(key, item)| (key, into_arrays(item)))
+            .collect(),
+    )))
+}
+
+/// Encodes an object as `application/x-www-form-urlencoded`, the inverse
+/// of `parse_urlencoded`: nested members become `a[b]` keys and array
+/// elements `a[0]`. Numbers and booleans are written as their JSON text and
+/// `null` as an empty value; empty arrays and objects are left out. Keys
+/// are written in ascending order.
+pub fn to_urlencoded(value: &JsonValue) -> Result<String, FormError> {
+    if !value.is_object() {
+        return Err(error("form data must be an object".to_string()));
+    }
+    let mut pairs = Vec::new();
+    for (key, item) in value.iter_sorted() {
+        flatten(key.clone(), item, &mut pairs);
+    }
+    Ok(pairs
+        .iter()
+        .map(|(key, value)| format!("{}={}", encode(key), encode(value)))
+        .collect::<Vec<_>>()
+        .join("&"))
+}
+
+// Stores `value` under `segments`, with objects standing in for arrays.
+fn insert(
+    object: &mut Map,
+    segments: &[String],
+    value: String,
+    key: &str,
+    nested: bool,
+) -> Result<(), FormError> {
+    let (first, rest) = segments.split_first().expect("a key has a name");
+    // `[]` appends: the next unused index.
+    let first = match first.as_str() {
+        "" if nested => object.len().to_string(),
+        _ => first.clone(),
+    };
+    if rest.is_empty() {
+        object.insert(first, JsonValue::String(value.into()));
+        return Ok(());
+    }
+    let child = object
+        .entry(first)
+        .or_insert_with(|| JsonValue::Object(Box::default()));
+    match child {
+        JsonValue::Object(child) => insert(child, rest, value, key, true),
+        _ => Err(error(format!("{} conflicts with an earlier value", key))),
+    }
+}
+
+// Turns objects keyed `0`..`n` into arrays, innermost first.
+fn into_arrays(value: JsonValue) -> JsonValue {
+    let JsonValue::Object(members) = value else {
+        return value;
+    };
+    let mut members: Map = members
+        .into_iter()
+        .map(|(key, item)| (key, into_arrays(item)))
+        .collect();
+    let is_array =
+        !members.is_empty() && (0..members.len()).all(|i| members.contains_key(&i.to_string()));
+    if is_array {
+        JsonValue::Array(
+            (0..members.len())
+                .map(|i| remove_member(&mut members, &i.to_string()).expect("checked above"))
+                .collect(),
+        )
+    } else {
+        JsonValue::Object(Box::new(members))
+    }
+}
+
+fn flatten(key: String, value: &JsonValue, pairs: &mut Vec<(String, String)>) {
+    match value {
+        JsonValue::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                flatten(format!("{}[{}]", key, i), item, pairs);
+            }
+        }
+        JsonValue::Object(_) => {
+            for (name, item) in value.iter_sorted() {
+                flatten(format!("{}[{}]", key, name), item, pairs);
+            }
+        }
+        JsonValue::Null => pairs.push((key, String::new())),
+        JsonValue::String(s) => pairs.push((key, s.to_string())),
+        JsonValue::Bytes(bytes) => pairs.push((key, String::from_utf8_lossy(bytes).into_owned())),
+        JsonValue::Raw(raw) => flatten(key, &raw.parse(), pairs),
+        _ => pairs.push((key, stringify_compact(value))),
+    }
+}
+
+// Percent-decodes, with `+` meaning a space.
+fn decode(text: &str) -> Result<String, FormError> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' => {
+                let byte = text
+                    .get(i + 1..i + 3)
+                    .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .ok_or_else(|| error(format!("invalid percent escape in {}", text)))?;
+                out.push(byte);
+                i += 2;
+            }
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8(out).map_err(|_| error(format!("{} is not UTF-8 once decoded", text)))
+}
+
+// Percent-encodes everything but unreserved characters, with spaces as `+`.
+fn encode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for &byte in text.as_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char)
+            }
+            b' ' => out.push('+'),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
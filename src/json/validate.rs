@@ -0,0 +1,149 @@
+use super::{CharSink, DuplicateKeyPolicy, ParseError, Parser, ParserOptions, is_identifier_start};
+use std::collections::HashSet;
+
+/// Checks that `input` is a single valid JSON document without building a
+/// `JsonValue` tree. Accepts exactly the documents `parse` accepts.
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    validate_with_options(input, ParserOptions::current())
+}
+
+/// Like `validate`, with the dialect and duplicate-key policy taken from
+/// `options`. Every value is checked, including those `key_filter` or
+/// `materialize_depth` would leave unparsed.
+pub fn validate_with_options(input: &str, options: ParserOptions) -> Result<(), ParseError> {
+    let mut parser = Parser::with_options(input, options);
+    parser.skip_whitespace();
+    parser.validate_value()?;
+    parser.skip_whitespace();
+    if parser.pos < parser.input.len() {
+        return Err(parser.unexpected());
+    }
+    Ok(())
+}
+
+// Drops the characters of strings that are only being validated.
+struct Discard;
+
+impl CharSink for Discard {
+    fn push(&mut self, _: char) {}
+}
+
+impl Parser<'_> {
+    fn validate_value(&mut self) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        match self.current_char() {
+            Some('"') => self.read_string_literal(&mut Discard),
+            Some('\'') if self.options.allow_single_quotes => {
+                self.read_string_literal(&mut Discard)
+            }
+            Some('[') => self.validate_nested(Self::validate_array),
+            Some('{') => self.validate_nested(Self::validate_object),
+            // Scalars other than strings do not allocate.
+            _ => self.parse_value().map(|_| ()),
+        }
+    }
+
+    fn validate_nested(
+        &mut self,
+        validate: fn(&mut Self) -> Result<(), ParseError>,
+    ) -> Result<(), ParseError> {
+        self.enter()?;
+        let result = validate(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn validate_array(&mut self) -> Result<(), ParseError> {
+        self.advance(); // Skip '['
+        self.skip_whitespace();
+        if self.current_char() == Some(']') {
+            self.advance();
+            return Ok(());
+        }
+
+        loop {
+            self.validate_value()?;
+            if self.parse_separator(']')? {
+                return Ok(());
+            }
+        }
+    }
+
+    fn validate_object(&mut self) -> Result<(), ParseError> {
+        self.advance(); // Skip '{'
+        self.skip_whitespace();
+        if self.current_char() == Some('}') {
+            self.advance();
+            return Ok(());
+        }
+
+        // Keys are only collected when duplicates are an error.
+        let mut keys = HashSet::new();
+        loop {
+            self.skip_whitespace();
+            let key_pos = self.pos;
+            let key = if self.options.duplicate_keys == DuplicateKeyPolicy::Error {
+                Some(self.parse_key()?)
+            } else {
+                self.validate_key()?;
+                None
+            };
+
+            self.skip_whitespace();
+            match self.current_char() {
+                Some(':') => self.advance(),
+                _ => return Err(self.unexpected()),
+            }
+            self.validate_value()?;
+            if let Some(key) = key
+                && !keys.insert(key.clone())
+            {
+                return Err(ParseError::DuplicateKey(key, self.position(key_pos)));
+            }
+            if self.parse_separator('}')? {
+                return Ok(());
+            }
+        }
+    }
+
+    fn validate_key(&mut self) -> Result<(), ParseError> {
+        match self.current_char() {
+            Some('"') => self.read_string_literal(&mut Discard),
+            Some('\'') if self.options.allow_single_quotes => {
+                self.read_string_literal(&mut Discard)
+            }
+            Some(c) if self.options.allow_unquoted_keys && is_identifier_start(c) => {
+                while self
+                    .current_char()
+                    .is_some_and(|c| is_identifier_start(c) || c.is_alphanumeric())
+                {
+                    self.advance();
+                }
+                Ok(())
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    // Consumes the separator after an element. Returns true once the
+    // container is closed.
+    pub(super) fn parse_separator(&mut self, close: char) -> Result<bool, ParseError> {
+        self.skip_whitespace();
+        match self.current_char() {
+            Some(',') => {
+                self.advance();
+                self.skip_whitespace();
+                if self.options.allow_trailing_commas && self.current_char() == Some(close) {
+                    self.advance();
+                    return Ok(true);
+                }
+                Ok(false)
+            }
+            Some(c) if c == close => {
+                self.advance();
+                Ok(true)
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+}
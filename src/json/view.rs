@@ -0,0 +1,223 @@
+use super::{JsonValue, Map, parse_integer_exact};
+use std::fmt;
+
+/// A field of a typed view that is missing or holds the wrong kind of value.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ViewError {
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for ViewError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "field {}: {}", self.field, self.message)
+    }
+}
+
+impl std::error::Error for ViewError {}
+
+/// A typed wrapper around a borrowed object, usually defined with
+/// `json_view!`. Fields are checked when their getters are called, so
+/// wrapping a value costs nothing however large it is.
+pub trait JsonView<'a>: Sized {
+    /// Wraps `value`, or returns `None` if it is not an object.
+    fn view(value: &'a JsonValue) -> Option<Self>;
+
+    fn json(&self) -> &'a JsonValue;
+}
+
+/// A Rust type a typed view getter can return, borrowed from the value
+/// where possible.
+pub trait ViewField<'a>: Sized {
+    /// What the value must be, for error messages, e.g. "a string".
+    const EXPECTED: &'static str;
+
+    fn from_json(value: &'a JsonValue) -> Option<Self>;
+
+    /// The value of a missing member. Only `Option` has one.
+    fn missing() -> Option<Self> {
+        None
+    }
+}
+
+/// Reads member `key` of `object` as `T`; used by `json_view!` getters.
+pub fn view_field<'a, T: ViewField<'a>>(object: &'a JsonValue, key: &str) -> Result<T, ViewError> {
+    let error = |message: String| ViewError {
+        field: key.to_string(),
+        message,
+    };
+    match object.get(key) {
+        Some(value) => T::from_json(value)
+            .ok_or_else(|| error(format!("expected {}, found {}", T::EXPECTED, kind(value)))),
+        None => T::missing().ok_or_else(|| error("missing".to_string())),
+    }
+}
+
+pub(super) fn kind(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "a boolean",
+        JsonValue::Number(_) | JsonValue::RawNumber(_) => "a number",
+        JsonValue::Raw(_) => "raw JSON",
+        JsonValue::String(_) => "a string",
+        JsonValue::Bytes(_) => "bytes",
+        JsonValue::Array(_) => "an array",
+        JsonValue::Object(_) => "an object",
+    }
+}
+
+impl<'a> ViewField<'a> for &'a JsonValue {
+    const EXPECTED: &'static str = "a value";
+
+    fn from_json(value: &'a JsonValue) -> Option<Self> {
+        Some(value)
+    }
+}
+
+impl<'a> ViewField<'a> for &'a str {
+    const EXPECTED: &'static str = "a string";
+
+    fn from_json(value: &'a JsonValue) -> Option<Self> {
+        value.as_str()
+    }
+}
+
+impl<'a> ViewField<'a> for bool {
+    const EXPECTED: &'static str = "a boolean";
+
+    fn from_json(value: &'a JsonValue) -> Option<Self> {
+        value.as_bool()
+    }
+}
+
+impl<'a> ViewField<'a> for f64 {
+    const EXPECTED: &'static str = "a number";
+
+    fn from_json(value: &'a JsonValue) -> Option<Self> {
+        value.as_f64()
+    }
+}
+
+impl<'a> ViewField<'a> for i64 {
+    const EXPECTED: &'static str = "an integer";
+
+    fn from_json(value: &'a JsonValue) -> Option<Self> {
+        integer(value)?.try_into().ok()
+    }
+}
+
+impl<'a> ViewField<'a> for u64 {
+    const EXPECTED: &'static str = "a non-negative integer";
+
+    fn from_json(value: &'a JsonValue) -> Option<Self> {
+        integer(value)?.try_into().ok()
+    }
+}
+
+// Whole numbers only; raw numbers are read exactly.
+fn integer(value: &JsonValue) -> Option<i128> {
+    match value {
+        JsonValue::Number(n) if n.fract() == 0.0 && n.abs() < i64::MAX as f64 => Some(*n as i128),
+        JsonValue::RawNumber(text) => parse_integer_exact(text).ok(),
+        _ => None,
+    }
+}
+
+impl<'a> ViewField<'a> for &'a [JsonValue] {
+    const EXPECTED: &'static str = "an array";
+
+    fn from_json(value: &'a JsonValue) -> Option<Self> {
+        value.as_array().map(Vec::as_slice)
+    }
+}
+
+impl<'a> ViewField<'a> for &'a Map {
+    const EXPECTED: &'static str = "an object";
+
+    fn from_json(value: &'a JsonValue) -> Option<Self> {
+        value.as_object()
+    }
+}
+
+impl<'a, T: ViewField<'a>> ViewField<'a> for Vec<T> {
+    const EXPECTED: &'static str = "an array of matching elements";
+
+    /// Every element must convert.
+    fn from_json(value: &'a JsonValue) -> Option<Self> {
+        value.as_array()?.iter().map(T::from_json).collect()
+    }
+}
+
+impl<'a, T: ViewField<'a>> ViewField<'a> for Option<T> {
+    const EXPECTED: &'static str = T::EXPECTED;
+
+    /// `null` and a missing member are `None`.
+    fn from_json(value: &'a JsonValue) -> Option<Self> {
+        match value {
+            JsonValue::Null => Some(None),
+            _ => T::from_json(value).map(Some),
+        }
+    }
+
+    fn missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+/// Defines a typed view: a `Copy` wrapper around a borrowed object with
+/// one getter per field. Each getter returns `Result<Type, ViewError>` and
+/// reads its member only when called. A field's member has the field's
+/// name unless another is given with `= "key"`. Views can be nested, and
+/// `Option` fields accept `null` or a missing member.
+#[macro_export]
+macro_rules! json_view {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident<$a:lifetime> {
+            $(
+                $(#[$field_meta:meta])*
+                $field:ident : $ty:ty $(= $key:literal)?
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        $vis struct $name<$a> {
+            value: &$a $crate::json::JsonValue,
+        }
+
+        impl<$a> $crate::json::JsonView<$a> for $name<$a> {
+            fn view(value: &$a $crate::json::JsonValue) -> Option<Self> {
+                value.as_object().map(|_| $name { value })
+            }
+
+            fn json(&self) -> &$a $crate::json::JsonValue {
+                self.value
+            }
+        }
+
+        impl<$a> $crate::json::ViewField<$a> for $name<$a> {
+            const EXPECTED: &'static str = "an object";
+
+            fn from_json(value: &$a $crate::json::JsonValue) -> Option<Self> {
+                <Self as $crate::json::JsonView<$a>>::view(value)
+            }
+        }
+
+        impl<$a> $name<$a> {
+            $(
+                $(#[$field_meta])*
+                pub fn $field(&self) -> Result<$ty, $crate::json::ViewError> {
+                    let key = $crate::json_view!(@key $field $($key)?);
+                    $crate::json::view_field(self.value, key)
+                }
+            )*
+        }
+    };
+    (@key $field:ident) => {
+        stringify!($field)
+    };
+    (@key $field:ident $key:literal) => {
+        $key
+    };
+}
@@ -0,0 +1,54 @@
+use super::{JsonValue, Map};
+
+/// Callbacks for a depth-first walk over a value with `JsonValue::accept`.
+/// Every method does nothing by default, so a visitor implements only the
+/// ones it needs.
+pub trait JsonVisitor {
+    /// Called before the members of an object.
+    fn enter_object(&mut self, _members: &Map) {}
+
+    /// Called after the members of an object.
+    fn leave_object(&mut self, _members: &Map) {}
+
+    /// Called before each member's value, with its key.
+    fn enter_member(&mut self, _key: &str) {}
+
+    /// Called after each member's value.
+    fn leave_member(&mut self, _key: &str) {}
+
+    /// Called before the elements of an array.
+    fn enter_array(&mut self, _items: &[JsonValue]) {}
+
+    /// Called after the elements of an array.
+    fn leave_array(&mut self, _items: &[JsonValue]) {}
+
+    /// Called for every value that is neither an array nor an object.
+    fn scalar(&mut self, _value: &JsonValue) {}
+}
+
+impl JsonValue {
+    /// Walks the value depth-first, calling `visitor` on the way into and
+    /// out of every array and object and once for every scalar. Object
+    /// members are visited in ascending key order.
+    pub fn accept(&self, visitor: &mut impl JsonVisitor) {
+        match self {
+            JsonValue::Object(members) => {
+                visitor.enter_object(members);
+                for (key, value) in self.iter_sorted() {
+                    visitor.enter_member(key);
+                    value.accept(visitor);
+                    visitor.leave_member(key);
+                }
+                visitor.leave_object(members);
+            }
+            JsonValue::Array(items) => {
+                visitor.enter_array(items);
+                for item in items {
+                    item.accept(visitor);
+                }
+                visitor.leave_array(items);
+            }
+            _ => visitor.scalar(self),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use super::JsonValue;
+use super::pointer::escape_pointer_token;
+use std::collections::VecDeque;
+
+/// The order in which `JsonValue::walk_with` visits nodes.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum WalkOrder {
+    /// Each node, then everything inside it, before its next sibling.
+    #[default]
+    DepthFirst,
+    /// All nodes at one depth before any node at the next.
+    BreadthFirst,
+}
+
+/// An iterator over every node of a value with its JSON Pointer, created by
+/// `JsonValue::walk`. Object members are visited in ascending key order.
+pub struct Walk<'a> {
+    order: WalkOrder,
+    pending: VecDeque<(String, &'a JsonValue)>,
+}
+
+impl JsonValue {
+    /// Iterates over every node, the value itself first with pointer `""`,
+    /// depth-first.
+    pub fn walk(&self) -> Walk<'_> {
+        self.walk_with(WalkOrder::DepthFirst)
+    }
+
+    /// Like `walk`, in the given order.
+    pub fn walk_with(&self, order: WalkOrder) -> Walk<'_> {
+        Walk {
+            order,
+            pending: VecDeque::from([(String::new(), self)]),
+        }
+    }
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = (String, &'a JsonValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (pointer, value) = match self.order {
+            WalkOrder::DepthFirst => self.pending.pop_back()?,
+            WalkOrder::BreadthFirst => self.pending.pop_front()?,
+        };
+        let children: Vec<(String, &'a JsonValue)> = match value {
+            JsonValue::Array(items) => items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| (format!("{}/{}", pointer, i), item))
+                .collect(),
+            _ => value
+                .iter_sorted()
+                .map(|(key, item)| (format!("{}/{}", pointer, escape_pointer_token(key)), item))
+                .collect(),
+        };
+        match self.order {
+            // Pushed in reverse so the first child is popped next.
+            WalkOrder::DepthFirst => self.pending.extend(children.into_iter().rev()),
+            WalkOrder::BreadthFirst => self.pending.extend(children),
+        }
+        Some((pointer, value))
+    }
+}
@@ -0,0 +1,184 @@
+use super::stringify::{stringify_compact, write_string};
+use super::{JsonValue, StringifyOptions};
+use std::fmt;
+use std::io::{self, Write};
+
+/// A failed `JsonWriter` call.
+#[derive(Debug)]
+pub enum WriterError {
+    Io(io::Error),
+    /// The call would have produced invalid JSON, such as a value where an
+    /// object key belongs. Nothing was written.
+    UnexpectedCall(&'static str),
+}
+
+impl fmt::Display for WriterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriterError::Io(e) => write!(f, "{}", e),
+            WriterError::UnexpectedCall(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for WriterError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WriterError::Io(e) => Some(e),
+            WriterError::UnexpectedCall(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for WriterError {
+    fn from(e: io::Error) -> Self {
+        WriterError::Io(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Frame {
+    Array { empty: bool },
+    Object { empty: bool, after_key: bool },
+}
+
+/// Writes compact JSON to `W` one token at a time, so a large document can
+/// be produced without building a `JsonValue` for it. Calls that would make
+/// the output invalid fail with `WriterError::UnexpectedCall` and write
+/// nothing; after an `Io` error the output is incomplete. Small writes go
+/// straight to `W`, so wrap unbuffered writers in a `BufWriter`.
+pub struct JsonWriter<W: Write> {
+    out: W,
+    stack: Vec<Frame>,
+    done: bool,
+    scratch: String,
+}
+
+impl<W: Write> JsonWriter<W> {
+    pub fn new(out: W) -> Self {
+        JsonWriter {
+            out,
+            stack: Vec::new(),
+            done: false,
+            scratch: String::new(),
+        }
+    }
+
+    pub fn begin_object(&mut self) -> Result<(), WriterError> {
+        self.before_value()?;
+        self.out.write_all(b"{")?;
+        self.stack.push(Frame::Object {
+            empty: true,
+            after_key: false,
+        });
+        Ok(())
+    }
+
+    pub fn begin_array(&mut self) -> Result<(), WriterError> {
+        self.before_value()?;
+        self.out.write_all(b"[")?;
+        self.stack.push(Frame::Array { empty: true });
+        Ok(())
+    }
+
+    /// Writes the key of the next object member, which the next value
+    /// completes.
+    pub fn key(&mut self, key: &str) -> Result<(), WriterError> {
+        let Some(Frame::Object { empty, after_key }) = self.stack.last_mut() else {
+            return Err(WriterError::UnexpectedCall("key outside an object"));
+        };
+        if *after_key {
+            return Err(WriterError::UnexpectedCall("key where a value is expected"));
+        }
+        let comma = !*empty;
+        *empty = false;
+        *after_key = true;
+        self.scratch.clear();
+        if comma {
+            self.scratch.push(',');
+        }
+        write_string(key, &StringifyOptions::default(), &mut self.scratch);
+        self.scratch.push(':');
+        self.out.write_all(self.scratch.as_bytes())?;
+        Ok(())
+    }
+
+    /// Writes a complete value, such as a string, a number or a small
+    /// subtree.
+    pub fn value(&mut self, value: impl Into<JsonValue>) -> Result<(), WriterError> {
+        self.value_ref(&value.into())
+    }
+
+    /// Like `value`, for a tree that is already built.
+    pub fn value_ref(&mut self, value: &JsonValue) -> Result<(), WriterError> {
+        self.before_value()?;
+        self.out.write_all(stringify_compact(value).as_bytes())?;
+        self.after_value();
+        Ok(())
+    }
+
+    /// Closes the innermost open object or array.
+    pub fn end(&mut self) -> Result<(), WriterError> {
+        let close: &[u8] = match self.stack.last() {
+            None => return Err(WriterError::UnexpectedCall("end with nothing open")),
+            Some(Frame::Object {
+                after_key: true, ..
+            }) => {
+                return Err(WriterError::UnexpectedCall(
+                    "end after a key without a value",
+                ));
+            }
+            Some(Frame::Object { .. }) => b"}",
+            Some(Frame::Array { .. }) => b"]",
+        };
+        self.out.write_all(close)?;
+        self.stack.pop();
+        self.after_value();
+        Ok(())
+    }
+
+    /// Whether a complete value has been written and nothing is left open.
+    pub fn is_complete(&self) -> bool {
+        self.done
+    }
+
+    /// Flushes and returns the underlying writer, failing if the document
+    /// is unfinished.
+    pub fn finish(mut self) -> Result<W, WriterError> {
+        if !self.done {
+            return Err(WriterError::UnexpectedCall(
+                "finish before the document is complete",
+            ));
+        }
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    // Checks that a value may start here and writes the comma before it.
+    fn before_value(&mut self) -> Result<(), WriterError> {
+        match self.stack.last_mut() {
+            None if self.done => Err(WriterError::UnexpectedCall(
+                "value after the document is complete",
+            )),
+            None => Ok(()),
+            Some(Frame::Object {
+                after_key: false, ..
+            }) => Err(WriterError::UnexpectedCall("value where a key is expected")),
+            Some(Frame::Object { .. }) => Ok(()),
+            Some(Frame::Array { empty }) => {
+                if !std::mem::replace(empty, false) {
+                    self.out.write_all(b",")?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn after_value(&mut self) {
+        match self.stack.last_mut() {
+            None => self.done = true,
+            Some(Frame::Object { after_key, .. }) => *after_key = false,
+            Some(Frame::Array { .. }) => {}
+        }
+    }
+}
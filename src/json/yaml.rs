@@ -0,0 +1,107 @@
+use super::{JsonValue, parse_integer_exact};
+use serde_yaml::{Mapping, Number, Value};
+use std::fmt;
+
+/// YAML that is malformed or has no JSON equivalent.
+#[derive(Debug, PartialEq, Clone)]
+pub struct YamlError {
+    pub message: String,
+}
+
+impl fmt::Display for YamlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for YamlError {}
+
+fn error(message: impl fmt::Display) -> YamlError {
+    YamlError {
+        message: message.to_string(),
+    }
+}
+
+/// Parses a single YAML document. Anchors and aliases are expanded, tags
+/// are dropped, and scalar mapping keys such as `1` or `true` become their
+/// text. Fails on mapping keys that are sequences or mappings. Integers
+/// beyond 2^53 are kept as `RawNumber`.
+pub fn from_yaml(input: &str) -> Result<JsonValue, YamlError> {
+    let value: Value = serde_yaml::from_str(input).map_err(error)?;
+    from_value(value)
+}
+
+/// Writes a value as a YAML document, with mapping keys in ascending order.
+pub fn to_yaml(value: &JsonValue) -> Result<String, YamlError> {
+    serde_yaml::to_string(&to_value(value)).map_err(error)
+}
+
+fn from_value(value: Value) -> Result<JsonValue, YamlError> {
+    Ok(match value {
+        Value::Null => JsonValue::Null,
+        Value::Bool(b) => JsonValue::Bool(b),
+        Value::Number(n) => from_number(&n),
+        Value::String(s) => JsonValue::String(s.into()),
+        Value::Sequence(items) => JsonValue::Array(
+            items
+                .into_iter()
+                .map(from_value)
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Mapping(members) => JsonValue::Object(Box::new(
+            members
+                .into_iter()
+                .map(|(key, value)| Ok((key_text(key)?, from_value(value)?)))
+                .collect::<Result<_, YamlError>>()?,
+        )),
+        Value::Tagged(tagged) => from_value(tagged.value)?,
+    })
+}
+
+fn from_number(n: &Number) -> JsonValue {
+    match (n.as_i64(), n.as_u64()) {
+        (Some(i), _) if i.unsigned_abs() > 1 << 53 => JsonValue::RawNumber(i.to_string().into()),
+        (None, Some(u)) => JsonValue::RawNumber(u.to_string().into()),
+        _ => JsonValue::Number(n.as_f64().unwrap_or(f64::NAN)),
+    }
+}
+
+fn key_text(key: Value) -> Result<String, YamlError> {
+    match key {
+        Value::String(s) => Ok(s),
+        Value::Null => Ok("null".to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Tagged(tagged) => key_text(tagged.value),
+        Value::Sequence(_) | Value::Mapping(_) => {
+            Err(error("a mapping key must be a scalar to convert to JSON"))
+        }
+    }
+}
+
+fn to_value(value: &JsonValue) -> Value {
+    match value {
+        JsonValue::Null => Value::Null,
+        JsonValue::Bool(b) => Value::Bool(*b),
+        JsonValue::Number(n) if n.fract() == 0.0 && n.abs() < 2f64.powi(63) => {
+            Value::Number((*n as i64).into())
+        }
+        JsonValue::Number(n) => Value::Number((*n).into()),
+        JsonValue::RawNumber(text) => match parse_integer_exact(text) {
+            Ok(n) if i64::try_from(n).is_ok() => Value::Number((n as i64).into()),
+            Ok(n) if u64::try_from(n).is_ok() => Value::Number((n as u64).into()),
+            _ => Value::Number(text.parse::<f64>().unwrap_or(f64::NAN).into()),
+        },
+        JsonValue::Raw(raw) => to_value(&raw.parse()),
+        JsonValue::String(s) => Value::String(s.to_string()),
+        JsonValue::Bytes(bytes) => Value::String(String::from_utf8_lossy(bytes).into_owned()),
+        JsonValue::Array(items) => Value::Sequence(items.iter().map(to_value).collect()),
+        JsonValue::Object(_) => {
+            let mut members = Mapping::new();
+            for (key, item) in value.iter_sorted() {
+                members.insert(Value::String(key.clone()), to_value(item));
+            }
+            Value::Mapping(members)
+        }
+    }
+}
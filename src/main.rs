@@ -1,71 +1,363 @@
-mod json;
-
-use json::{JsonValue, parse, stringify};
-use std::collections::HashMap;
-
-fn main() {
-    println!("=== JSON Parser and Stringifier Demo ===\n");
-
-    // Parse examples
-    let examples = vec![
-        "null",
-        "true",
-        "false",
-        "42",
-        "3.14",
-        r#""Hello, World!""#,
-        "[1, 2, 3, 4, 5]",
-        r#"{"name": "Alice", "age": 30}"#,
-        r#"{"users": [{"name": "Alice", "age": 30}, {"name": "Bob", "age": 25}]}"#,
-    ];
-
-    println!("Parsing examples:");
-    for example in &examples {
-        match parse(example) {
-            Ok(value) => println!("✓ {} -> {:?}", example, value),
-            Err(e) => println!("✗ {} -> Error: {:?}", example, e),
+mod bench;
+mod plugin;
+mod repl;
+
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::process::ExitCode;
+use world::json::{
+    ColorScheme, JsonValue, ParseError, ParserOptions, PatchOp, StringifyOptions,
+    decode_detect_encoding, diff, escape_pointer_token, minify_with_options, parse_with_options,
+    stringify, stringify_colored_with, stringify_with, validate_with_options,
+};
+
+const USAGE: &str = "\
+usage: rust-json <command> [options] [arguments] [file]
+
+commands:
+  bench        time parsing, streaming, lazy extraction, stringify and a query
+  diff <file>  compare the file with a second document; exits 1 if they differ
+  fmt          pretty-print a document
+  get <path>   print the value at a JSON Pointer (`/a/0`) or dot-path (`a.0`)
+  minify       remove insignificant whitespace, keeping numbers and strings as written
+  repl <file>  edit a document interactively with get, set, query, diff and save
+  validate     check that a document is valid JSON
+
+Any other command runs the plugin `json-<command>` found on PATH, passing it
+the remaining arguments and the document on stdin; it must print JSON.
+
+options:
+  -i, --in-place   rewrite the file instead of printing to stdout (fmt, minify)
+  --indent <n>     spaces per indentation level for fmt and get (default 2)
+  -r, --raw        print strings without quotes or escapes (get)
+  --color <when>   color output for fmt, get and diff patches: `auto` (default;
+                   when stdout is a terminal and NO_COLOR is unset), `always`
+                   or `never`
+  --format <f>     `report` (default) or an RFC 6902 `patch` (diff)
+  --query <q>      JSONPath expression to time (bench; default `$..*`)
+  --lenient        accept comments, trailing commas and other extensions
+  -h, --help       show this help
+
+With no file, or `-`, input is read from stdin.";
+
+// Each command with the number of arguments it takes before the file.
+const COMMANDS: &[(&str, usize)] = &[
+    ("bench", 0),
+    ("diff", 1),
+    ("fmt", 0),
+    ("get", 1),
+    ("minify", 0),
+    ("repl", 1),
+    ("validate", 0),
+];
+
+// When to color pretty-printed output.
+#[derive(Clone, Copy, PartialEq)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+// Why a run failed; each kind has its own exit status.
+enum Failure {
+    Usage(String),
+    Error(String),
+    Invalid(String),
+    // `diff` found differences, which it has already printed.
+    Differs,
+}
+
+struct Args {
+    command: String,
+    operands: Vec<String>,
+    file: Option<String>,
+    in_place: bool,
+    indent: usize,
+    lenient: bool,
+    raw: bool,
+    color: ColorChoice,
+    patch: bool,
+    query: Option<String>,
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    // Plugins handle their own options, `--help` included.
+    if let Some(command) = args.first()
+        && !COMMANDS.iter().any(|(name, _)| name == command)
+        && let Some(path) = plugin::find(command)
+    {
+        return exit(plugin::run(command, &path, &args[1..]));
+    }
+    if args.is_empty() || args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    exit(parse_args(args).and_then(|args| run(&args)))
+}
+
+fn exit(result: Result<(), Failure>) -> ExitCode {
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Failure::Invalid(message)) => {
+            eprintln!("{}", message);
+            ExitCode::from(1)
+        }
+        Err(Failure::Usage(message)) => {
+            eprintln!("rust-json: {}\n\n{}", message, USAGE);
+            ExitCode::from(2)
+        }
+        Err(Failure::Error(message)) => {
+            eprintln!("rust-json: {}", message);
+            ExitCode::from(2)
+        }
+        Err(Failure::Differs) => ExitCode::from(1),
+    }
+}
+
+fn parse_args(args: Vec<String>) -> Result<Args, Failure> {
+    let mut args = args.into_iter();
+    let command = args.next().unwrap();
+    let Some(&(_, arity)) = COMMANDS.iter().find(|(name, _)| *name == command) else {
+        return Err(Failure::Usage(format!("unknown command {}", command)));
+    };
+    let mut parsed = Args {
+        command,
+        operands: Vec::new(),
+        file: None,
+        in_place: false,
+        indent: 2,
+        lenient: false,
+        raw: false,
+        color: ColorChoice::Auto,
+        patch: false,
+        query: None,
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-i" | "--in-place" => parsed.in_place = true,
+            "--lenient" => parsed.lenient = true,
+            "-r" | "--raw" => parsed.raw = true,
+            "--indent" => {
+                let value = args.next().unwrap_or_default();
+                parsed.indent = value
+                    .parse()
+                    .map_err(|_| Failure::Usage(format!("invalid indent {:?}", value)))?;
+            }
+            "--query" => {
+                let query = args.next();
+                parsed.query =
+                    Some(query.ok_or(Failure::Usage("--query needs a value".to_string()))?);
+            }
+            "--color" => match args.next().as_deref() {
+                Some("auto") => parsed.color = ColorChoice::Auto,
+                Some("always") => parsed.color = ColorChoice::Always,
+                Some("never") => parsed.color = ColorChoice::Never,
+                other => {
+                    let other = other.unwrap_or_default();
+                    return Err(Failure::Usage(format!("unknown color choice {:?}", other)));
+                }
+            },
+            "--format" => match args.next().as_deref() {
+                Some("report") => parsed.patch = false,
+                Some("patch") => parsed.patch = true,
+                other => {
+                    let other = other.unwrap_or_default();
+                    return Err(Failure::Usage(format!("unknown format {:?}", other)));
+                }
+            },
+            _ if arg.starts_with('-') && arg != "-" => {
+                return Err(Failure::Usage(format!("unknown option {}", arg)));
+            }
+            _ if parsed.operands.len() < arity => parsed.operands.push(arg),
+            _ if parsed.file.is_some() => {
+                return Err(Failure::Usage(format!("unexpected argument {}", arg)));
+            }
+            _ => parsed.file = Some(arg),
+        }
+    }
+
+    if parsed.command == "repl"
+        && let Some(file) = parsed.file
+    {
+        return Err(Failure::Usage(format!("unexpected argument {}", file)));
+    }
+    if parsed.operands.len() < arity {
+        return Err(Failure::Usage(format!(
+            "{} needs {} argument(s)",
+            parsed.command, arity
+        )));
+    }
+    if parsed.in_place && !matches!(parsed.command.as_str(), "fmt" | "minify") {
+        return Err(Failure::Usage(format!(
+            "{} cannot write in place",
+            parsed.command
+        )));
+    }
+    if parsed.in_place && parsed.file.as_deref().is_none_or(|f| f == "-") {
+        return Err(Failure::Usage("--in-place needs a file".to_string()));
+    }
+    Ok(parsed)
+}
+
+fn run(args: &Args) -> Result<(), Failure> {
+    let options = if args.lenient {
+        ParserOptions::lenient()
+    } else {
+        ParserOptions::default()
+    };
+    // The session reads its commands from stdin.
+    if args.command == "repl" {
+        return repl::run(&args.operands[0], options, args.indent);
+    }
+
+    let input = read_input(args.file.as_deref())?;
+    let name = args.file.as_deref().unwrap_or("-");
+    let invalid = |e: ParseError| Failure::Invalid(format!("{}: {}", name, e));
+    let color = match args.color {
+        ColorChoice::Auto => {
+            !args.in_place && io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none()
+        }
+        ColorChoice::Always => !args.in_place,
+        ColorChoice::Never => false,
+    };
+    let pretty = |value: &JsonValue| pretty(value, args.indent, color);
+
+    let output = match args.command.as_str() {
+        "validate" => {
+            validate_with_options(&input, options).map_err(invalid)?;
+            return Ok(());
+        }
+        "minify" => minify_with_options(&input, options).map_err(invalid)?,
+        "fmt" => pretty(&parse_tree(&input, options).map_err(invalid)?)?,
+        "get" => {
+            let value = parse_tree(&input, options).map_err(invalid)?;
+            let path = &args.operands[0];
+            let found = value
+                .pointer(&to_pointer(path))
+                .ok_or_else(|| Failure::Invalid(format!("{}: no value at {}", name, path)))?;
+            match found {
+                JsonValue::String(s) if args.raw => s.to_string(),
+                _ => pretty(found)?,
+            }
         }
+        "bench" => {
+            let query = args.query.as_deref().unwrap_or("$..*");
+            return bench::run(&input, options, query);
+        }
+        "diff" => {
+            // Invalid input is an error here; exit status 1 means "differ".
+            let parse = |name: &str, text: &str| {
+                parse_with_options(text, options.clone())
+                    .map_err(|e| Failure::Error(format!("{}: {}", name, e)))
+            };
+            let first = &args.operands[0];
+            let from = parse(first, &read_input(Some(first))?)?;
+            let to = parse(name, &input)?;
+            let ops = diff(&from, &to);
+            if args.patch {
+                let patch = JsonValue::Array(ops.iter().map(PatchOp::to_json).collect());
+                print(&pretty(&patch)?)?;
+            } else {
+                for op in &ops {
+                    print(&describe(op, &from))?;
+                }
+            }
+            return if ops.is_empty() {
+                Ok(())
+            } else {
+                Err(Failure::Differs)
+            };
+        }
+        _ => unreachable!("commands are checked in parse_args"),
+    };
+
+    match &args.file {
+        Some(path) if args.in_place => fs::write(path, output + "\n")
+            .map_err(|e| Failure::Error(format!("cannot write {}: {}", path, e))),
+        _ => print(&output),
     }
+}
+
+fn print(output: &str) -> Result<(), Failure> {
+    writeln!(io::stdout(), "{}", output)
+        .map_err(|e| Failure::Error(format!("cannot write output: {}", e)))
+}
 
-    println!("\nStringifying examples:");
-    let values = vec![
-        JsonValue::Null,
-        JsonValue::Bool(true),
-        JsonValue::Number(42.0),
-        JsonValue::String("Hello, World!".to_string()),
-        JsonValue::Array(vec![
-            JsonValue::Number(1.0),
-            JsonValue::Number(2.0),
-            JsonValue::Number(3.0),
-        ]),
-    ];
-
-    for value in &values {
-        let json = stringify(value);
-        println!("✓ {:?} -> {}", value, json);
+// One line of the human-readable diff report.
+fn describe(op: &PatchOp, from: &JsonValue) -> String {
+    let path = match op.path() {
+        "" => "(root)",
+        path => path,
+    };
+    let old = || from.pointer(op.path()).map(stringify).unwrap_or_default();
+    match op {
+        PatchOp::Add { value, .. } => format!("+ {}: {}", path, stringify(value)),
+        PatchOp::Remove { .. } => format!("- {}: {}", path, old()),
+        PatchOp::Replace { value, .. } => {
+            format!("~ {}: {} -> {}", path, old(), stringify(value))
+        }
     }
+}
+
+// Numbers are kept as written so that printing does not change them.
+fn parse_tree(input: &str, options: ParserOptions) -> Result<JsonValue, ParseError> {
+    let options = ParserOptions {
+        preserve_raw_numbers: true,
+        ..options
+    };
+    parse_with_options(input, options)
+}
 
-    // Complex object example
-    println!("\nComplex object example:");
-    let mut person = HashMap::new();
-    person.insert("name".to_string(), JsonValue::String("Alice".to_string()));
-    person.insert("age".to_string(), JsonValue::Number(30.0));
-    person.insert("active".to_string(), JsonValue::Bool(true));
-
-    let mut scores = HashMap::new();
-    scores.insert("math".to_string(), JsonValue::Number(95.0));
-    scores.insert("science".to_string(), JsonValue::Number(87.0));
-    person.insert("scores".to_string(), JsonValue::Object(scores));
-
-    let complex = JsonValue::Object(person);
-    let json_str = stringify(&complex);
-    println!("Complex object: {}", json_str);
-
-    // Round-trip test
-    println!("\nRound-trip test:");
-    if let Ok(parsed_again) = parse(&json_str) {
-        println!("✓ Round-trip successful: {}", stringify(&parsed_again));
+fn pretty(value: &JsonValue, indent: usize, color: bool) -> Result<String, Failure> {
+    let options = StringifyOptions {
+        indent: Some(" ".repeat(indent)),
+        sort_keys: true,
+        ..StringifyOptions::default()
+    };
+    if color {
+        stringify_colored_with(value, ColorScheme::default(), &options)
     } else {
-        println!("✗ Round-trip failed");
+        stringify_with(value, &options)
     }
+    .map_err(|e| Failure::Invalid(e.to_string()))
+}
+
+// Accepts a JSON Pointer as is and turns a dot-path such as `users.0.name`
+// into one. A lone `.` is the whole document.
+fn to_pointer(path: &str) -> String {
+    if path.is_empty() || path.starts_with('/') {
+        return path.to_string();
+    }
+    let path = path.strip_prefix('.').unwrap_or(path);
+    if path.is_empty() {
+        return String::new();
+    }
+    path.split('.')
+        .map(|segment| format!("/{}", escape_pointer_token(segment)))
+        .collect()
+}
+
+fn read_input(file: Option<&str>) -> Result<String, Failure> {
+    let (name, bytes) = match file {
+        None | Some("-") => {
+            let mut input = Vec::new();
+            io::stdin()
+                .read_to_end(&mut input)
+                .map_err(|e| Failure::Error(format!("cannot read stdin: {}", e)))?;
+            ("stdin", input)
+        }
+        Some(path) => (
+            path,
+            fs::read(path).map_err(|e| Failure::Error(format!("cannot read {}: {}", path, e)))?,
+        ),
+    };
+    // Accept a byte order mark and UTF-16, as Windows tools often write.
+    decode_detect_encoding(&bytes)
+        .map(|text| text.into_owned())
+        .map_err(|e| Failure::Error(format!("cannot read {}: {}", name, e)))
 }
@@ -0,0 +1,50 @@
+use crate::{Failure, print, read_input};
+use std::env;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use world::json::{ParserOptions, validate_with_options};
+
+/// Finds the executable `json-<name>` on `PATH`.
+pub(crate) fn find(name: &str) -> Option<PathBuf> {
+    let file = format!("json-{}{}", name, env::consts::EXE_SUFFIX);
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(&file))
+        .find(|path| path.is_file())
+}
+
+/// Runs the plugin at `path` with `args`, giving it the JSON document on
+/// stdin and printing the JSON document it writes to stdout.
+pub(crate) fn run(name: &str, path: &Path, args: &[String]) -> Result<(), Failure> {
+    let input = read_input(None)?;
+    validate_with_options(&input, ParserOptions::default())
+        .map_err(|e| Failure::Invalid(format!("-: {}", e)))?;
+
+    let mut child = Command::new(path)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| Failure::Error(format!("cannot run json-{}: {}", name, e)))?;
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = child.stdout.take().unwrap();
+    // Feed the input from another thread so a plugin that writes before it
+    // has read everything cannot deadlock against us.
+    let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let mut output = String::new();
+    let read = stdout.read_to_string(&mut output);
+    // A plugin may exit without reading all of its input.
+    let _ = writer.join();
+    let status = child
+        .wait()
+        .map_err(|e| Failure::Error(format!("cannot run json-{}: {}", name, e)))?;
+
+    if !status.success() {
+        return Err(Failure::Error(format!("json-{} failed: {}", name, status)));
+    }
+    read.map_err(|e| Failure::Error(format!("cannot read json-{} output: {}", name, e)))?;
+    validate_with_options(&output, ParserOptions::default())
+        .map_err(|e| Failure::Error(format!("json-{} printed invalid JSON: {}", name, e)))?;
+    print(output.trim_end())
+}
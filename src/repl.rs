@@ -0,0 +1,131 @@
+use crate::{Failure, describe, parse_tree, pretty, to_pointer};
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use world::json::{JsonValue, ParserOptions, PatchOp, apply_patch, diff, stringify};
+
+const HELP: &str = "\
+get <path>          print the value at a JSON Pointer or dot-path
+set <path> <json>   set the value at a path, adding it if missing
+query <jsonpath>    print every match of a JSONPath expression, one per line
+diff                show changes since the document was loaded or saved
+save [file]         write the document, to its own file by default
+help                show this help
+quit                leave without saving";
+
+// The state of one session.
+struct Session {
+    path: String,
+    options: ParserOptions,
+    indent: usize,
+    saved: JsonValue,
+    doc: JsonValue,
+}
+
+/// Loads `path` and runs commands from stdin against it until `quit` or end
+/// of input.
+pub(crate) fn run(path: &str, options: ParserOptions, indent: usize) -> Result<(), Failure> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| Failure::Error(format!("cannot read {}: {}", path, e)))?;
+    let doc = parse_tree(&text, options.clone())
+        .map_err(|e| Failure::Invalid(format!("{}: {}", path, e)))?;
+    let mut session = Session {
+        path: path.to_string(),
+        options,
+        indent,
+        saved: doc.clone(),
+        doc,
+    };
+
+    let interactive = io::stdin().is_terminal();
+    if interactive {
+        println!("{} loaded; type help for commands", path);
+    }
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        if interactive {
+            print!("> ");
+            io::stdout().flush().ok();
+        }
+        let Some(line) = lines.next() else {
+            return Ok(());
+        };
+        let line = line.map_err(|e| Failure::Error(format!("cannot read stdin: {}", e)))?;
+        let line = line.trim();
+        let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let result = match command {
+            "" => continue,
+            "quit" | "exit" => return Ok(()),
+            "help" => Ok(HELP.to_string()),
+            "get" => session.get(rest.trim()),
+            "set" => session.set(rest.trim()),
+            "query" => session.query(rest.trim()),
+            "diff" => Ok(session.diff()),
+            "save" => session.save(rest.trim()),
+            _ => Err(format!("unknown command {}; try help", command)),
+        };
+        match result {
+            Ok(output) if output.is_empty() => {}
+            Ok(output) => println!("{}", output),
+            Err(message) => eprintln!("error: {}", message),
+        }
+    }
+}
+
+impl Session {
+    fn get(&self, path: &str) -> Result<String, String> {
+        let value = self
+            .doc
+            .pointer(&to_pointer(path))
+            .ok_or_else(|| format!("no value at {}", path))?;
+        self.pretty(value)
+    }
+
+    fn set(&mut self, args: &str) -> Result<String, String> {
+        let (path, json) = args
+            .split_once(char::is_whitespace)
+            .ok_or("usage: set <path> <json>")?;
+        let value = parse_tree(json.trim(), self.options.clone()).map_err(|e| e.to_string())?;
+        let path = to_pointer(path);
+        let op = match self.doc.pointer(&path) {
+            Some(_) => PatchOp::Replace { path, value },
+            None => PatchOp::Add { path, value },
+        };
+        apply_patch(&mut self.doc, &[op]).map_err(|e| e.to_string())?;
+        Ok(String::new())
+    }
+
+    fn query(&self, path: &str) -> Result<String, String> {
+        let matches = self.doc.query(path).map_err(|e| e.to_string())?;
+        Ok(matches
+            .into_iter()
+            .map(stringify)
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    fn diff(&self) -> String {
+        let ops = diff(&self.saved, &self.doc);
+        if ops.is_empty() {
+            return "no changes".to_string();
+        }
+        let lines: Vec<String> = ops.iter().map(|op| describe(op, &self.saved)).collect();
+        lines.join("\n")
+    }
+
+    fn save(&mut self, target: &str) -> Result<String, String> {
+        let target = if target.is_empty() {
+            &self.path
+        } else {
+            target
+        };
+        let text = self.pretty(&self.doc)? + "\n";
+        fs::write(target, text).map_err(|e| format!("cannot write {}: {}", target, e))?;
+        self.saved = self.doc.clone();
+        Ok(format!("saved {}", target))
+    }
+
+    fn pretty(&self, value: &JsonValue) -> Result<String, String> {
+        pretty(value, self.indent, false)
+            .map_err(|_| "cannot print a non-finite number".to_string())
+    }
+}